@@ -0,0 +1,363 @@
+pub mod category;
+pub mod sha256;
+pub mod user;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::acl::user::User;
+use crate::data_watcher::shard::Route;
+use crate::redis_protocol::key_spec;
+
+use anyhow::{Context, Result};
+use log::info;
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct AclConfig {
+    // the password of the default user, like requirepass of redis
+    pub requirepass: Option<String>,
+    // the users of ACL LOAD and ACL SAVE, like aclfile of redis
+    pub aclfile: Option<PathBuf>,
+}
+
+// the users of the server, shared by the connections
+// https://redis.io/docs/management/security/acl/
+#[derive(Debug)]
+pub struct Acl {
+    users: RwLock<BTreeMap<String, User>>,
+    aclfile: Option<PathBuf>,
+    // the password of the default user created when the ACL file doesn't have it
+    requirepass: RwLock<Option<String>>,
+}
+
+impl Acl {
+    // the default user only, with the password of requirepass
+    pub fn new(config: AclConfig) -> Self {
+        let default = User::default_user(config.requirepass.as_deref());
+        Acl {
+            users: RwLock::new(BTreeMap::from([(default.name().to_string(), default)])),
+            aclfile: config.aclfile,
+            requirepass: RwLock::new(config.requirepass),
+        }
+    }
+
+    // like redis the ACL file is loaded at startup, an invalid file stops the server
+    pub fn load(config: AclConfig) -> Result<Self> {
+        let acl = Acl::new(config);
+        if acl.aclfile.is_some() {
+            acl.load_file()?;
+        }
+        Ok(acl)
+    }
+
+    // ACL LOAD, the users are replaced only when the whole file is valid
+    pub fn load_file(&self) -> Result<()> {
+        let path = self.aclfile()?;
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            // ACL SAVE creates the file
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                info!("acl file {} not found", path.display());
+                String::new()
+            }
+            Err(e) => return Err(e).with_context(|| format!("read acl file {}", path.display())),
+        };
+        let requirepass = self.requirepass.read().unwrap().clone();
+        let users = parse_file(path, &content, requirepass.as_deref())?;
+        info!("{} acl users loaded from {}", users.len(), path.display());
+        *self.users.write().unwrap() = users;
+        Ok(())
+    }
+
+    // ACL SAVE, one line per user with the rules of ACL LIST
+    pub fn save_file(&self) -> Result<()> {
+        let path = self.aclfile()?;
+        let content: String = self.list().iter().map(|x| format!("{x}\n")).collect();
+        let temp = path.with_file_name(format!("temp-{}.acl", std::process::id()));
+        let write = || -> Result<()> {
+            fs::write(&temp, content)?;
+            fs::rename(&temp, path)?;
+            Ok(())
+        };
+        write().map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            e.context(format!("ERR write acl file {}", path.display()))
+        })
+    }
+
+    fn aclfile(&self) -> Result<&Path> {
+        self.aclfile.as_deref().ok_or_else(|| {
+            anyhow::anyhow!("ERR This instance is not configured to use an ACL file")
+        })
+    }
+
+    // CONFIG SET requirepass, the password of the default user is replaced, an empty one is nopass
+    pub fn set_requirepass(&self, requirepass: Option<&str>) -> Result<()> {
+        self.set_user("default", &requirepass_rules(requirepass))?;
+        *self.requirepass.write().unwrap() = requirepass.map(|x| x.to_string());
+        Ok(())
+    }
+
+    // CONFIG SET checks requirepass before it applies any parameter
+    pub fn check_requirepass(&self, requirepass: Option<&str>) -> Result<()> {
+        let mut user = self
+            .get_user("default")
+            .unwrap_or_else(|| User::new("default"));
+        user.apply_rules(&requirepass_rules(requirepass))
+    }
+
+    // the new connection is authenticated as the default user when it has no password like redis
+    pub fn default_user_nopass(&self) -> bool {
+        self.users
+            .read()
+            .unwrap()
+            .get("default")
+            .is_some_and(|x| x.enabled() && x.nopass())
+    }
+
+    pub fn authenticate(&self, username: &str, password: &[u8]) -> bool {
+        self.users
+            .read()
+            .unwrap()
+            .get(username)
+            .is_some_and(|x| x.check_password(password))
+    }
+
+    // the command and its keys are checked before the command is sent to the data watcher
+    // the connection of a deleted or disabled user has to authenticate again
+    pub fn check(&self, username: &str, command: &str, args: &[Vec<u8>]) -> Result<()> {
+        let users = self.users.read().unwrap();
+        let Some(user) = users.get(username).filter(|x| x.enabled()) else {
+            anyhow::bail!("NOAUTH Authentication required.");
+        };
+        anyhow::ensure!(
+            user.can_run(command),
+            "NOPERM User {username} has no permissions to run the '{command}' command"
+        );
+        // the commands on the whole keyspace like KEYS and SCAN are allowed by the command rules only
+        if let Route::Keys(keys) = key_spec::route(command, args) {
+            anyhow::ensure!(
+                keys.iter().all(|x| user.can_access(x)),
+                "NOPERM No permissions to access a key"
+            );
+        }
+        Ok(())
+    }
+
+    // ACL SETUSER creates the user when it doesn't exist
+    pub fn set_user(&self, name: &str, rules: &[Vec<u8>]) -> Result<()> {
+        check_name(name)?;
+        let mut users = self.users.write().unwrap();
+        let mut user = users.get(name).cloned().unwrap_or_else(|| User::new(name));
+        user.apply_rules(rules)?;
+        users.insert(name.to_string(), user);
+        Ok(())
+    }
+
+    pub fn get_user(&self, name: &str) -> Option<User> {
+        self.users.read().unwrap().get(name).cloned()
+    }
+
+    // return the number of deleted users
+    pub fn del_users(&self, names: &[String]) -> Result<usize> {
+        anyhow::ensure!(
+            !names.iter().any(|x| x == "default"),
+            "ERR The 'default' user cannot be removed"
+        );
+        let mut users = self.users.write().unwrap();
+        Ok(names.iter().filter(|x| users.remove(*x).is_some()).count())
+    }
+
+    pub fn usernames(&self) -> Vec<String> {
+        self.users.read().unwrap().keys().cloned().collect()
+    }
+
+    // ACL LIST, the users sorted by name
+    pub fn list(&self) -> Vec<String> {
+        self.users
+            .read()
+            .unwrap()
+            .values()
+            .map(|x| format!("user {} {}", x.name(), x.describe()))
+            .collect()
+    }
+}
+
+fn requirepass_rules(requirepass: Option<&str>) -> Vec<Vec<u8>> {
+    let rule = match requirepass {
+        Some(password) => format!(">{password}"),
+        None => "nopass".to_string(),
+    };
+    vec![b"resetpass".to_vec(), rule.into_bytes()]
+}
+
+// the name is a word of the ACL file
+fn check_name(name: &str) -> Result<()> {
+    anyhow::ensure!(
+        !name.is_empty() && !name.contains(|x: char| x.is_whitespace() || x == '\0'),
+        "ERR Usernames can't contain spaces or null characters"
+    );
+    Ok(())
+}
+
+// one user per line: user <name> <rule> ...
+// the default user is created like the server without ACL file when the file doesn't have it
+fn parse_file(
+    path: &Path,
+    content: &str,
+    requirepass: Option<&str>,
+) -> Result<BTreeMap<String, User>> {
+    let mut users = BTreeMap::new();
+    for (number, line) in content.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let parsed = match words.next() {
+            None => continue,
+            Some(x) if x.starts_with('#') => continue,
+            Some("user") => words
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("ERR missing username"))
+                .and_then(|name| {
+                    check_name(name)?;
+                    anyhow::ensure!(
+                        !users.contains_key(name),
+                        "ERR Duplicate user '{name}' found"
+                    );
+                    let rules: Vec<Vec<u8>> = words.map(|x| x.as_bytes().to_vec()).collect();
+                    let mut user = User::new(name);
+                    user.apply_rules(&rules)?;
+                    Ok(user)
+                }),
+            Some(_) => Err(anyhow::anyhow!("ERR line should start with user keyword")),
+        };
+        let user = parsed.with_context(|| format!("{}:{}", path.display(), number + 1))?;
+        users.insert(user.name().to_string(), user);
+    }
+    if !users.contains_key("default") {
+        let default = User::default_user(requirepass);
+        users.insert(default.name().to_string(), default);
+    }
+    Ok(users)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Acl, AclConfig};
+    use std::path::PathBuf;
+
+    fn args(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("predis-{}-{name}.acl", std::process::id()))
+    }
+
+    #[test]
+    fn test_requirepass() {
+        // arrange
+        let acl = Acl::new(AclConfig {
+            requirepass: Some("secret".to_string()),
+            aclfile: None,
+        });
+        // act & assert
+        assert!(!acl.default_user_nopass());
+        assert!(acl.authenticate("default", b"secret"));
+        assert!(!acl.authenticate("default", b"other"));
+        assert!(!acl.authenticate("unknown", b"secret"));
+        assert!(Acl::new(AclConfig::default()).default_user_nopass());
+        acl.set_requirepass(None).unwrap();
+        assert!(acl.default_user_nopass());
+        acl.set_requirepass(Some("other")).unwrap();
+        assert!(acl.authenticate("default", b"other"));
+        assert!(!acl.authenticate("default", b"secret"));
+    }
+
+    #[test]
+    fn test_check() {
+        // arrange
+        let acl = Acl::new(AclConfig::default());
+        acl.set_user(
+            "alice",
+            &args(&["on", "nopass", "~app:*", "+@string", "+keys"]),
+        )
+        .unwrap();
+        // act & assert
+        assert!(acl.check("alice", "get", &args(&["get", "app:1"])).is_ok());
+        assert!(acl.check("alice", "keys", &args(&["keys", "*"])).is_ok());
+        assert_eq!(
+            "NOPERM No permissions to access a key",
+            acl.check("alice", "mset", &args(&["mset", "app:1", "1", "x", "2"]))
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "NOPERM User alice has no permissions to run the 'lpush' command",
+            acl.check("alice", "lpush", &args(&["lpush", "app:l", "1"]))
+                .unwrap_err()
+                .to_string()
+        );
+        acl.set_user("alice", &args(&["off"])).unwrap();
+        assert_eq!(
+            "NOAUTH Authentication required.",
+            acl.check("alice", "get", &args(&["get", "app:1"]))
+                .unwrap_err()
+                .to_string()
+        );
+        acl.set_user("alice", &args(&["on"])).unwrap();
+        assert!(acl.check("alice", "get", &args(&["get", "app:1"])).is_ok());
+        assert_eq!(1, acl.del_users(&["alice".to_string()]).unwrap());
+        assert!(acl.check("alice", "get", &args(&["get", "app:1"])).is_err());
+        assert!(acl.del_users(&["default".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_save_then_load_file() {
+        // arrange
+        let path = temp_path("save");
+        let config = AclConfig {
+            requirepass: None,
+            aclfile: Some(path.clone()),
+        };
+        let acl = Acl::load(config.clone()).unwrap();
+        acl.set_user(
+            "alice",
+            &args(&["on", ">secret", "~*", "+@all", "-@dangerous"]),
+        )
+        .unwrap();
+        // act
+        acl.save_file().unwrap();
+        let loaded = Acl::load(config).unwrap();
+        // assert
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(acl.list(), loaded.list());
+        assert!(loaded.authenticate("alice", b"secret"));
+    }
+
+    #[test]
+    fn test_load_file_failed() {
+        // arrange
+        let path = temp_path("bad");
+        std::fs::write(
+            &path,
+            "user alice on nopass +@all\n\nuser bob on +unknown\n",
+        )
+        .unwrap();
+        let acl = Acl::new(AclConfig {
+            requirepass: None,
+            aclfile: Some(path.clone()),
+        });
+        // act
+        let result = acl.load_file();
+        // assert
+        std::fs::remove_file(&path).unwrap();
+        let error = format!("{:#}", result.unwrap_err());
+        assert!(
+            error.contains(":3: ERR Error in ACL SETUSER modifier '+unknown'"),
+            "{error}"
+        );
+        // the users are kept
+        assert_eq!(vec!["default".to_string()], acl.usernames());
+    }
+}
@@ -0,0 +1,186 @@
+// the ACL categories of the commands, like the command table of redis
+// https://redis.io/docs/management/security/acl/#command-categories
+pub const CATEGORIES: [&str; 16] = [
+    "keyspace",
+    "read",
+    "write",
+    "string",
+    "list",
+    "hash",
+    "set",
+    "sortedset",
+    "pubsub",
+    "admin",
+    "fast",
+    "slow",
+    "dangerous",
+    "connection",
+    "transaction",
+    "blocking",
+];
+
+const COMMANDS: &[(&str, &[&str])] = &[
+    ("set", &["write", "string", "slow"]),
+    ("get", &["read", "string", "fast"]),
+    ("setnx", &["write", "string", "fast"]),
+    ("setex", &["write", "string", "slow"]),
+    ("psetex", &["write", "string", "slow"]),
+    ("getset", &["write", "string", "fast"]),
+    ("getdel", &["write", "string", "fast"]),
+    ("getex", &["write", "string", "fast"]),
+    ("mget", &["read", "string", "fast"]),
+    ("mset", &["write", "string", "slow"]),
+    ("msetnx", &["write", "string", "slow"]),
+    ("incr", &["write", "string", "fast"]),
+    ("decr", &["write", "string", "fast"]),
+    ("incrby", &["write", "string", "fast"]),
+    ("decrby", &["write", "string", "fast"]),
+    ("incrbyfloat", &["write", "string", "fast"]),
+    ("append", &["write", "string", "fast"]),
+    ("strlen", &["read", "string", "fast"]),
+    ("getrange", &["read", "string", "slow"]),
+    ("setrange", &["write", "string", "slow"]),
+    ("del", &["keyspace", "write", "slow"]),
+    ("type", &["keyspace", "read", "fast"]),
+    ("exists", &["keyspace", "read", "fast"]),
+    ("keys", &["keyspace", "read", "slow", "dangerous"]),
+    ("scan", &["keyspace", "read", "slow"]),
+    ("randomkey", &["keyspace", "read", "slow"]),
+    ("dbsize", &["keyspace", "read", "fast"]),
+    ("expire", &["keyspace", "write", "fast"]),
+    ("pexpire", &["keyspace", "write", "fast"]),
+    ("expireat", &["keyspace", "write", "fast"]),
+    ("pexpireat", &["keyspace", "write", "fast"]),
+    ("ttl", &["keyspace", "read", "fast"]),
+    ("pttl", &["keyspace", "read", "fast"]),
+    ("expiretime", &["keyspace", "read", "fast"]),
+    ("pexpiretime", &["keyspace", "read", "fast"]),
+    ("persist", &["keyspace", "write", "fast"]),
+    ("lpush", &["write", "list", "fast"]),
+    ("rpush", &["write", "list", "fast"]),
+    ("lpushx", &["write", "list", "fast"]),
+    ("rpushx", &["write", "list", "fast"]),
+    ("lpop", &["write", "list", "fast"]),
+    ("rpop", &["write", "list", "fast"]),
+    ("lrange", &["read", "list", "slow"]),
+    ("llen", &["read", "list", "fast"]),
+    ("lindex", &["read", "list", "slow"]),
+    ("lset", &["write", "list", "slow"]),
+    ("lrem", &["write", "list", "slow"]),
+    ("ltrim", &["write", "list", "slow"]),
+    ("linsert", &["write", "list", "slow"]),
+    ("lmove", &["write", "list", "slow"]),
+    ("blpop", &["write", "list", "slow", "blocking"]),
+    ("brpop", &["write", "list", "slow", "blocking"]),
+    ("blmove", &["write", "list", "slow", "blocking"]),
+    ("hset", &["write", "hash", "fast"]),
+    ("hmset", &["write", "hash", "fast"]),
+    ("hget", &["read", "hash", "fast"]),
+    ("hmget", &["read", "hash", "fast"]),
+    ("hdel", &["write", "hash", "fast"]),
+    ("hgetall", &["read", "hash", "slow"]),
+    ("hkeys", &["read", "hash", "slow"]),
+    ("hvals", &["read", "hash", "slow"]),
+    ("hlen", &["read", "hash", "fast"]),
+    ("hexists", &["read", "hash", "fast"]),
+    ("hincrby", &["write", "hash", "fast"]),
+    ("hscan", &["read", "hash", "slow"]),
+    ("sadd", &["write", "set", "fast"]),
+    ("srem", &["write", "set", "fast"]),
+    ("smembers", &["read", "set", "slow"]),
+    ("sismember", &["read", "set", "fast"]),
+    ("scard", &["read", "set", "fast"]),
+    ("sinter", &["read", "set", "slow"]),
+    ("sunion", &["read", "set", "slow"]),
+    ("sdiff", &["read", "set", "slow"]),
+    ("sinterstore", &["write", "set", "slow"]),
+    ("sunionstore", &["write", "set", "slow"]),
+    ("sdiffstore", &["write", "set", "slow"]),
+    ("spop", &["write", "set", "fast"]),
+    ("srandmember", &["read", "set", "slow"]),
+    ("zadd", &["write", "sortedset", "fast"]),
+    ("zcard", &["read", "sortedset", "fast"]),
+    ("zscore", &["read", "sortedset", "fast"]),
+    ("zincrby", &["write", "sortedset", "fast"]),
+    ("zrem", &["write", "sortedset", "fast"]),
+    ("zrank", &["read", "sortedset", "fast"]),
+    ("zrevrank", &["read", "sortedset", "fast"]),
+    ("zcount", &["read", "sortedset", "fast"]),
+    ("zrange", &["read", "sortedset", "slow"]),
+    ("zrevrange", &["read", "sortedset", "slow"]),
+    ("zrangebyscore", &["read", "sortedset", "slow"]),
+    ("zrevrangebyscore", &["read", "sortedset", "slow"]),
+    ("zrangebylex", &["read", "sortedset", "slow"]),
+    ("zrevrangebylex", &["read", "sortedset", "slow"]),
+    ("zpopmin", &["write", "sortedset", "fast"]),
+    ("zpopmax", &["write", "sortedset", "fast"]),
+    ("save", &["admin", "slow", "dangerous"]),
+    ("bgsave", &["admin", "slow", "dangerous"]),
+    ("bgrewriteaof", &["admin", "slow", "dangerous"]),
+    ("lastsave", &["admin", "fast", "dangerous"]),
+    ("acl", &["admin", "slow", "dangerous"]),
+    ("config", &["admin", "slow", "dangerous"]),
+    ("info", &["slow", "dangerous"]),
+    ("replicaof", &["admin", "slow", "dangerous"]),
+    ("slaveof", &["admin", "slow", "dangerous"]),
+    ("psync", &["admin", "slow", "dangerous"]),
+    ("sync", &["admin", "slow", "dangerous"]),
+    ("replconf", &["admin", "slow", "dangerous"]),
+    ("command", &["connection", "slow"]),
+    ("hello", &["connection", "fast"]),
+    ("auth", &["connection", "fast"]),
+    ("ping", &["connection", "fast"]),
+    ("multi", &["transaction", "fast"]),
+    ("exec", &["transaction", "slow"]),
+    ("discard", &["transaction", "fast"]),
+    ("watch", &["transaction", "fast"]),
+    ("unwatch", &["transaction", "fast"]),
+    ("subscribe", &["pubsub", "slow"]),
+    ("unsubscribe", &["pubsub", "slow"]),
+    ("psubscribe", &["pubsub", "slow"]),
+    ("punsubscribe", &["pubsub", "slow"]),
+    ("publish", &["pubsub", "fast"]),
+    ("pubsub", &["pubsub", "slow"]),
+];
+
+// the command name as it is in the table, None for an unknown command
+pub fn command(name: &str) -> Option<&'static str> {
+    COMMANDS.iter().find(|(x, _)| *x == name).map(|(x, _)| *x)
+}
+
+pub fn is_category(name: &str) -> bool {
+    name == "all" || CATEGORIES.contains(&name)
+}
+
+// the commands of the category, all the commands for @all
+pub fn commands(category: &str) -> impl Iterator<Item = &'static str> + '_ {
+    COMMANDS
+        .iter()
+        .filter(move |(_, categories)| category == "all" || categories.contains(&category))
+        .map(|(x, _)| *x)
+}
+
+pub fn in_category(command: &str, category: &str) -> bool {
+    COMMANDS
+        .iter()
+        .any(|(x, categories)| *x == command && categories.contains(&category))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command, commands, is_category, CATEGORIES};
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(Some("get"), command("get"));
+        assert_eq!(None, command("unknown"));
+        assert!(is_category("all"));
+        assert!(!is_category("unknown"));
+        assert!(commands("dangerous").any(|x| x == "keys"));
+        assert!(!commands("read").any(|x| x == "set"));
+        // every category has a command
+        for category in CATEGORIES {
+            assert!(commands(category).next().is_some(), "{category}");
+        }
+    }
+}
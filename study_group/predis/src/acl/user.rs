@@ -0,0 +1,314 @@
+use std::collections::{BTreeSet, HashSet};
+
+use crate::acl::category;
+use crate::acl::sha256::sha256_hex;
+use crate::redis_protocol::string_match::string_match;
+
+use anyhow::Result;
+
+// one ACL user, the rules are applied in order like ACL SETUSER
+// https://redis.io/docs/management/security/acl/#acl-rules
+#[derive(Clone, PartialEq, Debug)]
+pub struct User {
+    name: String,
+    enabled: bool,
+    // any password is accepted
+    nopass: bool,
+    // the sha-256 of the passwords in hex, the passwords are not kept
+    passwords: BTreeSet<String>,
+    // the command rules in order like +@all -@dangerous, the allowed commands are computed from them
+    command_rules: Vec<String>,
+    allowed: HashSet<&'static str>,
+    key_patterns: Vec<Vec<u8>>,
+}
+
+impl User {
+    // a new user is disabled without password, command and key like redis
+    pub fn new(name: &str) -> Self {
+        User {
+            name: name.to_string(),
+            enabled: false,
+            nopass: false,
+            passwords: BTreeSet::new(),
+            command_rules: vec!["-@all".to_string()],
+            allowed: HashSet::new(),
+            key_patterns: Vec::new(),
+        }
+    }
+
+    // the user of the connections without AUTH, it requires the password when requirepass is set
+    pub fn default_user(requirepass: Option<&str>) -> Self {
+        let mut user = User::new("default");
+        user.enabled = true;
+        user.key_patterns.push(b"*".to_vec());
+        user.command_rule("+@all").unwrap();
+        match requirepass {
+            Some(password) => {
+                user.passwords.insert(sha256_hex(password.as_bytes()));
+            }
+            None => user.nopass = true,
+        }
+        user
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn nopass(&self) -> bool {
+        self.nopass
+    }
+
+    // the rules are applied together, the user is not changed when one of them is invalid
+    pub fn apply_rules(&mut self, rules: &[Vec<u8>]) -> Result<()> {
+        let mut user = self.clone();
+        for rule in rules {
+            user.apply_rule(rule).map_err(|e| {
+                anyhow::anyhow!(
+                    "ERR Error in ACL SETUSER modifier '{}': {e}",
+                    String::from_utf8_lossy(rule)
+                )
+            })?;
+        }
+        *self = user;
+        Ok(())
+    }
+
+    fn apply_rule(&mut self, rule: &[u8]) -> Result<()> {
+        match rule.to_ascii_lowercase().as_slice() {
+            b"on" => self.enabled = true,
+            b"off" => self.enabled = false,
+            b"nopass" => {
+                self.nopass = true;
+                self.passwords.clear();
+            }
+            b"resetpass" => {
+                self.nopass = false;
+                self.passwords.clear();
+            }
+            b"allkeys" => self.key_patterns = vec![b"*".to_vec()],
+            b"resetkeys" => self.key_patterns.clear(),
+            b"allcommands" => self.command_rule("+@all")?,
+            b"nocommands" => self.command_rule("-@all")?,
+            b"reset" => *self = User::new(&self.name),
+            _ => match rule.split_first() {
+                Some((b'>', password)) => {
+                    self.passwords.insert(sha256_hex(password));
+                    self.nopass = false;
+                }
+                Some((b'<', password)) => anyhow::ensure!(
+                    self.passwords.remove(&sha256_hex(password)),
+                    "The password you are trying to remove from the user does not exist"
+                ),
+                Some((b'#', hash)) => {
+                    self.passwords.insert(parse_hash(hash)?);
+                    self.nopass = false;
+                }
+                Some((b'!', hash)) => anyhow::ensure!(
+                    self.passwords.remove(&parse_hash(hash)?),
+                    "The password you are trying to remove from the user does not exist"
+                ),
+                Some((b'~', pattern)) => {
+                    if !self.key_patterns.iter().any(|x| x == pattern) {
+                        self.key_patterns.push(pattern.to_vec());
+                    }
+                }
+                Some((b'+' | b'-', _)) => {
+                    self.command_rule(&String::from_utf8_lossy(rule).to_lowercase())?
+                }
+                _ => anyhow::bail!("Syntax error"),
+            },
+        }
+        Ok(())
+    }
+
+    // +command, -command, +@category or -@category
+    fn command_rule(&mut self, rule: &str) -> Result<()> {
+        let (sign, name) = rule.split_at(1);
+        let commands: Vec<&'static str> = match name.strip_prefix('@') {
+            Some(category) if category::is_category(category) => {
+                category::commands(category).collect()
+            }
+            Some(_) => anyhow::bail!("Unknown command or category name in ACL"),
+            None => vec![category::command(name)
+                .ok_or_else(|| anyhow::anyhow!("Unknown command or category name in ACL"))?],
+        };
+        // the rules before +@all or -@all have no effect anymore
+        if name == "@all" {
+            self.command_rules.clear();
+        }
+        for command in commands {
+            if sign == "+" {
+                self.allowed.insert(command);
+            } else {
+                self.allowed.remove(command);
+            }
+        }
+        self.command_rules.push(rule.to_string());
+        Ok(())
+    }
+
+    // the password is checked with its hash, any password for nopass
+    pub fn check_password(&self, password: &[u8]) -> bool {
+        self.enabled && (self.nopass || self.passwords.contains(&sha256_hex(password)))
+    }
+
+    // the unknown command is left to the parser which rejects it
+    pub fn can_run(&self, command: &str) -> bool {
+        category::command(command).is_none_or(|x| self.allowed.contains(x))
+    }
+
+    pub fn can_access(&self, key: &[u8]) -> bool {
+        self.key_patterns
+            .iter()
+            .any(|pattern| string_match(pattern, key, false))
+    }
+
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut flags = vec![if self.enabled { "on" } else { "off" }];
+        if self.nopass {
+            flags.push("nopass");
+        }
+        flags
+    }
+
+    pub fn passwords(&self) -> impl Iterator<Item = &String> {
+        self.passwords.iter()
+    }
+
+    pub fn commands(&self) -> String {
+        self.command_rules.join(" ")
+    }
+
+    pub fn keys(&self) -> String {
+        self.key_patterns
+            .iter()
+            .map(|x| format!("~{}", String::from_utf8_lossy(x)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // the rules of ACL LIST and the ACL file, applied to a new user they create the same user
+    pub fn describe(&self) -> String {
+        let mut rules: Vec<String> = self.flags().iter().map(|x| x.to_string()).collect();
+        rules.extend(self.passwords.iter().map(|x| format!("#{x}")));
+        let keys = self.keys();
+        if !keys.is_empty() {
+            rules.push(keys);
+        }
+        rules.push(self.commands());
+        rules.join(" ")
+    }
+}
+
+// the #<hash> rule has the sha-256 in lowercase hex
+fn parse_hash(hash: &[u8]) -> Result<String> {
+    anyhow::ensure!(
+        hash.len() == 64 && hash.iter().all(|x| matches!(x, b'0'..=b'9' | b'a'..=b'f')),
+        "The password hash must be exactly 64 characters and contain only lowercase hexadecimal characters"
+    );
+    Ok(String::from_utf8_lossy(hash).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::User;
+    use crate::acl::sha256::sha256_hex;
+
+    fn rules(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_apply_rules() {
+        // arrange
+        let mut user = User::new("alice");
+        // act
+        user.apply_rules(&rules(&[
+            "on", ">secret", "~cache:*", "+@read", "-@hash", "+hset",
+        ]))
+        .unwrap();
+        // assert
+        assert!(user.check_password(b"secret"));
+        assert!(!user.check_password(b"other"));
+        assert!(user.can_run("get"));
+        assert!(!user.can_run("set"));
+        assert!(!user.can_run("hget"));
+        assert!(user.can_run("hset"));
+        assert!(user.can_access(b"cache:1"));
+        assert!(!user.can_access(b"other"));
+        assert_eq!(
+            format!(
+                "on #{} ~cache:* -@all +@read -@hash +hset",
+                sha256_hex(b"secret")
+            ),
+            user.describe()
+        );
+    }
+
+    #[test]
+    fn test_apply_rules_all() {
+        // arrange
+        let mut user = User::new("bob");
+        // act
+        user.apply_rules(&rules(&[
+            "+get",
+            "allcommands",
+            "-@dangerous",
+            "allkeys",
+            "nopass",
+        ]))
+        .unwrap();
+        // assert
+        assert!(!user.check_password(b"any"));
+        user.apply_rules(&rules(&["on"])).unwrap();
+        assert!(user.check_password(b"any"));
+        assert!(user.can_run("set"));
+        assert!(!user.can_run("keys"));
+        assert!(user.can_access(b"any"));
+        assert_eq!("on nopass ~* +@all -@dangerous", user.describe());
+    }
+
+    #[test]
+    fn test_apply_rules_failed() {
+        // arrange
+        let mut user = User::new("carol");
+        // act
+        let unknown = user.apply_rules(&rules(&["on", "+unknown"]));
+        let syntax = user.apply_rules(&rules(&["bad"]));
+        let password = user.apply_rules(&rules(&["<missing"]));
+        // assert
+        assert_eq!(
+            "ERR Error in ACL SETUSER modifier '+unknown': Unknown command or category name in ACL",
+            unknown.unwrap_err().to_string()
+        );
+        assert!(syntax.is_err());
+        assert!(password.is_err());
+        // the user is not changed by the invalid rules
+        assert_eq!(User::new("carol"), user);
+    }
+
+    #[test]
+    fn test_describe_round_trip() {
+        // arrange
+        let mut user = User::new("dave");
+        user.apply_rules(&rules(&[
+            "on", ">p1", ">p2", "~a*", "~b", "+@string", "-append",
+        ]))
+        .unwrap();
+        let description: Vec<Vec<u8>> = user
+            .describe()
+            .split(' ')
+            .map(|x| x.as_bytes().to_vec())
+            .collect();
+        // act
+        let mut created = User::new("dave");
+        created.apply_rules(&description).unwrap();
+        // assert
+        assert_eq!(user, created);
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::Write;
+
+use predis::data_watcher::{data_value::DataValue, rdb};
+
+// dump a redis rdb file as json for inspection
+// usage: predis-rdb <file>
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: predis-rdb <file>");
+        std::process::exit(2);
+    };
+    let entries = std::fs::read(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| rdb::decode(&bytes));
+    match entries {
+        Ok(entries) => print!("{}", to_json(&entries)),
+        Err(e) => {
+            eprintln!("load {path} error={e:#}");
+            std::process::exit(1);
+        }
+    }
+}
+
+// one key per line, the hash fields and set members are sorted so the output is stable
+fn to_json(entries: &[rdb::RdbEntry]) -> String {
+    let mut json = String::from("[\n");
+    for (i, entry) in entries.iter().enumerate() {
+        let expire_ms = match entry.value.expired_epoch() {
+            Some(expired) => expired.as_millis().to_string(),
+            None => "null".to_string(),
+        };
+        let value = match entry.value.value() {
+            DataValue::String(v) => string(v),
+            DataValue::List(v) => array(v.iter().map(|x| string(x))),
+            DataValue::Set(v) => {
+                let mut members: Vec<&Vec<u8>> = v.iter().collect();
+                members.sort();
+                array(members.into_iter().map(|x| string(x)))
+            }
+            DataValue::Hash(v) => {
+                let mut fields: Vec<(&Vec<u8>, &Vec<u8>)> = v.iter().collect();
+                fields.sort();
+                let fields: Vec<String> = fields
+                    .into_iter()
+                    .map(|(field, value)| format!("{}:{}", string(field), string(value)))
+                    .collect();
+                format!("{{{}}}", fields.join(","))
+            }
+            DataValue::ZSet(v) => array(
+                v.iter()
+                    .map(|(member, score)| format!("[{},{}]", string(member), number(score))),
+            ),
+        };
+        let _ = write!(
+            json,
+            "  {{\"db\":{},\"key\":{},\"type\":\"{}\",\"expire_ms\":{},\"value\":{}}}",
+            entry.db,
+            string(&entry.key),
+            entry.value.value().type_name(),
+            expire_ms,
+            value
+        );
+        json.push_str(if i + 1 < entries.len() { ",\n" } else { "\n" });
+    }
+    json.push_str("]\n");
+    json
+}
+
+fn array(items: impl Iterator<Item = String>) -> String {
+    format!("[{}]", items.collect::<Vec<_>>().join(","))
+}
+
+// json has no infinity or nan, they are written as strings
+fn number(score: f64) -> String {
+    if score.is_finite() {
+        score.to_string()
+    } else {
+        string(score.to_string().as_bytes())
+    }
+}
+
+// the bytes which are not utf-8 are written as the latin-1 characters \u0080-\u00ff
+fn string(s: &[u8]) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for chunk in s.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                '\n' => json.push_str("\\n"),
+                '\r' => json.push_str("\\r"),
+                '\t' => json.push_str("\\t"),
+                c if (c as u32) < 0x20 => {
+                    let _ = write!(json, "\\u{:04x}", c as u32);
+                }
+                c => json.push(c),
+            }
+        }
+        for b in chunk.invalid() {
+            let _ = write!(json, "\\u{b:04x}");
+        }
+    }
+    json.push('"');
+    json
+}
@@ -1,23 +1,578 @@
+pub mod config_file;
+pub mod live;
+
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::acl::AclConfig;
+use crate::data_watcher::aof::{AofConfig, AppendFsync};
+use crate::data_watcher::eviction::{MaxmemoryConfig, MaxmemoryPolicy};
+use crate::data_watcher::snapshot::{SaveRule, SnapshotConfig};
+use crate::replication::ReplicationConfig;
+
+use anyhow::{Context, Result};
+
+#[derive(Clone, PartialEq, Debug)]
 pub struct Configuration {
-    pub port: i32,
+    // the file of CONFIG REWRITE, None when the server starts without one
+    pub config_file: Option<PathBuf>,
+    pub bind: IpAddr,
+    pub port: u16,
     pub workers: usize,
+    // the number of data watchers owning one part of the keyspace each, like the number of cores
+    pub shards: usize,
+    pub snapshot: SnapshotConfig,
+    pub aof: AofConfig,
+    pub acl: AclConfig,
+    pub maxmemory: MaxmemoryConfig,
+    pub replication: ReplicationConfig,
+}
+
+// one parameter of the config file, the command line, CONFIG GET and CONFIG SET
+struct Param {
+    name: &'static str,
+    // CONFIG SET changes it while the server is running, see live::LiveConfig
+    mutable: bool,
+    get: fn(&Configuration) -> String,
+    set: fn(&mut Configuration, &str) -> Result<()>,
+}
+
+const PARAMS: &[Param] = &[
+    Param {
+        name: "bind",
+        mutable: false,
+        get: |config| config.bind.to_string(),
+        set: |config, value| {
+            config.bind = value
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid bind address '{value}'"))?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "port",
+        mutable: false,
+        get: |config| config.port.to_string(),
+        set: |config, value| {
+            config.port = parse_number(value, 0, u16::MAX)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "workers",
+        mutable: false,
+        get: |config| config.workers.to_string(),
+        set: |config, value| {
+            config.workers = parse_number(value, 1, usize::MAX)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "shards",
+        mutable: false,
+        get: |config| config.shards.to_string(),
+        set: |config, value| {
+            config.shards = parse_number(value, 1, usize::MAX)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "dbfilename",
+        mutable: true,
+        get: |config| config.snapshot.path.display().to_string(),
+        set: |config, value| {
+            config.snapshot.path = parse_path(value)?;
+            Ok(())
+        },
+    },
+    Param {
+        // an empty value disables the automatic save
+        name: "save",
+        mutable: true,
+        get: |config| {
+            config
+                .snapshot
+                .save_rules
+                .iter()
+                .map(|x| format!("{} {}", x.seconds, x.changes))
+                .collect::<Vec<_>>()
+                .join(" ")
+        },
+        set: |config, value| {
+            config.snapshot.save_rules = SaveRule::parse_rules(value)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "appendonly",
+        mutable: false,
+        get: |config| format_yes_no(config.aof.enabled),
+        set: |config, value| {
+            config.aof.enabled = parse_yes_no(value)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "appendfilename",
+        mutable: false,
+        get: |config| config.aof.path.display().to_string(),
+        set: |config, value| {
+            config.aof.path = parse_path(value)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "appendfsync",
+        mutable: true,
+        get: |config| {
+            match config.aof.fsync {
+                AppendFsync::Always => "always",
+                AppendFsync::EverySec => "everysec",
+                AppendFsync::No => "no",
+            }
+            .to_string()
+        },
+        set: |config, value| {
+            config.aof.fsync = AppendFsync::parse(value)?;
+            Ok(())
+        },
+    },
+    Param {
+        // an empty value is no password like redis
+        name: "requirepass",
+        mutable: true,
+        get: |config| config.acl.requirepass.clone().unwrap_or_default(),
+        set: |config, value| {
+            config.acl.requirepass = (!value.is_empty()).then(|| value.to_string());
+            Ok(())
+        },
+    },
+    Param {
+        name: "aclfile",
+        mutable: false,
+        get: |config| {
+            config
+                .acl
+                .aclfile
+                .as_ref()
+                .map(|x| x.display().to_string())
+                .unwrap_or_default()
+        },
+        set: |config, value| {
+            config.acl.aclfile = (!value.is_empty()).then(|| PathBuf::from(value));
+            Ok(())
+        },
+    },
+    Param {
+        name: "maxmemory",
+        mutable: true,
+        get: |config| config.maxmemory.maxmemory.to_string(),
+        set: |config, value| {
+            config.maxmemory.maxmemory = parse_memory(value)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "maxmemory-policy",
+        mutable: true,
+        get: |config| config.maxmemory.policy.name().to_string(),
+        set: |config, value| {
+            config.maxmemory.policy = MaxmemoryPolicy::parse(value)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "maxmemory-samples",
+        mutable: true,
+        get: |config| config.maxmemory.samples.to_string(),
+        set: |config, value| {
+            config.maxmemory.samples = parse_number(value, 1, 64)?;
+            Ok(())
+        },
+    },
+    Param {
+        // "host port" at startup, REPLICAOF changes it while the server is running
+        name: "replicaof",
+        mutable: false,
+        get: |config| {
+            config
+                .replication
+                .replicaof
+                .as_ref()
+                .map(|(host, port)| format!("{host} {port}"))
+                .unwrap_or_default()
+        },
+        set: |config, value| {
+            config.replication.replicaof = parse_replicaof(value)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "replica-read-only",
+        mutable: true,
+        get: |config| format_yes_no(config.replication.read_only),
+        set: |config, value| {
+            config.replication.read_only = parse_yes_no(value)?;
+            Ok(())
+        },
+    },
+    Param {
+        name: "masterauth",
+        mutable: true,
+        get: |config| config.replication.masterauth.clone().unwrap_or_default(),
+        set: |config, value| {
+            config.replication.masterauth = (!value.is_empty()).then(|| value.to_string());
+            Ok(())
+        },
+    },
+    Param {
+        name: "repl-backlog-size",
+        mutable: true,
+        get: |config| config.replication.backlog_size.to_string(),
+        set: |config, value| {
+            config.replication.backlog_size = parse_memory(value)?;
+            anyhow::ensure!(
+                config.replication.backlog_size > 0,
+                "argument must be a memory value"
+            );
+            Ok(())
+        },
+    },
+];
+
+fn param(name: &str) -> Option<&'static Param> {
+    PARAMS.iter().find(|x| x.name.eq_ignore_ascii_case(name))
 }
 
 impl Configuration {
-    pub fn new() -> Self {
-        const DEFAULT_PORT: i32 = 6379;
-        const DEFAULT_WORKERS: usize = 1;
-        let port = std::env::var("PORT").unwrap_or(DEFAULT_PORT.to_string());
-        let workers = std::env::var("WORKERS").unwrap_or(DEFAULT_WORKERS.to_string());
-        Configuration {
-            port: port.parse::<i32>().unwrap_or(DEFAULT_PORT),
-            workers: workers.parse::<usize>().unwrap_or(DEFAULT_WORKERS),
+    // predis [/path/to/predis.conf] [--port 6380] [--save 60 1000] ...
+    // the config file is overridden by the environment variables like PREDIS_PORT, then by the flags
+    pub fn load(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        Self::load_with_env(args, |x| std::env::var(x).ok())
+    }
+
+    fn load_with_env(
+        args: impl IntoIterator<Item = String>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut args = args.into_iter().peekable();
+        let mut config = Configuration::default();
+        if let Some(path) = args.next_if(|x| !x.starts_with("--")) {
+            let path = PathBuf::from(path);
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("read config file {}", path.display()))?;
+            for (line, name, value) in config_file::parse(&content)
+                .with_context(|| format!("config file {}", path.display()))?
+            {
+                config.set(&name, &value).with_context(|| {
+                    format!("config file {}:{line}: '{name} {value}'", path.display())
+                })?;
+            }
+            config.config_file = Some(path);
+        }
+        // the environment variable of dbfilename is PREDIS_DBFILENAME, PORT and WORKERS are kept
+        // from before the config file
+        for param in PARAMS {
+            let variable = format!("PREDIS_{}", param.name.to_uppercase().replace('-', "_"));
+            let legacy =
+                matches!(param.name, "port" | "workers").then(|| param.name.to_uppercase());
+            for variable in legacy.into_iter().chain([variable]) {
+                if let Some(value) = env(&variable) {
+                    config
+                        .set(param.name, &value)
+                        .with_context(|| format!("environment variable {variable}='{value}'"))?;
+                }
+            }
         }
+        // one flag takes the arguments until the next one, like --save 900 1 300 10
+        let mut flags: Vec<(String, Vec<String>)> = Vec::new();
+        for arg in args {
+            match (arg.strip_prefix("--"), flags.last_mut()) {
+                (Some(name), _) => flags.push((name.to_string(), Vec::new())),
+                (None, Some((_, values))) => values.push(arg),
+                (None, None) => anyhow::bail!("unexpected argument '{arg}'"),
+            }
+        }
+        for (name, values) in flags {
+            let value = values.join(" ");
+            config
+                .set(&name, &value)
+                .with_context(|| format!("command line --{name} '{value}'"))?;
+        }
+        Ok(config)
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        param(name).map(|x| (x.get)(self))
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        let param = param(name)
+            .ok_or_else(|| anyhow::anyhow!("Bad directive or wrong number of arguments"))?;
+        (param.set)(self, value)
+    }
+
+    // CONFIG REWRITE, the lines of the parameters are updated and the other lines are kept
+    // the parameters missing from the file are added when they are not the default
+    pub fn rewrite(&self) -> Result<()> {
+        let path = self
+            .config_file
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("ERR The server is running without a config file"))?;
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("ERR read {}", path.display())),
+        };
+        let default = Configuration::default();
+        let params: Vec<(&str, String, bool)> = PARAMS
+            .iter()
+            .map(|x| {
+                let value = (x.get)(self);
+                let is_default = value == (x.get)(&default);
+                (x.name, value, is_default)
+            })
+            .collect();
+        write_file(path, &config_file::rewrite(&content, &params))
+            .with_context(|| format!("ERR Rewriting config file {}", path.display()))
     }
 }
 
 impl Default for Configuration {
     fn default() -> Self {
-        Self::new()
+        Configuration {
+            config_file: None,
+            bind: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 6379,
+            workers: 1,
+            shards: 1,
+            snapshot: SnapshotConfig {
+                path: PathBuf::from("dump.pdb"),
+                // same as the default save rules of redis
+                save_rules: SaveRule::parse_rules("3600 1 300 100 60 10000").unwrap(),
+            },
+            // the append only file is loaded instead of the snapshot when it is enabled
+            aof: AofConfig::default(),
+            acl: AclConfig::default(),
+            maxmemory: MaxmemoryConfig::default(),
+            replication: ReplicationConfig::default(),
+        }
+    }
+}
+
+// write a temp file then rename it, the config file is never half written
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    let temp = path.with_file_name(format!("temp-{}.conf", std::process::id()));
+    let result = fs::write(&temp, content).and_then(|_| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    Ok(result?)
+}
+
+fn parse_number<T: FromStr + PartialOrd + std::fmt::Display>(
+    value: &str,
+    min: T,
+    max: T,
+) -> Result<T> {
+    let number: T = value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("argument couldn't be parsed into an integer"))?;
+    anyhow::ensure!(
+        number >= min && number <= max,
+        "argument must be between {min} and {max} inclusive"
+    );
+    Ok(number)
+}
+
+// the bytes with an optional unit like redis, 1k is 1000 bytes and 1kb is 1024 bytes
+fn parse_memory(value: &str) -> Result<usize> {
+    let lowercase = value.to_lowercase();
+    let split = lowercase
+        .find(|x: char| !x.is_ascii_digit())
+        .unwrap_or(lowercase.len());
+    let (number, unit) = lowercase.split_at(split);
+    let unit = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => anyhow::bail!("argument must be a memory value"),
+    };
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|x| x.checked_mul(unit))
+        .ok_or_else(|| anyhow::anyhow!("argument must be a memory value"))
+}
+
+// host port, an empty value or "no one" is a primary
+fn parse_replicaof(value: &str) -> Result<Option<(String, u16)>> {
+    if value.is_empty() || value.eq_ignore_ascii_case("no one") {
+        return Ok(None);
+    }
+    let Some((host, port)) = value.split_once(' ') else {
+        anyhow::bail!("argument must be 'host port'");
+    };
+    Ok(Some((host.to_string(), parse_number(port, 1, u16::MAX)?)))
+}
+
+fn parse_yes_no(value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => anyhow::bail!("argument must be 'yes' or 'no'"),
+    }
+}
+
+fn format_yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+fn parse_path(value: &str) -> Result<PathBuf> {
+    anyhow::ensure!(!value.is_empty(), "argument can't be empty");
+    Ok(PathBuf::from(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Configuration;
+    use crate::data_watcher::aof::AppendFsync;
+    use std::net::IpAddr;
+
+    fn args(input: &[&str]) -> Vec<String> {
+        input.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_load() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("predis-{}-load.conf", std::process::id()));
+        std::fs::write(
+            &path,
+            "# predis\nbind 0.0.0.0\nport 7000\nsave \"\"\nappendfsync always\n",
+        )
+        .unwrap();
+        let env = |x: &str| match x {
+            "PORT" => Some("7001".to_string()),
+            "WORKERS" => Some("3".to_string()),
+            "PREDIS_APPENDFSYNC" => Some("no".to_string()),
+            "SHARDS" => Some("4".to_string()),
+            _ => None,
+        };
+        // act
+        let config = Configuration::load_with_env(
+            args(&[
+                path.to_str().unwrap(),
+                "--port",
+                "7002",
+                "--save",
+                "900",
+                "1",
+                "--maxmemory",
+                "2mb",
+                "--maxmemory-policy",
+                "allkeys-lru",
+                "--replicaof",
+                "127.0.0.1",
+                "6380",
+            ]),
+            env,
+        );
+        // assert
+        std::fs::remove_file(&path).unwrap();
+        let config = config.unwrap();
+        assert_eq!(Some(path), config.config_file);
+        assert_eq!("0.0.0.0".parse::<IpAddr>().unwrap(), config.bind);
+        assert_eq!(7002, config.port);
+        assert_eq!(Some("900 1".to_string()), config.get("save"));
+        assert_eq!(3, config.workers);
+        assert_eq!(AppendFsync::No, config.aof.fsync);
+        // only PORT and WORKERS are read without the prefix
+        assert_eq!(1, config.shards);
+        assert_eq!(2 * 1024 * 1024, config.maxmemory.maxmemory);
+        assert_eq!(
+            Some("allkeys-lru".to_string()),
+            config.get("maxmemory-policy")
+        );
+        assert_eq!(
+            Some(("127.0.0.1".to_string(), 6380)),
+            config.replication.replicaof
+        );
+        assert!(config.replication.read_only);
+    }
+
+    #[test]
+    fn test_load_failed() {
+        let load = |input: &[&str]| {
+            format!(
+                "{:#}",
+                Configuration::load_with_env(args(input), |x| (x == "PREDIS_SHARDS")
+                    .then(|| "0".to_string()))
+                .unwrap_err()
+            )
+        };
+        assert_eq!(
+            "environment variable PREDIS_SHARDS='0': argument must be between 1 and 18446744073709551615 inclusive",
+            load(&[])
+        );
+        let load = |input: &[&str]| {
+            format!(
+                "{:#}",
+                Configuration::load_with_env(args(input), |_| None).unwrap_err()
+            )
+        };
+        assert_eq!(
+            "command line --port 'abc': argument couldn't be parsed into an integer",
+            load(&["--port", "abc"])
+        );
+        assert_eq!(
+            "command line --appendonly 'maybe': argument must be 'yes' or 'no'",
+            load(&["--appendonly", "maybe"])
+        );
+        assert_eq!(
+            "command line --maxmemory '1x': argument must be a memory value",
+            load(&["--maxmemory", "1x"])
+        );
+        assert_eq!(
+            "command line --replicaof '127.0.0.1': argument must be 'host port'",
+            load(&["--replicaof", "127.0.0.1"])
+        );
+        assert_eq!(
+            "command line --unknown '1': Bad directive or wrong number of arguments",
+            load(&["--unknown", "1"])
+        );
+        assert!(load(&["--bind", "localhost"]).contains("Invalid bind address"));
+        assert!(load(&["/missing/predis.conf"]).starts_with("read config file"));
+    }
+
+    #[test]
+    fn test_rewrite() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("predis-{}-rewrite.conf", std::process::id()));
+        std::fs::write(&path, "# predis\nport 7000\nsave 60 1\n").unwrap();
+        let mut config =
+            Configuration::load_with_env(args(&[path.to_str().unwrap()]), |_| None).unwrap();
+        config.set("save", "").unwrap();
+        config.set("appendfsync", "no").unwrap();
+        // act
+        let result = config.rewrite();
+        // assert
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_ok());
+        assert_eq!(
+            "# predis\nport 7000\nsave \"\"\n\n# Generated by CONFIG REWRITE\nappendfsync no\n",
+            content
+        );
+        assert_eq!(
+            "ERR The server is running without a config file",
+            Configuration::default().rewrite().unwrap_err().to_string()
+        );
     }
 }
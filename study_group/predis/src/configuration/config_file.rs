@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+// the redis.conf format, one parameter per line: name value, the comments start with #
+// the arguments of a line are joined, so save 900 1 300 10 and save "900 1 300 10" are the same
+// return the line number, the name in lowercase and the value of every parameter
+pub fn parse(content: &str) -> Result<Vec<(usize, String, String)>> {
+    let mut params = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut args = split_args(line).map_err(|e| anyhow::anyhow!("{}: {e}", number + 1))?;
+        let name = args.remove(0).to_lowercase();
+        params.push((number + 1, name, args.join(" ")));
+    }
+    Ok(params)
+}
+
+// split the line like sdssplitargs of redis, "double quotes" have the escapes \n \r \t \\ \" and \xHH,
+// 'single quotes' have \' only
+pub fn split_args(line: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|x| x.is_whitespace()).is_some() {}
+        let Some(first) = chars.next() else {
+            return Ok(args);
+        };
+        let mut arg = String::new();
+        match first {
+            '"' => loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some('n') => arg.push('\n'),
+                        Some('r') => arg.push('\r'),
+                        Some('t') => arg.push('\t'),
+                        Some('x') => {
+                            let hex: String = chars.by_ref().take(2).collect();
+                            let byte = u8::from_str_radix(&hex, 16)
+                                .map_err(|_| anyhow::anyhow!("Invalid escape \\x{hex}"))?;
+                            arg.push(byte as char);
+                        }
+                        Some(x) => arg.push(x),
+                        None => anyhow::bail!("Unbalanced quotes in configuration line"),
+                    },
+                    Some(x) => arg.push(x),
+                    None => anyhow::bail!("Unbalanced quotes in configuration line"),
+                }
+            },
+            '\'' => loop {
+                match chars.next() {
+                    Some('\'') => break,
+                    Some('\\') if chars.next_if_eq(&'\'').is_some() => arg.push('\''),
+                    Some(x) => arg.push(x),
+                    None => anyhow::bail!("Unbalanced quotes in configuration line"),
+                }
+            },
+            x => {
+                arg.push(x);
+                while let Some(x) = chars.next_if(|x| !x.is_whitespace()) {
+                    arg.push(x);
+                }
+                args.push(arg);
+                continue;
+            }
+        }
+        // the closing quote is followed by a space or the end of the line
+        anyhow::ensure!(
+            chars.peek().is_none_or(|x| x.is_whitespace()),
+            "Unbalanced quotes in configuration line"
+        );
+        args.push(arg);
+    }
+}
+
+// the value as one argument of split_args
+pub fn quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|x| x.is_ascii_graphic() && !matches!(x, '"' | '\'' | '\\'))
+    {
+        return value.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for x in value.chars() {
+        match x {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            x if x.is_control() => quoted.push_str(&format!("\\x{:02x}", x as u32)),
+            x => quoted.push(x),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// CONFIG REWRITE of the file content with the params (name, value, is the default value)
+// the first line of a parameter gets the value and its other lines are removed, the comments and
+// the unknown lines are kept, the parameters missing from the file are appended unless they are
+// the default
+pub fn rewrite(content: &str, params: &[(&str, String, bool)]) -> String {
+    let mut written = HashSet::new();
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        let name = split_args(line)
+            .ok()
+            .and_then(|args| args.into_iter().next())
+            .map(|x| x.to_lowercase());
+        let param = name
+            .filter(|_| !line.trim_start().starts_with('#'))
+            .and_then(|name| params.iter().find(|(x, _, _)| *x == name));
+        match param {
+            Some((name, value, _)) if written.insert(*name) => {
+                lines.push(format!("{name} {}", quote(value)))
+            }
+            Some(_) => {}
+            None => lines.push(line.to_string()),
+        }
+    }
+    let missing: Vec<String> = params
+        .iter()
+        .filter(|(name, _, is_default)| !is_default && !written.contains(name))
+        .map(|(name, value, _)| format!("{name} {}", quote(value)))
+        .collect();
+    if !missing.is_empty() {
+        lines.push(String::new());
+        lines.push("# Generated by CONFIG REWRITE".to_string());
+        lines.extend(missing);
+    }
+    lines.iter().map(|x| format!("{x}\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, quote, rewrite, split_args};
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            vec!["save", "900", "1"],
+            split_args("  save 900   1 ").unwrap()
+        );
+        assert_eq!(
+            vec!["requirepass", "a b\"c", "", "it's"],
+            split_args(r#"requirepass "a b\"c" "" 'it\'s'"#).unwrap()
+        );
+        assert_eq!(vec!["x", "\n\x01"], split_args(r#"x "\n\x01""#).unwrap());
+        assert!(split_args("requirepass \"secret").is_err());
+        assert!(split_args("requirepass \"a\"b").is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        // arrange
+        let content = "# comment\n\nport 7000\nSAVE 900 1 300 10\nrequirepass \"\"\n";
+        // act
+        let params = parse(content).unwrap();
+        // assert
+        assert_eq!(
+            vec![
+                (3, "port".to_string(), "7000".to_string()),
+                (4, "save".to_string(), "900 1 300 10".to_string()),
+                (5, "requirepass".to_string(), String::new()),
+            ],
+            params
+        );
+        assert_eq!(
+            "2: Unbalanced quotes in configuration line",
+            parse("port 1\nrequirepass 'x\n").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_quote_round_trip() {
+        for value in ["secret", "", "900 1", "a\"b\\c", "it's", "\t\x01é"] {
+            assert_eq!(
+                vec![value.to_string()],
+                split_args(&quote(value)).unwrap(),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rewrite() {
+        // arrange
+        let content = "# port 1\nport 7000\nunknown x\nport 7001\nsave 60 1\n";
+        let params = [
+            ("port", "7002".to_string(), false),
+            ("save", "3600 1".to_string(), true),
+            ("workers", "1".to_string(), true),
+            ("requirepass", "a b".to_string(), false),
+        ];
+        // act
+        let content = rewrite(content, &params);
+        // assert
+        assert_eq!(
+            "# port 1\nport 7002\nunknown x\nsave \"3600 1\"\n\n# Generated by CONFIG REWRITE\nrequirepass \"a b\"\n",
+            content
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::sync::{Arc, RwLock};
+
+use crate::acl::Acl;
+use crate::configuration::{param, Configuration, PARAMS};
+use crate::data_watcher::eviction::Memory;
+use crate::data_watcher::persistence::Persistence;
+use crate::redis_protocol::string_match::string_match;
+use crate::replication::Replication;
+
+use anyhow::Result;
+use log::info;
+
+// the configuration of the running server, shared by the connections
+// CONFIG SET applies the mutable parameters to the parts of the server using them
+#[derive(Debug)]
+pub struct LiveConfig {
+    config: RwLock<Configuration>,
+    acl: Arc<Acl>,
+    persistence: Arc<Persistence>,
+    memory: Arc<Memory>,
+    replication: Arc<Replication>,
+}
+
+impl LiveConfig {
+    pub fn new(
+        config: Configuration,
+        acl: Arc<Acl>,
+        persistence: Arc<Persistence>,
+        memory: Arc<Memory>,
+        replication: Arc<Replication>,
+    ) -> Self {
+        LiveConfig {
+            config: RwLock::new(config),
+            acl,
+            persistence,
+            memory,
+            replication,
+        }
+    }
+
+    pub fn current(&self) -> Configuration {
+        self.config.read().unwrap().clone()
+    }
+
+    // CONFIG GET, the parameters matching one of the glob patterns in the order of the table
+    pub fn get(&self, patterns: &[Vec<u8>]) -> Vec<(&'static str, String)> {
+        let config = self.config.read().unwrap();
+        PARAMS
+            .iter()
+            .filter(|x| {
+                patterns
+                    .iter()
+                    .any(|pattern| string_match(pattern, x.name.as_bytes(), true))
+            })
+            .map(|x| (x.name, (x.get)(&config)))
+            .collect()
+    }
+
+    // CONFIG SET, all the parameters are set or none of them
+    pub fn set(&self, params: &[(String, String)]) -> Result<()> {
+        let mut config = self.config.write().unwrap();
+        let mut updated = config.clone();
+        for (name, value) in params {
+            let Some(param) = param(name) else {
+                anyhow::bail!(
+                    "ERR Unknown option or number of arguments for CONFIG SET - '{name}'"
+                );
+            };
+            let failed = |reason: String| {
+                anyhow::anyhow!(
+                    "ERR CONFIG SET failed (possibly related to argument '{name}') - {reason}"
+                )
+            };
+            if !param.mutable {
+                return Err(failed("can't set immutable config".to_string()));
+            }
+            (param.set)(&mut updated, value).map_err(|e| failed(e.to_string()))?;
+        }
+        // every parameter is checked before any of them is applied
+        let requirepass = updated.acl.requirepass != config.acl.requirepass;
+        if requirepass {
+            self.acl
+                .check_requirepass(updated.acl.requirepass.as_deref())
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "ERR CONFIG SET failed (possibly related to argument 'requirepass') - {e}"
+                    )
+                })?;
+        }
+        if updated.snapshot != config.snapshot {
+            self.persistence
+                .set_snapshot_config(updated.snapshot.clone());
+        }
+        if updated.aof.fsync != config.aof.fsync {
+            self.persistence.set_aof_fsync(updated.aof.fsync);
+        }
+        if updated.maxmemory != config.maxmemory {
+            self.memory.set_config(updated.maxmemory);
+        }
+        if updated.replication != config.replication {
+            self.replication.set_config(updated.replication.clone());
+            self.persistence
+                .set_backlog_size(updated.replication.backlog_size);
+        }
+        // the password set by ACL SETUSER default is kept when requirepass doesn't change
+        if requirepass {
+            self.acl
+                .set_requirepass(updated.acl.requirepass.as_deref())?;
+        }
+        info!("config set {params:?}");
+        *config = updated;
+        Ok(())
+    }
+
+    // REPLICAOF, CONFIG GET and CONFIG REWRITE show the primary of the running server
+    pub fn set_replicaof(&self, primary: Option<(String, u16)>) {
+        self.config.write().unwrap().replication.replicaof = primary;
+    }
+
+    // CONFIG REWRITE
+    pub fn rewrite(&self) -> Result<()> {
+        self.config.read().unwrap().rewrite()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LiveConfig;
+    use crate::acl::{Acl, AclConfig};
+    use crate::configuration::Configuration;
+    use crate::data_watcher::DataStorage;
+    use crate::replication::Replication;
+    use std::sync::Arc;
+
+    fn params(input: &[(&str, &str)]) -> Vec<(String, String)> {
+        input
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn live_config() -> (LiveConfig, Arc<Acl>) {
+        live_config_of(&DataStorage::new())
+    }
+
+    fn live_config_of(storage: &DataStorage) -> (LiveConfig, Arc<Acl>) {
+        let acl = Arc::new(Acl::new(AclConfig::default()));
+        let live = LiveConfig::new(
+            Configuration::default(),
+            acl.clone(),
+            storage.persistence().clone(),
+            storage.memory().clone(),
+            Arc::new(Replication::default()),
+        );
+        (live, acl)
+    }
+
+    #[test]
+    fn test_get() {
+        // arrange
+        let (live, _) = live_config();
+        // act
+        let params = live.get(&[b"PORT".to_vec(), b"append*".to_vec()]);
+        // assert
+        assert_eq!(
+            vec![
+                ("port", "6379".to_string()),
+                ("appendonly", "no".to_string()),
+                ("appendfilename", "appendonly.aof".to_string()),
+                ("appendfsync", "everysec".to_string()),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_set() {
+        // arrange
+        let (live, acl) = live_config();
+        // act
+        let result = live.set(&params(&[("save", "60 1"), ("requirepass", "secret")]));
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(Some("60 1".to_string()), live.current().get("save"));
+        assert!(!acl.default_user_nopass());
+        assert!(acl.authenticate("default", b"secret"));
+    }
+
+    #[test]
+    fn test_set_failed() {
+        // arrange
+        let (live, _) = live_config();
+        // act
+        let immutable = live.set(&params(&[("save", ""), ("port", "7000")]));
+        let invalid = live.set(&params(&[("appendfsync", "sometimes")]));
+        let unknown = live.set(&params(&[("unknown", "1")]));
+        // assert
+        assert_eq!(
+            "ERR CONFIG SET failed (possibly related to argument 'port') - can't set immutable config",
+            immutable.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "ERR CONFIG SET failed (possibly related to argument 'appendfsync') - appendfsync should be always, everysec or no",
+            invalid.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "ERR Unknown option or number of arguments for CONFIG SET - 'unknown'",
+            unknown.unwrap_err().to_string()
+        );
+        // nothing is set when one parameter fails
+        assert_eq!(Configuration::default(), live.current());
+    }
+
+    #[test]
+    fn test_set_last_failed() {
+        // arrange
+        let storage = DataStorage::new();
+        let (live, acl) = live_config_of(&storage);
+        // act
+        let result = live.set(&params(&[
+            ("requirepass", "secret"),
+            ("save", "60 1"),
+            ("maxmemory", "1mb"),
+            ("appendfsync", "sometimes"),
+        ]));
+        // assert
+        assert!(result.is_err());
+        assert_eq!(Configuration::default(), live.current());
+        assert!(acl.default_user_nopass());
+        assert_eq!(0, storage.memory().config().maxmemory);
+    }
+}
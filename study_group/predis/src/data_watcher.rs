@@ -1,41 +1,422 @@
+pub mod aof;
+pub mod backlog;
+pub mod blocking;
+pub mod crc64;
+pub mod data_value;
+pub mod eviction;
 pub mod execution;
+pub mod expire;
 pub mod message;
+pub mod persistence;
+pub mod rdb;
+pub mod scan_index;
+pub mod shard;
+pub mod skip_list;
+pub mod snapshot;
+pub mod sorted_set;
+pub mod watched_keys;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    sync::Arc,
     time::{self, UNIX_EPOCH},
 };
 
-use crate::data_watcher::message::DataWatcherMessage;
+use crate::data_watcher::blocking::BlockedClients;
+use crate::data_watcher::data_value::{DataValue, TypedValue, WrongTypeError};
+use crate::data_watcher::eviction::{Access, EvictionPool, Memory};
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::expire::{ExpireIndex, ExpireStats};
+use crate::data_watcher::message::ShardMessage;
+use crate::data_watcher::persistence::Persistence;
+use crate::data_watcher::scan_index::ScanIndex;
+use crate::data_watcher::watched_keys::WatchedKeys;
 
-pub type DataStorage = HashMap<String, DataTTL>;
+use log::debug;
 
-#[derive(Default, PartialEq, Clone, Debug)]
+// like redis hz 10, the active expire cycle runs every 100ms and uses at most 25% of the cpu time
+const ACTIVE_EXPIRE_CYCLE_PERIOD: time::Duration = time::Duration::from_millis(100);
+const ACTIVE_EXPIRE_CYCLE_BUDGET: time::Duration = time::Duration::from_millis(25);
+const ACTIVE_EXPIRE_CYCLE_KEYS_PER_LOOP: usize = 20;
+const ACTIVE_EXPIRE_CYCLE_ACCEPTABLE_STALE: usize = 10;
+
+// the keys are written with insert/remove so the ttl index is kept in sync
+#[derive(Default, Debug)]
+pub struct DataStorage {
+    map: HashMap<Vec<u8>, DataTTL>,
+    expires: ExpireIndex,
+    expire_stats: ExpireStats,
+    keyspace_stats: KeyspaceStats,
+    // the keys in scan order for SCAN and RANDOMKEY
+    scan_index: ScanIndex,
+    // the number of changes since startup, like redis server.dirty
+    dirty: u64,
+    // the commands replacing the executed one in the append only file, see rewrite_command
+    propagate: Vec<Vec<Vec<u8>>>,
+    // the snapshot and append only file shared with the other shards
+    persistence: Arc<Persistence>,
+    // the versions of the keys for WATCH
+    watched: WatchedKeys,
+    // the used memory and maxmemory shared with the other shards
+    memory: Arc<Memory>,
+    // the estimated bytes of the keys of this shard
+    used: usize,
+    // BLPOP, BRPOP and BLMOVE waiting for the keys of this shard
+    blocked: BlockedClients,
+    // the keys changed in place by the command, their size is updated after it
+    resized: HashSet<Vec<u8>>,
+    eviction_pool: EvictionPool,
+}
+
+impl DataStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: Vec<u8>, mut value: DataTTL) -> Option<DataTTL> {
+        if value.expired_epoch.is_some() {
+            self.expires.insert(&key);
+        } else {
+            self.expires.remove(&key);
+        }
+        if !self.map.contains_key(&key) {
+            self.scan_index.insert(&key);
+        }
+        self.dirty += 1;
+        self.touch(&key);
+        value.access = Access::new();
+        self.account(&key, &mut value);
+        let old = self.map.insert(key, value)?;
+        self.unaccount(&old);
+        Some(old)
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<DataTTL> {
+        let value = self.unlink(key)?;
+        self.dirty += 1;
+        Some(value)
+    }
+
+    // remove without counting a change, the expired keys also expire when the changes are replayed
+    fn unlink(&mut self, key: &[u8]) -> Option<DataTTL> {
+        self.expires.remove(key);
+        let value = self.map.remove(key)?;
+        self.scan_index.remove(key);
+        self.touch(key);
+        self.unaccount(&value);
+        Some(value)
+    }
+
+    // the change of a watched key fails the transactions watching it
+    // the change of a key with blocked clients serves them after the command
+    fn touch(&mut self, key: &[u8]) {
+        if !self.watched.is_empty() {
+            self.watched.touch(key);
+        }
+        if !self.blocked.is_empty() {
+            self.signal_blocked(key);
+        }
+    }
+
+    // get the key if it is not expired, the expired key is removed (lazy expiration)
+    pub fn get_live(&mut self, key: &[u8]) -> Option<&mut DataTTL> {
+        if self.map.get(key)?.is_expired() {
+            self.unlink(key);
+            self.expire_stats.expired_lazy += 1;
+            return None;
+        }
+        let value = self.map.get_mut(key)?;
+        value.access.touch();
+        Some(value)
+    }
+
+    pub fn exists(&mut self, key: &[u8]) -> bool {
+        self.get_live(key).is_some()
+    }
+
+    // the read access, counted in the keyspace hits and misses
+    pub fn get_value(&mut self, key: &[u8]) -> Option<&mut DataValue> {
+        if self.get_live(key).is_none() {
+            self.keyspace_stats.misses += 1;
+            return None;
+        }
+        self.keyspace_stats.hits += 1;
+        self.map.get_mut(key).map(|x| &mut x.value)
+    }
+
+    // typed read access, WRONGTYPE error when the key holds another type
+    pub fn get_typed<T: TypedValue>(&mut self, key: &[u8]) -> Result<Option<&T>, WrongTypeError> {
+        match self.get_value(key) {
+            Some(v) => T::from_value(v).map(Some).ok_or(WrongTypeError),
+            None => Ok(None),
+        }
+    }
+
+    // the write access counts as a change even when the command leaves the value as it is
+    pub fn get_typed_mut<T: TypedValue>(
+        &mut self,
+        key: &[u8],
+    ) -> Result<Option<&mut T>, WrongTypeError> {
+        match self
+            .get_live(key)
+            .map(|x| T::from_value(&x.value).is_some())
+        {
+            Some(true) => self.dirty += 1,
+            Some(false) => return Err(WrongTypeError),
+            None => return Ok(None),
+        }
+        self.touch(key);
+        self.resized.insert(key.to_owned());
+        let v = &mut self.map.get_mut(key).unwrap().value;
+        Ok(T::from_value_mut(v))
+    }
+
+    // typed write access, an empty value is created when the key doesn't exist
+    pub fn get_typed_or_default<T: TypedValue>(
+        &mut self,
+        key: &[u8],
+    ) -> Result<&mut T, WrongTypeError> {
+        if !self.exists(key) {
+            self.insert(key.to_owned(), DataTTL::new(T::default().into_value()));
+        }
+        // the rejected write is not a change, it is not logged, counted by the save rules nor
+        // seen by WATCH
+        if T::from_value(&self.map[key].value).is_none() {
+            return Err(WrongTypeError);
+        }
+        self.dirty += 1;
+        self.touch(key);
+        self.resized.insert(key.to_owned());
+        let v = &mut self.map.get_mut(key).unwrap().value;
+        Ok(T::from_value_mut(v).unwrap())
+    }
+
+    // call after removing elements from list, hash, set or zset
+    pub fn remove_if_empty(&mut self, key: &[u8]) {
+        if self
+            .map
+            .get(key)
+            .is_some_and(|x| x.value.is_empty_aggregate())
+        {
+            self.remove(key);
+        }
+    }
+
+    // redis active expire cycle, sample the keys with a ttl and remove the expired ones
+    // repeat while more than 10% of the sampled keys are expired and the time budget is not used up
+    pub fn active_expire_cycle(&mut self, budget: time::Duration) -> usize {
+        let start = time::Instant::now();
+        let mut expired = 0;
+        loop {
+            let sampled = ACTIVE_EXPIRE_CYCLE_KEYS_PER_LOOP.min(self.expires.len());
+            let mut expired_in_loop = 0;
+            for _ in 0..sampled {
+                let Some(key) = self.expires.random_key() else {
+                    break;
+                };
+                if self.map.get(key).is_some_and(|x| x.is_expired()) {
+                    let key = key.to_owned();
+                    self.unlink(&key);
+                    expired_in_loop += 1;
+                }
+            }
+            expired += expired_in_loop;
+            if sampled == 0
+                || expired_in_loop * 100 <= sampled * ACTIVE_EXPIRE_CYCLE_ACCEPTABLE_STALE
+                || start.elapsed() > budget
+            {
+                break;
+            }
+        }
+        self.expire_stats.expired_active += expired as u64;
+        expired
+    }
+
+    // change the ttl of an existing key, None removes the ttl
+    // return false when the key doesn't exist
+    pub fn set_expired_epoch(&mut self, key: &[u8], expired: Option<time::Duration>) -> bool {
+        let Some(data_ttl) = self.get_live(key) else {
+            return false;
+        };
+        data_ttl.expired_epoch = expired;
+        self.dirty += 1;
+        self.touch(key);
+        match expired {
+            Some(_) => self.expires.insert(key),
+            None => self.expires.remove(key),
+        }
+        true
+    }
+
+    // the next batch of keys in scan order, the expired keys are included
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<&Vec<u8>>) {
+        self.scan_index.scan(cursor, count)
+    }
+
+    // a random live key, like redis it gives up after some expired keys
+    pub fn random_key(&mut self) -> Option<Vec<u8>> {
+        const MAX_TRIES: usize = 100;
+        for _ in 0..MAX_TRIES {
+            let key = self.scan_index.random_key()?.to_owned();
+            if self.exists(&key) {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    pub fn expire_stats(&self) -> ExpireStats {
+        self.expire_stats
+    }
+
+    pub fn keyspace_stats(&self) -> KeyspaceStats {
+        self.keyspace_stats
+    }
+
+    // the number of keys with a ttl
+    pub fn volatile_len(&self) -> usize {
+        self.expires.len()
+    }
+
+    pub fn dirty(&self) -> u64 {
+        self.dirty
+    }
+
+    pub fn persistence(&self) -> &Arc<Persistence> {
+        &self.persistence
+    }
+
+    // replace the executed command in the append only file, like redis rewriteClientCommandVector
+    // the command with a relative ttl or a random result is rewritten so the replay gets the same data
+    pub fn rewrite_command(&mut self, args: Vec<Vec<u8>>) {
+        self.propagate.push(args);
+    }
+
+    // log the executed command when it changed the data, dirty is the counter before exec
+    pub fn propagate(&mut self, args: &[Vec<u8>], dirty: u64) {
+        self.update_memory();
+        self.persistence.add_changes(self.dirty - dirty);
+        let commands = std::mem::take(&mut self.propagate);
+        if !commands.is_empty() {
+            commands.iter().for_each(|x| self.feed(x));
+        } else if self.dirty != dirty {
+            self.feed(args);
+        }
+    }
+
+    // the changes go to the append only file and the replicas
+    fn feed(&self, args: &[Vec<u8>]) {
+        self.feed_aof(args);
+        self.persistence.feed_replicas(args);
+    }
+
+    // WATCH, return the version compared by is_watched_key_changed
+    // the expired key is removed first so its expiration after WATCH is detected
+    pub fn watch(&mut self, key: &[u8]) -> u64 {
+        self.exists(key);
+        self.watched.watch(key)
+    }
+
+    pub fn unwatch(&mut self, key: &[u8]) {
+        self.watched.unwatch(key);
+    }
+
+    pub fn is_watched_key_changed(&self, key: &[u8], version: u64) -> bool {
+        self.watched.version(key) != Some(version)
+            || self.map.get(key).is_some_and(|x| x.is_expired())
+    }
+
+    // run the queued commands of EXEC, the changes are logged between MULTI and EXEC like redis
+    pub fn exec_multi<'a>(
+        &mut self,
+        commands: impl Iterator<Item = (&'a (dyn Execution + Send), &'a [Vec<u8>])>,
+    ) -> Vec<resp::Value> {
+        let mut propagate = Vec::new();
+        let replies = commands
+            .map(|(execution, args)| {
+                let dirty = self.dirty;
+                let reply = execution.exec(self);
+                self.take_propagate(args, dirty, &mut propagate);
+                reply
+            })
+            .collect();
+        self.propagate_multi(propagate);
+        replies
+    }
+
+    // the changes of one queued command of EXEC
+    fn take_propagate(&mut self, args: &[Vec<u8>], dirty: u64, propagate: &mut Vec<Vec<Vec<u8>>>) {
+        let rewritten = std::mem::take(&mut self.propagate);
+        if !rewritten.is_empty() {
+            propagate.extend(rewritten);
+        } else if self.dirty != dirty {
+            propagate.push(args.to_vec());
+        }
+    }
+
+    fn propagate_multi(&mut self, propagate: Vec<Vec<Vec<u8>>>) {
+        if !propagate.is_empty() {
+            self.propagate.push(vec![b"MULTI".to_vec()]);
+            self.propagate.extend(propagate);
+            self.propagate.push(vec![b"EXEC".to_vec()]);
+        }
+    }
+}
+
+impl Deref for DataStorage {
+    type Target = HashMap<Vec<u8>, DataTTL>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+// the duration since unix epoch, all the expired time is compared with it
+pub fn epoch_now() -> time::Duration {
+    time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+// the lookups of the read commands
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct KeyspaceStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Default, Clone, Debug)]
 pub struct DataTTL {
-    value: String,
+    value: DataValue,
     expired_epoch: Option<time::Duration>,
+    // for the eviction, see DataStorage::check_memory
+    access: Access,
+    size: usize,
+}
+
+// the same data, the access and the size are bookkeeping
+impl PartialEq for DataTTL {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.expired_epoch == other.expired_epoch
+    }
 }
 
 impl DataTTL {
-    pub fn new(value: String) -> Self {
+    pub fn new(value: impl Into<DataValue>) -> Self {
         DataTTL {
-            value,
+            value: value.into(),
             ..Default::default()
         }
     }
 
-    pub fn update(mut self, value: String) -> Self {
-        self.value = value;
+    pub fn update(mut self, value: impl Into<DataValue>) -> Self {
+        self.value = value.into();
         self
     }
 
     pub fn ttl(mut self, ttl: &time::Duration) -> Self {
-        self.expired_epoch = Some(
-            time::SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                + *ttl,
-        );
+        self.expired_epoch = Some(epoch_now() + *ttl);
         self
     }
 
@@ -44,27 +425,243 @@ impl DataTTL {
         self
     }
 
-    pub fn get(&self) -> Option<String> {
-        if let Some(expired) = self.expired_epoch {
-            if time::SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                > expired
-            {
-                return None;
-            }
+    pub fn expired_epoch(&self) -> Option<time::Duration> {
+        self.expired_epoch
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expired_epoch
+            .is_some_and(|expired| epoch_now() > expired)
+    }
+
+    pub fn value(&self) -> &DataValue {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut DataValue {
+        &mut self.value
+    }
+
+    // the string value, None when the key is expired or holds another type
+    pub fn get(&self) -> Option<Vec<u8>> {
+        if self.is_expired() {
+            return None;
+        }
+        match &self.value {
+            DataValue::String(v) => Some(v.to_owned()),
+            _ => None,
         }
-        Some(self.value.to_owned())
     }
 }
 
-pub async fn new(mut rx: tokio::sync::mpsc::Receiver<DataWatcherMessage>) {
+// one shard of the keyspace, the storage is loaded before the listener accepts connections
+pub async fn new(mut rx: tokio::sync::mpsc::Receiver<ShardMessage>, mut map: DataStorage) {
     // create data watcher
     tokio::spawn(async move {
-        let mut map = DataStorage::new();
-        while let Some(r) = rx.recv().await {
-            let response = r.data.exec(&mut map);
-            r.callback.send(response).unwrap();
+        let mut cron_interval = tokio::time::interval(ACTIVE_EXPIRE_CYCLE_PERIOD);
+        cron_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                r = rx.recv() => {
+                    let Some(r) = r else {
+                        break;
+                    };
+                    match r {
+                        ShardMessage::Command(r) => {
+                            let dirty = map.dirty();
+                            let response = match map.check_memory(&r.args) {
+                                Ok(()) => r.data.exec(&mut map),
+                                Err(e) => e.into(),
+                            };
+                            map.propagate(&r.args, dirty);
+                            // the connection may be closed meanwhile, the command ran anyway
+                            let _ = r.callback.send(response);
+                            map.serve_blocked();
+                        }
+                        // the blocking command waits when it has nothing to pop
+                        ShardMessage::Block(r) => {
+                            let dirty = map.dirty();
+                            let response = match map.check_memory(&r.args) {
+                                Ok(()) => r.data.exec(&mut map),
+                                Err(e) => e.into(),
+                            };
+                            map.propagate(&r.args, dirty);
+                            if response == r.data.timeout_reply() {
+                                map.block(r);
+                            } else {
+                                let _ = r.callback.send(response);
+                                map.serve_blocked();
+                            }
+                        }
+                        ShardMessage::Park(r) => map.park(r),
+                        ShardMessage::Unblock(id) => {
+                            if let Some(r) = map.unblock(id) {
+                                let _ = r.callback.send(r.data.timeout_reply());
+                            }
+                            map.serve_blocked();
+                        }
+                        // no command runs on the shard until the borrower gives the storage back
+                        ShardMessage::Lend(borrower) => {
+                            map = shard::lend(std::mem::take(&mut map), borrower).await;
+                            map.serve_blocked();
+                        }
+                    }
+                }
+                _ = cron_interval.tick() => {
+                    map.remove_disconnected_blocked();
+                    let expired = map.active_expire_cycle(ACTIVE_EXPIRE_CYCLE_BUDGET);
+                    if expired > 0 {
+                        debug!("active expired {expired} keys, {:?}", map.expire_stats());
+                    }
+                }
+            }
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DataStorage, DataTTL};
+    use crate::data_watcher::data_value::{DataValue, WrongTypeError};
+    use crate::data_watcher::execution::Execution;
+    use crate::redis_protocol::{cmd_push::Push, list_helper::Side};
+    use resp::Value;
+    use std::collections::VecDeque;
+    use std::time;
+
+    #[test]
+    fn test_get_typed_wrong_type() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let result = data.get_typed::<VecDeque<Vec<u8>>>(b"k");
+        // assert
+        assert_eq!(Err(WrongTypeError), result);
+        assert_eq!(Ok(Some(&b"v".to_vec())), data.get_typed::<Vec<u8>>(b"k"));
+    }
+
+    #[test]
+    fn test_get_typed_or_default_then_remove_if_empty() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .push_back(b"v".to_vec());
+        // assert
+        assert_eq!(
+            Some(&mut DataValue::List(VecDeque::from([b"v".to_vec()]))),
+            data.get_value(b"k")
+        );
+        data.get_typed_mut::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .unwrap()
+            .clear();
+        data.remove_if_empty(b"k");
+        assert!(!data.exists(b"k"));
+    }
+
+    #[test]
+    fn test_get_typed_or_default_wrong_type() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        let dirty = data.dirty();
+        let lpush = Push::parse(
+            VecDeque::from([b"k".to_vec(), b"x".to_vec()]),
+            Side::Left,
+            false,
+        )
+        .unwrap();
+        // act
+        let result = lpush.exec(&mut data);
+        // assert
+        assert_eq!(Value::Error(WrongTypeError.to_string()), result);
+        assert_eq!(dirty, data.dirty());
+    }
+
+    #[test]
+    fn test_get_typed_or_default_wrong_type_watched() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        let version = data.watch(b"k");
+        let lpush = Push::parse(
+            VecDeque::from([b"k".to_vec(), b"x".to_vec()]),
+            Side::Left,
+            false,
+        )
+        .unwrap();
+        // act
+        lpush.exec(&mut data);
+        // assert
+        assert!(!data.is_watched_key_changed(b"k", version));
+    }
+
+    #[test]
+    fn test_get_live_expired() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(
+            b"k".to_vec(),
+            DataTTL::new(b"v".to_vec()).expired_timestamp(&time::Duration::from_secs(1)),
+        );
+        // act
+        let result = data.get_live(b"k");
+        // assert
+        assert!(result.is_none());
+        assert!(!data.contains_key(b"k".as_slice()));
+    }
+
+    #[test]
+    fn test_active_expire_cycle() {
+        // arrange
+        let mut data = DataStorage::new();
+        for i in 0..1000 {
+            data.insert(
+                format!("expired{i}").into_bytes(),
+                DataTTL::new(b"v".to_vec()).expired_timestamp(&time::Duration::from_secs(1)),
+            );
+        }
+        data.insert(
+            b"volatile".to_vec(),
+            DataTTL::new(b"v".to_vec()).ttl(&time::Duration::from_secs(100)),
+        );
+        data.insert(b"persistent".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let mut expired = 0;
+        while data.len() > 2 {
+            expired += data.active_expire_cycle(time::Duration::from_secs(1));
+        }
+        // assert
+        assert_eq!(1000, expired);
+        assert!(data.exists(b"volatile"));
+        assert!(data.exists(b"persistent"));
+        assert_eq!(1, data.expires.len());
+        assert_eq!(1000, data.expire_stats().expired_active);
+        assert_eq!(0, data.expire_stats().expired_lazy);
+    }
+
+    #[test]
+    fn test_lazy_expire_stats() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(
+            b"k".to_vec(),
+            DataTTL::new(b"v".to_vec()).expired_timestamp(&time::Duration::from_secs(1)),
+        );
+        // act
+        data.get_live(b"k");
+        // assert
+        assert_eq!(1, data.expire_stats().expired_lazy);
+        assert!(data.expires.is_empty());
+        // overwrite without ttl leaves the index
+        data.insert(
+            b"k".to_vec(),
+            DataTTL::new(b"v".to_vec()).ttl(&time::Duration::from_secs(100)),
+        );
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        assert!(data.expires.is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time;
+
+use crate::data_watcher::data_value::DataValue;
+use crate::data_watcher::persistence::Persistence;
+use crate::data_watcher::{DataStorage, DataTTL};
+
+use anyhow::{Context, Result};
+use log::{error, info};
+use resp::Value;
+
+// redis AOF_REWRITE_ITEMS_PER_CMD, the big list, hash, set and zset are rewritten with several commands
+const REWRITE_ITEMS_PER_COMMAND: usize = 64;
+const EVERYSEC_PERIOD: time::Duration = time::Duration::from_secs(1);
+
+// appendfsync always|everysec|no
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum AppendFsync {
+    // fsync after every write command, the slowest and the safest
+    Always,
+    // fsync once per second in a blocking thread, lose at most one second of writes
+    EverySec,
+    // let the operating system flush the data
+    No,
+}
+
+impl AppendFsync {
+    pub fn parse(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "always" => Ok(AppendFsync::Always),
+            "everysec" => Ok(AppendFsync::EverySec),
+            "no" => Ok(AppendFsync::No),
+            _ => anyhow::bail!("appendfsync should be always, everysec or no"),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct AofConfig {
+    pub enabled: bool,
+    pub path: PathBuf,
+    pub fsync: AppendFsync,
+}
+
+impl Default for AofConfig {
+    fn default() -> Self {
+        AofConfig {
+            enabled: false,
+            path: PathBuf::from("appendonly.aof"),
+            fsync: AppendFsync::EverySec,
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct AofState {
+    config: AofConfig,
+    file: Option<fs::File>,
+    // written but not fsync yet, for everysec
+    unsynced: bool,
+    last_fsync: Option<time::Instant>,
+    fsync_in_progress: Arc<AtomicBool>,
+    // the commands during BGREWRITEAOF, appended to the new file when the rewrite is done
+    rewrite_buf: Option<Vec<u8>>,
+}
+
+impl AofState {
+    fn feed(&mut self, args: &[Vec<u8>]) {
+        if self.file.is_none() && self.rewrite_buf.is_none() {
+            return;
+        }
+        let bytes = encode_command(args);
+        if let Some(buf) = self.rewrite_buf.as_mut() {
+            buf.extend(&bytes);
+        }
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        let result = file.write_all(&bytes).and_then(|_| {
+            if self.config.fsync == AppendFsync::Always {
+                file.sync_data()
+            } else {
+                Ok(())
+            }
+        });
+        match result {
+            Ok(()) => self.unsynced = self.config.fsync == AppendFsync::EverySec,
+            Err(e) => error!("write aof error={e}"),
+        }
+    }
+}
+
+impl DataStorage {
+    // open the append only file after it is replayed, the commands are logged from now on
+    pub fn open_aof(&self, config: AofConfig) -> Result<()> {
+        let mut aof = self.persistence.aof.lock().unwrap();
+        if config.enabled {
+            aof.file = Some(open_append(&config.path)?);
+        }
+        aof.config = config;
+        Ok(())
+    }
+
+    pub(super) fn feed_aof(&self, args: &[Vec<u8>]) {
+        self.persistence.aof.lock().unwrap().feed(args);
+    }
+}
+
+impl Persistence {
+    // INFO persistence, the aof fields of redis
+    pub fn aof_info(&self) -> Vec<(&'static str, String)> {
+        let aof = self.aof.lock().unwrap();
+        vec![
+            ("aof_enabled", (aof.file.is_some() as u8).to_string()),
+            (
+                "aof_rewrite_in_progress",
+                (aof.rewrite_buf.is_some() as u8).to_string(),
+            ),
+        ]
+    }
+
+    // CONFIG SET appendfsync, the next write uses it
+    pub fn set_aof_fsync(&self, fsync: AppendFsync) {
+        self.aof.lock().unwrap().config.fsync = fsync;
+    }
+
+    // fsync the everysec file in a blocking thread, called on every tick
+    pub fn aof_cron(&self) {
+        let mut aof = self.aof.lock().unwrap();
+        if !aof.unsynced
+            || aof.fsync_in_progress.load(Ordering::Acquire)
+            || aof
+                .last_fsync
+                .is_some_and(|x| x.elapsed() < EVERYSEC_PERIOD)
+        {
+            return;
+        }
+        let Some(file) = aof.file.as_ref().and_then(|x| x.try_clone().ok()) else {
+            return;
+        };
+        let in_progress = aof.fsync_in_progress.clone();
+        in_progress.store(true, Ordering::Release);
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = file.sync_data() {
+                error!("fsync aof error={e}");
+            }
+            in_progress.store(false, Ordering::Release);
+        });
+        aof.unsynced = false;
+        aof.last_fsync = Some(time::Instant::now());
+    }
+
+    // append the commands during the rewrite then replace the append only file
+    fn aof_rewrite_done(&self, result: Result<PathBuf>) {
+        let mut aof = self.aof.lock().unwrap();
+        let Some(buf) = aof.rewrite_buf.take() else {
+            return;
+        };
+        let finish = |temp: &Path| -> Result<()> {
+            let mut file = open_append(temp)?;
+            file.write_all(&buf)?;
+            file.sync_all()?;
+            fs::rename(temp, &aof.config.path)?;
+            Ok(())
+        };
+        if let Err(e) = result.and_then(|temp| finish(&temp)) {
+            error!("background append only file rewriting error={e:#}");
+            let _ = fs::remove_file(temp_path(&aof.config.path));
+            return;
+        }
+        // the old file handle points to the replaced file
+        if aof.config.enabled {
+            match open_append(&aof.config.path) {
+                Ok(file) => aof.file = Some(file),
+                Err(e) => error!("reopen aof error={e:#}"),
+            }
+        }
+        info!("background append only file rewriting terminated with success");
+    }
+}
+
+// BGREWRITEAOF, write the commands rebuilding the live keys of all the shards to a new file in a blocking thread
+// the copy stops all the lent shards for O(keys), unlike BGSAVE they can't be copied one at a time:
+// the rewrite buffer starts with the copy, so every shard must be copied at that same point
+pub fn bgrewriteaof(shards: &[&DataStorage]) -> Result<()> {
+    let persistence = shards[0].persistence.clone();
+    let mut aof = persistence.aof.lock().unwrap();
+    anyhow::ensure!(
+        aof.rewrite_buf.is_none(),
+        "Background append only file rewriting already in progress"
+    );
+    let entries: Vec<(Vec<u8>, DataTTL)> = shards
+        .iter()
+        .flat_map(|x| x.map.iter())
+        .filter(|(_, value)| !value.is_expired())
+        .map(|(key, value)| (key.to_owned(), value.clone()))
+        .collect();
+    let temp = temp_path(&aof.config.path);
+    aof.rewrite_buf = Some(Vec::new());
+    drop(aof);
+    let done = persistence.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut bytes = Vec::new();
+        for (key, value) in entries.iter() {
+            for command in rewrite_commands(key, value) {
+                bytes.extend(encode_command(&command));
+            }
+        }
+        let result = fs::write(&temp, bytes)
+            .and_then(|_| fs::File::open(&temp)?.sync_all())
+            .map(|_| temp)
+            .context("write rewritten aof");
+        done.aof_rewrite_done(result);
+    });
+    info!("background append only file rewriting started");
+    Ok(())
+}
+
+fn open_append(path: &Path) -> Result<fs::File> {
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("open aof {}", path.display()))
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    path.with_file_name(format!("temp-rewriteaof-bg-{}.aof", std::process::id()))
+}
+
+pub fn encode_command(args: &[Vec<u8>]) -> Vec<u8> {
+    Value::Array(args.iter().map(|x| Value::BufBulk(x.to_owned())).collect()).encode()
+}
+
+// the commands creating the key, the ttl is an absolute PEXPIREAT
+pub fn rewrite_commands(key: &[u8], value: &DataTTL) -> Vec<Vec<Vec<u8>>> {
+    let command = |name: &str, items: &[Vec<u8>]| {
+        let mut args = vec![name.as_bytes().to_vec(), key.to_owned()];
+        args.extend_from_slice(items);
+        args
+    };
+    let batches = |name: &str, items: Vec<Vec<u8>>, item_len: usize| -> Vec<Vec<Vec<u8>>> {
+        items
+            .chunks(REWRITE_ITEMS_PER_COMMAND * item_len)
+            .map(|x| command(name, x))
+            .collect()
+    };
+    let mut commands = match value.value() {
+        DataValue::String(v) => vec![command("SET", &[v.to_owned()])],
+        DataValue::List(v) => batches("RPUSH", v.iter().cloned().collect(), 1),
+        DataValue::Hash(v) => batches(
+            "HSET",
+            v.iter()
+                .flat_map(|(f, v)| [f.to_owned(), v.to_owned()])
+                .collect(),
+            2,
+        ),
+        DataValue::Set(v) => batches("SADD", v.iter().cloned().collect(), 1),
+        DataValue::ZSet(v) => batches(
+            "ZADD",
+            v.iter()
+                .flat_map(|(m, score)| [score.to_string().into_bytes(), m.to_owned()])
+                .collect(),
+            2,
+        ),
+    };
+    if let Some(expired) = value.expired_epoch() {
+        commands.push(command(
+            "PEXPIREAT",
+            &[expired.as_millis().to_string().into_bytes()],
+        ));
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_command, rewrite_commands, AofConfig, AppendFsync};
+    use crate::data_watcher::data_value::DataValue;
+    use crate::data_watcher::{DataStorage, DataTTL};
+    use std::collections::VecDeque;
+    use std::time;
+
+    fn args(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_rewrite_commands() {
+        // arrange
+        let list: VecDeque<Vec<u8>> = (0..100).map(|x| x.to_string().into_bytes()).collect();
+        let value = DataTTL::new(b"v".to_vec())
+            .update(DataValue::List(list))
+            .expired_timestamp(&time::Duration::from_millis(5000));
+        // act
+        let commands = rewrite_commands(b"k", &value);
+        // assert
+        assert_eq!(3, commands.len());
+        assert_eq!(66, commands[0].len());
+        assert_eq!(38, commands[1].len());
+        assert_eq!(args(&["PEXPIREAT", "k", "5000"]), commands[2]);
+    }
+
+    #[test]
+    fn test_propagate_changes_only() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("predis-{}-feed.aof", std::process::id()));
+        let mut data = DataStorage::new();
+        data.open_aof(AofConfig {
+            enabled: true,
+            path: path.clone(),
+            fsync: AppendFsync::Always,
+        })
+        .unwrap();
+        // act
+        let dirty = data.dirty();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        data.propagate(&args(&["set", "k", "v"]), dirty);
+        let dirty = data.dirty();
+        data.propagate(&args(&["get", "k"]), dirty);
+        data.rewrite_command(args(&["del", "k"]));
+        data.propagate(&args(&["getdel", "k"]), dirty);
+        // assert
+        let content = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut expected = encode_command(&args(&["set", "k", "v"]));
+        expected.extend(encode_command(&args(&["del", "k"])));
+        assert_eq!(expected, content);
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::aof::encode_command;
+use crate::data_watcher::persistence::Persistence;
+use crate::data_watcher::{snapshot, DataStorage};
+
+use log::info;
+use rand::Rng;
+use tokio::sync::mpsc;
+
+// the commands waiting for a slow replica, it is disconnected when its queue is full and it may
+// continue from the backlog when it reconnects
+const REPLICA_QUEUE: usize = 16 * 1024;
+
+// the write commands of the server in the replication stream, like the replication backlog of redis
+// the offset counts the bytes of the stream since startup, a replica continues from its offset
+// while the bytes after it are still in the backlog
+#[derive(Debug)]
+pub struct Backlog {
+    replid: String,
+    offset: u64,
+    // the last bytes of the stream, up to size
+    buf: VecDeque<u8>,
+    size: usize,
+    // the stream is kept after the first replica connects, like redis
+    active: bool,
+    replicas: Vec<mpsc::Sender<Vec<u8>>>,
+}
+
+impl Default for Backlog {
+    fn default() -> Self {
+        Backlog {
+            replid: new_replid(),
+            offset: 0,
+            buf: VecDeque::new(),
+            size: 1024 * 1024,
+            active: false,
+            replicas: Vec::new(),
+        }
+    }
+}
+
+impl Backlog {
+    fn feed(&mut self, bytes: &[u8]) {
+        self.offset += bytes.len() as u64;
+        self.buf.extend(bytes);
+        let over = self.buf.len().saturating_sub(self.size);
+        self.buf.drain(..over);
+        self.replicas.retain(|x| match x.try_send(bytes.to_vec()) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                info!("replica queue overflow, disconnect the replica");
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        });
+    }
+
+    // the bytes after the offset when they are all in the backlog
+    fn since(&self, replid: &str, offset: u64) -> Option<Vec<u8>> {
+        let start = self.offset - self.buf.len() as u64;
+        if !self.active || replid != self.replid || offset < start || offset > self.offset {
+            return None;
+        }
+        Some(
+            self.buf
+                .iter()
+                .skip((offset - start) as usize)
+                .copied()
+                .collect(),
+        )
+    }
+
+    fn attach(&mut self) -> mpsc::Receiver<Vec<u8>> {
+        let (tx, rx) = mpsc::channel(REPLICA_QUEUE);
+        self.active = true;
+        self.replicas.push(tx);
+        rx
+    }
+}
+
+// 40 random hex characters like the replication id of redis
+fn new_replid() -> String {
+    let mut rng = rand::thread_rng();
+    (0..40)
+        .map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap())
+        .collect()
+}
+
+impl Persistence {
+    pub(super) fn feed_replicas(&self, args: &[Vec<u8>]) {
+        let mut backlog = self.backlog.lock().unwrap();
+        if backlog.active {
+            backlog.feed(&encode_command(args));
+        }
+    }
+
+    // CONFIG SET repl-backlog-size, the bytes over it are dropped with the next command
+    pub fn set_backlog_size(&self, size: usize) {
+        self.backlog.lock().unwrap().size = size;
+    }
+
+    // INFO replication, the replication id, the offset and the number of replicas
+    pub fn replication_info(&self) -> (String, u64, usize) {
+        let mut backlog = self.backlog.lock().unwrap();
+        backlog.replicas.retain(|x| !x.is_closed());
+        (
+            backlog.replid.clone(),
+            backlog.offset,
+            backlog.replicas.len(),
+        )
+    }
+}
+
+// PSYNC replid offset, the reply to the replica and the stream after it
+// the replica continues from the backlog when it has the offset, otherwise it gets a snapshot of
+// the keys of all the shards, no command runs until the stream is attached so the snapshot is
+// exactly the data at the offset, the full resynchronization stops all the shards for O(keys)
+pub fn psync(
+    shards: &[&DataStorage],
+    replid: &[u8],
+    offset: Option<u64>,
+) -> (Vec<u8>, mpsc::Receiver<Vec<u8>>) {
+    let mut backlog = shards[0].persistence.backlog.lock().unwrap();
+    let replid = String::from_utf8_lossy(replid);
+    if let Some(bytes) = offset.and_then(|x| backlog.since(&replid, x)) {
+        info!(
+            "partial resynchronization from offset {}, {} bytes",
+            offset.unwrap_or_default(),
+            bytes.len()
+        );
+        let mut reply = format!("+CONTINUE {}\r\n", backlog.replid).into_bytes();
+        reply.extend(bytes);
+        return (reply, backlog.attach());
+    }
+    let snapshot = snapshot::encode(
+        shards
+            .iter()
+            .flat_map(|x| x.iter())
+            .filter(|(_, value)| !value.is_expired()),
+    );
+    info!(
+        "full resynchronization at offset {}, {} bytes",
+        backlog.offset,
+        snapshot.len()
+    );
+    let mut reply = format!(
+        "+FULLRESYNC {} {}\r\n${}\r\n",
+        backlog.replid,
+        backlog.offset,
+        snapshot.len()
+    )
+    .into_bytes();
+    reply.extend(snapshot);
+    (reply, backlog.attach())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::psync;
+    use crate::data_watcher::{snapshot, DataStorage, DataTTL};
+
+    fn args(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_psync_full() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let (reply, mut rx) = psync(&[&data], b"?", None);
+        data.persistence().feed_replicas(&args(&["SET", "a", "1"]));
+        // assert
+        let (replid, offset, replicas) = data.persistence().replication_info();
+        let header = format!("+FULLRESYNC {replid} 0\r\n$");
+        assert!(reply.starts_with(header.as_bytes()));
+        let body = &reply[reply.iter().position(|x| *x == b'\n').unwrap() + 1..];
+        let body = &body[body.iter().position(|x| *x == b'\n').unwrap() + 1..];
+        assert_eq!(
+            vec![(b"k".to_vec(), DataTTL::new(b"v".to_vec()))],
+            snapshot::decode(body).unwrap()
+        );
+        assert_eq!(
+            b"*3\r\n$3\r\nSET\r\n$1\r\na\r\n$1\r\n1\r\n".to_vec(),
+            rx.try_recv().unwrap()
+        );
+        assert_eq!(27, offset);
+        assert_eq!(1, replicas);
+    }
+
+    #[test]
+    fn test_psync_partial() {
+        // arrange
+        let data = DataStorage::new();
+        let (_, rx) = psync(&[&data], b"?", None);
+        data.persistence().feed_replicas(&args(&["DEL", "a"]));
+        data.persistence().feed_replicas(&args(&["DEL", "b"]));
+        drop(rx);
+        let (replid, _, replicas) = data.persistence().replication_info();
+        // act
+        let (partial, _) = psync(&[&data], replid.as_bytes(), Some(20));
+        let (unknown, _) = psync(&[&data], b"other", Some(20));
+        let (ahead, _) = psync(&[&data], replid.as_bytes(), Some(100));
+        // assert
+        assert_eq!(0, replicas);
+        assert_eq!(
+            format!("+CONTINUE {replid}\r\n*2\r\n$3\r\nDEL\r\n$1\r\nb\r\n").into_bytes(),
+            partial
+        );
+        assert!(unknown.starts_with(b"+FULLRESYNC"));
+        assert!(ahead.starts_with(b"+FULLRESYNC"));
+    }
+}
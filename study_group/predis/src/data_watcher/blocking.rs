@@ -0,0 +1,313 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::data_watcher::data_value::DataValue;
+use crate::data_watcher::message::{BlockingMessage, ParkMessage};
+use crate::data_watcher::DataStorage;
+
+static NEXT_BLOCKED_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn next_id() -> u64 {
+    NEXT_BLOCKED_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+// the blocked client of one shard
+enum Waiter {
+    // all the keys of the command are in this shard, it is served here
+    Blocked(BlockingMessage),
+    // the keys are in several shards, the client is woken to run the command on the lent shards
+    Parked(ParkMessage),
+}
+
+impl Waiter {
+    fn keys(&self) -> &[Vec<u8>] {
+        match self {
+            Waiter::Blocked(x) => x.data.keys(),
+            Waiter::Parked(x) => &x.keys,
+        }
+    }
+
+    // the connection is gone
+    fn is_closed(&self) -> bool {
+        match self {
+            Waiter::Blocked(x) => x.callback.is_closed(),
+            Waiter::Parked(x) => x.wake.is_closed(),
+        }
+    }
+}
+
+// the clients blocked on the keys of one shard, like redis blocking.c
+// the clients of a key are served in the order they blocked, after the command pushing to it
+#[derive(Default)]
+pub struct BlockedClients {
+    clients: HashMap<u64, Waiter>,
+    keys: HashMap<Vec<u8>, VecDeque<u64>>,
+    // the keys with blocked clients changed by the last command, like redis ready_keys
+    ready: VecDeque<Vec<u8>>,
+}
+
+impl std::fmt::Debug for BlockedClients {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockedClients")
+            .field("clients", &self.clients.len())
+            .field("keys", &self.keys.len())
+            .finish()
+    }
+}
+
+impl BlockedClients {
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    fn block(&mut self, id: u64, client: Waiter) {
+        for key in client.keys() {
+            let ids = self.keys.entry(key.to_owned()).or_default();
+            // BLPOP k k 0 waits once on k
+            if ids.back() != Some(&id) {
+                ids.push_back(id);
+            }
+        }
+        self.clients.insert(id, client);
+    }
+
+    fn remove(&mut self, id: u64) -> Option<Waiter> {
+        let client = self.clients.remove(&id)?;
+        for key in client.keys() {
+            let Some(ids) = self.keys.get_mut(key) else {
+                continue;
+            };
+            ids.retain(|x| *x != id);
+            if ids.is_empty() {
+                self.keys.remove(key);
+            }
+        }
+        Some(client)
+    }
+
+    // the key is changed, its blocked clients are served after the command
+    fn signal(&mut self, key: &[u8]) {
+        if self.keys.contains_key(key) && !self.ready.iter().any(|x| x == key) {
+            self.ready.push_back(key.to_owned());
+        }
+    }
+}
+
+impl DataStorage {
+    // park the command until one of its keys is pushed, its timeout or its disconnection
+    pub fn block(&mut self, client: BlockingMessage) {
+        self.blocked.block(client.id, Waiter::Blocked(client));
+    }
+
+    // wait for the keys of this shard, the command of several shards isn't served here
+    pub fn park(&mut self, client: ParkMessage) {
+        self.blocked.block(client.id, Waiter::Parked(client));
+    }
+
+    // the timeout of the blocked command, None when it is already served
+    // the removed client may be woken for a key, the next clients of its keys are served instead
+    pub fn unblock(&mut self, id: u64) -> Option<BlockingMessage> {
+        let client = self.blocked.remove(id)?;
+        for key in client.keys() {
+            self.blocked.signal(key);
+        }
+        match client {
+            Waiter::Blocked(client) => Some(client),
+            Waiter::Parked(_) => None,
+        }
+    }
+
+    pub fn blocked_clients(&self) -> usize {
+        self.blocked
+            .clients
+            .values()
+            .filter(|x| match x {
+                Waiter::Blocked(_) => true,
+                Waiter::Parked(x) => x.counted,
+            })
+            .count()
+    }
+
+    pub(super) fn signal_blocked(&mut self, key: &[u8]) {
+        self.blocked.signal(key);
+    }
+
+    // serve the clients blocked on the keys pushed by the last command, the first blocked first
+    // one push may serve several clients, and a served BLMOVE may push to another ready key
+    pub fn serve_blocked(&mut self) {
+        while let Some(key) = self.blocked.ready.pop_front() {
+            // the clients keep waiting while the key is not a list, an empty list is removed
+            while self
+                .get_live(&key)
+                .is_some_and(|x| matches!(x.value(), DataValue::List(_)))
+            {
+                let Some(id) = self.blocked.keys.get(&key).and_then(|x| x.front().copied()) else {
+                    break;
+                };
+                // the parked client runs the command itself, the next clients wait until it
+                // unblocks, then the key is served again
+                if let Some(Waiter::Parked(client)) = self.blocked.clients.get(&id) {
+                    if !client.wake.is_closed() {
+                        let _ = client.wake.try_send(());
+                        break;
+                    }
+                }
+                // the client disconnected, the element is kept for the next one
+                let Some(Waiter::Blocked(client)) = self.blocked.remove(id) else {
+                    continue;
+                };
+                if client.callback.is_closed() {
+                    continue;
+                }
+                let dirty = self.dirty();
+                let reply = client.data.serve(self, &key);
+                self.propagate(&client.args, dirty);
+                let _ = client.callback.send(reply);
+            }
+        }
+    }
+
+    // the blocked clients closed without timeout, their connections are gone
+    // the keys a parked client was woken for are served to the next clients
+    pub fn remove_disconnected_blocked(&mut self) {
+        let closed: Vec<u64> = self
+            .blocked
+            .clients
+            .iter()
+            .filter(|(_, x)| x.is_closed())
+            .map(|(id, _)| *id)
+            .collect();
+        for id in closed {
+            let Some(client) = self.blocked.remove(id) else {
+                continue;
+            };
+            for key in client.keys() {
+                self.blocked.signal(key);
+            }
+        }
+        self.serve_blocked();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_id;
+    use crate::data_watcher::execution::Blocking;
+    use crate::data_watcher::execution::Execution;
+    use crate::data_watcher::message::{BlockingMessage, ParkMessage};
+    use crate::data_watcher::DataStorage;
+    use crate::redis_protocol::cmd_bpop::BPop;
+    use crate::redis_protocol::cmd_push::Push;
+    use crate::redis_protocol::list_helper::Side;
+    use resp::Value;
+    use std::collections::VecDeque;
+    use tokio::sync::{mpsc, oneshot};
+
+    fn input(input: &[&str]) -> VecDeque<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    fn block(data: &mut DataStorage, keys: &[&str]) -> oneshot::Receiver<Value> {
+        let mut args = keys.to_vec();
+        args.push("0");
+        let bpop: Box<dyn Blocking + Send> = BPop::parse(input(&args), Side::Left).unwrap();
+        let (callback, rx) = oneshot::channel();
+        data.block(BlockingMessage {
+            id: next_id(),
+            data: bpop,
+            args: Vec::new(),
+            callback,
+        });
+        rx
+    }
+
+    fn push(data: &mut DataStorage, key: &str, elements: &[&str]) {
+        let mut args = vec![key];
+        args.extend(elements);
+        Push::parse(input(&args), Side::Right, false)
+            .unwrap()
+            .exec(data);
+        data.serve_blocked();
+    }
+
+    fn served(key: &str, element: &str) -> Value {
+        Value::Array(vec![
+            Value::BufBulk(key.as_bytes().to_vec()),
+            Value::BufBulk(element.as_bytes().to_vec()),
+        ])
+    }
+
+    #[test]
+    fn test_serve_in_blocking_order() {
+        // arrange
+        let mut data = DataStorage::new();
+        let mut first = block(&mut data, &["a", "b"]);
+        let mut second = block(&mut data, &["b"]);
+        let mut third = block(&mut data, &["b"]);
+        // act
+        push(&mut data, "b", &["1", "2"]);
+        // assert
+        assert_eq!(Ok(served("b", "1")), first.try_recv());
+        assert_eq!(Ok(served("b", "2")), second.try_recv());
+        assert!(third.try_recv().is_err());
+        assert_eq!(1, data.blocked_clients());
+        assert!(!data.exists(b"b"));
+    }
+
+    #[test]
+    fn test_serve_skip_disconnected() {
+        // arrange
+        let mut data = DataStorage::new();
+        drop(block(&mut data, &["k"]));
+        let mut waiting = block(&mut data, &["k"]);
+        drop(block(&mut data, &["other"]));
+        // act
+        push(&mut data, "k", &["1"]);
+        data.remove_disconnected_blocked();
+        // assert
+        assert_eq!(Ok(served("k", "1")), waiting.try_recv());
+        assert_eq!(0, data.blocked_clients());
+    }
+
+    #[test]
+    fn test_unblock() {
+        // arrange
+        let mut data = DataStorage::new();
+        let _rx = block(&mut data, &["k"]);
+        let id = data.blocked.clients.keys().next().copied().unwrap();
+        // act
+        let unblocked = data.unblock(id);
+        push(&mut data, "k", &["1"]);
+        // assert
+        assert_eq!(Value::NullArray, unblocked.unwrap().data.timeout_reply());
+        assert!(data.unblock(id).is_none());
+        assert!(data.exists(b"k"));
+    }
+
+    #[test]
+    fn test_park() {
+        // arrange
+        let mut data = DataStorage::new();
+        let (wake, mut woken) = mpsc::channel(1);
+        let id = next_id();
+        data.park(ParkMessage {
+            id,
+            keys: vec![b"k".to_vec()],
+            wake,
+            counted: true,
+        });
+        let mut waiting = block(&mut data, &["k"]);
+        // act
+        push(&mut data, "k", &["1"]);
+        let parked = woken.try_recv();
+        let blocked = waiting.try_recv().is_err();
+        let unblocked = data.unblock(id);
+        data.serve_blocked();
+        // assert
+        assert_eq!(Ok(()), parked);
+        assert!(blocked);
+        assert!(unblocked.is_none());
+        assert_eq!(Ok(served("k", "1")), waiting.try_recv());
+        assert_eq!(0, data.blocked_clients());
+    }
+}
@@ -0,0 +1,43 @@
+// crc-64-jones, the checksum of the redis rdb file
+// reflected input and output, the polynomial 0xad93d23594c935a9 is reflected below
+const POLY: u64 = 0x95ac9329ac4bc9b5;
+
+const TABLE: [u64; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+// continue the checksum from crc, start with 0
+pub fn crc64(mut crc: u64, input: &[u8]) -> u64 {
+    for b in input {
+        crc = TABLE[((crc ^ *b as u64) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::crc64;
+
+    #[test]
+    fn test_crc64() {
+        // the check value in redis crc64.c
+        assert_eq!(0xe9c6d914c4b8d9ca, crc64(0, b"123456789"));
+        assert_eq!(crc64(0, b"123456789"), crc64(crc64(0, b"1234"), b"56789"));
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::data_watcher::sorted_set::SortedSet;
+
+use resp::Value;
+
+// https://redis.io/docs/data-types/
+#[derive(PartialEq, Clone, Debug)]
+pub enum DataValue {
+    String(Vec<u8>),
+    List(VecDeque<Vec<u8>>),
+    Hash(HashMap<Vec<u8>, Vec<u8>>),
+    Set(HashSet<Vec<u8>>),
+    ZSet(SortedSet),
+}
+
+impl DataValue {
+    // the name returned by the TYPE command
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DataValue::String(_) => "string",
+            DataValue::List(_) => "list",
+            DataValue::Hash(_) => "hash",
+            DataValue::Set(_) => "set",
+            DataValue::ZSet(_) => "zset",
+        }
+    }
+
+    // redis removes the key when the aggregate value becomes empty
+    pub fn is_empty_aggregate(&self) -> bool {
+        match self {
+            DataValue::String(_) => false,
+            DataValue::List(v) => v.is_empty(),
+            DataValue::Hash(v) => v.is_empty(),
+            DataValue::Set(v) => v.is_empty(),
+            DataValue::ZSet(v) => v.is_empty(),
+        }
+    }
+}
+
+impl Default for DataValue {
+    fn default() -> Self {
+        DataValue::String(Vec::new())
+    }
+}
+
+impl From<Vec<u8>> for DataValue {
+    fn from(value: Vec<u8>) -> Self {
+        DataValue::String(value)
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct WrongTypeError;
+
+impl std::fmt::Display for WrongTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "WRONGTYPE Operation against a key holding the wrong kind of value"
+        )
+    }
+}
+
+impl std::error::Error for WrongTypeError {}
+
+impl From<WrongTypeError> for Value {
+    fn from(value: WrongTypeError) -> Self {
+        Value::Error(value.to_string())
+    }
+}
+
+// the rust type behind one DataValue variant, so the storage can return typed references
+pub trait TypedValue: Default + Sized {
+    fn from_value(value: &DataValue) -> Option<&Self>;
+    fn from_value_mut(value: &mut DataValue) -> Option<&mut Self>;
+    fn into_value(self) -> DataValue;
+}
+
+macro_rules! typed_value {
+    ($t:ty, $variant:ident) => {
+        impl TypedValue for $t {
+            fn from_value(value: &DataValue) -> Option<&Self> {
+                match value {
+                    DataValue::$variant(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            fn from_value_mut(value: &mut DataValue) -> Option<&mut Self> {
+                match value {
+                    DataValue::$variant(v) => Some(v),
+                    _ => None,
+                }
+            }
+
+            fn into_value(self) -> DataValue {
+                DataValue::$variant(self)
+            }
+        }
+    };
+}
+
+typed_value!(Vec<u8>, String);
+typed_value!(VecDeque<Vec<u8>>, List);
+typed_value!(HashMap<Vec<u8>, Vec<u8>>, Hash);
+typed_value!(HashSet<Vec<u8>>, Set);
+typed_value!(SortedSet, ZSet);
@@ -0,0 +1,566 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+use crate::acl::category;
+use crate::data_watcher::data_value::DataValue;
+use crate::data_watcher::{epoch_now, DataStorage, DataTTL};
+
+use anyhow::Result;
+use log::debug;
+use rand::Rng;
+use resp::Value;
+
+// the candidates kept between the evictions, like EVPOOL_SIZE of redis
+const EVICTION_POOL_SIZE: usize = 16;
+// the elements sampled to estimate the size of a list, hash, set or zset, like MEMORY USAGE
+const SIZE_SAMPLES: usize = 5;
+// the hash table entry, the key and the value headers of one key
+const KEY_OVERHEAD: usize = 64;
+// the counter of a new key, so it is not evicted before it gets a chance to be accessed
+const LFU_INIT_VAL: u8 = 5;
+// lfu-log-factor 10 and lfu-decay-time 1 of redis
+const LFU_LOG_FACTOR: f64 = 10.0;
+const LFU_DECAY_MINUTES: u64 = 1;
+
+// the write commands which don't add data, they run over maxmemory like the commands without the
+// denyoom flag of redis
+const NO_DENYOOM: &[&str] = &[
+    "del",
+    "getdel",
+    "getex",
+    "expire",
+    "pexpire",
+    "expireat",
+    "pexpireat",
+    "persist",
+    "lpop",
+    "rpop",
+    "blpop",
+    "brpop",
+    "lrem",
+    "ltrim",
+    "hdel",
+    "srem",
+    "spop",
+    "zrem",
+    "zpopmin",
+    "zpopmax",
+];
+
+// maxmemory-policy, the keys evicted when the used memory is over maxmemory
+// https://redis.io/docs/reference/eviction/
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MaxmemoryPolicy {
+    // the commands adding data fail
+    NoEviction,
+    AllkeysLru,
+    VolatileLru,
+    AllkeysLfu,
+    VolatileLfu,
+    AllkeysRandom,
+    VolatileRandom,
+    // the key with the nearest expire time
+    VolatileTtl,
+}
+
+impl MaxmemoryPolicy {
+    const NAMES: [(&'static str, MaxmemoryPolicy); 8] = [
+        ("noeviction", MaxmemoryPolicy::NoEviction),
+        ("allkeys-lru", MaxmemoryPolicy::AllkeysLru),
+        ("volatile-lru", MaxmemoryPolicy::VolatileLru),
+        ("allkeys-lfu", MaxmemoryPolicy::AllkeysLfu),
+        ("volatile-lfu", MaxmemoryPolicy::VolatileLfu),
+        ("allkeys-random", MaxmemoryPolicy::AllkeysRandom),
+        ("volatile-random", MaxmemoryPolicy::VolatileRandom),
+        ("volatile-ttl", MaxmemoryPolicy::VolatileTtl),
+    ];
+
+    pub fn parse(input: &str) -> Result<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(input))
+            .map(|(_, policy)| *policy)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "argument must be one of the following: {}",
+                    Self::NAMES.map(|(name, _)| name).join(", ")
+                )
+            })
+    }
+
+    pub fn name(&self) -> &'static str {
+        Self::NAMES.iter().find(|(_, x)| x == self).unwrap().0
+    }
+
+    // only the keys with a ttl are evicted
+    fn volatile(&self) -> bool {
+        matches!(
+            self,
+            MaxmemoryPolicy::VolatileLru
+                | MaxmemoryPolicy::VolatileLfu
+                | MaxmemoryPolicy::VolatileRandom
+                | MaxmemoryPolicy::VolatileTtl
+        )
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct MaxmemoryConfig {
+    // the limit in bytes, 0 is no limit
+    pub maxmemory: usize,
+    pub policy: MaxmemoryPolicy,
+    // the keys sampled for one eviction, more is closer to the exact LRU, LFU or TTL
+    pub samples: usize,
+}
+
+impl Default for MaxmemoryConfig {
+    fn default() -> Self {
+        MaxmemoryConfig {
+            maxmemory: 0,
+            policy: MaxmemoryPolicy::NoEviction,
+            samples: 5,
+        }
+    }
+}
+
+// the memory used by the keys of all the shards and its limit, shared by the shards
+// the shard running a write command only samples its own keys, so it evicts them down to its share
+// of maxmemory, the keys are spread evenly over the shards by their hash
+#[derive(Default, Debug)]
+pub struct Memory {
+    // the estimated bytes of the keys, see estimate
+    used: AtomicUsize,
+    config: RwLock<MaxmemoryConfig>,
+    evicted_keys: AtomicU64,
+    // the number of shards sharing maxmemory, 0 before the keyspace is sharded
+    shards: AtomicUsize,
+}
+
+impl Memory {
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    pub fn config(&self) -> MaxmemoryConfig {
+        *self.config.read().unwrap()
+    }
+
+    // maxmemory at startup and CONFIG SET maxmemory, the next write command evicts the keys over it
+    pub fn set_config(&self, config: MaxmemoryConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    pub fn evicted_keys(&self) -> u64 {
+        self.evicted_keys.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn add(&self, size: usize) {
+        self.used.fetch_add(size, Ordering::Relaxed);
+    }
+
+    pub(super) fn sub(&self, size: usize) {
+        self.used.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    pub(super) fn set_shards(&self, count: usize) {
+        self.shards.store(count, Ordering::Relaxed);
+    }
+
+    // all the shards are over maxmemory and the shard with shard_used bytes is over its share
+    fn over_limit(&self, config: &MaxmemoryConfig, shard_used: usize) -> bool {
+        let shards = self.shards.load(Ordering::Relaxed).max(1);
+        config.maxmemory > 0
+            && self.used() > config.maxmemory
+            && shard_used > config.maxmemory / shards
+    }
+}
+
+// the last access of a key, like the lru field of the redis object
+// both LRU and LFU are kept so CONFIG SET maxmemory-policy works with the past accesses
+#[derive(Default, Clone, Copy, Debug)]
+pub struct Access {
+    // milliseconds since the unix epoch
+    last: u64,
+    // the logarithmic access counter and the minutes since the unix epoch of its last decay
+    counter: u8,
+    decayed_at: u64,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Access {
+            last: epoch_now().as_millis() as u64,
+            counter: LFU_INIT_VAL,
+            decayed_at: epoch_now().as_secs() / 60,
+        }
+    }
+
+    pub fn touch(&mut self) {
+        let now = epoch_now();
+        let counter = self.decayed(now.as_secs() / 60);
+        // the counter grows slower as it gets bigger, 255 is about one million accesses
+        let base = counter.saturating_sub(LFU_INIT_VAL) as f64;
+        let increment = counter < u8::MAX
+            && rand::thread_rng().gen::<f64>() < 1.0 / (base * LFU_LOG_FACTOR + 1.0);
+        self.counter = counter + increment as u8;
+        self.decayed_at = now.as_secs() / 60;
+        self.last = now.as_millis() as u64;
+    }
+
+    // like redis the counter is decremented by one per decay period without access
+    fn decayed(&self, minutes: u64) -> u8 {
+        let periods = minutes.saturating_sub(self.decayed_at) / LFU_DECAY_MINUTES;
+        self.counter
+            .saturating_sub(periods.min(u8::MAX as u64) as u8)
+    }
+}
+
+// the best candidates of the past samples, the highest score is evicted first
+#[derive(Default, Debug)]
+pub(super) struct EvictionPool {
+    policy: Option<MaxmemoryPolicy>,
+    candidates: Vec<(u64, Vec<u8>)>,
+}
+
+impl EvictionPool {
+    fn insert(&mut self, score: u64, key: Vec<u8>) {
+        if self.candidates.iter().any(|(_, x)| *x == key) {
+            return;
+        }
+        let position = self.candidates.partition_point(|(x, _)| *x < score);
+        if self.candidates.len() == EVICTION_POOL_SIZE {
+            if position == 0 {
+                return;
+            }
+            self.candidates.remove(0);
+            self.candidates.insert(position - 1, (score, key));
+        } else {
+            self.candidates.insert(position, (score, key));
+        }
+    }
+}
+
+// the write command failed because the keys can't be evicted
+#[derive(PartialEq, Debug)]
+pub struct OutOfMemoryError;
+
+impl std::fmt::Display for OutOfMemoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OOM command not allowed when used memory > 'maxmemory'.")
+    }
+}
+
+impl std::error::Error for OutOfMemoryError {}
+
+impl From<OutOfMemoryError> for Value {
+    fn from(value: OutOfMemoryError) -> Self {
+        Value::Error(value.to_string())
+    }
+}
+
+// the command may add data, the commands removing data run over maxmemory to free memory
+pub fn deny_oom(command: &str) -> bool {
+    category::in_category(command, "write") && !NO_DENYOOM.contains(&command)
+}
+
+// the bytes of the key, the size of a list, hash, set or zset is estimated with the first
+// elements like MEMORY USAGE, so it is computed in O(1) after every change
+pub fn estimate(key: &[u8], value: &DataValue) -> usize {
+    fn sampled(len: usize, sizes: impl Iterator<Item = usize>) -> usize {
+        let (count, total) = sizes
+            .take(SIZE_SAMPLES)
+            .fold((0, 0), |(count, total), size| (count + 1, total + size));
+        (total * len).checked_div(count).unwrap_or_default()
+    }
+    let value = match value {
+        DataValue::String(v) => v.len(),
+        DataValue::List(v) => sampled(v.len(), v.iter().map(|x| x.len() + 16)),
+        DataValue::Hash(v) => sampled(v.len(), v.iter().map(|(k, x)| k.len() + x.len() + 48)),
+        DataValue::Set(v) => sampled(v.len(), v.iter().map(|x| x.len() + 32)),
+        // the member is in the skip list and in the hash table
+        DataValue::ZSet(v) => sampled(v.len(), v.iter().map(|(x, _)| x.len() * 2 + 64)),
+    };
+    KEY_OVERHEAD + key.len() + value
+}
+
+impl DataStorage {
+    pub fn memory(&self) -> &std::sync::Arc<Memory> {
+        &self.memory
+    }
+
+    // the size of the new value replacing the one of the key
+    pub(super) fn account(&mut self, key: &[u8], value: &mut DataTTL) {
+        value.size = estimate(key, &value.value);
+        self.used += value.size;
+        self.memory.add(value.size);
+    }
+
+    // the size of the removed value
+    pub(super) fn unaccount(&mut self, value: &DataTTL) {
+        self.used -= value.size;
+        self.memory.sub(value.size);
+    }
+
+    // the values changed in place by the command, see get_typed_mut
+    pub(super) fn update_memory(&mut self) {
+        for key in std::mem::take(&mut self.resized) {
+            let Some(value) = self.map.get_mut(&key) else {
+                continue;
+            };
+            let size = estimate(&key, &value.value);
+            self.used = self.used - value.size + size;
+            self.memory.sub(value.size);
+            self.memory.add(size);
+            value.size = size;
+        }
+    }
+
+    // evict keys before the command adding data when the used memory is over maxmemory,
+    // like performEvictions of redis, the command fails when the keys can't be evicted
+    pub fn check_memory(&mut self, args: &[Vec<u8>]) -> Result<(), OutOfMemoryError> {
+        let config = self.memory.config();
+        if !self.memory.over_limit(&config, self.used) {
+            return Ok(());
+        }
+        let command = args
+            .first()
+            .map(|x| String::from_utf8_lossy(x).to_lowercase())
+            .unwrap_or_default();
+        if !deny_oom(&command) {
+            return Ok(());
+        }
+        let mut evicted = 0;
+        while self.memory.over_limit(&config, self.used) {
+            let Some(key) = self.eviction_candidate(&config) else {
+                debug!("{evicted} keys evicted, used memory is still over maxmemory");
+                return Err(OutOfMemoryError);
+            };
+            self.evict(&key);
+            evicted += 1;
+        }
+        debug!("{evicted} keys evicted");
+        Ok(())
+    }
+
+    fn evict(&mut self, key: &[u8]) {
+        self.unlink(key);
+        self.memory.evicted_keys.fetch_add(1, Ordering::Relaxed);
+        // the evicted key is deleted by the replay of the append only file and by the replicas too
+        self.feed(&[b"DEL".to_vec(), key.to_vec()]);
+    }
+
+    fn eviction_candidate(&mut self, config: &MaxmemoryConfig) -> Option<Vec<u8>> {
+        let policy = config.policy;
+        if policy == MaxmemoryPolicy::NoEviction {
+            return None;
+        }
+        if matches!(
+            policy,
+            MaxmemoryPolicy::AllkeysRandom | MaxmemoryPolicy::VolatileRandom
+        ) {
+            return self.sample_key(policy).cloned();
+        }
+        if self.eviction_pool.policy != Some(policy) {
+            self.eviction_pool = EvictionPool {
+                policy: Some(policy),
+                candidates: Vec::new(),
+            };
+        }
+        let now = epoch_now();
+        for _ in 0..config.samples {
+            let Some(key) = self.sample_key(policy) else {
+                break;
+            };
+            let value = &self.map[key];
+            let score = match policy {
+                // the idle time
+                MaxmemoryPolicy::AllkeysLru | MaxmemoryPolicy::VolatileLru => {
+                    (now.as_millis() as u64).saturating_sub(value.access.last)
+                }
+                // the least frequently used
+                MaxmemoryPolicy::AllkeysLfu | MaxmemoryPolicy::VolatileLfu => {
+                    (u8::MAX - value.access.decayed(now.as_secs() / 60)) as u64
+                }
+                // the nearest expire time
+                _ => u64::MAX - value.expired_epoch.unwrap_or_default().as_millis() as u64,
+            };
+            let key = key.clone();
+            self.eviction_pool.insert(score, key);
+        }
+        // the candidate may be removed or persisted since it was sampled
+        while let Some((_, key)) = self.eviction_pool.candidates.pop() {
+            let valid = self
+                .map
+                .get(&key)
+                .is_some_and(|x| !policy.volatile() || x.expired_epoch.is_some());
+            if valid {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    fn sample_key(&self, policy: MaxmemoryPolicy) -> Option<&Vec<u8>> {
+        if policy.volatile() {
+            self.expires.random_key()
+        } else {
+            self.scan_index.random_key()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deny_oom, estimate, MaxmemoryConfig, MaxmemoryPolicy, OutOfMemoryError};
+    use crate::data_watcher::data_value::DataValue;
+    use crate::data_watcher::{DataStorage, DataTTL};
+    use std::collections::VecDeque;
+    use std::time;
+
+    fn args(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    // the storage with the keys a b c d of the same size and maxmemory for three of them
+    fn storage(policy: MaxmemoryPolicy) -> DataStorage {
+        let mut data = DataStorage::new();
+        for key in ["a", "b", "c", "d"] {
+            data.insert(key.as_bytes().to_vec(), DataTTL::new(b"v".to_vec()));
+        }
+        data.memory().set_config(MaxmemoryConfig {
+            maxmemory: data.memory().used() / 4 * 3,
+            policy,
+            samples: 64,
+        });
+        data
+    }
+
+    #[test]
+    fn test_memory_accounting() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        data.insert(b"k".to_vec(), DataTTL::new(b"value".to_vec()));
+        let string = data.memory().used();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"l")
+            .unwrap()
+            .extend([b"a".to_vec(), b"b".to_vec()]);
+        data.update_memory();
+        let list = data.memory().used() - string;
+        data.remove(b"k");
+        data.remove(b"l");
+        // assert
+        assert_eq!(
+            estimate(b"k", &DataValue::String(b"value".to_vec())),
+            string
+        );
+        assert_eq!(
+            estimate(
+                b"l",
+                &DataValue::List(VecDeque::from([b"a".to_vec(), b"b".to_vec()]))
+            ),
+            list
+        );
+        assert_eq!(0, data.memory().used());
+    }
+
+    #[test]
+    fn test_check_memory_lru() {
+        // arrange
+        let mut data = storage(MaxmemoryPolicy::AllkeysLru);
+        // b is the least recently used
+        for (key, idle) in [("a", 3), ("b", 9), ("c", 1), ("d", 2)] {
+            data.map.get_mut(key.as_bytes()).unwrap().access.last -= idle * 1000;
+        }
+        // act
+        let read = data.check_memory(&args(&["GET", "a"]));
+        let write = data.check_memory(&args(&["SET", "e", "v"]));
+        // assert
+        assert!(read.is_ok());
+        assert!(write.is_ok());
+        assert!(!data.contains_key(b"b".as_slice()));
+        assert_eq!(3, data.len());
+        assert_eq!(1, data.memory().evicted_keys());
+    }
+
+    #[test]
+    fn test_check_memory_volatile_ttl() {
+        // arrange
+        let mut data = storage(MaxmemoryPolicy::VolatileTtl);
+        data.set_expired_epoch(b"c", Some(time::Duration::from_secs(u32::MAX as u64)));
+        data.set_expired_epoch(b"d", Some(time::Duration::from_secs(u32::MAX as u64 - 1)));
+        // act
+        let result = data.check_memory(&args(&["set", "e", "v"]));
+        // assert
+        assert!(result.is_ok());
+        assert!(!data.contains_key(b"d".as_slice()));
+        assert_eq!(3, data.len());
+    }
+
+    #[test]
+    fn test_check_memory_failed() {
+        // arrange
+        let mut noeviction = storage(MaxmemoryPolicy::NoEviction);
+        let mut volatile = storage(MaxmemoryPolicy::VolatileLfu);
+        // act & assert
+        assert_eq!(
+            Err(OutOfMemoryError),
+            noeviction.check_memory(&args(&["set", "e", "v"]))
+        );
+        // the commands removing data run over maxmemory
+        assert!(noeviction.check_memory(&args(&["del", "a"])).is_ok());
+        // no key with a ttl to evict
+        assert_eq!(
+            Err(OutOfMemoryError),
+            volatile.check_memory(&args(&["lpush", "l", "v"]))
+        );
+        assert_eq!(4, volatile.len());
+    }
+
+    #[test]
+    fn test_check_memory_shards() {
+        // arrange
+        let mut data = DataStorage::new();
+        for i in 0..20 {
+            data.insert(format!("k{i:02}").into_bytes(), DataTTL::new(b"v".to_vec()));
+        }
+        let memory = data.memory().clone();
+        memory.set_config(MaxmemoryConfig {
+            maxmemory: memory.used() / 2,
+            policy: MaxmemoryPolicy::AllkeysLru,
+            samples: 64,
+        });
+        let share = memory.used() / 4;
+        let mut shards = data.split(2);
+        assert!(shards.iter().all(|x| x.used > share));
+        // act
+        let first = shards[1].check_memory(&args(&["set", "a", "v"]));
+        let shard_len = shards[1].len();
+        let under_share = shards[1].check_memory(&args(&["set", "a", "v"]));
+        let second = shards[0].check_memory(&args(&["set", "b", "v"]));
+        // assert
+        assert!(first.is_ok());
+        // the shard only evicts its own keys down to its share, not all of them
+        assert!(shards[1].used <= share && shard_len > 0);
+        assert!(under_share.is_ok());
+        assert_eq!(shard_len, shards[1].len());
+        assert!(second.is_ok());
+        assert!(memory.used() <= memory.config().maxmemory);
+        assert_eq!(memory.used(), shards[0].used + shards[1].used);
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(
+            MaxmemoryPolicy::AllkeysLfu,
+            MaxmemoryPolicy::parse("ALLKEYS-LFU").unwrap()
+        );
+        assert_eq!("volatile-ttl", MaxmemoryPolicy::VolatileTtl.name());
+        assert!(MaxmemoryPolicy::parse("lru").is_err());
+        assert!(deny_oom("set"));
+        assert!(!deny_oom("del"));
+        assert!(!deny_oom("blpop"));
+        assert!(deny_oom("blmove"));
+        assert!(!deny_oom("get"));
+    }
+}
@@ -1,7 +1,35 @@
+use std::time;
+
+use crate::data_watcher::shard::LentShards;
 use crate::data_watcher::DataStorage;
 
 use resp::Value;
 
 pub trait Execution {
     fn exec(&self, data: &mut DataStorage) -> Value;
+
+    // the command on the whole keyspace with all the shards lent, see shard::Route::All
+    // the commands without keys run on the first shard
+    fn exec_shards(&self, shards: &mut LentShards, args: &[Vec<u8>]) -> Value {
+        let data = shards.first_mut();
+        let dirty = data.dirty();
+        let reply = self.exec(data);
+        data.propagate(args, dirty);
+        reply
+    }
+}
+
+// the command waiting in its shard until one of its keys is pushed, see blocking::BlockedClients
+// exec doesn't wait, it replies timeout_reply when there is nothing to pop like in MULTI
+pub trait Blocking: Execution {
+    // the keys waited for
+    fn keys(&self) -> &[Vec<u8>];
+
+    // None waits forever
+    fn timeout(&self) -> Option<time::Duration>;
+
+    fn timeout_reply(&self) -> Value;
+
+    // the reply of the blocked command served by the list of the key
+    fn serve(&self, data: &mut DataStorage, key: &[u8]) -> Value;
 }
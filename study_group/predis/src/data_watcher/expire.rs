@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+// the keys with a ttl, like the redis db->expires dict
+// a vector with the position map removes a key and picks a random key in O(1)
+#[derive(Default, Debug)]
+pub struct ExpireIndex {
+    keys: Vec<Vec<u8>>,
+    position: HashMap<Vec<u8>, usize>,
+}
+
+impl ExpireIndex {
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn insert(&mut self, key: &[u8]) {
+        if self.position.contains_key(key) {
+            return;
+        }
+        self.position.insert(key.to_owned(), self.keys.len());
+        self.keys.push(key.to_owned());
+    }
+
+    pub fn remove(&mut self, key: &[u8]) {
+        let Some(index) = self.position.remove(key) else {
+            return;
+        };
+        self.keys.swap_remove(index);
+        if let Some(moved) = self.keys.get(index) {
+            self.position.insert(moved.to_owned(), index);
+        }
+    }
+
+    pub fn random_key(&self) -> Option<&Vec<u8>> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        Some(&self.keys[rand::thread_rng().gen_range(0..self.keys.len())])
+    }
+}
+
+// how the expired keys are removed
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct ExpireStats {
+    // removed by the active expire cycle
+    pub expired_active: u64,
+    // removed when a command touches an expired key
+    pub expired_lazy: u64,
+}
+
+impl ExpireStats {
+    pub fn expired_keys(&self) -> u64 {
+        self.expired_active + self.expired_lazy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpireIndex;
+
+    #[test]
+    fn test_insert_remove() {
+        // arrange
+        let mut index = ExpireIndex::default();
+        for key in [b"a", b"b", b"c"] {
+            index.insert(key);
+        }
+        index.insert(b"a");
+        // act
+        index.remove(b"a");
+        index.remove(b"x");
+        // assert
+        assert_eq!(2, index.len());
+        assert_eq!(Some(&1), index.position.get(b"b".as_slice()));
+        assert_eq!(Some(&0), index.position.get(b"c".as_slice()));
+        assert!([b"b".as_slice(), b"c"].contains(&index.random_key().unwrap().as_slice()));
+    }
+}
@@ -1,10 +1,59 @@
-use crate::data_watcher::execution;
+use crate::data_watcher::{execution, DataStorage};
 
 use resp::Value;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
 
 // communicate with data watcher
 pub struct DataWatcherMessage {
     pub data: Box<dyn execution::Execution + Send>,
+    // the command and arguments, logged to the append only file when the data is changed
+    pub args: Vec<Vec<u8>>,
     pub callback: oneshot::Sender<Value>,
 }
+
+// the blocking command, it is parked in the shard when there is nothing to pop
+pub struct BlockingMessage {
+    pub id: u64,
+    pub data: Box<dyn execution::Blocking + Send>,
+    pub args: Vec<Vec<u8>>,
+    pub callback: oneshot::Sender<Value>,
+}
+
+// the blocking command waiting for the keys of several shards, it is parked in each of them
+// the shard wakes the client when one of the keys is pushed, the client runs the command on the lent
+// shards then unblocks itself from all of them
+pub struct ParkMessage {
+    pub id: u64,
+    // the waited keys in this shard
+    pub keys: Vec<Vec<u8>>,
+    pub wake: mpsc::Sender<()>,
+    // the client is counted by INFO in the first of its shards only
+    pub counted: bool,
+}
+
+// the messages of one shard, see shard::Shards
+pub enum ShardMessage {
+    Command(DataWatcherMessage),
+    Block(BlockingMessage),
+    Park(ParkMessage),
+    // the timeout of the blocked command, it gets its timeout reply unless it is already served
+    // the parked client is removed, its keys are served to the next clients
+    Unblock(u64),
+    // move the storage to the command touching the keys of several shards
+    Lend(oneshot::Sender<LentStorage>),
+}
+
+// the storage of a shard moved to the borrower, it is sent back to the shard when dropped
+#[derive(Debug)]
+pub struct LentStorage {
+    pub storage: DataStorage,
+    pub give_back: Option<oneshot::Sender<DataStorage>>,
+}
+
+impl Drop for LentStorage {
+    fn drop(&mut self) {
+        if let Some(give_back) = self.give_back.take() {
+            let _ = give_back.send(std::mem::take(&mut self.storage));
+        }
+    }
+}
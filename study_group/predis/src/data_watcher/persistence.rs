@@ -0,0 +1,27 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::data_watcher::aof::AofState;
+use crate::data_watcher::backlog::Backlog;
+use crate::data_watcher::snapshot::SnapshotState;
+
+// the snapshot, the append only file and the replication backlog of the server, shared by the shards
+// the shards lock it when they log a command, SAVE and BGSAVE lend all the shards first
+#[derive(Default, Debug)]
+pub struct Persistence {
+    pub(super) snapshot: Mutex<SnapshotState>,
+    pub(super) aof: Mutex<AofState>,
+    pub(super) backlog: Mutex<Backlog>,
+    // the number of changes of all the shards since startup, compared with the save rules
+    changes: AtomicU64,
+}
+
+impl Persistence {
+    pub fn changes(&self) -> u64 {
+        self.changes.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn add_changes(&self, changes: u64) {
+        self.changes.fetch_add(changes, Ordering::Relaxed);
+    }
+}
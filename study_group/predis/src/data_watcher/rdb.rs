@@ -0,0 +1,579 @@
+pub mod encoding;
+pub mod lzf;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time;
+
+use crate::data_watcher::crc64::crc64;
+use crate::data_watcher::data_value::DataValue;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::{epoch_now, DataTTL};
+
+use anyhow::{Context, Result};
+
+// the redis rdb file, https://rdb.fnordig.de/file_format.html
+//
+// "REDIS" version(4 digits)
+// AUX, SELECTDB, RESIZEDB, entries: [EXPIRETIME_MS ms | EXPIRETIME s] [IDLE | FREQ] type key value
+// EOF crc64(u64) of all the bytes before it, 0 when the checksum is disabled
+//
+// the writer uses version 9 (redis 5.0) with the plain types, so every redis since 5.0 can load it
+// the reader also knows the compact encodings of redis 7
+const MAGIC: &[u8] = b"REDIS";
+const WRITE_VERSION: u32 = 9;
+const MAX_READ_VERSION: u32 = 12;
+
+const OPCODE_SLOT_INFO: u8 = 244;
+const OPCODE_FUNCTION2: u8 = 245;
+const OPCODE_IDLE: u8 = 248;
+const OPCODE_FREQ: u8 = 249;
+const OPCODE_AUX: u8 = 250;
+const OPCODE_RESIZEDB: u8 = 251;
+const OPCODE_EXPIRETIME_MS: u8 = 252;
+const OPCODE_EXPIRETIME: u8 = 253;
+const OPCODE_SELECTDB: u8 = 254;
+const OPCODE_EOF: u8 = 255;
+
+const TYPE_STRING: u8 = 0;
+const TYPE_LIST: u8 = 1;
+const TYPE_SET: u8 = 2;
+const TYPE_ZSET: u8 = 3;
+const TYPE_HASH: u8 = 4;
+const TYPE_ZSET_2: u8 = 5;
+const TYPE_LIST_ZIPLIST: u8 = 10;
+const TYPE_SET_INTSET: u8 = 11;
+const TYPE_ZSET_ZIPLIST: u8 = 12;
+const TYPE_HASH_ZIPLIST: u8 = 13;
+const TYPE_LIST_QUICKLIST: u8 = 14;
+const TYPE_HASH_LISTPACK: u8 = 16;
+const TYPE_ZSET_LISTPACK: u8 = 17;
+const TYPE_LIST_QUICKLIST_2: u8 = 18;
+const TYPE_SET_LISTPACK: u8 = 20;
+
+// the quicklist 2 node holds one big element or a listpack
+const QUICKLIST_NODE_PLAIN: u64 = 1;
+
+// the length prefix, the top 2 bits of the first byte
+const LEN_6BIT: u8 = 0;
+const LEN_14BIT: u8 = 1;
+const LEN_32BIT: u8 = 0x80;
+const LEN_64BIT: u8 = 0x81;
+const LEN_ENCODED: u8 = 3;
+const ENC_INT8: u8 = 0;
+const ENC_INT16: u8 = 1;
+const ENC_INT32: u8 = 2;
+const ENC_LZF: u8 = 3;
+
+// one key of the rdb file, predis only has the database 0
+#[derive(PartialEq, Debug)]
+pub struct RdbEntry {
+    pub db: u64,
+    pub key: Vec<u8>,
+    pub value: DataTTL,
+}
+
+pub fn encode<'a>(entries: impl Iterator<Item = (&'a Vec<u8>, &'a DataTTL)>) -> Vec<u8> {
+    let entries: Vec<(&Vec<u8>, &DataTTL)> = entries.collect();
+    let mut buf = Vec::from(MAGIC);
+    buf.extend(format!("{WRITE_VERSION:04}").as_bytes());
+    for (key, value) in [
+        ("redis-bits", "64".to_string()),
+        ("ctime", epoch_now().as_secs().to_string()),
+    ] {
+        buf.push(OPCODE_AUX);
+        put_string(&mut buf, key.as_bytes());
+        put_string(&mut buf, value.as_bytes());
+    }
+    buf.push(OPCODE_SELECTDB);
+    put_len(&mut buf, 0);
+    buf.push(OPCODE_RESIZEDB);
+    put_len(&mut buf, entries.len() as u64);
+    put_len(
+        &mut buf,
+        entries
+            .iter()
+            .filter(|(_, x)| x.expired_epoch().is_some())
+            .count() as u64,
+    );
+    for (key, value) in entries {
+        if let Some(expired) = value.expired_epoch() {
+            buf.push(OPCODE_EXPIRETIME_MS);
+            buf.extend((expired.as_millis() as u64).to_le_bytes());
+        }
+        let strings = |buf: &mut Vec<u8>, items: Vec<&Vec<u8>>| {
+            put_len(buf, items.len() as u64);
+            items.iter().for_each(|x| put_string(buf, x));
+        };
+        match value.value() {
+            DataValue::String(v) => {
+                buf.push(TYPE_STRING);
+                put_string(&mut buf, key);
+                put_string(&mut buf, v);
+            }
+            DataValue::List(v) => {
+                buf.push(TYPE_LIST);
+                put_string(&mut buf, key);
+                strings(&mut buf, v.iter().collect());
+            }
+            DataValue::Set(v) => {
+                buf.push(TYPE_SET);
+                put_string(&mut buf, key);
+                strings(&mut buf, v.iter().collect());
+            }
+            DataValue::Hash(v) => {
+                buf.push(TYPE_HASH);
+                put_string(&mut buf, key);
+                put_len(&mut buf, v.len() as u64);
+                for (field, value) in v.iter() {
+                    put_string(&mut buf, field);
+                    put_string(&mut buf, value);
+                }
+            }
+            DataValue::ZSet(v) => {
+                buf.push(TYPE_ZSET_2);
+                put_string(&mut buf, key);
+                put_len(&mut buf, v.len() as u64);
+                for (member, score) in v.iter() {
+                    put_string(&mut buf, member);
+                    buf.extend(score.to_le_bytes());
+                }
+            }
+        }
+    }
+    buf.push(OPCODE_EOF);
+    let checksum = crc64(0, &buf);
+    buf.extend(checksum.to_le_bytes());
+    buf
+}
+
+fn put_len(buf: &mut Vec<u8>, len: u64) {
+    if len < 1 << 6 {
+        buf.push((LEN_6BIT << 6) | len as u8);
+    } else if len < 1 << 14 {
+        buf.push((LEN_14BIT << 6) | (len >> 8) as u8);
+        buf.push(len as u8);
+    } else if len <= u32::MAX as u64 {
+        buf.push(LEN_32BIT);
+        buf.extend((len as u32).to_be_bytes());
+    } else {
+        buf.push(LEN_64BIT);
+        buf.extend(len.to_be_bytes());
+    }
+}
+
+fn put_string(buf: &mut Vec<u8>, s: &[u8]) {
+    put_len(buf, s.len() as u64);
+    buf.extend(s);
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<RdbEntry>> {
+    anyhow::ensure!(
+        bytes.len() >= 9 && bytes.starts_with(MAGIC),
+        "not a rdb file"
+    );
+    let version: u32 = std::str::from_utf8(&bytes[5..9])?
+        .parse()
+        .context("invalid rdb version")?;
+    anyhow::ensure!(
+        (1..=MAX_READ_VERSION).contains(&version),
+        "unsupported rdb version {version}"
+    );
+    let mut reader = Reader { buf: bytes, pos: 9 };
+    let mut entries = Vec::new();
+    let mut db = 0;
+    let mut expired_ms = None;
+    loop {
+        let opcode = reader.u8()?;
+        match opcode {
+            OPCODE_EOF => break,
+            OPCODE_SELECTDB => db = reader.len()?,
+            OPCODE_RESIZEDB => {
+                reader.len()?;
+                reader.len()?;
+            }
+            OPCODE_AUX => {
+                reader.string()?;
+                reader.string()?;
+            }
+            OPCODE_SLOT_INFO => {
+                for _ in 0..3 {
+                    reader.len()?;
+                }
+            }
+            // the functions of redis 7 are not supported, skip the library code
+            OPCODE_FUNCTION2 => {
+                reader.string()?;
+            }
+            // the lru and lfu information of the next key
+            OPCODE_IDLE => {
+                reader.len()?;
+            }
+            OPCODE_FREQ => {
+                reader.u8()?;
+            }
+            OPCODE_EXPIRETIME_MS => {
+                expired_ms = Some(u64::from_le_bytes(reader.array()?));
+            }
+            OPCODE_EXPIRETIME => {
+                expired_ms = Some(u32::from_le_bytes(reader.array()?) as u64 * 1000);
+            }
+            value_type => {
+                let key = reader.string()?;
+                let value = reader
+                    .value(value_type)
+                    .with_context(|| format!("load key {}", String::from_utf8_lossy(&key)))?;
+                let mut value = DataTTL::new(value);
+                if let Some(ms) = expired_ms.take() {
+                    value = value.expired_timestamp(&time::Duration::from_millis(ms));
+                }
+                entries.push(RdbEntry { db, key, value });
+            }
+        }
+    }
+    // the checksum is added in version 5
+    if version >= 5 {
+        let body_len = reader.pos;
+        let checksum = u64::from_le_bytes(reader.array()?);
+        anyhow::ensure!(
+            checksum == 0 || checksum == crc64(0, &bytes[..body_len]),
+            "rdb checksum mismatch"
+        );
+    }
+    Ok(entries)
+}
+
+fn parse_score(bytes: &[u8]) -> Result<f64> {
+    let score = std::str::from_utf8(bytes)?;
+    match score {
+        "inf" | "+inf" => Ok(f64::INFINITY),
+        "-inf" => Ok(f64::NEG_INFINITY),
+        _ => checked_score(score.parse().context("invalid zset score")?),
+    }
+}
+
+// like redis, the file with a nan score is corrupted
+fn checked_score(score: f64) -> Result<f64> {
+    anyhow::ensure!(!score.is_nan(), "zset with nan score detected");
+    Ok(score)
+}
+
+fn pairs(items: Vec<Vec<u8>>) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    anyhow::ensure!(items.len().is_multiple_of(2), "invalid pairs");
+    let mut items = items.into_iter();
+    let mut pairs = Vec::new();
+    while let (Some(a), Some(b)) = (items.next(), items.next()) {
+        pairs.push((a, b));
+    }
+    Ok(pairs)
+}
+
+fn zset(pairs: Vec<(Vec<u8>, Vec<u8>)>) -> Result<DataValue> {
+    let mut zset = SortedSet::new();
+    for (member, score) in pairs {
+        zset.insert(member, parse_score(&score)?);
+    }
+    Ok(DataValue::ZSet(zset))
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|x| *x <= self.buf.len())
+            .context("unexpected end of rdb file")?;
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.bytes(N)?.try_into()?)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    // the length, or the special string encoding
+    fn len_or_encoding(&mut self) -> Result<(u64, bool)> {
+        let first = self.u8()?;
+        Ok(match first >> 6 {
+            LEN_6BIT => ((first & 0x3f) as u64, false),
+            LEN_14BIT => ((((first & 0x3f) as u64) << 8) | self.u8()? as u64, false),
+            LEN_ENCODED => ((first & 0x3f) as u64, true),
+            _ => match first {
+                LEN_32BIT => (u32::from_be_bytes(self.array()?) as u64, false),
+                LEN_64BIT => (u64::from_be_bytes(self.array()?), false),
+                _ => anyhow::bail!("invalid rdb length {first}"),
+            },
+        })
+    }
+
+    fn len(&mut self) -> Result<u64> {
+        match self.len_or_encoding()? {
+            (len, false) => Ok(len),
+            _ => anyhow::bail!("invalid rdb length"),
+        }
+    }
+
+    fn string(&mut self) -> Result<Vec<u8>> {
+        let (len, encoded) = self.len_or_encoding()?;
+        if !encoded {
+            return Ok(self.bytes(usize::try_from(len)?)?.to_vec());
+        }
+        let value = match len as u8 {
+            ENC_INT8 => self.u8()? as i8 as i64,
+            ENC_INT16 => i16::from_le_bytes(self.array()?) as i64,
+            ENC_INT32 => i32::from_le_bytes(self.array()?) as i64,
+            ENC_LZF => {
+                let compressed_len = usize::try_from(self.len()?)?;
+                let len = usize::try_from(self.len()?)?;
+                return lzf::decompress(self.bytes(compressed_len)?, len);
+            }
+            encoding => anyhow::bail!("invalid rdb string encoding {encoding}"),
+        };
+        Ok(value.to_string().into_bytes())
+    }
+
+    fn strings(&mut self) -> Result<Vec<Vec<u8>>> {
+        (0..self.len()?).map(|_| self.string()).collect()
+    }
+
+    fn value(&mut self, value_type: u8) -> Result<DataValue> {
+        Ok(match value_type {
+            TYPE_STRING => DataValue::String(self.string()?),
+            TYPE_LIST => DataValue::List(VecDeque::from(self.strings()?)),
+            TYPE_SET => DataValue::Set(HashSet::from_iter(self.strings()?)),
+            TYPE_HASH => {
+                let mut hash = HashMap::new();
+                for _ in 0..self.len()? {
+                    hash.insert(self.string()?, self.string()?);
+                }
+                DataValue::Hash(hash)
+            }
+            // the score is a string with one byte length, 253 nan 254 +inf 255 -inf
+            TYPE_ZSET => {
+                let mut zset = SortedSet::new();
+                for _ in 0..self.len()? {
+                    let member = self.string()?;
+                    let score = match self.u8()? {
+                        253 => checked_score(f64::NAN)?,
+                        254 => f64::INFINITY,
+                        255 => f64::NEG_INFINITY,
+                        len => parse_score(self.bytes(len as usize)?)?,
+                    };
+                    zset.insert(member, score);
+                }
+                DataValue::ZSet(zset)
+            }
+            TYPE_ZSET_2 => {
+                let mut zset = SortedSet::new();
+                for _ in 0..self.len()? {
+                    let member = self.string()?;
+                    zset.insert(member, checked_score(f64::from_le_bytes(self.array()?))?);
+                }
+                DataValue::ZSet(zset)
+            }
+            TYPE_LIST_ZIPLIST => {
+                DataValue::List(VecDeque::from(encoding::ziplist(&self.string()?)?))
+            }
+            TYPE_LIST_QUICKLIST => {
+                let mut list = VecDeque::new();
+                for _ in 0..self.len()? {
+                    list.extend(encoding::ziplist(&self.string()?)?);
+                }
+                DataValue::List(list)
+            }
+            TYPE_LIST_QUICKLIST_2 => {
+                let mut list = VecDeque::new();
+                for _ in 0..self.len()? {
+                    let container = self.len()?;
+                    let node = self.string()?;
+                    if container == QUICKLIST_NODE_PLAIN {
+                        list.push_back(node);
+                    } else {
+                        list.extend(encoding::listpack(&node)?);
+                    }
+                }
+                DataValue::List(list)
+            }
+            TYPE_SET_INTSET => {
+                DataValue::Set(HashSet::from_iter(encoding::intset(&self.string()?)?))
+            }
+            TYPE_SET_LISTPACK => {
+                DataValue::Set(HashSet::from_iter(encoding::listpack(&self.string()?)?))
+            }
+            TYPE_HASH_ZIPLIST => DataValue::Hash(HashMap::from_iter(pairs(encoding::ziplist(
+                &self.string()?,
+            )?)?)),
+            TYPE_HASH_LISTPACK => DataValue::Hash(HashMap::from_iter(pairs(encoding::listpack(
+                &self.string()?,
+            )?)?)),
+            TYPE_ZSET_ZIPLIST => zset(pairs(encoding::ziplist(&self.string()?)?)?)?,
+            TYPE_ZSET_LISTPACK => zset(pairs(encoding::listpack(&self.string()?)?)?)?,
+            _ => anyhow::bail!("unsupported rdb type {value_type}"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, put_len, put_string, RdbEntry};
+    use crate::data_watcher::crc64::crc64;
+    use crate::data_watcher::data_value::DataValue;
+    use crate::data_watcher::sorted_set::SortedSet;
+    use crate::data_watcher::{DataStorage, DataTTL};
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use std::time;
+
+    fn strings(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(
+            b"s".to_vec(),
+            DataTTL::new(b"v".repeat(100)).expired_timestamp(&time::Duration::from_millis(5000)),
+        );
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"l")
+            .unwrap()
+            .extend(strings(&["a", "b"]));
+        data.get_typed_or_default::<HashMap<Vec<u8>, Vec<u8>>>(b"h")
+            .unwrap()
+            .insert(b"f".to_vec(), b"v".to_vec());
+        data.get_typed_or_default::<HashSet<Vec<u8>>>(b"set")
+            .unwrap()
+            .insert(b"m".to_vec());
+        data.get_typed_or_default::<SortedSet>(b"z")
+            .unwrap()
+            .insert(b"m".to_vec(), -1.5);
+        // act
+        let entries = decode(&encode(data.iter())).unwrap();
+        // assert
+        assert_eq!(5, entries.len());
+        for entry in entries {
+            assert_eq!(0, entry.db);
+            assert_eq!(data.get(&entry.key), Some(&entry.value));
+        }
+    }
+
+    // a file like redis 7 writes, with the compact encodings and the special strings
+    #[test]
+    fn test_decode_redis_7() {
+        // arrange
+        let mut buf = b"REDIS0011".to_vec();
+        buf.push(250);
+        put_string(&mut buf, b"redis-ver");
+        put_string(&mut buf, b"7.2.4");
+        buf.extend([254, 0, 251, 4, 1]);
+        // integer encoded string with the expire time in seconds
+        buf.extend([253, 10, 0, 0, 0, 0]);
+        put_string(&mut buf, b"int");
+        buf.extend([0xc1, 0x39, 0x30]);
+        // lzf compressed string
+        buf.push(0);
+        put_string(&mut buf, b"lzf");
+        buf.extend([0xc3, 6, 11, 0x01, b'a', b'b', 0xe0, 0x00, 0x01]);
+        // quicklist 2 with one listpack node ["x", 7]
+        buf.extend([18]);
+        put_string(&mut buf, b"list");
+        buf.extend([1, 2]);
+        put_string(
+            &mut buf,
+            &[0, 0, 0, 0, 2, 0, 0x81, b'x', 0x02, 0x07, 0x01, 0xff],
+        );
+        // zset listpack ["m", "1.5"] with the lru idle time
+        buf.extend([248, 5, 17]);
+        put_string(&mut buf, b"zset");
+        put_string(
+            &mut buf,
+            &[
+                0, 0, 0, 0, 2, 0, 0x81, b'm', 0x02, 0x83, b'1', b'.', b'5', 0x04, 0xff,
+            ],
+        );
+        buf.push(255);
+        let checksum = crc64(0, &buf);
+        buf.extend(checksum.to_le_bytes());
+        // act
+        let entries = decode(&buf).unwrap();
+        // assert
+        let mut zset = SortedSet::new();
+        zset.insert(b"m".to_vec(), 1.5);
+        assert_eq!(
+            vec![
+                RdbEntry {
+                    db: 0,
+                    key: b"int".to_vec(),
+                    value: DataTTL::new(b"12345".to_vec())
+                        .expired_timestamp(&time::Duration::from_secs(10)),
+                },
+                RdbEntry {
+                    db: 0,
+                    key: b"lzf".to_vec(),
+                    value: DataTTL::new(b"abababababa".to_vec()),
+                },
+                RdbEntry {
+                    db: 0,
+                    key: b"list".to_vec(),
+                    value: DataTTL::new(DataValue::List(VecDeque::from(strings(&["x", "7"])))),
+                },
+                RdbEntry {
+                    db: 0,
+                    key: b"zset".to_vec(),
+                    value: DataTTL::new(DataValue::ZSet(zset)),
+                },
+            ],
+            entries
+        );
+    }
+
+    #[test]
+    fn test_decode_failed() {
+        // arrange
+        let bytes = encode(std::iter::empty());
+        let mut flipped = bytes.clone();
+        flipped[12] ^= 1;
+        let mut unknown_type = b"REDIS0009".to_vec();
+        unknown_type.push(15);
+        put_string(&mut unknown_type, b"stream");
+        let mut nan_score = b"REDIS0009".to_vec();
+        nan_score.push(3);
+        put_string(&mut nan_score, b"zset");
+        nan_score.push(1);
+        put_string(&mut nan_score, b"m");
+        nan_score.extend([253, 255]);
+        let checksum = crc64(0, &nan_score);
+        nan_score.extend(checksum.to_le_bytes());
+        // act & assert
+        assert!(decode(&bytes).is_ok());
+        assert!(decode(&flipped).is_err());
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode(b"REDIS0099").is_err());
+        assert!(decode(&unknown_type).is_err());
+        assert_eq!(
+            "zset with nan score detected",
+            decode(&nan_score).unwrap_err().root_cause().to_string()
+        );
+    }
+
+    #[test]
+    fn test_put_len() {
+        let encoded = |len| {
+            let mut buf = Vec::new();
+            put_len(&mut buf, len);
+            buf
+        };
+        assert_eq!(vec![0x3f], encoded(63));
+        assert_eq!(vec![0x41, 0x00], encoded(256));
+        assert_eq!(vec![0x80, 0, 1, 0, 0], encoded(65536));
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+
+// the compact encodings of small lists, hashes, sets and zsets in the rdb file
+// the integer entries are returned as their decimal strings like redis does when it converts them
+
+// https://github.com/redis/redis/blob/7.0/src/ziplist.c
+// <zlbytes u32> <zltail u32> <zllen u16> <entry> ... <0xff>
+// entry: <prevlen 1 or 5 bytes> <encoding> <data>
+pub fn ziplist(buf: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut cursor = Cursor::new(buf);
+    cursor.skip(10)?;
+    let mut entries = Vec::new();
+    loop {
+        let prevlen = cursor.u8()?;
+        if prevlen == 0xff {
+            break;
+        }
+        if prevlen == 0xfe {
+            cursor.skip(4)?;
+        }
+        let encoding = cursor.u8()?;
+        let entry = match encoding >> 6 {
+            0b00 => cursor.bytes((encoding & 0x3f) as usize)?.to_vec(),
+            0b01 => {
+                let len = ((encoding as usize & 0x3f) << 8) | cursor.u8()? as usize;
+                cursor.bytes(len)?.to_vec()
+            }
+            0b10 => {
+                let len = u32::from_be_bytes(cursor.array()?) as usize;
+                cursor.bytes(len)?.to_vec()
+            }
+            _ => {
+                let value = match encoding {
+                    0xc0 => i16::from_le_bytes(cursor.array()?) as i64,
+                    0xd0 => i32::from_le_bytes(cursor.array()?) as i64,
+                    0xe0 => i64::from_le_bytes(cursor.array()?),
+                    0xf0 => cursor.int24()?,
+                    0xfe => cursor.u8()? as i8 as i64,
+                    0xf1..=0xfd => (encoding & 0x0f) as i64 - 1,
+                    _ => anyhow::bail!("invalid ziplist encoding {encoding}"),
+                };
+                value.to_string().into_bytes()
+            }
+        };
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+// https://github.com/redis/redis/blob/7.0/src/listpack.c
+// <total bytes u32> <num elements u16> <entry> ... <0xff>
+// entry: <encoding> <data> <backlen>, backlen is the size of encoding and data in 1 to 5 bytes
+pub fn listpack(buf: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut cursor = Cursor::new(buf);
+    cursor.skip(6)?;
+    let mut entries = Vec::new();
+    loop {
+        let start = cursor.pos;
+        let encoding = cursor.u8()?;
+        if encoding == 0xff {
+            break;
+        }
+        let entry = if encoding & 0x80 == 0 {
+            (encoding as i64).to_string().into_bytes()
+        } else if encoding & 0xc0 == 0x80 {
+            cursor.bytes((encoding & 0x3f) as usize)?.to_vec()
+        } else if encoding & 0xe0 == 0xc0 {
+            let value = ((encoding as i64 & 0x1f) << 8) | cursor.u8()? as i64;
+            // 13 bits two's complement
+            let value = if value >= 1 << 12 {
+                value - (1 << 13)
+            } else {
+                value
+            };
+            value.to_string().into_bytes()
+        } else if encoding & 0xf0 == 0xe0 {
+            let len = ((encoding as usize & 0x0f) << 8) | cursor.u8()? as usize;
+            cursor.bytes(len)?.to_vec()
+        } else {
+            match encoding {
+                0xf0 => {
+                    let len = u32::from_le_bytes(cursor.array()?) as usize;
+                    cursor.bytes(len)?.to_vec()
+                }
+                0xf1 => i16::from_le_bytes(cursor.array()?).to_string().into_bytes(),
+                0xf2 => cursor.int24()?.to_string().into_bytes(),
+                0xf3 => i32::from_le_bytes(cursor.array()?).to_string().into_bytes(),
+                0xf4 => i64::from_le_bytes(cursor.array()?).to_string().into_bytes(),
+                _ => anyhow::bail!("invalid listpack encoding {encoding}"),
+            }
+        };
+        cursor.skip(backlen_size(cursor.pos - start))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn backlen_size(len: usize) -> usize {
+    match len {
+        0..=127 => 1,
+        128..=16382 => 2,
+        16383..=2097150 => 3,
+        2097151..=268435454 => 4,
+        _ => 5,
+    }
+}
+
+// https://github.com/redis/redis/blob/7.0/src/intset.c
+// <encoding u32: 2, 4 or 8> <length u32> <integers little endian>
+pub fn intset(buf: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut cursor = Cursor::new(buf);
+    let encoding = u32::from_le_bytes(cursor.array()?);
+    let len = u32::from_le_bytes(cursor.array()?);
+    (0..len)
+        .map(|_| {
+            let value = match encoding {
+                2 => i16::from_le_bytes(cursor.array()?) as i64,
+                4 => i32::from_le_bytes(cursor.array()?) as i64,
+                8 => i64::from_le_bytes(cursor.array()?),
+                _ => anyhow::bail!("invalid intset encoding {encoding}"),
+            };
+            Ok(value.to_string().into_bytes())
+        })
+        .collect()
+}
+
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Cursor { buf, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + len)
+            .context("unexpected end of compact encoding")?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.bytes(N)?.try_into()?)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn int24(&mut self) -> Result<i64> {
+        let [a, b, c] = self.array()?;
+        // shift into the top of i32 to keep the sign
+        Ok((i32::from_le_bytes([0, a, b, c]) >> 8) as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{intset, listpack, ziplist};
+
+    fn strings(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_ziplist() {
+        // arrange
+        let mut buf = vec![0; 10];
+        buf.extend([0x00, 0x02, b'a', b'b']);
+        buf.extend([0x04, 0xf2]);
+        buf.extend([0x02, 0xfe, 0x9c]);
+        buf.extend([0x03, 0xc0, 0xe8, 0x03]);
+        buf.push(0xff);
+        // act
+        let result = ziplist(&buf);
+        // assert
+        assert_eq!(strings(&["ab", "1", "-100", "1000"]), result.unwrap());
+    }
+
+    #[test]
+    fn test_listpack() {
+        // arrange
+        let mut buf = vec![0; 6];
+        buf.extend([0x82, b'a', b'b', 0x03]);
+        buf.extend([0x05, 0x01]);
+        buf.extend([0xdf, 0x9c, 0x02]);
+        buf.extend([0xf1, 0x10, 0x27, 0x03]);
+        buf.push(0xff);
+        // act
+        let result = listpack(&buf);
+        // assert
+        assert_eq!(strings(&["ab", "5", "-100", "10000"]), result.unwrap());
+    }
+
+    #[test]
+    fn test_intset() {
+        assert_eq!(
+            strings(&["-1", "300"]),
+            intset(&[2, 0, 0, 0, 2, 0, 0, 0, 0xff, 0xff, 0x2c, 0x01]).unwrap()
+        );
+        assert!(intset(&[2, 0, 0, 0, 2, 0, 0, 0, 0xff]).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+
+// the most bytes one input byte expands to, a 3 bytes match copies 7 + 255 + 2 bytes
+const MAX_EXPANSION: usize = 88;
+
+// decompress the lzf string of the rdb file, see lzf_d.c of redis
+// the control byte below 32 is a literal run of ctrl + 1 bytes
+// otherwise the top 3 bits are the match length - 2 (7 takes one more byte) and the rest is the offset
+pub fn decompress(input: &[u8], len: usize) -> Result<Vec<u8>> {
+    // the length comes from the file, it can't be more than the input expands to
+    anyhow::ensure!(
+        len <= input.len().saturating_mul(MAX_EXPANSION),
+        "invalid lzf string length"
+    );
+    let mut output = Vec::with_capacity(len);
+    let mut pos = 0;
+    let byte = |pos: usize| {
+        input
+            .get(pos)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("invalid lzf string"))
+    };
+    while pos < input.len() {
+        let ctrl = input[pos] as usize;
+        pos += 1;
+        if ctrl < 32 {
+            let literal = input
+                .get(pos..pos + ctrl + 1)
+                .ok_or_else(|| anyhow::anyhow!("invalid lzf string"))?;
+            output.extend_from_slice(literal);
+            pos += ctrl + 1;
+            continue;
+        }
+        let mut match_len = ctrl >> 5;
+        if match_len == 7 {
+            match_len += byte(pos)? as usize;
+            pos += 1;
+        }
+        let offset = ((ctrl & 0x1f) << 8) + byte(pos)? as usize + 1;
+        pos += 1;
+        anyhow::ensure!(offset <= output.len(), "invalid lzf string");
+        // the match may overlap the bytes it produces
+        let start = output.len() - offset;
+        for i in 0..match_len + 2 {
+            output.push(output[start + i]);
+        }
+    }
+    anyhow::ensure!(output.len() == len, "invalid lzf string length");
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decompress;
+
+    #[test]
+    fn test_decompress() {
+        // literal "ab" then copy 9 bytes from 2 bytes back
+        assert_eq!(
+            b"abababababa".to_vec(),
+            decompress(&[0x01, b'a', b'b', 0xe0, 0x00, 0x01], 11).unwrap()
+        );
+        assert!(decompress(&[0x01, b'a'], 2).is_err());
+        assert!(decompress(&[0x20, 0x05], 3).is_err());
+        assert!(decompress(&[0x00, b'a'], usize::MAX).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeSet;
+use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+
+use rand::Rng;
+
+// the position of an element in the scan order
+// the order only depends on the element itself, so an element which exists during the whole iteration
+// is always returned no matter how the collection is changed between the calls
+pub fn scan_hash(input: &[u8]) -> u64 {
+    BuildHasherDefault::<DefaultHasher>::default().hash_one(input)
+}
+
+// the keys ordered by scan hash, SCAN reads one batch in O(log n + count)
+#[derive(Default, Debug)]
+pub struct ScanIndex {
+    keys: BTreeSet<(u64, Vec<u8>)>,
+}
+
+impl ScanIndex {
+    pub fn insert(&mut self, key: &[u8]) {
+        self.keys.insert((scan_hash(key), key.to_owned()));
+    }
+
+    pub fn remove(&mut self, key: &[u8]) {
+        self.keys.remove(&(scan_hash(key), key.to_owned()));
+    }
+
+    // return at least count keys in scan order from the cursor and the next cursor, 0 means finished
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<&Vec<u8>>) {
+        let mut iter = self.keys.range((cursor, Vec::new())..).peekable();
+        let mut output = Vec::new();
+        while let Some((hash, key)) = iter.next() {
+            output.push(key);
+            // keys with the same hash are returned in the same batch
+            let same_hash = iter.peek().is_some_and(|(next, _)| next == hash);
+            if output.len() >= count && !same_hash {
+                break;
+            }
+        }
+        let next_cursor = iter.next().map_or(0, |(hash, _)| *hash);
+        (next_cursor, output)
+    }
+
+    // the first key after a random position, O(log n)
+    pub fn random_key(&self) -> Option<&Vec<u8>> {
+        let position = rand::thread_rng().gen::<u64>();
+        self.keys
+            .range((position, Vec::new())..)
+            .next()
+            .or_else(|| self.keys.first())
+            .map(|(_, key)| key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScanIndex;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_scan_with_mutation() {
+        // arrange
+        let mut index = ScanIndex::default();
+        for i in 0..100 {
+            index.insert(i.to_string().as_bytes());
+        }
+        let mut returned = HashSet::new();
+        let mut cursor = 0;
+        // act
+        loop {
+            let (next, batch) = index.scan(cursor, 7);
+            assert!(batch.len() >= 7 || next == 0);
+            returned.extend(batch.into_iter().cloned());
+            // mutate the index between the calls
+            index.insert(format!("new{next}").as_bytes());
+            index.remove(format!("new{cursor}").as_bytes());
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+        // assert
+        for i in 0..100 {
+            assert!(returned.contains(i.to_string().as_bytes()));
+        }
+        assert!(index.random_key().is_some());
+    }
+}
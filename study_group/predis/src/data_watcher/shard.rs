@@ -0,0 +1,764 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time;
+
+use crate::data_watcher::blocking;
+use crate::data_watcher::crc64::crc64;
+use crate::data_watcher::execution::{Blocking, Execution};
+use crate::data_watcher::message::{
+    BlockingMessage, DataWatcherMessage, LentStorage, ParkMessage, ShardMessage,
+};
+use crate::data_watcher::persistence::Persistence;
+use crate::data_watcher::watched_keys::WatchedKey;
+use crate::data_watcher::{snapshot, DataStorage, DataTTL};
+
+use anyhow::Result;
+use log::{error, info};
+use resp::Value;
+use tokio::sync::{mpsc, oneshot};
+
+// the save rules and the everysec fsync are checked every 100ms like the data watcher cron
+const PERSISTENCE_CRON_PERIOD: time::Duration = time::Duration::from_millis(100);
+
+// the shard of the key, only the part between the first { and the next } is hashed when it is not empty
+// like the redis cluster hash tags, {user1}.name and {user1}.age are always in the same shard
+pub fn shard_index(key: &[u8], count: usize) -> usize {
+    if count == 1 {
+        return 0;
+    }
+    let mut hashed = key;
+    if let Some(start) = key.iter().position(|x| *x == b'{') {
+        let tag = key[start + 1..].iter().position(|x| *x == b'}');
+        if let Some(len) = tag.filter(|x| *x > 0) {
+            hashed = &key[start + 1..start + 1 + len];
+        }
+    }
+    (crc64(0, hashed) % count as u64) as usize
+}
+
+// the timeout of a blocking command, None waits forever
+async fn sleep(timeout: Option<time::Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => std::future::pending().await,
+    }
+}
+
+// the keys of a command decide the shards running it, see redis_protocol::key_spec
+#[derive(PartialEq, Debug)]
+pub enum Route {
+    // the command without keys runs on the first shard
+    Keys(Vec<Vec<u8>>),
+    // the command on the whole keyspace, like KEYS, SCAN and SAVE
+    All,
+}
+
+// the data watchers owning one part of the keyspace each
+// the command of one shard is a message to it, so the shards run in parallel
+// the command of several shards borrows their storages in ascending order and runs in the caller
+#[derive(Clone, Debug)]
+pub struct Shards {
+    senders: Vec<mpsc::Sender<ShardMessage>>,
+}
+
+impl Shards {
+    pub fn new(senders: Vec<mpsc::Sender<ShardMessage>>) -> Self {
+        Shards { senders }
+    }
+
+    // split the loaded storage, then start the data watchers and the persistence cron
+    pub async fn start(storage: DataStorage, count: usize, capacity: usize) -> Self {
+        let persistence = storage.persistence.clone();
+        let mut senders = Vec::with_capacity(count);
+        for shard in storage.split(count) {
+            let (tx, rx) = mpsc::channel(capacity);
+            super::new(rx, shard).await;
+            senders.push(tx);
+        }
+        info!("keyspace is sharded over {count} data watchers");
+        let shards = Shards::new(senders);
+        tokio::spawn(persistence_cron(shards.clone(), persistence));
+        shards
+    }
+
+    pub fn count(&self) -> usize {
+        self.senders.len()
+    }
+
+    pub async fn exec(
+        &self,
+        route: Route,
+        data: Box<dyn Execution + Send>,
+        args: Vec<Vec<u8>>,
+    ) -> Result<Value> {
+        let keys = match route {
+            Route::Keys(keys) => keys,
+            Route::All => {
+                let mut shards = self.lend((0..self.count()).collect()).await?;
+                return Ok(data.exec_shards(&mut shards, &args));
+            }
+        };
+        let indices = self.indices(&keys);
+        if indices.len() <= 1 {
+            return self
+                .send(indices.first().copied().unwrap_or(0), data, args)
+                .await;
+        }
+        let mut shards = self.lend(indices).await?;
+        Ok(shards.exec_merged(&keys, data.as_ref(), &args))
+    }
+
+    async fn send(
+        &self,
+        index: usize,
+        data: Box<dyn Execution + Send>,
+        args: Vec<Vec<u8>>,
+    ) -> Result<Value> {
+        let (callback, callback_rx) = oneshot::channel();
+        self.senders[index]
+            .send(ShardMessage::Command(DataWatcherMessage {
+                data,
+                args,
+                callback,
+            }))
+            .await
+            .map_err(|_| anyhow::anyhow!("get data failed"))?;
+        callback_rx
+            .await
+            .map_err(|_| anyhow::anyhow!("get data failed"))
+    }
+
+    // BLPOP, BRPOP and BLMOVE, the command waits in the shard of its keys until it is served
+    // the shard replies the timeout reply to Unblock unless it served the command first, so the
+    // popped element is never lost, the dropped future leaves the command to the disconnect cleanup
+    pub async fn exec_blocking(
+        &self,
+        route: Route,
+        data: Box<dyn Blocking + Send>,
+        args: Vec<Vec<u8>>,
+    ) -> Result<Value> {
+        let Route::Keys(keys) = route else {
+            anyhow::bail!("blocking command without keys");
+        };
+        let indices = self.indices(&keys);
+        if indices.len() > 1 {
+            return self.exec_parked(keys, indices, data, args).await;
+        }
+        let index = indices.first().copied().unwrap_or(0);
+        let id = blocking::next_id();
+        let timeout = data.timeout();
+        let (callback, mut callback_rx) = oneshot::channel();
+        self.senders[index]
+            .send(ShardMessage::Block(BlockingMessage {
+                id,
+                data,
+                args,
+                callback,
+            }))
+            .await
+            .map_err(|_| anyhow::anyhow!("get data failed"))?;
+        tokio::select! {
+            reply = &mut callback_rx => {
+                return reply.map_err(|_| anyhow::anyhow!("get data failed"));
+            }
+            _ = sleep(timeout) => {}
+        }
+        self.senders[index]
+            .send(ShardMessage::Unblock(id))
+            .await
+            .map_err(|_| anyhow::anyhow!("get data failed"))?;
+        callback_rx
+            .await
+            .map_err(|_| anyhow::anyhow!("get data failed"))
+    }
+
+    // the blocking command of several shards, like BLPOP with keys of two shards or BLMOVE with the
+    // destination in another shard, is parked in the shards of its waited keys before it runs on
+    // the lent shards, so a push in between wakes it, the woken command runs again until it gets
+    // a reply or times out, then it is unblocked from the shards
+    async fn exec_parked(
+        &self,
+        keys: Vec<Vec<u8>>,
+        indices: Vec<usize>,
+        data: Box<dyn Blocking + Send>,
+        args: Vec<Vec<u8>>,
+    ) -> Result<Value> {
+        let id = blocking::next_id();
+        let (wake, mut woken) = mpsc::channel(1);
+        let waited = self.indices(data.keys());
+        for (i, index) in waited.iter().enumerate() {
+            let keys = data
+                .keys()
+                .iter()
+                .filter(|x| shard_index(x, self.count()) == *index)
+                .cloned()
+                .collect();
+            self.senders[*index]
+                .send(ShardMessage::Park(ParkMessage {
+                    id,
+                    keys,
+                    wake: wake.clone(),
+                    counted: i == 0,
+                }))
+                .await
+                .map_err(|_| anyhow::anyhow!("get data failed"))?;
+        }
+        drop(wake);
+        let timeout = sleep(data.timeout());
+        tokio::pin!(timeout);
+        let reply = loop {
+            let mut shards = self.lend(indices.clone()).await?;
+            let reply = shards.exec_merged(&keys, data.as_ref(), &args);
+            drop(shards);
+            if reply != data.timeout_reply() {
+                break reply;
+            }
+            tokio::select! {
+                _ = woken.recv() => {}
+                _ = &mut timeout => break reply,
+            }
+        };
+        for index in waited {
+            self.senders[index]
+                .send(ShardMessage::Unblock(id))
+                .await
+                .map_err(|_| anyhow::anyhow!("get data failed"))?;
+        }
+        Ok(reply)
+    }
+
+    // the shards of the keys in ascending order
+    fn indices(&self, keys: &[Vec<u8>]) -> Vec<usize> {
+        let mut indices: Vec<usize> = keys.iter().map(|x| shard_index(x, self.count())).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    // the storages of all the shards, no command runs until LentShards is dropped
+    pub async fn lend_all(&self) -> Result<LentShards> {
+        self.lend((0..self.count()).collect()).await
+    }
+
+    // the storage of one shard, the other shards keep running
+    pub async fn lend_one(&self, index: usize) -> Result<LentShards> {
+        self.lend(vec![index]).await
+    }
+
+    // the shards are borrowed in ascending order, so two borrowers never wait for each other
+    // the borrowed storages go back to their shards when LentShards is dropped
+    async fn lend(&self, indices: Vec<usize>) -> Result<LentShards> {
+        let mut shards = LentShards {
+            count: self.count(),
+            indices: Vec::with_capacity(indices.len()),
+            storages: Vec::with_capacity(indices.len()),
+        };
+        for index in indices {
+            let (tx, rx) = oneshot::channel();
+            self.senders[index]
+                .send(ShardMessage::Lend(tx))
+                .await
+                .map_err(|_| anyhow::anyhow!("get data failed"))?;
+            let storage = rx.await.map_err(|_| anyhow::anyhow!("get data failed"))?;
+            shards.indices.push(index);
+            shards.storages.push(storage);
+        }
+        Ok(shards)
+    }
+}
+
+// move the storage of the shard to the borrower and wait until it is given back
+pub(super) async fn lend(
+    storage: DataStorage,
+    borrower: oneshot::Sender<LentStorage>,
+) -> DataStorage {
+    let (give_back, given_back) = oneshot::channel();
+    let lent = LentStorage {
+        storage,
+        give_back: Some(give_back),
+    };
+    if let Err(mut lent) = borrower.send(lent) {
+        lent.give_back = None;
+        return std::mem::take(&mut lent.storage);
+    }
+    given_back
+        .await
+        .expect("the lent storage is given back when it is dropped")
+}
+
+// the storages borrowed from the shards, in ascending shard order
+#[derive(Debug)]
+pub struct LentShards {
+    // the number of all the shards
+    count: usize,
+    indices: Vec<usize>,
+    storages: Vec<LentStorage>,
+}
+
+impl LentShards {
+    // the storages of all the shards without data watchers, the keys should be in their shards
+    pub fn new(storages: Vec<DataStorage>) -> Self {
+        LentShards {
+            count: storages.len(),
+            indices: (0..storages.len()).collect(),
+            storages: storages
+                .into_iter()
+                .map(|storage| LentStorage {
+                    storage,
+                    give_back: None,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn storages(&self) -> Vec<&DataStorage> {
+        self.storages.iter().map(|x| &x.storage).collect()
+    }
+
+    pub fn storages_mut(&mut self) -> impl Iterator<Item = &mut DataStorage> {
+        self.storages.iter_mut().map(|x| &mut x.storage)
+    }
+
+    pub fn first_mut(&mut self) -> &mut DataStorage {
+        &mut self.storages[0].storage
+    }
+
+    // run the command in the first storage, the keys of the other storages are moved into it
+    // during the command, so it sees all its keys in one place and the watched versions follow them
+    pub fn exec_merged(
+        &mut self,
+        keys: &[Vec<u8>],
+        execution: &dyn Execution,
+        args: &[Vec<u8>],
+    ) -> Value {
+        self.merged(keys, |shards| shards.exec_first(execution, args))
+    }
+
+    // run f with the keys moved into the first storage, they go back to their shards after it
+    pub fn merged<T>(&mut self, keys: &[Vec<u8>], f: impl FnOnce(&mut Self) -> T) -> T {
+        let mut moved = HashSet::new();
+        let keys: Vec<(&Vec<u8>, usize)> = keys
+            .iter()
+            .filter(|x| moved.insert(x.as_slice()))
+            .map(|x| (x, self.position(x)))
+            .filter(|(_, position)| *position != 0)
+            .collect();
+        for (key, position) in keys.iter() {
+            self.move_key(key, *position, 0);
+        }
+        let output = f(self);
+        for (key, position) in keys {
+            self.move_key(key, 0, position);
+        }
+        output
+    }
+
+    // exec_merged with every key of the lent shards, the keys created by the command are moved
+    // to their shards after it
+    pub fn exec_merged_all(&mut self, execution: &dyn Execution, args: &[Vec<u8>]) -> Value {
+        for position in 1..self.storages.len() {
+            for key in self.storages[position].storage.all_keys() {
+                self.move_key(&key, position, 0);
+            }
+        }
+        let reply = self.exec_first(execution, args);
+        for key in self.storages[0].storage.all_keys() {
+            let position = self.position(&key);
+            if position != 0 {
+                self.move_key(&key, 0, position);
+            }
+        }
+        reply
+    }
+
+    // DataStorage::exec_multi with the commands on the whole keyspace, they run on the lent storages
+    // in place, the other commands run in the first storage where their keys are merged
+    pub fn exec_multi<'a>(
+        &mut self,
+        commands: impl Iterator<Item = (&'a (dyn Execution + Send), &'a [Vec<u8>], Route)>,
+    ) -> Vec<Value> {
+        let mut propagate = Vec::new();
+        let replies = commands
+            .map(|(execution, args, route)| {
+                let dirty = self.first_mut().dirty();
+                let reply = match route {
+                    Route::All => execution.exec_shards(self, args),
+                    Route::Keys(_) => execution.exec(self.first_mut()),
+                };
+                self.first_mut().take_propagate(args, dirty, &mut propagate);
+                reply
+            })
+            .collect();
+        self.first_mut().propagate_multi(propagate);
+        replies
+    }
+
+    fn exec_first(&mut self, execution: &dyn Execution, args: &[Vec<u8>]) -> Value {
+        let data = self.first_mut();
+        let dirty = data.dirty();
+        let reply = match data.check_memory(args) {
+            Ok(()) => execution.exec(data),
+            Err(e) => e.into(),
+        };
+        data.propagate(args, dirty);
+        reply
+    }
+
+    // the position of the key's shard in the lent storages
+    fn position(&self, key: &[u8]) -> usize {
+        self.indices
+            .binary_search(&shard_index(key, self.count))
+            .expect("the shard of the key is lent")
+    }
+
+    fn move_key(&mut self, key: &[u8], from: usize, to: usize) {
+        let detached = self.storages[from].storage.detach(key);
+        self.storages[to].storage.attach(key.to_owned(), detached);
+    }
+}
+
+// a key moving between the shards with its watched version
+struct Detached {
+    value: Option<DataTTL>,
+    watched: Option<WatchedKey>,
+}
+
+impl DataStorage {
+    // split the keys into the storages of the shards, they share the persistence of this storage
+    pub fn split(mut self, count: usize) -> Vec<DataStorage> {
+        self.memory.set_shards(count);
+        let mut shards: Vec<DataStorage> = (0..count)
+            .map(|_| DataStorage {
+                persistence: self.persistence.clone(),
+                memory: self.memory.clone(),
+                ..Default::default()
+            })
+            .collect();
+        for key in self.all_keys() {
+            let detached = self.detach(&key);
+            shards[shard_index(&key, count)].attach(key, detached);
+        }
+        shards
+    }
+
+    // the keys and the watched keys which don't exist
+    fn all_keys(&self) -> Vec<Vec<u8>> {
+        let mut keys: Vec<Vec<u8>> = self.map.keys().cloned().collect();
+        keys.extend(
+            self.watched
+                .keys()
+                .filter(|x| !self.map.contains_key(*x))
+                .cloned(),
+        );
+        keys
+    }
+
+    // remove the key without counting a change, the other shard takes it as it is
+    fn detach(&mut self, key: &[u8]) -> Detached {
+        self.expires.remove(key);
+        let value = self.map.remove(key);
+        if let Some(value) = value.as_ref() {
+            self.scan_index.remove(key);
+            self.used -= value.size;
+        }
+        Detached {
+            value,
+            watched: self.watched.take(key),
+        }
+    }
+
+    fn attach(&mut self, key: Vec<u8>, detached: Detached) {
+        if let Some(watched) = detached.watched {
+            self.watched.put(key.clone(), watched);
+        }
+        let Some(value) = detached.value else {
+            return;
+        };
+        // the list pushed in the first lent shard comes back to the shard of its blocked clients
+        if !self.blocked.is_empty() {
+            self.signal_blocked(&key);
+        }
+        if value.expired_epoch.is_some() {
+            self.expires.insert(&key);
+        }
+        self.scan_index.insert(&key);
+        self.used += value.size;
+        self.map.insert(key, value);
+    }
+}
+
+// the save rules and the append only file fsync of all the shards
+async fn persistence_cron(shards: Shards, persistence: Arc<Persistence>) {
+    let mut interval = tokio::time::interval(PERSISTENCE_CRON_PERIOD);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        if persistence.snapshot_due() {
+            if let Err(e) = snapshot::bgsave_shards(&shards).await {
+                error!("background saving error={e:#}");
+                persistence.bgsave_failed();
+            }
+        }
+        persistence.aof_cron();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shard_index, LentShards, Route, Shards};
+    use crate::data_watcher::{DataStorage, DataTTL};
+    use crate::redis_protocol::{
+        cmd_blmove::BLMove, cmd_bpop::BPop, cmd_dbsize::DbSize, cmd_del::Del, cmd_mget::MGet,
+        cmd_mset::MSet, cmd_push::Push, cmd_watch::Watch, list_helper::Side,
+    };
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn args(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    // the keys of different shards
+    fn keys_of_shards(count: usize) -> Vec<Vec<u8>> {
+        let mut keys: Vec<Option<Vec<u8>>> = vec![None; count];
+        for i in 0.. {
+            let key = format!("k{i}").into_bytes();
+            keys[shard_index(&key, count)].get_or_insert(key);
+            if keys.iter().all(|x| x.is_some()) {
+                return keys.into_iter().flatten().collect();
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_shard_index_hash_tag() {
+        assert_eq!(0, shard_index(b"a", 1));
+        assert_eq!(
+            shard_index(b"{user1}.name", 16),
+            shard_index(b"{user1}.age", 16)
+        );
+        assert_eq!(shard_index(b"user1", 16), shard_index(b"{user1}.age", 16));
+        // the empty tag hashes the whole key
+        assert!((0..100)
+            .any(|i| shard_index(format!("{{}}{i}").as_bytes(), 16) != shard_index(b"{}", 16)));
+    }
+
+    #[test]
+    fn test_split() {
+        // arrange
+        let mut data = DataStorage::new();
+        for i in 0..100 {
+            data.insert(format!("k{i}").into_bytes(), DataTTL::new(b"v".to_vec()));
+        }
+        // act
+        let shards = data.split(4);
+        // assert
+        assert_eq!(100, shards.iter().map(|x| x.len()).sum::<usize>());
+        for (index, shard) in shards.iter().enumerate() {
+            assert!(shard.keys().all(|x| shard_index(x, 4) == index));
+            assert_eq!((0, Vec::new()), shard.scan(u64::MAX, 1));
+        }
+    }
+
+    #[test]
+    fn test_exec_merged() {
+        // arrange
+        let keys = keys_of_shards(2);
+        let mut storages = DataStorage::new().split(2);
+        for (index, key) in keys.iter().enumerate() {
+            storages[index].insert(key.to_owned(), DataTTL::new(key.to_owned()));
+        }
+        storages[1].watch(&keys[1]);
+        let mut shards = LentShards::new(storages);
+        let mget = MGet::parse(keys.iter().cloned().collect()).unwrap();
+        let del = Del::parse(VecDeque::from([keys[1].to_owned()])).unwrap();
+        // act
+        let values = shards.exec_merged(&keys, mget.as_ref(), &args(&["mget"]));
+        shards.exec_merged(&keys[1..], del.as_ref(), &args(&["del"]));
+        // assert
+        assert_eq!(
+            Value::Array(keys.iter().map(|x| Value::BufBulk(x.to_owned())).collect()),
+            values
+        );
+        let storages = shards.storages();
+        assert!(storages[0].contains_key(&keys[0]));
+        assert!(!storages[1].contains_key(&keys[1]));
+        // the watched version is changed in the shard of the key
+        assert!(storages[1].is_watched_key_changed(&keys[1], 0));
+        assert!(!storages[1].is_watched_key_changed(&keys[1], 1));
+    }
+
+    #[tokio::test]
+    async fn test_exec_across_shards() {
+        // arrange
+        let keys = keys_of_shards(4);
+        let shards = Shards::start(DataStorage::new(), 4, 8).await;
+        let mut mset: VecDeque<Vec<u8>> = VecDeque::new();
+        for key in keys.iter() {
+            mset.extend([key.to_owned(), b"v".to_vec()]);
+        }
+        let mset_args: Vec<Vec<u8>> = mset.iter().cloned().collect();
+        // act
+        let watch = shards
+            .exec(
+                Route::Keys(keys.clone()),
+                Watch::parse(keys.iter().cloned().collect()).unwrap(),
+                args(&["watch"]),
+            )
+            .await
+            .unwrap();
+        shards
+            .exec(
+                Route::Keys(keys.clone()),
+                MSet::parse(mset, false).unwrap(),
+                mset_args,
+            )
+            .await
+            .unwrap();
+        let dbsize = shards
+            .exec(
+                Route::All,
+                DbSize::parse(VecDeque::new()).unwrap(),
+                args(&["dbsize"]),
+            )
+            .await
+            .unwrap();
+        // assert
+        assert_eq!(Value::Array(vec![Value::Integer(0); 4]), watch);
+        assert_eq!(Value::Integer(4), dbsize);
+    }
+
+    #[tokio::test]
+    async fn test_exec_blocking() {
+        // arrange
+        let keys = keys_of_shards(2);
+        let shards = Shards::start(DataStorage::new(), 2, 8).await;
+        let blpop = |keys: &[Vec<u8>], timeout: &str| {
+            let mut input: VecDeque<Vec<u8>> = keys.iter().cloned().collect();
+            input.push_back(timeout.as_bytes().to_vec());
+            BPop::parse(input, Side::Left).unwrap()
+        };
+        let waiting = {
+            let (shards, keys) = (shards.clone(), keys[1..].to_vec());
+            let bpop = blpop(&keys, "0");
+            tokio::spawn(async move {
+                shards
+                    .exec_blocking(Route::Keys(keys), bpop, args(&["blpop"]))
+                    .await
+            })
+        };
+        // act
+        let timeout = shards
+            .exec_blocking(
+                Route::Keys(keys[..1].to_vec()),
+                blpop(&keys[..1], "0.05"),
+                args(&["blpop"]),
+            )
+            .await
+            .unwrap();
+        let spread = {
+            let (shards, keys) = (shards.clone(), keys.clone());
+            let bpop = blpop(&keys, "0");
+            tokio::spawn(async move {
+                shards
+                    .exec_blocking(Route::Keys(keys), bpop, args(&["blpop"]))
+                    .await
+            })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        for key in [&keys[1], &keys[0]] {
+            let push = VecDeque::from([key.to_owned(), b"v".to_vec()]);
+            shards
+                .exec(
+                    Route::Keys(vec![key.to_owned()]),
+                    Push::parse(push, Side::Left, false).unwrap(),
+                    args(&["lpush"]),
+                )
+                .await
+                .unwrap();
+        }
+        // assert
+        assert_eq!(Value::NullArray, timeout);
+        // the first client of keys[1] is served first
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(keys[1].to_owned()),
+                Value::BufBulk(b"v".to_vec())
+            ]),
+            waiting.await.unwrap().unwrap()
+        );
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(keys[0].to_owned()),
+                Value::BufBulk(b"v".to_vec())
+            ]),
+            spread.await.unwrap().unwrap()
+        );
+        let dbsize = shards
+            .exec(
+                Route::All,
+                DbSize::parse(VecDeque::new()).unwrap(),
+                args(&["dbsize"]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(Value::Integer(0), dbsize);
+    }
+
+    #[tokio::test]
+    async fn test_exec_blocking_move() {
+        // arrange
+        let keys = keys_of_shards(2);
+        let shards = Shards::start(DataStorage::new(), 2, 8).await;
+        let blmove = {
+            let (shards, keys) = (shards.clone(), keys.clone());
+            let input = VecDeque::from([
+                keys[0].to_owned(),
+                keys[1].to_owned(),
+                b"left".to_vec(),
+                b"right".to_vec(),
+                b"0".to_vec(),
+            ]);
+            tokio::spawn(async move {
+                shards
+                    .exec_blocking(
+                        Route::Keys(keys),
+                        BLMove::parse(input).unwrap(),
+                        args(&["blmove"]),
+                    )
+                    .await
+            })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        // act
+        let push = VecDeque::from([keys[0].to_owned(), b"v".to_vec()]);
+        shards
+            .exec(
+                Route::Keys(keys[..1].to_vec()),
+                Push::parse(push, Side::Left, false).unwrap(),
+                args(&["lpush"]),
+            )
+            .await
+            .unwrap();
+        let moved = blmove.await.unwrap().unwrap();
+        let input = VecDeque::from([keys[1].to_owned(), b"0.05".to_vec()]);
+        let popped = shards
+            .exec_blocking(
+                Route::Keys(keys[1..].to_vec()),
+                BPop::parse(input, Side::Left).unwrap(),
+                args(&["blpop"]),
+            )
+            .await
+            .unwrap();
+        // assert
+        assert_eq!(Value::BufBulk(b"v".to_vec()), moved);
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(keys[1].to_owned()),
+                Value::BufBulk(b"v".to_vec())
+            ]),
+            popped
+        );
+    }
+}
@@ -0,0 +1,565 @@
+use rand::Rng;
+
+// skip list ordered by (score, member), port of the redis zskiplist
+// every level keeps the span (number of nodes it jumps over) to get the rank in O(log n)
+// nodes live in a vector and link each other by index, index 0 is the header
+const MAX_LEVEL: usize = 32;
+const P: f64 = 0.25;
+const HEADER: usize = 0;
+
+#[derive(Clone, Debug)]
+struct Level {
+    forward: Option<usize>,
+    span: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    member: Vec<u8>,
+    score: f64,
+    backward: Option<usize>,
+    levels: Vec<Level>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SkipList {
+    nodes: Vec<Node>,
+    // freed node index for reuse
+    free: Vec<usize>,
+    tail: Option<usize>,
+    level: usize,
+    length: usize,
+}
+
+// the score interval of ZRANGEBYSCORE, ZCOUNT...
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct ScoreRange {
+    pub min: f64,
+    pub max: f64,
+    pub min_exclusive: bool,
+    pub max_exclusive: bool,
+}
+
+impl ScoreRange {
+    pub fn gte_min(&self, score: f64) -> bool {
+        if self.min_exclusive {
+            score > self.min
+        } else {
+            score >= self.min
+        }
+    }
+
+    pub fn lte_max(&self, score: f64) -> bool {
+        if self.max_exclusive {
+            score < self.max
+        } else {
+            score <= self.max
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.min > self.max || (self.min == self.max && (self.min_exclusive || self.max_exclusive))
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum LexBound {
+    // "-"
+    Min,
+    // "+"
+    Max,
+    // "[member"
+    Inclusive(Vec<u8>),
+    // "(member"
+    Exclusive(Vec<u8>),
+}
+
+// the member interval of ZRANGE BYLEX, members are expected to have the same score
+#[derive(PartialEq, Clone, Debug)]
+pub struct LexRange {
+    pub min: LexBound,
+    pub max: LexBound,
+}
+
+impl LexRange {
+    pub fn gte_min(&self, member: &[u8]) -> bool {
+        match &self.min {
+            LexBound::Min => true,
+            LexBound::Max => false,
+            LexBound::Inclusive(min) => member >= min.as_slice(),
+            LexBound::Exclusive(min) => member > min.as_slice(),
+        }
+    }
+
+    pub fn lte_max(&self, member: &[u8]) -> bool {
+        match &self.max {
+            LexBound::Min => false,
+            LexBound::Max => true,
+            LexBound::Inclusive(max) => member <= max.as_slice(),
+            LexBound::Exclusive(max) => member < max.as_slice(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match (&self.min, &self.max) {
+            (LexBound::Max, _) | (_, LexBound::Min) => true,
+            (LexBound::Min, _) | (_, LexBound::Max) => false,
+            (LexBound::Inclusive(min), LexBound::Inclusive(max)) => min > max,
+            (LexBound::Inclusive(min), LexBound::Exclusive(max))
+            | (LexBound::Exclusive(min), LexBound::Inclusive(max))
+            | (LexBound::Exclusive(min), LexBound::Exclusive(max)) => min >= max,
+        }
+    }
+}
+
+impl SkipList {
+    pub fn new() -> Self {
+        let header = Node {
+            member: Vec::new(),
+            score: 0.0,
+            backward: None,
+            levels: vec![
+                Level {
+                    forward: None,
+                    span: 0
+                };
+                MAX_LEVEL
+            ],
+        };
+        SkipList {
+            nodes: vec![header],
+            free: Vec::new(),
+            tail: None,
+            level: 1,
+            length: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn member(&self, node: usize) -> (&Vec<u8>, f64) {
+        (&self.nodes[node].member, self.nodes[node].score)
+    }
+
+    pub fn first(&self) -> Option<usize> {
+        self.forward(HEADER, 0)
+    }
+
+    pub fn last(&self) -> Option<usize> {
+        self.tail
+    }
+
+    pub fn next(&self, node: usize) -> Option<usize> {
+        self.forward(node, 0)
+    }
+
+    pub fn prev(&self, node: usize) -> Option<usize> {
+        self.nodes[node].backward
+    }
+
+    fn forward(&self, node: usize, level: usize) -> Option<usize> {
+        self.nodes[node].levels[level].forward
+    }
+
+    fn span(&self, node: usize, level: usize) -> usize {
+        self.nodes[node].levels[level].span
+    }
+
+    fn less_than(&self, node: usize, score: f64, member: &[u8]) -> bool {
+        let node = &self.nodes[node];
+        node.score < score || (node.score == score && node.member.as_slice() < member)
+    }
+
+    fn random_level() -> usize {
+        let mut rng = rand::thread_rng();
+        let mut level = 1;
+        while level < MAX_LEVEL && rng.gen::<f64>() < P {
+            level += 1;
+        }
+        level
+    }
+
+    // the caller makes sure the member doesn't exist
+    pub fn insert(&mut self, score: f64, member: Vec<u8>) {
+        let mut update = [HEADER; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL];
+        let mut x = HEADER;
+        for i in (0..self.level).rev() {
+            rank[i] = if i == self.level - 1 { 0 } else { rank[i + 1] };
+            while let Some(next) = self.forward(x, i) {
+                if !self.less_than(next, score, &member) {
+                    break;
+                }
+                rank[i] += self.span(x, i);
+                x = next;
+            }
+            update[i] = x;
+        }
+        let level = Self::random_level();
+        if level > self.level {
+            for i in self.level..level {
+                rank[i] = 0;
+                update[i] = HEADER;
+                self.nodes[HEADER].levels[i].span = self.length;
+            }
+            self.level = level;
+        }
+        let node = Node {
+            member,
+            score,
+            backward: None,
+            levels: vec![
+                Level {
+                    forward: None,
+                    span: 0
+                };
+                level
+            ],
+        };
+        let x = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        for i in 0..level {
+            let prev = update[i];
+            self.nodes[x].levels[i].forward = self.forward(prev, i);
+            self.nodes[prev].levels[i].forward = Some(x);
+            // update span covered by update[i] as x is inserted here
+            self.nodes[x].levels[i].span = self.span(prev, i) - (rank[0] - rank[i]);
+            self.nodes[prev].levels[i].span = (rank[0] - rank[i]) + 1;
+        }
+        // increment span for untouched levels
+        for (i, prev) in update.iter().enumerate().take(self.level).skip(level) {
+            self.nodes[*prev].levels[i].span += 1;
+        }
+        self.nodes[x].backward = if update[0] == HEADER {
+            None
+        } else {
+            Some(update[0])
+        };
+        match self.forward(x, 0) {
+            Some(next) => self.nodes[next].backward = Some(x),
+            None => self.tail = Some(x),
+        }
+        self.length += 1;
+    }
+
+    // return true when the element is found and removed
+    pub fn delete(&mut self, score: f64, member: &[u8]) -> bool {
+        let mut update = [HEADER; MAX_LEVEL];
+        let mut x = HEADER;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i) {
+                if !self.less_than(next, score, member) {
+                    break;
+                }
+                x = next;
+            }
+            update[i] = x;
+        }
+        match self.forward(x, 0) {
+            Some(x) if self.nodes[x].score == score && self.nodes[x].member == member => {
+                self.delete_node(x, &update);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn delete_node(&mut self, x: usize, update: &[usize; MAX_LEVEL]) {
+        for (i, prev) in update.iter().enumerate().take(self.level) {
+            if self.forward(*prev, i) == Some(x) {
+                self.nodes[*prev].levels[i].span += self.span(x, i);
+                self.nodes[*prev].levels[i].span -= 1;
+                self.nodes[*prev].levels[i].forward = self.forward(x, i);
+            } else {
+                self.nodes[*prev].levels[i].span -= 1;
+            }
+        }
+        match self.forward(x, 0) {
+            Some(next) => self.nodes[next].backward = self.nodes[x].backward,
+            None => self.tail = self.nodes[x].backward,
+        }
+        while self.level > 1 && self.forward(HEADER, self.level - 1).is_none() {
+            self.level -= 1;
+        }
+        self.length -= 1;
+        self.nodes[x].member = Vec::new();
+        self.free.push(x);
+    }
+
+    // 1-based rank of the element, None when it doesn't exist
+    pub fn rank(&self, score: f64, member: &[u8]) -> Option<usize> {
+        let mut rank = 0;
+        let mut x = HEADER;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i) {
+                let node = &self.nodes[next];
+                if node.score < score || (node.score == score && node.member.as_slice() <= member) {
+                    rank += self.span(x, i);
+                    x = next;
+                } else {
+                    break;
+                }
+            }
+            if x != HEADER && self.nodes[x].member == member {
+                return Some(rank);
+            }
+        }
+        None
+    }
+
+    // node by 1-based rank
+    pub fn by_rank(&self, rank: usize) -> Option<usize> {
+        let mut traversed = 0;
+        let mut x = HEADER;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i) {
+                if traversed + self.span(x, i) > rank {
+                    break;
+                }
+                traversed += self.span(x, i);
+                x = next;
+            }
+            if traversed == rank && x != HEADER {
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    fn is_in_score_range(&self, range: &ScoreRange) -> bool {
+        if range.is_empty() {
+            return false;
+        }
+        match (self.tail, self.first()) {
+            (Some(last), Some(first)) => {
+                range.gte_min(self.nodes[last].score) && range.lte_max(self.nodes[first].score)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn first_in_score_range(&self, range: &ScoreRange) -> Option<usize> {
+        if !self.is_in_score_range(range) {
+            return None;
+        }
+        let mut x = HEADER;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i) {
+                if range.gte_min(self.nodes[next].score) {
+                    break;
+                }
+                x = next;
+            }
+        }
+        // this is an inner range, so the next node can not be None
+        let x = self.forward(x, 0)?;
+        range.lte_max(self.nodes[x].score).then_some(x)
+    }
+
+    pub fn last_in_score_range(&self, range: &ScoreRange) -> Option<usize> {
+        if !self.is_in_score_range(range) {
+            return None;
+        }
+        let mut x = HEADER;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i) {
+                if !range.lte_max(self.nodes[next].score) {
+                    break;
+                }
+                x = next;
+            }
+        }
+        (x != HEADER && range.gte_min(self.nodes[x].score)).then_some(x)
+    }
+
+    fn is_in_lex_range(&self, range: &LexRange) -> bool {
+        if range.is_empty() {
+            return false;
+        }
+        match (self.tail, self.first()) {
+            (Some(last), Some(first)) => {
+                range.gte_min(&self.nodes[last].member) && range.lte_max(&self.nodes[first].member)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn first_in_lex_range(&self, range: &LexRange) -> Option<usize> {
+        if !self.is_in_lex_range(range) {
+            return None;
+        }
+        let mut x = HEADER;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i) {
+                if range.gte_min(&self.nodes[next].member) {
+                    break;
+                }
+                x = next;
+            }
+        }
+        let x = self.forward(x, 0)?;
+        range.lte_max(&self.nodes[x].member).then_some(x)
+    }
+
+    pub fn last_in_lex_range(&self, range: &LexRange) -> Option<usize> {
+        if !self.is_in_lex_range(range) {
+            return None;
+        }
+        let mut x = HEADER;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i) {
+                if !range.lte_max(&self.nodes[next].member) {
+                    break;
+                }
+                x = next;
+            }
+        }
+        (x != HEADER && range.gte_min(&self.nodes[x].member)).then_some(x)
+    }
+}
+
+impl Default for SkipList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LexBound, LexRange, ScoreRange, SkipList};
+
+    fn skip_list(n: usize) -> SkipList {
+        let mut list = SkipList::new();
+        // insert in reverse order to exercise the ordering
+        for i in (0..n).rev() {
+            list.insert(i as f64, format!("m{i:04}").into_bytes());
+        }
+        list
+    }
+
+    #[test]
+    fn test_insert_rank_by_rank() {
+        // arrange
+        let list = skip_list(1000);
+        // act & assert
+        assert_eq!(1000, list.len());
+        for i in 0..1000 {
+            let member = format!("m{i:04}").into_bytes();
+            assert_eq!(Some(i + 1), list.rank(i as f64, &member));
+            let node = list.by_rank(i + 1).unwrap();
+            assert_eq!((&member, i as f64), list.member(node));
+        }
+        assert_eq!(None, list.by_rank(1001));
+        assert_eq!(None, list.rank(1.0, b"other"));
+    }
+
+    #[test]
+    fn test_delete_keep_rank() {
+        // arrange
+        let mut list = skip_list(100);
+        // act
+        for i in (0..100).step_by(2) {
+            assert!(list.delete(i as f64, format!("m{i:04}").as_bytes()));
+        }
+        // assert
+        assert!(!list.delete(0.0, b"m0000"));
+        assert_eq!(50, list.len());
+        for (rank, i) in (1..100).step_by(2).enumerate() {
+            assert_eq!(
+                Some(rank + 1),
+                list.rank(i as f64, format!("m{i:04}").as_bytes())
+            );
+        }
+        // the freed nodes are reused
+        list.insert(0.5, b"new".to_vec());
+        assert_eq!(Some(1), list.rank(0.5, b"new"));
+        assert_eq!(101, list.nodes.len());
+    }
+
+    #[test]
+    fn test_same_score_order_by_member() {
+        // arrange
+        let mut list = SkipList::new();
+        list.insert(1.0, b"b".to_vec());
+        list.insert(1.0, b"a".to_vec());
+        list.insert(0.0, b"c".to_vec());
+        // act
+        let mut output = Vec::new();
+        let mut node = list.first();
+        while let Some(x) = node {
+            output.push(list.member(x).0.to_owned());
+            node = list.next(x);
+        }
+        // assert
+        assert_eq!(vec![b"c".to_vec(), b"a".to_vec(), b"b".to_vec()], output);
+        assert_eq!(b"b", list.member(list.last().unwrap()).0.as_slice());
+    }
+
+    #[test]
+    fn test_score_range() {
+        // arrange
+        let list = skip_list(10);
+        let range = ScoreRange {
+            min: 2.0,
+            max: 5.0,
+            min_exclusive: true,
+            max_exclusive: false,
+        };
+        // act & assert
+        assert_eq!(
+            3.0,
+            list.member(list.first_in_score_range(&range).unwrap()).1
+        );
+        assert_eq!(
+            5.0,
+            list.member(list.last_in_score_range(&range).unwrap()).1
+        );
+        let out_of_range = ScoreRange {
+            min: 20.0,
+            max: 30.0,
+            min_exclusive: false,
+            max_exclusive: false,
+        };
+        assert_eq!(None, list.first_in_score_range(&out_of_range));
+    }
+
+    #[test]
+    fn test_lex_range() {
+        // arrange
+        let mut list = SkipList::new();
+        for member in [b"a", b"b", b"c", b"d"] {
+            list.insert(0.0, member.to_vec());
+        }
+        let range = LexRange {
+            min: LexBound::Exclusive(b"a".to_vec()),
+            max: LexBound::Inclusive(b"c".to_vec()),
+        };
+        // act & assert
+        assert_eq!(
+            b"b",
+            list.member(list.first_in_lex_range(&range).unwrap())
+                .0
+                .as_slice()
+        );
+        assert_eq!(
+            b"c",
+            list.member(list.last_in_lex_range(&range).unwrap())
+                .0
+                .as_slice()
+        );
+    }
+}
@@ -0,0 +1,746 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time;
+
+use crate::data_watcher::crc64::crc64;
+use crate::data_watcher::data_value::DataValue;
+use crate::data_watcher::persistence::Persistence;
+use crate::data_watcher::rdb;
+use crate::data_watcher::shard::Shards;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::{epoch_now, DataStorage, DataTTL};
+
+use anyhow::{Context, Result};
+use log::{error, info, warn};
+
+// the point-in-time snapshot file, like the redis rdb file with a simpler encoding
+//
+// "PREDIS" version
+// entries: [EXPIRE_MS expire-ms(u64)] type key value
+// EOF crc64(u64) of all the bytes before it
+//
+// the numbers are little endian, the strings are length(u64) then bytes
+const MAGIC: &[u8] = b"PREDIS";
+const VERSION: u8 = 1;
+const OPCODE_EXPIRE_MS: u8 = 0xfc;
+const OPCODE_EOF: u8 = 0xff;
+const TYPE_STRING: u8 = 0;
+const TYPE_LIST: u8 = 1;
+const TYPE_HASH: u8 = 2;
+const TYPE_SET: u8 = 3;
+const TYPE_ZSET: u8 = 4;
+
+// wait before the save rules trigger another BGSAVE after a failed one, like redis
+const BGSAVE_RETRY_DELAY: time::Duration = time::Duration::from_secs(5);
+
+// the keys copied by BGSAVE each time it borrows a shard, bounds how long the shard stops
+const BGSAVE_BATCH: usize = 1024;
+
+// save <seconds> <changes>, save when both the time and the number of changes are reached
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SaveRule {
+    pub seconds: u64,
+    pub changes: u64,
+}
+
+impl SaveRule {
+    // "3600 1 300 100" is two rules, an empty string disables the automatic save
+    pub fn parse_rules(input: &str) -> Result<Vec<SaveRule>> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        anyhow::ensure!(
+            tokens.len().is_multiple_of(2),
+            "save rules should be pairs of seconds and changes"
+        );
+        tokens
+            .chunks(2)
+            .map(|pair| {
+                let (Ok(seconds), Ok(changes)) = (pair[0].parse(), pair[1].parse()) else {
+                    anyhow::bail!("invalid save rule {} {}", pair[0], pair[1]);
+                };
+                Ok(SaveRule { seconds, changes })
+            })
+            .collect()
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SnapshotConfig {
+    pub path: PathBuf,
+    pub save_rules: Vec<SaveRule>,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        SnapshotConfig {
+            path: PathBuf::from("dump.pdb"),
+            save_rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct SnapshotState {
+    config: SnapshotConfig,
+    // unix time of the last successful save
+    last_save: time::Duration,
+    changes_at_last_save: u64,
+    // the changes counter when the running BGSAVE started
+    bgsave_changes: Option<u64>,
+    last_bgsave_failed_at: Option<time::Instant>,
+}
+
+impl DataStorage {
+    // an empty storage saving to the snapshot file
+    pub fn with_snapshot(config: SnapshotConfig) -> Self {
+        let storage = DataStorage::new();
+        *storage.persistence.snapshot.lock().unwrap() = SnapshotState {
+            config,
+            last_save: epoch_now(),
+            ..Default::default()
+        };
+        storage
+    }
+
+    // load the snapshot file, the missing file starts an empty storage
+    pub fn load(config: SnapshotConfig) -> Result<Self> {
+        let path = config.path.clone();
+        let mut storage = DataStorage::with_snapshot(config);
+        match fs::read(&path) {
+            Ok(bytes) => {
+                let now = epoch_now();
+                for (key, value) in decode_file(&bytes)
+                    .with_context(|| format!("load snapshot {}", path.display()))?
+                {
+                    // the keys expired while the server was down are not loaded
+                    if value.expired_epoch().is_some_and(|x| x < now) {
+                        continue;
+                    }
+                    storage.insert(key, value);
+                }
+                info!(
+                    "loaded {} keys from snapshot {}",
+                    storage.len(),
+                    path.display()
+                );
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                info!("snapshot {} not found", path.display());
+            }
+            Err(e) => return Err(e).with_context(|| format!("read snapshot {}", path.display())),
+        }
+        storage.dirty = 0;
+        Ok(storage)
+    }
+
+    // SAVE of the storage alone, see save
+    pub fn save(&self) -> Result<()> {
+        save(&[self])
+    }
+
+    // BGSAVE of the storage alone, see bgsave
+    pub fn bgsave(&self) -> Result<()> {
+        bgsave(&[self])
+    }
+
+    // unix time of the last successful save
+    pub fn last_save(&self) -> time::Duration {
+        self.persistence.snapshot.lock().unwrap().last_save
+    }
+}
+
+impl Persistence {
+    // CONFIG SET save and dbfilename, the next save uses them
+    pub fn set_snapshot_config(&self, config: SnapshotConfig) {
+        self.snapshot.lock().unwrap().config = config;
+    }
+
+    // check the save rules, called on every tick, the caller starts the BGSAVE when it returns true
+    pub fn snapshot_due(&self) -> bool {
+        let snapshot = self.snapshot.lock().unwrap();
+        if snapshot.bgsave_changes.is_some()
+            || snapshot
+                .last_bgsave_failed_at
+                .is_some_and(|x| x.elapsed() < BGSAVE_RETRY_DELAY)
+        {
+            return false;
+        }
+        let changes = self.changes() - snapshot.changes_at_last_save;
+        let elapsed = epoch_now().saturating_sub(snapshot.last_save);
+        let rule = snapshot.config.save_rules.iter().find(|rule| {
+            changes >= rule.changes && elapsed >= time::Duration::from_secs(rule.seconds)
+        });
+        if let Some(rule) = rule {
+            info!(
+                "{} changes in {} seconds. Saving...",
+                rule.changes, rule.seconds
+            );
+        }
+        rule.is_some()
+    }
+
+    pub fn bgsave_in_progress(&self) -> bool {
+        self.snapshot.lock().unwrap().bgsave_changes.is_some()
+    }
+
+    // INFO persistence, the rdb fields of redis
+    pub fn snapshot_info(&self) -> Vec<(&'static str, String)> {
+        let snapshot = self.snapshot.lock().unwrap();
+        let status = if snapshot.last_bgsave_failed_at.is_some() {
+            "err"
+        } else {
+            "ok"
+        };
+        vec![
+            (
+                "rdb_changes_since_last_save",
+                (self.changes() - snapshot.changes_at_last_save).to_string(),
+            ),
+            (
+                "rdb_bgsave_in_progress",
+                (snapshot.bgsave_changes.is_some() as u8).to_string(),
+            ),
+            (
+                "rdb_last_save_time",
+                snapshot.last_save.as_secs().to_string(),
+            ),
+            ("rdb_last_bgsave_status", status.to_string()),
+        ]
+    }
+
+    // the save rules wait before retrying the failed BGSAVE
+    pub fn bgsave_failed(&self) {
+        self.snapshot.lock().unwrap().last_bgsave_failed_at = Some(time::Instant::now());
+    }
+
+    fn bgsave_done(&self, result: Result<()>) {
+        let mut snapshot = self.snapshot.lock().unwrap();
+        let Some(changes) = snapshot.bgsave_changes.take() else {
+            return;
+        };
+        match result {
+            Ok(()) => {
+                info!("background saving terminated with success");
+                snapshot.last_save = epoch_now();
+                // the changes during the BGSAVE are not in the snapshot
+                snapshot.changes_at_last_save = changes;
+                snapshot.last_bgsave_failed_at = None;
+            }
+            Err(e) => {
+                error!("background saving error={e:#}");
+                snapshot.last_bgsave_failed_at = Some(time::Instant::now());
+            }
+        }
+    }
+}
+
+// SAVE, serialise the keys of all the shards and write the snapshot in the caller
+pub fn save(shards: &[&DataStorage]) -> Result<()> {
+    let persistence = &shards[0].persistence;
+    let mut snapshot = persistence.snapshot.lock().unwrap();
+    anyhow::ensure!(
+        snapshot.bgsave_changes.is_none(),
+        "Background save already in progress"
+    );
+    let bytes = encode_file(
+        &snapshot.config.path,
+        shards.iter().flat_map(|x| x.map.iter()),
+    );
+    write_file(&snapshot.config.path, &bytes)?;
+    snapshot.last_save = epoch_now();
+    snapshot.changes_at_last_save = persistence.changes();
+    Ok(())
+}
+
+// BGSAVE of the lent storages, copy their live keys then serialise and write them in a blocking thread
+// the lent shards stop for the whole copy, BGSAVE out of a transaction uses bgsave_shards instead
+pub fn bgsave(shards: &[&DataStorage]) -> Result<()> {
+    let persistence = shards[0].persistence.clone();
+    let path = bgsave_start(&persistence)?;
+    let entries = shards.iter().flat_map(|x| live_entries(x)).collect();
+    bgsave_write(persistence, path, entries);
+    Ok(())
+}
+
+// BGSAVE of the server, the keys are copied in scan batches of BGSAVE_BATCH keys, a shard only stops
+// for the copy of one batch, like the fork of redis the serialisation runs in a blocking thread
+// unlike the fork, the snapshot is not taken at one point in time, like SCAN a key changed before its
+// batch is saved with the change, a key changed after its batch without it, and a key existing during
+// the whole copy is saved exactly once
+pub async fn bgsave_shards(shards: &Shards) -> Result<()> {
+    let persistence = shards.lend_one(0).await?.storages()[0].persistence.clone();
+    let path = bgsave_start(&persistence)?;
+    match copy_shards(shards).await {
+        Ok(entries) => {
+            bgsave_write(persistence, path, entries);
+            Ok(())
+        }
+        Err(e) => {
+            persistence.bgsave_done(Err(anyhow::anyhow!("{e:#}")));
+            Err(e)
+        }
+    }
+}
+
+async fn copy_shards(shards: &Shards) -> Result<Vec<(Vec<u8>, DataTTL)>> {
+    let mut entries = Vec::new();
+    for index in 0..shards.count() {
+        let mut cursor = 0;
+        loop {
+            let lent = shards.lend_one(index).await?;
+            cursor = copy_batch(lent.storages()[0], cursor, &mut entries);
+            if cursor == 0 {
+                break;
+            }
+        }
+    }
+    Ok(entries)
+}
+
+// copy the live keys of one scan batch, return the next cursor, 0 means finished
+fn copy_batch(storage: &DataStorage, cursor: u64, entries: &mut Vec<(Vec<u8>, DataTTL)>) -> u64 {
+    let (cursor, keys) = storage.scan_index.scan(cursor, BGSAVE_BATCH);
+    entries.extend(
+        keys.into_iter()
+            .map(|key| (key, &storage.map[key]))
+            .filter(|(_, value)| !value.is_expired())
+            .map(|(key, value)| (key.to_owned(), value.clone())),
+    );
+    cursor
+}
+
+// only one save at a time, the changes from now on are not in the snapshot
+fn bgsave_start(persistence: &Persistence) -> Result<PathBuf> {
+    let mut snapshot = persistence.snapshot.lock().unwrap();
+    anyhow::ensure!(
+        snapshot.bgsave_changes.is_none(),
+        "Background save already in progress"
+    );
+    snapshot.bgsave_changes = Some(persistence.changes());
+    Ok(snapshot.config.path.clone())
+}
+
+fn live_entries(storage: &DataStorage) -> Vec<(Vec<u8>, DataTTL)> {
+    storage
+        .map
+        .iter()
+        .filter(|(_, value)| !value.is_expired())
+        .map(|(key, value)| (key.to_owned(), value.clone()))
+        .collect()
+}
+
+fn bgsave_write(persistence: Arc<Persistence>, path: PathBuf, entries: Vec<(Vec<u8>, DataTTL)>) {
+    tokio::task::spawn_blocking(move || {
+        let bytes = encode_file(&path, entries.iter().map(|(key, value)| (key, value)));
+        persistence.bgsave_done(write_file(&path, &bytes));
+    });
+    info!("background saving started");
+}
+
+// write a temporary file then rename it, the old snapshot stays complete if the save fails
+fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    let temp = path.with_file_name(format!("temp-{}.pdb", std::process::id()));
+    let write = || -> Result<()> {
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&temp, path)?;
+        Ok(())
+    };
+    write().map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        e.context(format!("write snapshot {}", path.display()))
+    })
+}
+
+// the file named *.rdb is written in the redis rdb format, so redis can load it
+fn encode_file<'a>(
+    path: &Path,
+    entries: impl Iterator<Item = (&'a Vec<u8>, &'a DataTTL)>,
+) -> Vec<u8> {
+    if path.extension().is_some_and(|x| x == "rdb") {
+        rdb::encode(entries)
+    } else {
+        encode(entries)
+    }
+}
+
+// the format is detected by the magic, the file of redis is loaded as well
+fn decode_file(bytes: &[u8]) -> Result<Vec<(Vec<u8>, DataTTL)>> {
+    if !bytes.starts_with(b"REDIS") {
+        return decode(bytes);
+    }
+    let (entries, others): (Vec<_>, Vec<_>) =
+        rdb::decode(bytes)?.into_iter().partition(|x| x.db == 0);
+    // predis only has the database 0
+    if !others.is_empty() {
+        warn!("skip {} keys of the other databases", others.len());
+    }
+    Ok(entries.into_iter().map(|x| (x.key, x.value)).collect())
+}
+
+pub fn encode<'a>(entries: impl Iterator<Item = (&'a Vec<u8>, &'a DataTTL)>) -> Vec<u8> {
+    let mut buf = Vec::from(MAGIC);
+    buf.push(VERSION);
+    for (key, value) in entries {
+        if let Some(expired) = value.expired_epoch() {
+            buf.push(OPCODE_EXPIRE_MS);
+            buf.extend((expired.as_millis() as u64).to_le_bytes());
+        }
+        match value.value() {
+            DataValue::String(v) => {
+                buf.push(TYPE_STRING);
+                put_string(&mut buf, key);
+                put_string(&mut buf, v);
+            }
+            DataValue::List(v) => {
+                buf.push(TYPE_LIST);
+                put_string(&mut buf, key);
+                put_len(&mut buf, v.len());
+                v.iter().for_each(|x| put_string(&mut buf, x));
+            }
+            DataValue::Hash(v) => {
+                buf.push(TYPE_HASH);
+                put_string(&mut buf, key);
+                put_len(&mut buf, v.len());
+                for (field, value) in v.iter() {
+                    put_string(&mut buf, field);
+                    put_string(&mut buf, value);
+                }
+            }
+            DataValue::Set(v) => {
+                buf.push(TYPE_SET);
+                put_string(&mut buf, key);
+                put_len(&mut buf, v.len());
+                v.iter().for_each(|x| put_string(&mut buf, x));
+            }
+            DataValue::ZSet(v) => {
+                buf.push(TYPE_ZSET);
+                put_string(&mut buf, key);
+                put_len(&mut buf, v.len());
+                for (member, score) in v.iter() {
+                    put_string(&mut buf, member);
+                    buf.extend(score.to_le_bytes());
+                }
+            }
+        }
+    }
+    buf.push(OPCODE_EOF);
+    let checksum = crc64(0, &buf);
+    buf.extend(checksum.to_le_bytes());
+    buf
+}
+
+fn put_len(buf: &mut Vec<u8>, len: usize) {
+    buf.extend((len as u64).to_le_bytes());
+}
+
+fn put_string(buf: &mut Vec<u8>, s: &[u8]) {
+    put_len(buf, s.len());
+    buf.extend(s);
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<(Vec<u8>, DataTTL)>> {
+    anyhow::ensure!(
+        bytes.len() > MAGIC.len() + 1 + 8 && bytes.starts_with(MAGIC),
+        "not a snapshot file"
+    );
+    anyhow::ensure!(
+        bytes[MAGIC.len()] == VERSION,
+        "unsupported snapshot version {}",
+        bytes[MAGIC.len()]
+    );
+    let (body, checksum) = bytes.split_at(bytes.len() - 8);
+    anyhow::ensure!(
+        crc64(0, body) == u64::from_le_bytes(checksum.try_into()?),
+        "snapshot checksum mismatch"
+    );
+    let mut reader = Reader {
+        buf: body,
+        pos: MAGIC.len() + 1,
+    };
+    let mut entries = Vec::new();
+    loop {
+        let mut opcode = reader.u8()?;
+        let mut data_ttl = DataTTL::default();
+        if opcode == OPCODE_EXPIRE_MS {
+            data_ttl = data_ttl.expired_timestamp(&time::Duration::from_millis(reader.u64()?));
+            opcode = reader.u8()?;
+        }
+        let value = match opcode {
+            OPCODE_EOF => break,
+            TYPE_STRING => {
+                let key = reader.string()?;
+                (key, DataValue::String(reader.string()?))
+            }
+            TYPE_LIST => {
+                let key = reader.string()?;
+                let list = (0..reader.u64()?)
+                    .map(|_| reader.string())
+                    .collect::<Result<VecDeque<_>>>()?;
+                (key, DataValue::List(list))
+            }
+            TYPE_HASH => {
+                let key = reader.string()?;
+                let hash = (0..reader.u64()?)
+                    .map(|_| Ok((reader.string()?, reader.string()?)))
+                    .collect::<Result<HashMap<_, _>>>()?;
+                (key, DataValue::Hash(hash))
+            }
+            TYPE_SET => {
+                let key = reader.string()?;
+                let set = (0..reader.u64()?)
+                    .map(|_| reader.string())
+                    .collect::<Result<HashSet<_>>>()?;
+                (key, DataValue::Set(set))
+            }
+            TYPE_ZSET => {
+                let key = reader.string()?;
+                let mut zset = SortedSet::new();
+                for _ in 0..reader.u64()? {
+                    let member = reader.string()?;
+                    zset.insert(member, f64::from_le_bytes(reader.bytes(8)?.try_into()?));
+                }
+                (key, DataValue::ZSet(zset))
+            }
+            _ => anyhow::bail!("unknown snapshot opcode {opcode}"),
+        };
+        let (key, value) = value;
+        entries.push((key, data_ttl.update(value)));
+    }
+    anyhow::ensure!(
+        reader.pos == body.len(),
+        "unexpected bytes after the end of snapshot"
+    );
+    Ok(entries)
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|x| *x <= self.buf.len())
+            .context("unexpected end of snapshot")?;
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into()?))
+    }
+
+    fn string(&mut self) -> Result<Vec<u8>> {
+        let len = usize::try_from(self.u64()?)?;
+        Ok(self.bytes(len)?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bgsave_shards, decode, encode, SaveRule, SnapshotConfig};
+    use crate::data_watcher::shard::Shards;
+    use crate::data_watcher::sorted_set::SortedSet;
+    use crate::data_watcher::{epoch_now, DataStorage, DataTTL};
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use std::path::PathBuf;
+    use std::time;
+
+    fn storage() -> DataStorage {
+        let mut data = DataStorage::new();
+        // the snapshot keeps the expired time in milliseconds
+        data.insert(
+            b"string".to_vec(),
+            DataTTL::new(b"v".to_vec()).expired_timestamp(&time::Duration::from_millis(
+                epoch_now().as_millis() as u64 + 100_000,
+            )),
+        );
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"list")
+            .unwrap()
+            .extend([b"a".to_vec(), b"b".to_vec()]);
+        data.get_typed_or_default::<HashMap<Vec<u8>, Vec<u8>>>(b"hash")
+            .unwrap()
+            .insert(b"f".to_vec(), b"v".to_vec());
+        data.get_typed_or_default::<HashSet<Vec<u8>>>(b"set")
+            .unwrap()
+            .insert(b"m".to_vec());
+        data.get_typed_or_default::<SortedSet>(b"zset")
+            .unwrap()
+            .insert(b"m".to_vec(), 1.5);
+        // the keys and values are any bytes
+        data.insert(
+            b"\xff\r\n".to_vec(),
+            DataTTL::new(b"\x00\r\n$1\r\n\xc3\x28".to_vec()),
+        );
+        data
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("predis-{}-{name}.pdb", std::process::id()))
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        // arrange
+        let data = storage();
+        // act
+        let entries = decode(&encode(data.iter())).unwrap();
+        // assert
+        assert_eq!(6, entries.len());
+        for (key, value) in entries {
+            assert_eq!(data.get(&key), Some(&value));
+        }
+    }
+
+    #[test]
+    fn test_decode_corrupted() {
+        // arrange
+        let bytes = encode(storage().iter());
+        let mut flipped = bytes.clone();
+        flipped[10] ^= 1;
+        // act & assert
+        assert!(decode(&flipped).is_err());
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode(b"PREDIS").is_err());
+    }
+
+    #[test]
+    fn test_save_then_load_skip_expired() {
+        // arrange
+        let config = SnapshotConfig {
+            path: temp_path("load"),
+            save_rules: Vec::new(),
+        };
+        let mut data = DataStorage::load(config.clone()).unwrap();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        data.insert(
+            b"expired".to_vec(),
+            DataTTL::new(b"v".to_vec()).expired_timestamp(&time::Duration::from_secs(1)),
+        );
+        // act
+        data.save().unwrap();
+        let loaded = DataStorage::load(config.clone()).unwrap();
+        // assert
+        std::fs::remove_file(&config.path).unwrap();
+        assert_eq!(1, loaded.len());
+        assert!(loaded.contains_key(b"k".as_slice()));
+        assert_eq!(0, loaded.dirty());
+    }
+
+    #[test]
+    fn test_save_then_load_rdb() {
+        // arrange
+        let config = SnapshotConfig {
+            path: temp_path("rdb").with_extension("rdb"),
+            save_rules: Vec::new(),
+        };
+        let mut data = DataStorage::load(config.clone()).unwrap();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        data.save().unwrap();
+        let content = std::fs::read(&config.path).unwrap();
+        let loaded = DataStorage::load(config.clone()).unwrap();
+        // assert
+        std::fs::remove_file(&config.path).unwrap();
+        assert!(content.starts_with(b"REDIS0009"));
+        assert_eq!(data.get(b"k".as_slice()), loaded.get(b"k".as_slice()));
+    }
+
+    #[tokio::test]
+    async fn test_bgsave_by_save_rule() {
+        // arrange
+        let config = SnapshotConfig {
+            path: temp_path("bgsave"),
+            save_rules: vec![SaveRule {
+                seconds: 0,
+                changes: 2,
+            }],
+        };
+        let mut data = DataStorage::load(config.clone()).unwrap();
+        let set = |data: &mut DataStorage, key: &str| {
+            let dirty = data.dirty();
+            data.insert(key.as_bytes().to_vec(), DataTTL::new(b"v".to_vec()));
+            data.propagate(&[], dirty);
+        };
+        set(&mut data, "a");
+        assert!(!data.persistence().snapshot_due());
+        // act
+        set(&mut data, "b");
+        assert!(data.persistence().snapshot_due());
+        data.bgsave().unwrap();
+        assert!(data.bgsave().is_err());
+        set(&mut data, "c");
+        while data.persistence().bgsave_in_progress() {
+            tokio::time::sleep(time::Duration::from_millis(10)).await;
+        }
+        // assert
+        let loaded = DataStorage::load(config.clone()).unwrap();
+        std::fs::remove_file(&config.path).unwrap();
+        assert_eq!(2, loaded.len());
+        assert_eq!(
+            2,
+            data.persistence()
+                .snapshot
+                .lock()
+                .unwrap()
+                .changes_at_last_save
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bgsave_shards() {
+        // arrange
+        let config = SnapshotConfig {
+            path: temp_path("bgsave-shards"),
+            save_rules: Vec::new(),
+        };
+        let mut data = DataStorage::load(config.clone()).unwrap();
+        // several batches in each shard
+        for i in 0..5000 {
+            data.insert(format!("k{i}").into_bytes(), DataTTL::new(b"v".to_vec()));
+        }
+        let persistence = data.persistence().clone();
+        let shards = Shards::start(data, 2, 16).await;
+        // act
+        bgsave_shards(&shards).await.unwrap();
+        while persistence.bgsave_in_progress() {
+            tokio::time::sleep(time::Duration::from_millis(10)).await;
+        }
+        // assert
+        let loaded = DataStorage::load(config.clone()).unwrap();
+        std::fs::remove_file(&config.path).unwrap();
+        assert_eq!(5000, loaded.len());
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(
+            vec![
+                SaveRule {
+                    seconds: 3600,
+                    changes: 1
+                },
+                SaveRule {
+                    seconds: 60,
+                    changes: 10000
+                }
+            ],
+            SaveRule::parse_rules("3600 1 60 10000").unwrap()
+        );
+        assert!(SaveRule::parse_rules("").unwrap().is_empty());
+        assert!(SaveRule::parse_rules("3600").is_err());
+        assert!(SaveRule::parse_rules("a 1").is_err());
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use crate::data_watcher::skip_list::{LexRange, ScoreRange, SkipList};
+
+// member with score, members with the same score are ordered lexicographically
+// the map answers the score of a member in O(1), the skip list keeps the order for rank and range
+#[derive(Default, Clone, Debug)]
+pub struct SortedSet {
+    scores: HashMap<Vec<u8>, f64>,
+    index: SkipList,
+}
+
+impl PartialEq for SortedSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.scores == other.scores
+    }
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    // insert or update the score, return true when the member is new
+    pub fn insert(&mut self, member: Vec<u8>, score: f64) -> bool {
+        match self.scores.get_mut(&member) {
+            Some(current) => {
+                if *current != score {
+                    self.index.delete(*current, &member);
+                    *current = score;
+                    self.index.insert(score, member);
+                }
+                false
+            }
+            None => {
+                self.scores.insert(member.clone(), score);
+                self.index.insert(score, member);
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, member: &[u8]) -> Option<f64> {
+        let score = self.scores.remove(member)?;
+        self.index.delete(score, member);
+        Some(score)
+    }
+
+    // 0-based rank, reverse ranks from the highest score
+    pub fn rank(&self, member: &[u8], reverse: bool) -> Option<usize> {
+        let score = self.score(member)?;
+        let rank = self.index.rank(score, member)?;
+        Some(if reverse { self.len() - rank } else { rank - 1 })
+    }
+
+    // elements between 0-based ranks start and stop (inclusive)
+    pub fn range_by_rank(&self, start: usize, stop: usize, reverse: bool) -> Vec<(&Vec<u8>, f64)> {
+        if start > stop || start >= self.len() {
+            return Vec::new();
+        }
+        let stop = stop.min(self.len() - 1);
+        let first = if reverse {
+            self.index.by_rank(self.len() - start)
+        } else {
+            self.index.by_rank(start + 1)
+        };
+        self.walk(first, reverse, |_| true)
+            .take(stop - start + 1)
+            .collect()
+    }
+
+    pub fn range_by_score(
+        &self,
+        range: &ScoreRange,
+        reverse: bool,
+        offset: usize,
+        count: Option<usize>,
+    ) -> Vec<(&Vec<u8>, f64)> {
+        let first = if reverse {
+            self.index.last_in_score_range(range)
+        } else {
+            self.index.first_in_score_range(range)
+        };
+        let iter = self
+            .walk(first, reverse, move |(_, score)| {
+                if reverse {
+                    range.gte_min(score)
+                } else {
+                    range.lte_max(score)
+                }
+            })
+            .skip(offset);
+        match count {
+            Some(count) => iter.take(count).collect(),
+            None => iter.collect(),
+        }
+    }
+
+    pub fn range_by_lex(
+        &self,
+        range: &LexRange,
+        reverse: bool,
+        offset: usize,
+        count: Option<usize>,
+    ) -> Vec<(&Vec<u8>, f64)> {
+        let first = if reverse {
+            self.index.last_in_lex_range(range)
+        } else {
+            self.index.first_in_lex_range(range)
+        };
+        let iter = self
+            .walk(first, reverse, move |(member, _)| {
+                if reverse {
+                    range.gte_min(member)
+                } else {
+                    range.lte_max(member)
+                }
+            })
+            .skip(offset);
+        match count {
+            Some(count) => iter.take(count).collect(),
+            None => iter.collect(),
+        }
+    }
+
+    // count the elements in the score range with the rank of both ends
+    pub fn count_by_score(&self, range: &ScoreRange) -> usize {
+        let Some(first) = self.index.first_in_score_range(range) else {
+            return 0;
+        };
+        let Some(last) = self.index.last_in_score_range(range) else {
+            return 0;
+        };
+        let rank = |node| {
+            let (member, score) = self.index.member(node);
+            self.index.rank(score, member).unwrap_or_default()
+        };
+        rank(last) + 1 - rank(first)
+    }
+
+    // remove up to count elements with the lowest (or highest) scores
+    pub fn pop(&mut self, count: usize, highest: bool) -> Vec<(Vec<u8>, f64)> {
+        if count == 0 {
+            return Vec::new();
+        }
+        let popped: Vec<(Vec<u8>, f64)> = self
+            .range_by_rank(0, count.saturating_sub(1), highest)
+            .into_iter()
+            .map(|(member, score)| (member.to_owned(), score))
+            .collect();
+        for (member, _) in popped.iter() {
+            self.remove(member);
+        }
+        popped
+    }
+
+    // all elements ordered by score
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<u8>, f64)> {
+        self.walk(self.index.first(), false, |_| true)
+    }
+
+    fn walk<'a: 'b, 'b>(
+        &'a self,
+        first: Option<usize>,
+        reverse: bool,
+        in_range: impl Fn((&Vec<u8>, f64)) -> bool + 'b,
+    ) -> impl Iterator<Item = (&'a Vec<u8>, f64)> + 'b {
+        std::iter::successors(first, move |node| {
+            if reverse {
+                self.index.prev(*node)
+            } else {
+                self.index.next(*node)
+            }
+        })
+        .map(|node| self.index.member(node))
+        .take_while(move |x| in_range(*x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SortedSet;
+    use crate::data_watcher::skip_list::ScoreRange;
+
+    fn sorted_set() -> SortedSet {
+        let mut zset = SortedSet::new();
+        for (member, score) in [("a", 1.0), ("b", 2.0), ("c", 3.0), ("d", 4.0)] {
+            zset.insert(member.as_bytes().to_vec(), score);
+        }
+        zset
+    }
+
+    #[test]
+    fn test_update_score() {
+        // arrange
+        let mut zset = sorted_set();
+        // act
+        assert!(!zset.insert(b"a".to_vec(), 10.0));
+        // assert
+        assert_eq!(4, zset.len());
+        assert_eq!(Some(3), zset.rank(b"a", false));
+        assert_eq!(Some(0), zset.rank(b"a", true));
+        assert_eq!(Some(0), zset.rank(b"b", false));
+    }
+
+    #[test]
+    fn test_range_by_rank() {
+        // arrange
+        let zset = sorted_set();
+        // act
+        let forward = zset.range_by_rank(1, 100, false);
+        let reverse = zset.range_by_rank(0, 1, true);
+        // assert
+        let members = |x: Vec<(&Vec<u8>, f64)>| {
+            x.into_iter()
+                .map(|(m, _)| String::from_utf8_lossy(m).to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["b", "c", "d"], members(forward));
+        assert_eq!(vec!["d", "c"], members(reverse));
+    }
+
+    #[test]
+    fn test_range_and_count_by_score() {
+        // arrange
+        let zset = sorted_set();
+        let range = ScoreRange {
+            min: 1.0,
+            max: 4.0,
+            min_exclusive: true,
+            max_exclusive: false,
+        };
+        // act
+        let forward = zset.range_by_score(&range, false, 1, Some(1));
+        let reverse = zset.range_by_score(&range, true, 0, None);
+        // assert
+        assert_eq!(vec![(&b"c".to_vec(), 3.0)], forward);
+        assert_eq!(3, reverse.len());
+        assert_eq!(b"d", reverse[0].0.as_slice());
+        assert_eq!(3, zset.count_by_score(&range));
+    }
+
+    #[test]
+    fn test_pop() {
+        // arrange
+        let mut zset = sorted_set();
+        // act
+        let popped = zset.pop(2, false);
+        // assert
+        assert_eq!(vec![(b"a".to_vec(), 1.0), (b"b".to_vec(), 2.0)], popped);
+        assert_eq!(2, zset.len());
+        assert_eq!(None, zset.score(b"a"));
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+// the version counters of the keys watched by WATCH, like the redis db->watched_keys dict
+// only the watched keys are counted, a key is forgotten when the last client unwatches it
+#[derive(Default, Debug)]
+pub struct WatchedKeys {
+    keys: HashMap<Vec<u8>, WatchedKey>,
+}
+
+// the version of one key, moved with the key between the shards
+#[derive(Default, Debug)]
+pub struct WatchedKey {
+    version: u64,
+    watchers: usize,
+}
+
+impl WatchedKeys {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // return the current version of the key
+    pub fn watch(&mut self, key: &[u8]) -> u64 {
+        let watched = self.keys.entry(key.to_owned()).or_default();
+        watched.watchers += 1;
+        watched.version
+    }
+
+    pub fn unwatch(&mut self, key: &[u8]) {
+        let Some(watched) = self.keys.get_mut(key) else {
+            return;
+        };
+        watched.watchers -= 1;
+        if watched.watchers == 0 {
+            self.keys.remove(key);
+        }
+    }
+
+    // called on every change of the key
+    pub fn touch(&mut self, key: &[u8]) {
+        if let Some(watched) = self.keys.get_mut(key) {
+            watched.version += 1;
+        }
+    }
+
+    pub fn version(&self, key: &[u8]) -> Option<u64> {
+        self.keys.get(key).map(|x| x.version)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Vec<u8>> {
+        self.keys.keys()
+    }
+
+    pub fn take(&mut self, key: &[u8]) -> Option<WatchedKey> {
+        self.keys.remove(key)
+    }
+
+    pub fn put(&mut self, key: Vec<u8>, watched: WatchedKey) {
+        self.keys.insert(key, watched);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WatchedKeys;
+
+    #[test]
+    fn test_watch_touch_unwatch() {
+        // arrange
+        let mut watched = WatchedKeys::default();
+        let version = watched.watch(b"a");
+        watched.watch(b"a");
+        // act
+        watched.touch(b"a");
+        watched.touch(b"b");
+        watched.unwatch(b"a");
+        // assert
+        assert_eq!(Some(version + 1), watched.version(b"a"));
+        assert_eq!(None, watched.version(b"b"));
+        watched.unwatch(b"a");
+        assert!(watched.is_empty());
+    }
+}
@@ -1,4 +1,7 @@
+pub mod acl;
 pub mod configuration;
 pub mod data_watcher;
+pub mod pubsub;
 pub mod redis_protocol;
+pub mod replication;
 pub mod tcp_server;
@@ -1,18 +1,37 @@
+use std::collections::VecDeque;
 use std::os::fd::AsRawFd;
+use std::sync::Arc;
+use std::time;
 
 use predis::{
-    configuration::Configuration,
-    data_watcher::{self, message::DataWatcherMessage},
-    tcp_server::graceful_shutdown,
+    acl::Acl,
+    configuration::{live::LiveConfig, Configuration},
+    data_watcher::{
+        shard::{Route, Shards},
+        DataStorage,
+    },
+    pubsub::{self, message::PubSubMessage},
+    redis_protocol::{aof_loader, cmd_save::Save},
+    replication::Replication,
+    tcp_server::{graceful_shutdown, server_stats::ServerStats, ServerContext},
 };
 
 use env_logger::Env;
+use log::{error, info};
+use resp::Value;
 use tokio::{net::TcpListener, sync::mpsc};
 
 #[tokio::main]
 async fn main() {
     env_logger::Builder::from_env(Env::default().default_filter_or("debug")).init();
-    let config = Configuration::new();
+    // predis [/path/to/predis.conf] [--port 6380] ..., an invalid parameter stops the server
+    let config = match Configuration::load(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("invalid configuration: {e:#}");
+            std::process::exit(1);
+        }
+    };
     predis_server(config).await;
     // tcp server
     // import library https://docs.rs/resp/latest/resp/struct.Decoder.html
@@ -37,15 +56,107 @@ async fn main() {
 }
 
 async fn predis_server(config: Configuration) {
-    let listener = TcpListener::bind(format!("127.0.0.1:{}", config.port))
-        .await
-        .unwrap();
+    // load the data before accepting connections, a corrupted file stops the server like redis
+    let storage = match load_storage(&config) {
+        Ok(storage) => storage,
+        Err(e) => {
+            error!("{e:#}");
+            std::process::exit(1);
+        }
+    };
+    // an invalid ACL file stops the server like redis
+    let acl = match Acl::load(config.acl.clone()) {
+        Ok(acl) => Arc::new(acl),
+        Err(e) => {
+            error!("{e:#}");
+            std::process::exit(1);
+        }
+    };
+    let listener = match TcpListener::bind((config.bind, config.port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("bind {}:{} error={e}", config.bind, config.port);
+            std::process::exit(1);
+        }
+    };
     let shutdown_channel =
         graceful_shutdown::listen_sig_interrupt_to_close_socket_fd(AsRawFd::as_raw_fd(&listener));
 
-    let (tx, rx) = mpsc::channel::<DataWatcherMessage>(config.workers);
+    // the keys over maxmemory are evicted by the next write command
+    storage.memory().set_config(config.maxmemory);
+    storage
+        .persistence()
+        .set_backlog_size(config.replication.backlog_size);
+    let replication = Arc::new(Replication::new(config.replication.clone()));
+    let live_config = Arc::new(LiveConfig::new(
+        config.clone(),
+        acl.clone(),
+        storage.persistence().clone(),
+        storage.memory().clone(),
+        replication.clone(),
+    ));
+    let shards = Shards::start(storage, config.shards, config.workers).await;
+    // the replica loads the snapshot of its primary over the data loaded from the files
+    replication.replicaof(config.replication.replicaof.clone(), &shards, config.port);
 
-    data_watcher::new(rx).await;
+    let (pubsub_tx, pubsub_rx) = mpsc::channel::<PubSubMessage>(config.workers);
+    pubsub::new(pubsub_rx).await;
 
-    predis::tcp_server::tcp_listener_handle(shutdown_channel, &listener, config.workers, tx).await;
+    predis::tcp_server::tcp_listener_handle(
+        shutdown_channel,
+        &listener,
+        config.workers,
+        ServerContext {
+            shards: shards.clone(),
+            pubsub_sender: pubsub_tx,
+            acl,
+            config: live_config.clone(),
+            stats: Arc::new(ServerStats::new()),
+            replication,
+        },
+    )
+    .await;
+
+    // like redis, save on shutdown when the save rules are configured, CONFIG SET save included
+    if !live_config.current().snapshot.save_rules.is_empty() {
+        save_on_shutdown(shards).await;
+    }
+}
+
+// the append only file has the latest changes, it is loaded instead of the snapshot when enabled
+fn load_storage(config: &Configuration) -> anyhow::Result<DataStorage> {
+    let storage = if config.aof.enabled {
+        let mut storage = DataStorage::with_snapshot(config.snapshot.clone());
+        aof_loader::load(&config.aof.path, &mut storage)?;
+        storage
+    } else {
+        DataStorage::load(config.snapshot.clone())?
+    };
+    storage.open_aof(config.aof.clone())?;
+    Ok(storage)
+}
+
+// SAVE fails while a BGSAVE is running, retry until it finishes
+async fn save_on_shutdown(shards: Shards) {
+    const RETRY_DELAY: time::Duration = time::Duration::from_millis(100);
+    loop {
+        let save = Save::parse(VecDeque::new(), false).unwrap();
+        match shards.exec(Route::All, save, vec![b"SAVE".to_vec()]).await {
+            Ok(Value::Error(e)) if e.contains("in progress") => {
+                tokio::time::sleep(RETRY_DELAY).await
+            }
+            Ok(Value::Error(e)) => {
+                error!("save on shutdown error={e}");
+                return;
+            }
+            Ok(_) => {
+                info!("snapshot saved on shutdown");
+                return;
+            }
+            Err(e) => {
+                error!("save on shutdown error={e}");
+                return;
+            }
+        }
+    }
 }
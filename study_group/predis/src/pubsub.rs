@@ -0,0 +1,269 @@
+pub mod message;
+
+use std::collections::HashMap;
+
+use crate::pubsub::message::PubSubMessage;
+use crate::redis_protocol::string_match::string_match;
+
+use log::warn;
+use resp::Value;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+// the messages waiting to be written to one subscriber, a slow subscriber is disconnected when it is full
+// like redis client-output-buffer-limit pubsub
+pub const SUBSCRIBER_QUEUE_CAPACITY: usize = 1024;
+
+pub type ClientId = u64;
+
+type Subscribers = HashMap<ClientId, mpsc::Sender<Value>>;
+
+// the channels and patterns with their subscribers, owned by the pub/sub task
+#[derive(Default, Debug)]
+pub struct Registry {
+    channels: HashMap<Vec<u8>, Subscribers>,
+    patterns: HashMap<Vec<u8>, Subscribers>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&mut self, client: ClientId, channel: Vec<u8>, sender: mpsc::Sender<Value>) {
+        self.channels
+            .entry(channel)
+            .or_default()
+            .insert(client, sender);
+    }
+
+    pub fn unsubscribe(&mut self, client: ClientId, channel: &[u8]) {
+        remove(&mut self.channels, client, channel);
+    }
+
+    pub fn psubscribe(&mut self, client: ClientId, pattern: Vec<u8>, sender: mpsc::Sender<Value>) {
+        self.patterns
+            .entry(pattern)
+            .or_default()
+            .insert(client, sender);
+    }
+
+    pub fn punsubscribe(&mut self, client: ClientId, pattern: &[u8]) {
+        remove(&mut self.patterns, client, pattern);
+    }
+
+    // push the message to the channel subscribers and the matched pattern subscribers
+    // return the number of receivers, a client matched twice is counted twice like redis
+    pub fn publish(&mut self, channel: &[u8], message: &[u8]) -> usize {
+        let mut receivers = 0;
+        let mut dropped = Vec::new();
+        let mut push = |client: ClientId, sender: &mpsc::Sender<Value>, value: Value| match sender
+            .try_send(value)
+        {
+            Ok(()) => receivers += 1,
+            Err(TrySendError::Full(_)) => {
+                warn!("client {client} is disconnected for overcoming the pubsub queue limit");
+                dropped.push(client);
+            }
+            Err(TrySendError::Closed(_)) => dropped.push(client),
+        };
+        if let Some(subscribers) = self.channels.get(channel) {
+            for (client, sender) in subscribers.iter() {
+                push(*client, sender, message_value(channel, message));
+            }
+        }
+        for (pattern, subscribers) in self.patterns.iter() {
+            if !string_match(pattern, channel, false) {
+                continue;
+            }
+            for (client, sender) in subscribers.iter() {
+                push(*client, sender, pmessage_value(pattern, channel, message));
+            }
+        }
+        for client in dropped {
+            self.remove_client(client);
+        }
+        receivers
+    }
+
+    // the active channels (with at least one subscriber) matching the pattern
+    pub fn channels(&self, pattern: Option<&[u8]>) -> Vec<Vec<u8>> {
+        self.channels
+            .keys()
+            .filter(|x| pattern.is_none_or(|p| string_match(p, x, false)))
+            .cloned()
+            .collect()
+    }
+
+    pub fn numsub(&self, channel: &[u8]) -> usize {
+        self.channels.get(channel).map_or(0, |x| x.len())
+    }
+
+    pub fn numpat(&self) -> usize {
+        self.patterns.len()
+    }
+
+    // drop every subscription of the client, its output queue is closed when the last sender is dropped
+    fn remove_client(&mut self, client: ClientId) {
+        for subscribers in [&mut self.channels, &mut self.patterns] {
+            subscribers.retain(|_, x| {
+                x.remove(&client);
+                !x.is_empty()
+            });
+        }
+    }
+}
+
+fn remove(subscribers: &mut HashMap<Vec<u8>, Subscribers>, client: ClientId, name: &[u8]) {
+    if let Some(x) = subscribers.get_mut(name) {
+        x.remove(&client);
+        if x.is_empty() {
+            subscribers.remove(name);
+        }
+    }
+}
+
+fn message_value(channel: &[u8], message: &[u8]) -> Value {
+    Value::Array(vec![
+        Value::BufBulk(b"message".to_vec()),
+        Value::BufBulk(channel.to_owned()),
+        Value::BufBulk(message.to_owned()),
+    ])
+}
+
+fn pmessage_value(pattern: &[u8], channel: &[u8], message: &[u8]) -> Value {
+    Value::Array(vec![
+        Value::BufBulk(b"pmessage".to_vec()),
+        Value::BufBulk(pattern.to_owned()),
+        Value::BufBulk(channel.to_owned()),
+        Value::BufBulk(message.to_owned()),
+    ])
+}
+
+pub async fn new(mut rx: mpsc::Receiver<PubSubMessage>) {
+    // create the pub/sub registry alongside the data watcher
+    tokio::spawn(async move {
+        let mut registry = Registry::new();
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                PubSubMessage::Subscribe {
+                    client,
+                    channels,
+                    sender,
+                } => {
+                    for channel in channels {
+                        registry.subscribe(client, channel, sender.clone());
+                    }
+                }
+                PubSubMessage::Unsubscribe { client, channels } => {
+                    for channel in channels {
+                        registry.unsubscribe(client, &channel);
+                    }
+                }
+                PubSubMessage::PSubscribe {
+                    client,
+                    patterns,
+                    sender,
+                } => {
+                    for pattern in patterns {
+                        registry.psubscribe(client, pattern, sender.clone());
+                    }
+                }
+                PubSubMessage::PUnsubscribe { client, patterns } => {
+                    for pattern in patterns {
+                        registry.punsubscribe(client, &pattern);
+                    }
+                }
+                PubSubMessage::Publish {
+                    channel,
+                    message,
+                    callback,
+                } => {
+                    let _ = callback.send(registry.publish(&channel, &message));
+                }
+                PubSubMessage::Channels { pattern, callback } => {
+                    let _ = callback.send(registry.channels(pattern.as_deref()));
+                }
+                PubSubMessage::NumSub { channels, callback } => {
+                    let _ = callback.send(channels.iter().map(|x| registry.numsub(x)).collect());
+                }
+                PubSubMessage::NumPat { callback } => {
+                    let _ = callback.send(registry.numpat());
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Registry;
+    use resp::Value;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_publish_channel_and_pattern() {
+        // arrange
+        let mut registry = Registry::new();
+        let (tx1, mut rx1) = mpsc::channel(8);
+        let (tx2, mut rx2) = mpsc::channel(8);
+        registry.subscribe(1, b"news.tech".to_vec(), tx1.clone());
+        registry.psubscribe(1, b"news.*".to_vec(), tx1);
+        registry.psubscribe(2, b"news.[ab]*".to_vec(), tx2);
+        // act
+        let receivers = registry.publish(b"news.tech", b"hello");
+        // assert
+        assert_eq!(2, receivers);
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(b"message".to_vec()),
+                Value::BufBulk(b"news.tech".to_vec()),
+                Value::BufBulk(b"hello".to_vec()),
+            ]),
+            rx1.try_recv().unwrap()
+        );
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(b"pmessage".to_vec()),
+                Value::BufBulk(b"news.*".to_vec()),
+                Value::BufBulk(b"news.tech".to_vec()),
+                Value::BufBulk(b"hello".to_vec()),
+            ]),
+            rx1.try_recv().unwrap()
+        );
+        assert!(rx2.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_publish_full_queue_drops_client() {
+        // arrange
+        let mut registry = Registry::new();
+        let (tx, mut rx) = mpsc::channel(1);
+        registry.subscribe(1, b"a".to_vec(), tx.clone());
+        registry.psubscribe(1, b"*".to_vec(), tx);
+        // act
+        let receivers = registry.publish(b"a", b"1");
+        // assert
+        assert_eq!(1, receivers);
+        assert_eq!(0, registry.numsub(b"a"));
+        assert_eq!(0, registry.numpat());
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_channels_and_numsub() {
+        // arrange
+        let mut registry = Registry::new();
+        let (tx, _rx) = mpsc::channel(8);
+        registry.subscribe(1, b"foo".to_vec(), tx.clone());
+        registry.subscribe(2, b"foo".to_vec(), tx.clone());
+        registry.subscribe(1, b"bar".to_vec(), tx);
+        // act
+        registry.unsubscribe(1, b"bar");
+        // assert
+        assert_eq!(vec![b"foo".to_vec()], registry.channels(Some(b"f*")));
+        assert!(registry.channels(Some(b"b*")).is_empty());
+        assert_eq!(2, registry.numsub(b"foo"));
+        assert_eq!(0, registry.numsub(b"bar"));
+    }
+}
@@ -0,0 +1,44 @@
+use crate::pubsub::ClientId;
+
+use resp::Value;
+use tokio::sync::{mpsc, oneshot};
+
+// communicate with the pub/sub registry
+pub enum PubSubMessage {
+    // the sender is the output queue of the subscriber, the registry pushes the messages into it
+    Subscribe {
+        client: ClientId,
+        channels: Vec<Vec<u8>>,
+        sender: mpsc::Sender<Value>,
+    },
+    Unsubscribe {
+        client: ClientId,
+        channels: Vec<Vec<u8>>,
+    },
+    PSubscribe {
+        client: ClientId,
+        patterns: Vec<Vec<u8>>,
+        sender: mpsc::Sender<Value>,
+    },
+    PUnsubscribe {
+        client: ClientId,
+        patterns: Vec<Vec<u8>>,
+    },
+    // reply the number of clients receiving the message
+    Publish {
+        channel: Vec<u8>,
+        message: Vec<u8>,
+        callback: oneshot::Sender<usize>,
+    },
+    Channels {
+        pattern: Option<Vec<u8>>,
+        callback: oneshot::Sender<Vec<Vec<u8>>>,
+    },
+    NumSub {
+        channels: Vec<Vec<u8>>,
+        callback: oneshot::Sender<Vec<usize>>,
+    },
+    NumPat {
+        callback: oneshot::Sender<usize>,
+    },
+}
@@ -1,118 +1,727 @@
+pub mod acl_client;
+pub mod aof_loader;
+pub mod cmd_append;
+pub mod cmd_bgrewriteaof;
+pub mod cmd_blmove;
+pub mod cmd_bpop;
 pub mod cmd_command;
+pub mod cmd_config;
+pub mod cmd_dbsize;
 pub mod cmd_del;
+pub mod cmd_exec;
+pub mod cmd_exists;
+pub mod cmd_expire;
 pub mod cmd_get;
+pub mod cmd_getdel;
+pub mod cmd_getex;
+pub mod cmd_getrange;
+pub mod cmd_hdel;
+pub mod cmd_hello;
+pub mod cmd_hexists;
+pub mod cmd_hget;
+pub mod cmd_hgetall;
+pub mod cmd_hincrby;
+pub mod cmd_hlen;
+pub mod cmd_hmget;
+pub mod cmd_hscan;
+pub mod cmd_hset;
+pub mod cmd_incrby;
+pub mod cmd_incrbyfloat;
+pub mod cmd_info;
+pub mod cmd_keys;
+pub mod cmd_lastsave;
+pub mod cmd_lindex;
+pub mod cmd_linsert;
+pub mod cmd_llen;
+pub mod cmd_lmove;
+pub mod cmd_lrange;
+pub mod cmd_lrem;
+pub mod cmd_lset;
+pub mod cmd_ltrim;
+pub mod cmd_mget;
+pub mod cmd_mset;
+pub mod cmd_persist;
+pub mod cmd_ping;
+pub mod cmd_pop;
+pub mod cmd_push;
+pub mod cmd_randomkey;
+pub mod cmd_sadd;
+pub mod cmd_save;
+pub mod cmd_scan;
+pub mod cmd_scard;
 pub mod cmd_set;
+pub mod cmd_setop;
+pub mod cmd_setrange;
+pub mod cmd_sismember;
+pub mod cmd_smembers;
+pub mod cmd_spop;
+pub mod cmd_srandmember;
+pub mod cmd_srem;
+pub mod cmd_strlen;
+pub mod cmd_ttl;
+pub mod cmd_type;
+pub mod cmd_watch;
+pub mod cmd_zadd;
+pub mod cmd_zcard;
+pub mod cmd_zcount;
+pub mod cmd_zincrby;
+pub mod cmd_zpop;
+pub mod cmd_zrange;
+pub mod cmd_zrank;
+pub mod cmd_zrem;
+pub mod cmd_zscore;
+pub mod frame_decoder;
+pub mod key_spec;
+pub mod list_helper;
+pub mod pubsub_client;
+pub mod replication_client;
+pub mod resp3;
+pub mod scan_helper;
+pub mod string_match;
+pub mod transaction;
+pub mod ttl_helper;
+pub mod zset_helper;
 
 use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time;
 
-use crate::data_watcher::{execution::Execution, message::DataWatcherMessage};
+use crate::acl::Acl;
+use crate::configuration::live::LiveConfig;
+use crate::data_watcher::{
+    execution::{Blocking, Execution},
+    shard::{Route, Shards},
+};
+use crate::pubsub::message::PubSubMessage;
+use crate::redis_protocol::acl_client::AclClient;
+use crate::redis_protocol::cmd_config::Config;
+use crate::redis_protocol::cmd_hello::Hello;
+use crate::redis_protocol::cmd_info::Info;
+use crate::redis_protocol::cmd_zrange::RangeKind;
+use crate::redis_protocol::pubsub_client::PubSubClient;
+use crate::redis_protocol::replication_client::ReplicationClient;
+use crate::redis_protocol::resp3::{Protocol, Reply};
+use crate::redis_protocol::transaction::Transaction;
+use crate::redis_protocol::{cmd_hgetall::HashPart, cmd_setop::SetOp, list_helper::Side};
+use crate::replication::Replication;
+use crate::tcp_server::server_stats::ServerStats;
 
 use anyhow::Result;
 use resp::Value;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::mpsc;
 
 pub struct RedisProtocolAnalyzer {
-    query_data_channel: mpsc::Sender<DataWatcherMessage>,
+    shards: Shards,
+    acl: AclClient,
+    config: Arc<LiveConfig>,
+    stats: Arc<ServerStats>,
+    pubsub: PubSubClient,
+    replication: ReplicationClient,
+    transaction: Transaction,
+    protocol: Protocol,
 }
 
 impl RedisProtocolAnalyzer {
-    pub fn new(tx: mpsc::Sender<DataWatcherMessage>) -> Self {
+    pub fn new(
+        shards: Shards,
+        pubsub_tx: mpsc::Sender<PubSubMessage>,
+        acl: Arc<Acl>,
+        config: Arc<LiveConfig>,
+        stats: Arc<ServerStats>,
+        replication: Arc<Replication>,
+    ) -> Self {
         RedisProtocolAnalyzer {
-            query_data_channel: tx,
+            shards: shards.clone(),
+            acl: AclClient::new(acl),
+            config,
+            stats,
+            pubsub: PubSubClient::new(pubsub_tx),
+            replication: ReplicationClient::new(replication, shards.clone()),
+            transaction: Transaction::new(shards),
+            protocol: Protocol::Resp2,
         }
     }
-    // return the encoded server result
-    pub async fn apply(&self, client_input: &[u8]) -> Vec<u8> {
-        let mut resp_decoder = resp::Decoder::new(std::io::BufReader::new(client_input));
-        if let Ok(Value::Array(v)) = resp_decoder.decode() {
-            match Self::parse(v) {
-                Ok(cmd) => {
-                    let (callback_tx, callback_rx) = oneshot::channel();
-                    let msg = DataWatcherMessage {
-                        data: cmd,
-                        callback: callback_tx,
-                    };
-                    let _ = self.query_data_channel.send(msg).await;
-                    match callback_rx.await {
-                        Ok(v) => v.encode(),
-                        Err(_) => Value::Error("get data failed".to_string()).encode(),
-                    }
+    // return the encoded server result of one frame from the frame decoder
+    pub async fn apply(&mut self, client_input: Value) -> Vec<u8> {
+        if let Value::Array(v) = client_input {
+            let args = match v.iter().map(|x| x.to_bytes()).collect::<Result<Vec<_>>>() {
+                Ok(args) => args,
+                Err(e) => return Value::Error(e.to_string()).encode(),
+            };
+            let command = args
+                .first()
+                .map(|x| String::from_utf8_lossy(x).to_lowercase())
+                .unwrap_or_default();
+            // the queued commands are checked too, the rejected one fails the transaction like redis
+            if let Err(e) = self
+                .acl
+                .check(&command, &args)
+                .and_then(|_| self.replication.check(&command))
+            {
+                self.transaction.abort();
+                self.stats.reject(&command);
+                return Value::Error(e.to_string()).encode();
+            }
+            let start = time::Instant::now();
+            let reply = self.apply_command(&command, args).await;
+            // the error replies start with - in RESP2 and RESP3
+            self.stats
+                .record(&command, start.elapsed(), reply.first() == Some(&b'-'));
+            reply
+        } else {
+            Value::Error("command should be resp array".to_string()).encode()
+        }
+    }
+
+    // BLPOP, BRPOP and BLMOVE may wait for a push, they don't wait in a transaction
+    pub fn blocks(&self, frame: &Value) -> bool {
+        let Value::Array(v) = frame else {
+            return false;
+        };
+        let Some(Ok(command)) = v.first().map(|x| x.to_bytes()) else {
+            return false;
+        };
+        let command = String::from_utf8_lossy(&command).to_lowercase();
+        matches!(command.as_str(), "blpop" | "brpop" | "blmove")
+            && !self.transaction.handles(&command)
+    }
+
+    // the command allowed by the ACL
+    async fn apply_command(&mut self, command: &str, args: Vec<Vec<u8>>) -> Vec<u8> {
+        // RESP3 has the push type, the subscribed connection runs any command
+        if self.protocol == Protocol::Resp2 {
+            if let Err(e) = self.pubsub.check_allowed(command) {
+                return Value::Error(e.to_string()).encode();
+            }
+        }
+        if command == "hello" {
+            return match self.hello(&args) {
+                Ok(reply) => reply.encode(self.protocol),
+                Err(e) => Value::Error(e.to_string()).encode(),
+            };
+        }
+        if self.acl.handles(command) {
+            let input = args.iter().skip(1).cloned().collect();
+            return match self.acl.apply(command, input) {
+                Ok(value) => self.encode(command, &args, value),
+                Err(e) => Value::Error(e.to_string()).encode(),
+            };
+        }
+        if command == "config" {
+            let config = Config::parse(args.iter().skip(1).cloned().collect());
+            return match config.and_then(|x| x.apply(&self.config)) {
+                Ok(value) => self.encode(command, &args, value),
+                Err(e) => Value::Error(e.to_string()).encode(),
+            };
+        }
+        if command == "info" {
+            let input = args.iter().skip(1).cloned().collect();
+            let info = Info::new(
+                input,
+                self.stats.clone(),
+                self.config.clone(),
+                self.replication.replication().clone(),
+            );
+            return match self.shards.exec(Route::All, info, args.clone()).await {
+                Ok(value) => self.encode(command, &args, value),
+                Err(e) => Value::Error(e.to_string()).encode(),
+            };
+        }
+        if self.replication.handles(command) {
+            let input = args.iter().skip(1).cloned().collect();
+            return match self.replication.apply(command, input, &self.config).await {
+                Ok(reply) => reply,
+                Err(e) => Value::Error(e.to_string()).encode(),
+            };
+        }
+        if self.transaction.handles(command) {
+            let queued = self.transaction.queued_args();
+            let value = self.transaction.apply(command, args.clone()).await;
+            return match (self.protocol, command, value) {
+                (Protocol::Resp3, "exec", Value::Array(replies)) => Reply::Array(
+                    replies
+                        .into_iter()
+                        .zip(queued)
+                        .map(|(value, args)| {
+                            let command = String::from_utf8_lossy(&args[0]).to_lowercase();
+                            resp3::upgrade(&command, &args, value)
+                        })
+                        .collect(),
+                )
+                .encode(self.protocol),
+                (_, _, value) => self.encode(command, &args, value),
+            };
+        }
+        if self.pubsub.handles(command) {
+            let input = args.iter().skip(1).cloned().collect();
+            return match self.pubsub.apply(command, input).await {
+                Ok(replies)
+                    if self.protocol == Protocol::Resp3 && command.contains("subscribe") =>
+                {
+                    replies
+                        .into_iter()
+                        .flat_map(|x| resp3::push(x).encode(self.protocol))
+                        .collect()
+                }
+                Ok(replies) => replies
+                    .into_iter()
+                    .flat_map(|x| self.encode(command, &args, x))
+                    .collect(),
+                Err(e) => Value::Error(e.to_string()).encode(),
+            };
+        }
+        if command == "bgsave" {
+            let input = args.iter().skip(1).cloned().collect();
+            return match cmd_save::Save::parse(input, true) {
+                Ok(save) => {
+                    let value = save.bgsave_shards(&self.shards).await;
+                    self.encode(command, &args, value)
                 }
                 Err(e) => Value::Error(e.to_string()).encode(),
+            };
+        }
+        if matches!(command, "blpop" | "brpop" | "blmove") {
+            let input = args.iter().skip(1).cloned().collect();
+            return match Self::parse_blocking(command, input) {
+                Ok(cmd) => match self
+                    .shards
+                    .exec_blocking(key_spec::route(command, &args), cmd, args.clone())
+                    .await
+                {
+                    Ok(value) => self.encode(command, &args, value),
+                    Err(e) => Value::Error(e.to_string()).encode(),
+                },
+                Err(e) => Value::Error(e.to_string()).encode(),
+            };
+        }
+        match Self::parse(args.clone()) {
+            Ok(cmd) => {
+                let route = key_spec::route(command, &args);
+                // the arguments are only kept for the RESP3 types
+                let upgrade_args = (self.protocol == Protocol::Resp3).then(|| args.clone());
+                match self.shards.exec(route, cmd, args).await {
+                    Ok(v) => match upgrade_args {
+                        Some(args) => self.encode(command, &args, v),
+                        None => v.encode(),
+                    },
+                    Err(e) => Value::Error(e.to_string()).encode(),
+                }
             }
-        } else {
-            Value::Error("decode error".to_string()).encode()
+            Err(e) => Value::Error(e.to_string()).encode(),
+        }
+    }
+
+    // the message pushed to the subscribed connection, see PubSubClient::next_push
+    // or the write commands streamed to the replica, see ReplicationClient::next_feed
+    pub async fn next_push(&mut self) -> Option<Vec<u8>> {
+        let protocol = self.protocol;
+        tokio::select! {
+            push = self.pubsub.next_push() => Some(resp3::push(push?).encode(protocol)),
+            feed = self.replication.next_feed() => feed,
+        }
+    }
+
+    // the RESP2 reply is encoded as it is, RESP3 gets the types of redis
+    fn encode(&self, command: &str, args: &[Vec<u8>], value: Value) -> Vec<u8> {
+        match self.protocol {
+            Protocol::Resp2 => value.encode(),
+            Protocol::Resp3 => resp3::upgrade(command, args, value).encode(self.protocol),
+        }
+    }
+
+    // switch the protocol of the connection, the AUTH option authenticates it at the same time
+    fn hello(&mut self, args: &[Vec<u8>]) -> Result<Reply> {
+        let hello = Hello::parse(args.iter().skip(1).cloned().collect())?;
+        match hello.auth {
+            Some((username, password)) => self.acl.authenticate(&username, &password)?,
+            None => anyhow::ensure!(
+                self.acl.is_authenticated(),
+                "NOAUTH HELLO must be called with the client already authenticated, otherwise the HELLO <proto> AUTH <user> <pass> option can be used to authenticate the client and select the RESP protocol version at the same time"
+            ),
+        }
+        if let Some(protocol) = hello.protocol {
+            self.protocol = protocol;
+        }
+        Ok(Hello::reply(
+            self.protocol,
+            self.pubsub.id(),
+            self.replication.replication().is_replica(),
+        ))
+    }
+
+    // BLPOP, BRPOP and BLMOVE, see Shards::exec_blocking
+    fn parse_blocking(command: &str, cmd: VecDeque<Vec<u8>>) -> Result<Box<dyn Blocking + Send>> {
+        match command {
+            "blpop" => Ok(cmd_bpop::BPop::parse(cmd, Side::Left)?),
+            "brpop" => Ok(cmd_bpop::BPop::parse(cmd, Side::Right)?),
+            "blmove" => Ok(cmd_blmove::BLMove::parse(cmd)?),
+            _ => anyhow::bail!("command {command} not support"),
         }
     }
 
     // parse resp array to command and value
-    fn parse(cmd: Vec<Value>) -> Result<Box<dyn Execution + Send>> {
-        // command value or command key value
+    pub(crate) fn parse(cmd: Vec<Vec<u8>>) -> Result<Box<dyn Execution + Send>> {
+        // command with zero or more arguments
         anyhow::ensure!(
-            cmd.len() >= 2,
-            "format should be [some command] [one or more argument]"
+            !cmd.is_empty(),
+            "format should be [some command] [zero or more argument]"
         );
-        // convert to Vec<String> then change to VecDeque
-        let cmd: Vec<String> = cmd.iter().map(|x| x.to_string()).collect();
         let mut cmd = VecDeque::from(cmd);
-        let command = cmd.pop_front().unwrap().to_lowercase();
+        let command = String::from_utf8_lossy(&cmd.pop_front().unwrap()).to_lowercase();
         match command.as_str() {
             "set" => Ok(cmd_set::Set::parse(cmd)?),
             "get" => Ok(cmd_get::Get::parse(cmd)?),
             "del" => Ok(cmd_del::Del::parse(cmd)?),
+            "type" => Ok(cmd_type::Type::parse(cmd)?),
+            "exists" => Ok(cmd_exists::Exists::parse(cmd)?),
+            "keys" => Ok(cmd_keys::Keys::parse(cmd)?),
+            "scan" => Ok(cmd_scan::Scan::parse(cmd)?),
+            "randomkey" => Ok(cmd_randomkey::RandomKey::parse(cmd)?),
+            "dbsize" => Ok(cmd_dbsize::DbSize::parse(cmd)?),
+            "setnx" => Ok(cmd_mset::MSet::parse_setnx(cmd)?),
+            "setex" => Ok(cmd_set::Set::parse_setex(cmd, false)?),
+            "psetex" => Ok(cmd_set::Set::parse_setex(cmd, true)?),
+            "getset" => Ok(cmd_set::Set::parse_getset(cmd)?),
+            "getdel" => Ok(cmd_getdel::GetDel::parse(cmd)?),
+            "getex" => Ok(cmd_getex::GetEx::parse(cmd)?),
+            "mget" => Ok(cmd_mget::MGet::parse(cmd)?),
+            "mset" => Ok(cmd_mset::MSet::parse(cmd, false)?),
+            "msetnx" => Ok(cmd_mset::MSet::parse(cmd, true)?),
+            "incr" => Ok(cmd_incrby::IncrBy::parse(cmd, false, false)?),
+            "decr" => Ok(cmd_incrby::IncrBy::parse(cmd, false, true)?),
+            "incrby" => Ok(cmd_incrby::IncrBy::parse(cmd, true, false)?),
+            "decrby" => Ok(cmd_incrby::IncrBy::parse(cmd, true, true)?),
+            "incrbyfloat" => Ok(cmd_incrbyfloat::IncrByFloat::parse(cmd)?),
+            "append" => Ok(cmd_append::Append::parse(cmd)?),
+            "strlen" => Ok(cmd_strlen::StrLen::parse(cmd)?),
+            "getrange" => Ok(cmd_getrange::GetRange::parse(cmd)?),
+            "setrange" => Ok(cmd_setrange::SetRange::parse(cmd)?),
+            "expire" => Ok(cmd_expire::Expire::parse(cmd, false, false)?),
+            "pexpire" => Ok(cmd_expire::Expire::parse(cmd, true, false)?),
+            "expireat" => Ok(cmd_expire::Expire::parse(cmd, false, true)?),
+            "pexpireat" => Ok(cmd_expire::Expire::parse(cmd, true, true)?),
+            "ttl" => Ok(cmd_ttl::Ttl::parse(cmd, false, false)?),
+            "pttl" => Ok(cmd_ttl::Ttl::parse(cmd, true, false)?),
+            "expiretime" => Ok(cmd_ttl::Ttl::parse(cmd, false, true)?),
+            "pexpiretime" => Ok(cmd_ttl::Ttl::parse(cmd, true, true)?),
+            "persist" => Ok(cmd_persist::Persist::parse(cmd)?),
+            "lpush" => Ok(cmd_push::Push::parse(cmd, Side::Left, false)?),
+            "rpush" => Ok(cmd_push::Push::parse(cmd, Side::Right, false)?),
+            "lpushx" => Ok(cmd_push::Push::parse(cmd, Side::Left, true)?),
+            "rpushx" => Ok(cmd_push::Push::parse(cmd, Side::Right, true)?),
+            "lpop" => Ok(cmd_pop::Pop::parse(cmd, Side::Left)?),
+            "rpop" => Ok(cmd_pop::Pop::parse(cmd, Side::Right)?),
+            "lrange" => Ok(cmd_lrange::LRange::parse(cmd)?),
+            "llen" => Ok(cmd_llen::LLen::parse(cmd)?),
+            "lindex" => Ok(cmd_lindex::LIndex::parse(cmd)?),
+            "lset" => Ok(cmd_lset::LSet::parse(cmd)?),
+            "lrem" => Ok(cmd_lrem::LRem::parse(cmd)?),
+            "ltrim" => Ok(cmd_ltrim::LTrim::parse(cmd)?),
+            "linsert" => Ok(cmd_linsert::LInsert::parse(cmd)?),
+            "lmove" => Ok(cmd_lmove::LMove::parse(cmd)?),
+            // the blocking commands don't wait in MULTI like redis
+            "blpop" | "brpop" | "blmove" => Ok(Self::parse_blocking(&command, cmd)?),
+            "hset" => Ok(cmd_hset::HSet::parse(cmd, false)?),
+            "hmset" => Ok(cmd_hset::HSet::parse(cmd, true)?),
+            "hget" => Ok(cmd_hget::HGet::parse(cmd)?),
+            "hmget" => Ok(cmd_hmget::HMGet::parse(cmd)?),
+            "hdel" => Ok(cmd_hdel::HDel::parse(cmd)?),
+            "hgetall" => Ok(cmd_hgetall::HGetAll::parse(cmd, HashPart::FieldValues)?),
+            "hkeys" => Ok(cmd_hgetall::HGetAll::parse(cmd, HashPart::Fields)?),
+            "hvals" => Ok(cmd_hgetall::HGetAll::parse(cmd, HashPart::Values)?),
+            "hlen" => Ok(cmd_hlen::HLen::parse(cmd)?),
+            "hexists" => Ok(cmd_hexists::HExists::parse(cmd)?),
+            "hincrby" => Ok(cmd_hincrby::HIncrBy::parse(cmd)?),
+            "hscan" => Ok(cmd_hscan::HScan::parse(cmd)?),
+            "sadd" => Ok(cmd_sadd::SAdd::parse(cmd)?),
+            "srem" => Ok(cmd_srem::SRem::parse(cmd)?),
+            "smembers" => Ok(cmd_smembers::SMembers::parse(cmd)?),
+            "sismember" => Ok(cmd_sismember::SIsMember::parse(cmd)?),
+            "scard" => Ok(cmd_scard::SCard::parse(cmd)?),
+            "sinter" => Ok(cmd_setop::SetOperation::parse(cmd, SetOp::Inter, false)?),
+            "sunion" => Ok(cmd_setop::SetOperation::parse(cmd, SetOp::Union, false)?),
+            "sdiff" => Ok(cmd_setop::SetOperation::parse(cmd, SetOp::Diff, false)?),
+            "sinterstore" => Ok(cmd_setop::SetOperation::parse(cmd, SetOp::Inter, true)?),
+            "sunionstore" => Ok(cmd_setop::SetOperation::parse(cmd, SetOp::Union, true)?),
+            "sdiffstore" => Ok(cmd_setop::SetOperation::parse(cmd, SetOp::Diff, true)?),
+            "spop" => Ok(cmd_spop::SPop::parse(cmd)?),
+            "srandmember" => Ok(cmd_srandmember::SRandMember::parse(cmd)?),
+            "zadd" => Ok(cmd_zadd::ZAdd::parse(cmd)?),
+            "zcard" => Ok(cmd_zcard::ZCard::parse(cmd)?),
+            "zscore" => Ok(cmd_zscore::ZScore::parse(cmd)?),
+            "zincrby" => Ok(cmd_zincrby::ZIncrBy::parse(cmd)?),
+            "zrem" => Ok(cmd_zrem::ZRem::parse(cmd)?),
+            "zrank" => Ok(cmd_zrank::ZRank::parse(cmd, false)?),
+            "zrevrank" => Ok(cmd_zrank::ZRank::parse(cmd, true)?),
+            "zcount" => Ok(cmd_zcount::ZCount::parse(cmd)?),
+            "zrange" => Ok(cmd_zrange::ZRange::parse(cmd, None, false)?),
+            "zrevrange" => Ok(cmd_zrange::ZRange::parse(cmd, Some(RangeKind::Rank), true)?),
+            "zrangebyscore" => Ok(cmd_zrange::ZRange::parse(
+                cmd,
+                Some(RangeKind::Score),
+                false,
+            )?),
+            "zrevrangebyscore" => Ok(cmd_zrange::ZRange::parse(
+                cmd,
+                Some(RangeKind::Score),
+                true,
+            )?),
+            "zrangebylex" => Ok(cmd_zrange::ZRange::parse(cmd, Some(RangeKind::Lex), false)?),
+            "zrevrangebylex" => Ok(cmd_zrange::ZRange::parse(cmd, Some(RangeKind::Lex), true)?),
+            "zpopmin" => Ok(cmd_zpop::ZPop::parse(cmd, false)?),
+            "zpopmax" => Ok(cmd_zpop::ZPop::parse(cmd, true)?),
+            "save" => Ok(cmd_save::Save::parse(cmd, false)?),
+            "bgsave" => Ok(cmd_save::Save::parse(cmd, true)?),
+            "lastsave" => Ok(cmd_lastsave::LastSave::parse(cmd)?),
+            "ping" => Ok(cmd_ping::Ping::parse(cmd)?),
+            "bgrewriteaof" => Ok(cmd_bgrewriteaof::BgRewriteAof::parse(cmd)?),
             "command" => Ok(cmd_command::Command::parse(cmd)?),
             _ => anyhow::bail!("command {command} not support",),
         }
     }
 }
 
+// parse the argument as integer with the redis error message
+// like string2ll of redis, the leading + is rejected
+pub fn parse_integer<T: std::str::FromStr>(input: &[u8]) -> Result<T> {
+    std::str::from_utf8(input)
+        .ok()
+        .filter(|x| !x.starts_with('+'))
+        .and_then(|x| x.parse::<T>().ok())
+        .ok_or_else(|| anyhow::anyhow!("ERR value is not an integer or out of range"))
+}
+
 pub trait RespValueExt {
-    fn to_string(&self) -> String;
+    fn to_bytes(&self) -> Result<Vec<u8>>;
 }
 
 impl RespValueExt for Value {
-    fn to_string(&self) -> String {
+    // the argument of a command, any bytes are allowed in the bulk string
+    fn to_bytes(&self) -> Result<Vec<u8>> {
         match self {
-            Value::String(s) => s.clone(),
-            Value::Bulk(s) => s.clone(),
-            _ => String::new(),
+            Value::String(s) | Value::Bulk(s) => Ok(s.as_bytes().to_vec()),
+            Value::BufBulk(bytes) => Ok(bytes.to_owned()),
+            _ => anyhow::bail!("Protocol error: expected bulk string argument"),
         }
     }
 }
 
+// the configuration of the analyzer in the tests
+#[cfg(test)]
+pub(crate) fn test_config(acl: &Arc<Acl>) -> Arc<LiveConfig> {
+    let storage = crate::data_watcher::DataStorage::new();
+    Arc::new(LiveConfig::new(
+        crate::configuration::Configuration::default(),
+        acl.clone(),
+        storage.persistence().clone(),
+        storage.memory().clone(),
+        Arc::new(Replication::default()),
+    ))
+}
+
 #[tokio::test]
 async fn test_apply_set_command() {
     // arrange
     //*3\r\n$3\r\nset\r\n$3\r\nkey\r\n$5\r\nvalue\r\n
     let input_value = Value::Array(vec![
-        Value::Bulk("set".to_string()),
-        Value::Bulk("key".to_string()),
-        Value::Bulk("value".to_string()),
+        Value::BufBulk(b"set".to_vec()),
+        Value::BufBulk(b"key".to_vec()),
+        Value::BufBulk(b"value".to_vec()),
     ]);
-    let (tx, mut rx) = mpsc::channel::<DataWatcherMessage>(1);
-    let rpa = RedisProtocolAnalyzer {
-        query_data_channel: tx,
-    };
+    let (tx, mut rx) = mpsc::channel::<crate::data_watcher::message::ShardMessage>(1);
+    let (pubsub_tx, _pubsub_rx) = mpsc::channel::<PubSubMessage>(1);
+    let acl = Arc::new(Acl::new(crate::acl::AclConfig::default()));
+    let mut rpa = RedisProtocolAnalyzer::new(
+        Shards::new(vec![tx]),
+        pubsub_tx,
+        acl.clone(),
+        test_config(&acl),
+        Arc::new(ServerStats::new()),
+        Arc::new(Replication::default()),
+    );
     // mock data watcher
     tokio::spawn(async move {
-        let data = rx.recv().await.unwrap();
+        let Some(crate::data_watcher::message::ShardMessage::Command(data)) = rx.recv().await
+        else {
+            panic!("command expected");
+        };
         assert!(data.callback.send(Value::String("ok".to_string())).is_ok())
     });
     // act
-    println!("{:?}", String::from_utf8(input_value.encode()));
-    let r = rpa.apply(&input_value.encode()).await;
+    let r = rpa.apply(input_value).await;
     // assert
     assert_eq!(Value::String("ok".to_string()).encode(), r);
 }
 
+#[tokio::test]
+async fn test_apply_binary_args() {
+    // arrange
+    let key = b"\xc3\x28\r\n".to_vec();
+    let value = b"$1\r\n\x00\xff".to_vec();
+    let input_value = Value::Array(vec![
+        Value::BufBulk(b"set".to_vec()),
+        Value::BufBulk(key.clone()),
+        Value::BufBulk(value.clone()),
+    ]);
+    let (tx, mut rx) = mpsc::channel::<crate::data_watcher::message::ShardMessage>(1);
+    let (pubsub_tx, _pubsub_rx) = mpsc::channel::<PubSubMessage>(1);
+    let acl = Arc::new(Acl::new(crate::acl::AclConfig::default()));
+    let mut rpa = RedisProtocolAnalyzer::new(
+        Shards::new(vec![tx]),
+        pubsub_tx,
+        acl.clone(),
+        test_config(&acl),
+        Arc::new(ServerStats::new()),
+        Arc::new(Replication::default()),
+    );
+    // mock data watcher
+    let expected = vec![b"set".to_vec(), key, value];
+    tokio::spawn(async move {
+        let Some(crate::data_watcher::message::ShardMessage::Command(data)) = rx.recv().await
+        else {
+            panic!("command expected");
+        };
+        assert_eq!(expected, data.args);
+        assert!(data.callback.send(Value::String("OK".to_string())).is_ok())
+    });
+    // act
+    let r = rpa.apply(input_value).await;
+    let not_bulk = rpa
+        .apply(Value::Array(vec![
+            Value::BufBulk(b"get".to_vec()),
+            Value::Integer(1),
+        ]))
+        .await;
+    // assert
+    assert_eq!(b"+OK\r\n".to_vec(), r);
+    assert_eq!(
+        b"-Protocol error: expected bulk string argument\r\n".to_vec(),
+        not_bulk
+    );
+}
+
+#[tokio::test]
+async fn test_apply_hello() {
+    // arrange
+    let (tx, _rx) = mpsc::channel::<crate::data_watcher::message::ShardMessage>(1);
+    let (pubsub_tx, _pubsub_rx) = mpsc::channel::<PubSubMessage>(1);
+    let acl = Arc::new(Acl::new(crate::acl::AclConfig::default()));
+    let mut rpa = RedisProtocolAnalyzer::new(
+        Shards::new(vec![tx]),
+        pubsub_tx,
+        acl.clone(),
+        test_config(&acl),
+        Arc::new(ServerStats::new()),
+        Arc::new(Replication::default()),
+    );
+    let hello = |version: &str| {
+        Value::Array(vec![
+            Value::BufBulk(b"hello".to_vec()),
+            Value::BufBulk(version.as_bytes().to_vec()),
+        ])
+    };
+    // act
+    let resp3 = rpa.apply(hello("3")).await;
+    let unsupported = rpa.apply(hello("4")).await;
+    // assert
+    assert!(resp3.starts_with(b"%7\r\n$6\r\nserver\r\n$6\r\npredis\r\n"));
+    assert_eq!(
+        b"-NOPROTO unsupported protocol version\r\n".to_vec(),
+        unsupported
+    );
+    assert_eq!(Protocol::Resp3, rpa.protocol);
+}
+
+#[tokio::test]
+async fn test_apply_acl() {
+    // arrange
+    let (tx, _rx) = mpsc::channel::<crate::data_watcher::message::ShardMessage>(1);
+    let (pubsub_tx, _pubsub_rx) = mpsc::channel::<PubSubMessage>(1);
+    let acl = Arc::new(Acl::new(crate::acl::AclConfig {
+        requirepass: Some("secret".to_string()),
+        aclfile: None,
+    }));
+    acl.set_user(
+        "alice",
+        &[b"on".to_vec(), b">pw".to_vec(), b"~app:*".to_vec()],
+    )
+    .unwrap();
+    acl.set_user("alice", &[b"+get".to_vec()]).unwrap();
+    let config = test_config(&acl);
+    let mut rpa = RedisProtocolAnalyzer::new(
+        Shards::new(vec![tx]),
+        pubsub_tx,
+        acl,
+        config,
+        Arc::new(ServerStats::new()),
+        Arc::new(Replication::default()),
+    );
+    let command = |input: &[&str]| {
+        Value::Array(
+            input
+                .iter()
+                .map(|x| Value::BufBulk(x.as_bytes().to_vec()))
+                .collect(),
+        )
+    };
+    // act
+    let noauth = rpa.apply(command(&["get", "app:1"])).await;
+    let hello = rpa.apply(command(&["hello", "2"])).await;
+    let auth = rpa.apply(command(&["auth", "alice", "pw"])).await;
+    let command_denied = rpa.apply(command(&["set", "app:1", "v"])).await;
+    let key_denied = rpa.apply(command(&["get", "other"])).await;
+    // assert
+    assert_eq!(b"-NOAUTH Authentication required.\r\n".to_vec(), noauth);
+    assert!(hello.starts_with(b"-NOAUTH HELLO must be called"));
+    assert_eq!(b"+OK\r\n".to_vec(), auth);
+    assert_eq!(
+        b"-NOPERM User alice has no permissions to run the 'set' command\r\n".to_vec(),
+        command_denied
+    );
+    assert_eq!(
+        b"-NOPERM No permissions to access a key\r\n".to_vec(),
+        key_denied
+    );
+}
+
+#[tokio::test]
+async fn test_apply_config() {
+    // arrange
+    let (tx, _rx) = mpsc::channel::<crate::data_watcher::message::ShardMessage>(1);
+    let (pubsub_tx, _pubsub_rx) = mpsc::channel::<PubSubMessage>(1);
+    let acl = Arc::new(Acl::new(crate::acl::AclConfig::default()));
+    let config = test_config(&acl);
+    let mut rpa = RedisProtocolAnalyzer::new(
+        Shards::new(vec![tx]),
+        pubsub_tx,
+        acl,
+        config,
+        Arc::new(ServerStats::new()),
+        Arc::new(Replication::default()),
+    );
+    let command = |input: &[&str]| {
+        Value::Array(
+            input
+                .iter()
+                .map(|x| Value::BufBulk(x.as_bytes().to_vec()))
+                .collect(),
+        )
+    };
+    // act
+    let set = rpa.apply(command(&["config", "set", "save", "60 1"])).await;
+    let resp2 = rpa.apply(command(&["config", "get", "save"])).await;
+    rpa.apply(command(&["hello", "3"])).await;
+    let resp3 = rpa.apply(command(&["config", "get", "save"])).await;
+    // assert
+    assert_eq!(b"+OK\r\n".to_vec(), set);
+    assert_eq!(b"*2\r\n$4\r\nsave\r\n$4\r\n60 1\r\n".to_vec(), resp2);
+    assert_eq!(b"%1\r\n$4\r\nsave\r\n$4\r\n60 1\r\n".to_vec(), resp3);
+}
+
 #[test]
 fn test_parse_command_set_key_with_value_string() {
     // arrange
-    let input_value = vec![
-        Value::Bulk("set".to_string()),
-        Value::Bulk("key".to_string()),
-        Value::Bulk("value".to_string()),
-    ];
+    let input_value = vec![b"set".to_vec(), b"key".to_vec(), b"value".to_vec()];
     // act
     let r = RedisProtocolAnalyzer::parse(input_value);
     // assert
@@ -121,10 +730,22 @@ fn test_parse_command_set_key_with_value_string() {
 
 #[test]
 fn test_parse_command_get_key() {
+    // arrange
+    let input_value = vec![b"get".to_vec(), b"key".to_vec()];
+    // act
+    let r = RedisProtocolAnalyzer::parse(input_value);
+    // assert
+    assert!(r.is_ok());
+}
+
+#[test]
+fn test_parse_command_zrevrangebylex() {
     // arrange
     let input_value = vec![
-        Value::Bulk("get".to_string()),
-        Value::Bulk("key".to_string()),
+        b"zrevrangebylex".to_vec(),
+        b"key".to_vec(),
+        b"+".to_vec(),
+        b"-".to_vec(),
     ];
     // act
     let r = RedisProtocolAnalyzer::parse(input_value);
@@ -135,10 +756,7 @@ fn test_parse_command_get_key() {
 #[test]
 fn test_parse_command_del_key() {
     // arrange
-    let input_value = vec![
-        Value::Bulk("del".to_string()),
-        Value::Bulk("key".to_string()),
-    ];
+    let input_value = vec![b"del".to_vec(), b"key".to_vec()];
     // act
     let r = RedisProtocolAnalyzer::parse(input_value);
     // assert
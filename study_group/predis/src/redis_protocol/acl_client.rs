@@ -0,0 +1,250 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::acl::{category, Acl};
+
+use anyhow::Result;
+use resp::Value;
+
+// the authenticated user of one connection
+// https://redis.io/commands/auth/ https://redis.io/commands/acl/
+pub struct AclClient {
+    acl: Arc<Acl>,
+    // None until the connection is authenticated
+    user: Option<String>,
+}
+
+impl AclClient {
+    pub fn new(acl: Arc<Acl>) -> Self {
+        let user = acl.default_user_nopass().then(|| "default".to_string());
+        AclClient { acl, user }
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.user.is_some()
+    }
+
+    // the commands handled here instead of being sent to the data watcher
+    pub fn handles(&self, command: &str) -> bool {
+        matches!(command, "auth" | "acl")
+    }
+
+    // AUTH and HELLO are the only commands before the authentication like redis
+    pub fn check(&self, command: &str, args: &[Vec<u8>]) -> Result<()> {
+        if matches!(command, "auth" | "hello") {
+            return Ok(());
+        }
+        let Some(user) = &self.user else {
+            anyhow::bail!("NOAUTH Authentication required.");
+        };
+        self.acl.check(user, command, args)
+    }
+
+    pub fn authenticate(&mut self, username: &[u8], password: &[u8]) -> Result<()> {
+        let username = String::from_utf8_lossy(username).to_string();
+        anyhow::ensure!(
+            self.acl.authenticate(&username, password),
+            "WRONGPASS invalid username-password pair or user is disabled."
+        );
+        self.user = Some(username);
+        Ok(())
+    }
+
+    pub fn apply(&mut self, command: &str, mut input: VecDeque<Vec<u8>>) -> Result<Value> {
+        match command {
+            // AUTH [username] password, the username is default when it is omitted
+            "auth" => {
+                let (username, password) = match input.len() {
+                    1 => {
+                        anyhow::ensure!(
+                            !self.acl.default_user_nopass(),
+                            "ERR AUTH <password> called without any password configured for the default user. Are you sure your configuration is correct?"
+                        );
+                        (b"default".to_vec(), input.pop_front().unwrap())
+                    }
+                    2 => (input.pop_front().unwrap(), input.pop_front().unwrap()),
+                    _ => anyhow::bail!("wrong number of arguments for auth"),
+                };
+                self.authenticate(&username, &password)?;
+                Ok(Value::String("OK".to_string()))
+            }
+            "acl" => self.acl(input),
+            _ => anyhow::bail!("command {command} not support"),
+        }
+    }
+
+    fn acl(&mut self, mut input: VecDeque<Vec<u8>>) -> Result<Value> {
+        let subcommand = input
+            .pop_front()
+            .ok_or_else(|| anyhow::anyhow!("wrong number of arguments for acl"))?;
+        let subcommand = String::from_utf8_lossy(&subcommand).to_lowercase();
+        let bulk = |x: &str| Value::BufBulk(x.as_bytes().to_vec());
+        let names: Vec<String> = input
+            .iter()
+            .map(|x| String::from_utf8_lossy(x).to_string())
+            .collect();
+        match subcommand.as_str() {
+            "setuser" => {
+                let Some(name) = names.first() else {
+                    anyhow::bail!("wrong number of arguments for acl setuser");
+                };
+                self.acl
+                    .set_user(name, input.make_contiguous().split_at(1).1)?;
+                Ok(Value::String("OK".to_string()))
+            }
+            "getuser" => {
+                anyhow::ensure!(
+                    names.len() == 1,
+                    "wrong number of arguments for acl getuser"
+                );
+                let Some(user) = self.acl.get_user(&names[0]) else {
+                    return Ok(Value::Null);
+                };
+                Ok(Value::Array(vec![
+                    bulk("flags"),
+                    Value::Array(user.flags().into_iter().map(bulk).collect()),
+                    bulk("passwords"),
+                    Value::Array(user.passwords().map(|x| bulk(x)).collect()),
+                    bulk("commands"),
+                    bulk(&user.commands()),
+                    bulk("keys"),
+                    bulk(&user.keys()),
+                ]))
+            }
+            "deluser" => {
+                anyhow::ensure!(
+                    !names.is_empty(),
+                    "wrong number of arguments for acl deluser"
+                );
+                Ok(Value::Integer(self.acl.del_users(&names)? as i64))
+            }
+            "list" | "users" | "whoami" | "load" | "save" => {
+                anyhow::ensure!(
+                    names.is_empty(),
+                    "wrong number of arguments for acl {subcommand}"
+                );
+                match subcommand.as_str() {
+                    "list" => Ok(Value::Array(
+                        self.acl.list().iter().map(|x| bulk(x)).collect(),
+                    )),
+                    "users" => Ok(Value::Array(
+                        self.acl.usernames().iter().map(|x| bulk(x)).collect(),
+                    )),
+                    "whoami" => Ok(bulk(self.user.as_deref().unwrap_or_default())),
+                    "load" => self
+                        .acl
+                        .load_file()
+                        .map(|_| Value::String("OK".to_string())),
+                    _ => self
+                        .acl
+                        .save_file()
+                        .map(|_| Value::String("OK".to_string())),
+                }
+            }
+            // ACL CAT [category]
+            "cat" => match names.first() {
+                None => Ok(Value::Array(
+                    category::CATEGORIES.iter().map(|x| bulk(x)).collect(),
+                )),
+                Some(name) if names.len() == 1 && category::is_category(name) => {
+                    Ok(Value::Array(category::commands(name).map(bulk).collect()))
+                }
+                Some(name) if names.len() == 1 => anyhow::bail!("ERR Unknown category '{name}'"),
+                Some(_) => anyhow::bail!("wrong number of arguments for acl cat"),
+            },
+            _ => anyhow::bail!("ERR unknown subcommand '{subcommand}'. Try ACL HELP."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AclClient;
+    use crate::acl::{Acl, AclConfig};
+    use resp::Value;
+    use std::sync::Arc;
+
+    fn args(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_auth_requirepass() {
+        // arrange
+        let acl = Arc::new(Acl::new(AclConfig {
+            requirepass: Some("secret".to_string()),
+            aclfile: None,
+        }));
+        let mut client = AclClient::new(acl);
+        // act
+        let noauth = client.check("get", &args(&["get", "k"]));
+        let wrong = client.apply("auth", args(&["other"]).into());
+        let auth = client.apply("auth", args(&["secret"]).into());
+        // assert
+        assert_eq!(
+            "NOAUTH Authentication required.",
+            noauth.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "WRONGPASS invalid username-password pair or user is disabled.",
+            wrong.unwrap_err().to_string()
+        );
+        assert_eq!(Value::String("OK".to_string()), auth.unwrap());
+        assert!(client.check("get", &args(&["get", "k"])).is_ok());
+    }
+
+    #[test]
+    fn test_auth_without_password() {
+        // arrange
+        let mut client = AclClient::new(Arc::new(Acl::new(AclConfig::default())));
+        // act
+        let result = client.apply("auth", args(&["secret"]).into());
+        // assert
+        assert!(client.is_authenticated());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("ERR AUTH <password> called without any password configured"));
+    }
+
+    #[test]
+    fn test_acl_users() {
+        // arrange
+        let acl = Arc::new(Acl::new(AclConfig::default()));
+        let mut admin = AclClient::new(acl.clone());
+        let mut client = AclClient::new(acl);
+        // act
+        let setuser = admin.apply(
+            "acl",
+            args(&["setuser", "alice", "on", ">pw", "~k*", "+get"]).into(),
+        );
+        let auth = client.apply("auth", args(&["alice", "pw"]).into());
+        let whoami = client.apply("acl", args(&["whoami"]).into());
+        let denied = client.check("set", &args(&["set", "k", "v"]));
+        let getuser = admin.apply("acl", args(&["getuser", "alice"]).into());
+        let users = admin.apply("acl", args(&["users"]).into());
+        // assert
+        assert!(setuser.is_ok());
+        assert!(auth.is_ok());
+        assert_eq!(Value::BufBulk(b"alice".to_vec()), whoami.unwrap());
+        assert!(denied.is_err());
+        let Value::Array(getuser) = getuser.unwrap() else {
+            panic!("array expected");
+        };
+        assert_eq!(Value::BufBulk(b"-@all +get".to_vec()), getuser[5]);
+        assert_eq!(Value::BufBulk(b"~k*".to_vec()), getuser[7]);
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(b"alice".to_vec()),
+                Value::BufBulk(b"default".to_vec())
+            ]),
+            users.unwrap()
+        );
+        assert_eq!(
+            Value::Null,
+            admin
+                .apply("acl", args(&["getuser", "bob"]).into())
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::fs;
+use std::path::Path;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::cmd_exec::QueuedCommand;
+use crate::redis_protocol::frame_decoder::FrameDecoder;
+use crate::redis_protocol::{RedisProtocolAnalyzer, RespValueExt};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use resp::Value;
+
+// replay the append only file into the storage, like redis loadAppendOnlyFile
+// the incomplete command at the end (a crash in the middle of a write) is truncated from the file
+pub fn load(path: &Path, data: &mut DataStorage) -> Result<()> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            info!("aof {} not found", path.display());
+            return Ok(());
+        }
+        Err(e) => return Err(e).with_context(|| format!("read aof {}", path.display())),
+    };
+    let mut decoder = FrameDecoder::new();
+    decoder.extend(&bytes);
+    let mut commands = 0;
+    // the commands of a transaction are applied together on EXEC, like the fake client of redis
+    let mut multi: Option<(usize, Vec<QueuedCommand>)> = None;
+    loop {
+        let offset = bytes.len() - decoder.buffered();
+        let frame = decoder
+            .next_frame()
+            .with_context(|| format!("bad format of aof {} at {offset}", path.display()))?;
+        let Some(frame) = frame else {
+            break;
+        };
+        let Value::Array(cmd) = frame else {
+            anyhow::bail!("bad format of aof {} at {offset}", path.display());
+        };
+        let args = cmd
+            .iter()
+            .map(|x| x.to_bytes())
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("bad command of aof {} at {offset}", path.display()))?;
+        let command = args
+            .first()
+            .map(|x| String::from_utf8_lossy(x).to_lowercase())
+            .unwrap_or_default();
+        match (command.as_str(), multi.as_mut()) {
+            ("multi", None) => {
+                multi = Some((offset, Vec::new()));
+                continue;
+            }
+            ("exec", Some(_)) => {
+                let (_, queued) = multi.take().unwrap();
+                for queued in queued {
+                    replay(data, queued.data.as_ref(), &queued.args);
+                    commands += 1;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        let execution = RedisProtocolAnalyzer::parse(args.clone())
+            .with_context(|| format!("bad command of aof {} at {offset}", path.display()))?;
+        match multi.as_mut() {
+            Some((_, queued)) => queued.push(QueuedCommand {
+                data: execution,
+                args,
+            }),
+            None => {
+                replay(data, execution.as_ref(), &args);
+                commands += 1;
+            }
+        }
+    }
+    // the transaction without EXEC is discarded with the incomplete tail
+    let valid = match multi {
+        Some((offset, _)) => offset,
+        None => bytes.len() - decoder.buffered(),
+    };
+    if valid < bytes.len() {
+        warn!(
+            "aof {} is truncated, remove the last {} bytes",
+            path.display(),
+            bytes.len() - valid
+        );
+        fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_len(valid as u64))
+            .with_context(|| format!("truncate aof {}", path.display()))?;
+    }
+    info!("replayed {commands} commands from aof {}", path.display());
+    Ok(())
+}
+
+// the append only file is not opened yet, the propagation only clears the rewritten commands
+fn replay(data: &mut DataStorage, execution: &(dyn Execution + Send), args: &[Vec<u8>]) {
+    let dirty = data.dirty();
+    execution.exec(data);
+    data.propagate(args, dirty);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load;
+    use crate::data_watcher::aof::{encode_command, AofConfig, AppendFsync};
+    use crate::data_watcher::DataStorage;
+    use crate::redis_protocol::RedisProtocolAnalyzer;
+
+    fn command(input: &[&str]) -> Vec<u8> {
+        encode_command(
+            &input
+                .iter()
+                .map(|x| x.as_bytes().to_vec())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_load_truncated() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("predis-{}-load.aof", std::process::id()));
+        let mut content = command(&["set", "a", "1"]);
+        content.extend(command(&["rpush", "l", "x", "y"]));
+        content.extend(command(&["pexpireat", "a", "1"]));
+        let valid = content.len();
+        content.extend(&command(&["set", "b", "2"])[..10]);
+        std::fs::write(&path, content).unwrap();
+        let mut data = DataStorage::new();
+        // act
+        let result = load(&path, &mut data);
+        // assert
+        let len = std::fs::metadata(&path).unwrap().len();
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_ok());
+        assert_eq!(valid as u64, len);
+        assert!(!data.exists(b"a"));
+        assert!(data.exists(b"l"));
+        assert!(!data.exists(b"b"));
+    }
+
+    #[test]
+    fn test_load_incomplete_transaction() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("predis-{}-multi.aof", std::process::id()));
+        let mut content = command(&["multi"]);
+        content.extend(command(&["set", "a", "1"]));
+        content.extend(command(&["exec"]));
+        let valid = content.len();
+        content.extend(command(&["multi"]));
+        content.extend(command(&["set", "b", "1"]));
+        std::fs::write(&path, content).unwrap();
+        let mut data = DataStorage::new();
+        // act
+        let result = load(&path, &mut data);
+        // assert
+        let len = std::fs::metadata(&path).unwrap().len();
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_ok());
+        assert_eq!(valid as u64, len);
+        assert!(data.exists(b"a"));
+        assert!(!data.exists(b"b"));
+    }
+
+    #[test]
+    fn test_load_binary() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("predis-{}-binary.aof", std::process::id()));
+        let args = [
+            b"set".to_vec(),
+            b"k\r\n\xff".to_vec(),
+            b"*1\r\n$1\r\n\x00".to_vec(),
+        ];
+        std::fs::write(&path, encode_command(&args)).unwrap();
+        let mut data = DataStorage::new();
+        // act
+        let result = load(&path, &mut data);
+        // assert
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_ok());
+        assert_eq!(Ok(Some(&args[2])), data.get_typed::<Vec<u8>>(&args[1]));
+    }
+
+    #[test]
+    fn test_load_bad_command() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("predis-{}-bad.aof", std::process::id()));
+        std::fs::write(&path, command(&["unknown", "a"])).unwrap();
+        // act
+        let result = load(&path, &mut DataStorage::new());
+        // assert
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_replay_keeps_absolute_ttl() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("predis-{}-ttl.aof", std::process::id()));
+        let mut data = DataStorage::new();
+        data.open_aof(AofConfig {
+            enabled: true,
+            path: path.clone(),
+            fsync: AppendFsync::No,
+        })
+        .unwrap();
+        for input in [["set", "a", "1", "ex", "100"], ["set", "b", "1", "px", "1"]] {
+            let args: Vec<Vec<u8>> = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+            let dirty = data.dirty();
+            RedisProtocolAnalyzer::parse(args.clone())
+                .unwrap()
+                .exec(&mut data);
+            data.propagate(&args, dirty);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        // act
+        let mut replayed = DataStorage::new();
+        let result = load(&path, &mut replayed);
+        // assert
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_ok());
+        assert_eq!(
+            data.get(b"a".as_slice())
+                .unwrap()
+                .expired_epoch()
+                .unwrap()
+                .as_millis(),
+            replayed
+                .get(b"a".as_slice())
+                .unwrap()
+                .expired_epoch()
+                .unwrap()
+                .as_millis()
+        );
+        assert!(!replayed.exists(b"b"));
+    }
+}
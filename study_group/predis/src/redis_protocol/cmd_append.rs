@@ -0,0 +1,63 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/append/
+#[derive(Default, PartialEq, Debug)]
+pub struct Append {
+    key: Vec<u8>,
+    value: Vec<u8>,
+}
+
+impl Append {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 2, "wrong number of arguments for append");
+        Ok(Box::new(Append {
+            key: input[0].to_owned(),
+            value: input[1].to_owned(),
+        }))
+    }
+}
+
+impl Execution for Append {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed_or_default::<Vec<u8>>(&self.key) {
+            Ok(value) => {
+                value.extend_from_slice(&self.value);
+                Value::Integer(value.len() as i64)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Append;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        let append = |value: &str| Append {
+            key: b"k".to_vec(),
+            value: value.as_bytes().to_vec(),
+        };
+        // act
+        let result = append("Hello").exec(&mut data);
+        let result2 = append(" World").exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(5), result);
+        assert_eq!(Value::Integer(11), result2);
+        assert_eq!(
+            Ok(Some(&b"Hello World".to_vec())),
+            data.get_typed::<Vec<u8>>(b"k")
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::aof;
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::shard::LentShards;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/bgrewriteaof/
+#[derive(Default, PartialEq, Debug)]
+pub struct BgRewriteAof;
+
+impl BgRewriteAof {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            input.is_empty(),
+            "wrong number of arguments for bgrewriteaof"
+        );
+        Ok(Box::new(BgRewriteAof))
+    }
+}
+
+impl BgRewriteAof {
+    fn rewrite(&self, shards: &[&DataStorage]) -> Value {
+        match aof::bgrewriteaof(shards) {
+            Ok(()) => Value::String("Background append only file rewriting started".to_string()),
+            Err(e) => Value::Error(format!("ERR {e:#}")),
+        }
+    }
+}
+
+impl Execution for BgRewriteAof {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        self.rewrite(&[&*data])
+    }
+
+    // the rewritten file has the keys of all the shards
+    fn exec_shards(&self, shards: &mut LentShards, _args: &[Vec<u8>]) -> Value {
+        self.rewrite(&shards.storages())
+    }
+}
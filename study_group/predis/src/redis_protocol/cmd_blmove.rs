@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+use std::time;
+
+use crate::data_watcher::execution::{Blocking, Execution};
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::cmd_lmove::LMove;
+use crate::redis_protocol::list_helper::parse_timeout;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/blmove/
+#[derive(PartialEq, Debug)]
+pub struct BLMove {
+    // the source, the only key waited for
+    keys: Vec<Vec<u8>>,
+    // source destination wherefrom whereto, logged as LMOVE like redis
+    lmove_args: Vec<Vec<u8>>,
+    lmove: Box<LMove>,
+    timeout: Option<time::Duration>,
+}
+
+impl BLMove {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 5, "wrong number of arguments for blmove");
+        let timeout = parse_timeout(&input.pop_back().unwrap())?;
+        Ok(Box::new(BLMove {
+            keys: vec![input[0].to_owned()],
+            lmove_args: input.iter().cloned().collect(),
+            lmove: LMove::parse(input)?,
+            timeout,
+        }))
+    }
+}
+
+impl Execution for BLMove {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let reply = self.lmove.exec(data);
+        if matches!(reply, Value::BufBulk(_)) {
+            let mut command = vec![b"LMOVE".to_vec()];
+            command.extend(self.lmove_args.iter().cloned());
+            data.rewrite_command(command);
+        }
+        reply
+    }
+}
+
+impl Blocking for BLMove {
+    fn keys(&self) -> &[Vec<u8>] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<time::Duration> {
+        self.timeout
+    }
+
+    fn timeout_reply(&self) -> Value {
+        Value::Null
+    }
+
+    fn serve(&self, data: &mut DataStorage, _key: &[u8]) -> Value {
+        self.exec(data)
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::BLMove;
+    use std::collections::VecDeque;
+
+    fn input(input: &[&str]) -> VecDeque<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert!(BLMove::parse(input(&["a", "b", "left", "right"])).is_err());
+        assert!(BLMove::parse(input(&["a", "b", "up", "right", "0"])).is_err());
+        assert!(BLMove::parse(input(&["a", "b", "left", "right", "x"])).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::BLMove;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn input(input: &[&str]) -> VecDeque<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_exec_move() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"src")
+            .unwrap()
+            .push_back(b"a".to_vec());
+        let blmove = BLMove::parse(input(&["src", "dst", "RIGHT", "LEFT", "0"])).unwrap();
+        // act
+        let moved = blmove.exec(&mut data);
+        let empty = blmove.exec(&mut data);
+        // assert
+        assert_eq!(Value::BufBulk(b"a".to_vec()), moved);
+        assert_eq!(Value::Null, empty);
+        assert!(data.exists(b"dst"));
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+use std::time;
+
+use crate::data_watcher::execution::{Blocking, Execution};
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::list_helper::{parse_timeout, Side};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/blpop/
+// https://redis.io/commands/brpop/
+#[derive(PartialEq, Debug)]
+pub struct BPop {
+    keys: Vec<Vec<u8>>,
+    side: Side,
+    timeout: Option<time::Duration>,
+}
+
+impl BPop {
+    pub fn parse(mut input: VecDeque<Vec<u8>>, side: Side) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 2, "wrong number of arguments for bpop");
+        let timeout = parse_timeout(&input.pop_back().unwrap())?;
+        Ok(Box::new(BPop {
+            keys: input.into(),
+            side,
+            timeout,
+        }))
+    }
+}
+
+impl Execution for BPop {
+    // pop from the first non empty list of the keys
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        for key in self.keys.iter() {
+            match data.get_typed::<VecDeque<Vec<u8>>>(key) {
+                Ok(Some(_)) => return self.serve(data, key),
+                Ok(None) => {}
+                Err(e) => return e.into(),
+            }
+        }
+        self.timeout_reply()
+    }
+}
+
+impl Blocking for BPop {
+    fn keys(&self) -> &[Vec<u8>] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<time::Duration> {
+        self.timeout
+    }
+
+    fn timeout_reply(&self) -> Value {
+        Value::NullArray
+    }
+
+    // the key with the popped element, logged as LPOP or RPOP like redis
+    fn serve(&self, data: &mut DataStorage, key: &[u8]) -> Value {
+        let Ok(Some(list)) = data.get_typed_mut::<VecDeque<Vec<u8>>>(key) else {
+            return self.timeout_reply();
+        };
+        let element = match self.side {
+            Side::Left => list.pop_front(),
+            Side::Right => list.pop_back(),
+        };
+        let Some(element) = element else {
+            return self.timeout_reply();
+        };
+        data.remove_if_empty(key);
+        let command = match self.side {
+            Side::Left => b"LPOP".to_vec(),
+            Side::Right => b"RPOP".to_vec(),
+        };
+        data.rewrite_command(vec![command, key.to_owned()]);
+        Value::Array(vec![
+            Value::BufBulk(key.to_owned()),
+            Value::BufBulk(element),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::BPop;
+    use crate::redis_protocol::list_helper::Side;
+    use std::collections::VecDeque;
+    use std::time;
+
+    fn input(input: &[&str]) -> VecDeque<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_success() {
+        // arrange
+        let expected = BPop {
+            keys: vec![b"a".to_vec(), b"b".to_vec()],
+            side: Side::Left,
+            timeout: Some(time::Duration::from_millis(100)),
+        };
+        // act
+        let result = BPop::parse(input(&["a", "b", "0.1"]), Side::Left);
+        // assert
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert!(BPop::parse(input(&["0"]), Side::Left).is_err());
+        assert!(BPop::parse(input(&["k", "-1"]), Side::Right).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::BPop;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use crate::redis_protocol::list_helper::Side;
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn bpop(keys: &[&str], side: Side) -> BPop {
+        BPop {
+            keys: keys.iter().map(|x| x.as_bytes().to_vec()).collect(),
+            side,
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn test_exec_first_non_empty_list() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"b")
+            .unwrap()
+            .extend([b"1".to_vec(), b"2".to_vec()]);
+        // act
+        let result = bpop(&["a", "b"], Side::Right).exec(&mut data);
+        // assert
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(b"b".to_vec()),
+                Value::BufBulk(b"2".to_vec())
+            ]),
+            result
+        );
+        assert_eq!(
+            Some(b"1".to_vec()),
+            data.get_typed::<VecDeque<Vec<u8>>>(b"b")
+                .unwrap()
+                .and_then(|x| x.front().cloned())
+        );
+    }
+
+    #[test]
+    fn test_exec_empty() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = bpop(&["a"], Side::Left).exec(&mut data);
+        // assert
+        assert_eq!(Value::NullArray, result);
+    }
+
+    #[test]
+    fn test_exec_wrong_type() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"a".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let result = bpop(&["a"], Side::Left).exec(&mut data);
+        // assert
+        assert!(matches!(result, Value::Error(e) if e.starts_with("WRONGTYPE")));
+    }
+}
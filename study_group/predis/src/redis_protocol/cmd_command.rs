@@ -18,19 +18,22 @@ impl Command {
     pub const DOCS_DEL: &'static str = "*2\r\n$3\r\ndel\r\n*10\r\n$7\r\nsummary\r\n$25\r\nDeletes one or more keys.\r\n$5\r\nsince\r\n$5\r\n1.0.0\r\n$5\r\ngroup\r\n$7\r\ngeneric\r\n$10\r\ncomplexity\r\n$288\r\nO(N) where N is the number of keys that will be removed. When a key to remove holds a value other than a string, the individual complexity for this key is O(M) where M is the number of elements in the list, set, sorted set or hash. Removing a single key that holds a string value is O(1).\r\n$9\r\narguments\r\n*1\r\n*10\r\n$4\r\nname\r\n$3\r\nkey\r\n$4\r\ntype\r\n$3\r\nkey\r\n$12\r\ndisplay_text\r\n$3\r\nkey\r\n$14\r\nkey_spec_index\r\n:0\r\n$5\r\nflags\r\n*1\r\n+multiple\r\n";
     pub const DOCS: [&'static str; 3] = [Command::DOCS_SET, Command::DOCS_GET, Command::DOCS_DEL];
 
-    pub fn parse(mut input: VecDeque<String>) -> Result<Box<Self>> {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
         let mut docs = Vec::new();
         while let Some(token) = input.pop_front() {
-            if "docs" == token.to_lowercase().as_str() {
+            if token.eq_ignore_ascii_case(b"docs") {
                 if input.is_empty() {
                     docs.append(&mut Self::DOCS.to_vec());
                 }
                 while let Some(token) = input.pop_front() {
-                    match token.to_lowercase().as_str() {
-                        "set" => docs.push(Self::DOCS_SET),
-                        "get" => docs.push(Self::DOCS_GET),
-                        "del" => docs.push(Self::DOCS_DEL),
-                        _ => anyhow::bail!("{token} command not supported",),
+                    match token.to_ascii_lowercase().as_slice() {
+                        b"set" => docs.push(Self::DOCS_SET),
+                        b"get" => docs.push(Self::DOCS_GET),
+                        b"del" => docs.push(Self::DOCS_DEL),
+                        _ => anyhow::bail!(
+                            "{} command not supported",
+                            String::from_utf8_lossy(&token)
+                        ),
                     }
                 }
             }
@@ -63,7 +66,9 @@ mod tests {
     #[test]
     fn exec() {
         // arrange
-        let cmd = Command::default();
+        let cmd = Command {
+            docs: Command::DOCS.to_vec(),
+        };
         // act
         let result = cmd.exec(&mut DataStorage::new());
         // assert
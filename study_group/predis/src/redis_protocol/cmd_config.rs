@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+
+use crate::configuration::live::LiveConfig;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/config-get/ https://redis.io/commands/config-set/
+// https://redis.io/commands/config-rewrite/
+// CONFIG GET parameter [parameter ...] | CONFIG SET parameter value [parameter value ...] |
+// CONFIG REWRITE, handled by the connection
+#[derive(PartialEq, Debug)]
+pub enum Config {
+    Get(Vec<Vec<u8>>),
+    Set(Vec<(String, String)>),
+    Rewrite,
+}
+
+impl Config {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Self> {
+        let subcommand = input
+            .pop_front()
+            .ok_or_else(|| anyhow::anyhow!("ERR wrong number of arguments for 'config' command"))?;
+        let wrong_number = || {
+            anyhow::anyhow!(
+                "ERR wrong number of arguments for 'config|{}' command",
+                String::from_utf8_lossy(&subcommand).to_lowercase()
+            )
+        };
+        match subcommand.to_ascii_lowercase().as_slice() {
+            b"get" if !input.is_empty() => Ok(Config::Get(input.into())),
+            b"set" if !input.is_empty() && input.len().is_multiple_of(2) => {
+                let input: Vec<String> = input
+                    .iter()
+                    .map(|x| String::from_utf8_lossy(x).to_string())
+                    .collect();
+                Ok(Config::Set(
+                    input
+                        .chunks(2)
+                        .map(|x| (x[0].clone(), x[1].clone()))
+                        .collect(),
+                ))
+            }
+            b"rewrite" if input.is_empty() => Ok(Config::Rewrite),
+            b"get" | b"set" | b"rewrite" => Err(wrong_number()),
+            _ => anyhow::bail!(
+                "ERR unknown subcommand '{}'. Try CONFIG HELP.",
+                String::from_utf8_lossy(&subcommand)
+            ),
+        }
+    }
+
+    // the parameters of CONFIG GET are a flat array of names and values, a map in RESP3
+    pub fn apply(&self, live: &LiveConfig) -> Result<Value> {
+        match self {
+            Config::Get(patterns) => Ok(Value::Array(
+                live.get(patterns)
+                    .into_iter()
+                    .flat_map(|(name, value)| {
+                        [
+                            Value::BufBulk(name.as_bytes().to_vec()),
+                            Value::BufBulk(value.into_bytes()),
+                        ]
+                    })
+                    .collect(),
+            )),
+            Config::Set(params) => {
+                live.set(params)?;
+                Ok(Value::String("OK".to_string()))
+            }
+            Config::Rewrite => {
+                live.rewrite()?;
+                Ok(Value::String("OK".to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::Config;
+    use std::collections::VecDeque;
+
+    fn input(input: &[&str]) -> VecDeque<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_success() {
+        assert_eq!(
+            Config::Get(vec![b"port".to_vec(), b"save".to_vec()]),
+            Config::parse(input(&["GET", "port", "save"])).unwrap()
+        );
+        assert_eq!(
+            Config::Set(vec![
+                ("save".to_string(), "".to_string()),
+                ("appendfsync".to_string(), "no".to_string())
+            ]),
+            Config::parse(input(&["set", "save", "", "appendfsync", "no"])).unwrap()
+        );
+        assert_eq!(Config::Rewrite, Config::parse(input(&["rewrite"])).unwrap());
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert_eq!(
+            "ERR wrong number of arguments for 'config|set' command",
+            Config::parse(input(&["SET", "save"]))
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(Config::parse(input(&["get"])).is_err());
+        assert!(Config::parse(input(&["rewrite", "x"])).is_err());
+        assert_eq!(
+            "ERR unknown subcommand 'x'. Try CONFIG HELP.",
+            Config::parse(input(&["x"])).unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::shard::LentShards;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/dbsize/
+#[derive(Default, PartialEq, Debug)]
+pub struct DbSize;
+
+impl DbSize {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.is_empty(), "wrong number of arguments for dbsize");
+        Ok(Box::new(DbSize))
+    }
+}
+
+impl Execution for DbSize {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        // like redis, the expired keys which are not removed yet are counted
+        Value::Integer(data.len() as i64)
+    }
+
+    fn exec_shards(&self, shards: &mut LentShards, _args: &[Vec<u8>]) -> Value {
+        Value::Integer(shards.storages().iter().map(|x| x.len()).sum::<usize>() as i64)
+    }
+}
@@ -8,11 +8,11 @@ use resp::Value;
 
 #[derive(Default, PartialEq, Debug)]
 pub struct Del {
-    key: VecDeque<String>,
+    key: VecDeque<Vec<u8>>,
 }
 
 impl Del {
-    pub fn parse(input: VecDeque<String>) -> Result<Box<Self>> {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
         anyhow::ensure!(!input.is_empty(), "at least one argument for del");
         Ok(Box::new(Del { key: input }))
     }
@@ -23,7 +23,9 @@ impl Execution for Del {
         let mut result = 0;
         let mut keys = self.key.clone();
         while let Some(key) = keys.pop_front() {
-            if data.remove(&key).is_some() {
+            // the expired key is removed by exists
+            if data.exists(&key) {
+                data.remove(&key);
                 result += 1;
             }
         }
@@ -0,0 +1,182 @@
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::shard::{LentShards, Route};
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::key_spec;
+
+use resp::Value;
+
+// the command queued after MULTI
+pub struct QueuedCommand {
+    pub data: Box<dyn Execution + Send>,
+    pub args: Vec<Vec<u8>>,
+}
+
+// https://redis.io/commands/exec/
+// the queued commands run in one message of the data watcher, no other command runs between them
+pub struct Exec {
+    commands: Vec<QueuedCommand>,
+    // the keys with the versions returned by WATCH
+    watched: Vec<(Vec<u8>, u64)>,
+}
+
+impl Exec {
+    pub fn new(commands: Vec<QueuedCommand>, watched: Vec<(Vec<u8>, u64)>) -> Box<Self> {
+        Box::new(Exec { commands, watched })
+    }
+}
+
+impl Exec {
+    // the aborted transaction replies before any queued command runs
+    fn check(&self, data: &mut DataStorage) -> Option<Value> {
+        // the transaction is aborted when a watched key changed after WATCH
+        let changed = self
+            .watched
+            .iter()
+            .any(|(key, version)| data.is_watched_key_changed(key, *version));
+        // EXEC unwatches all keys like redis
+        self.watched.iter().for_each(|(key, _)| data.unwatch(key));
+        if changed {
+            return Some(Value::NullArray);
+        }
+        // like redis the transaction fails when one of its commands may add data over maxmemory
+        self.commands
+            .iter()
+            .find_map(|x| data.check_memory(&x.args).err())
+            .map(|e| e.into())
+    }
+
+    fn route(command: &QueuedCommand) -> Route {
+        let name = String::from_utf8_lossy(&command.args[0]).to_lowercase();
+        key_spec::route(&name, &command.args)
+    }
+}
+
+impl Execution for Exec {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        if let Some(reply) = self.check(data) {
+            return reply;
+        }
+        Value::Array(
+            data.exec_multi(
+                self.commands
+                    .iter()
+                    .map(|x| (x.data.as_ref(), x.args.as_slice())),
+            ),
+        )
+    }
+
+    // a queued command is on the whole keyspace, it runs on the lent shards in place, only the
+    // watched keys and the keys of the other queued commands are moved to the first shard
+    fn exec_shards(&self, shards: &mut LentShards, args: &[Vec<u8>]) -> Value {
+        let mut keys: Vec<Vec<u8>> = self.watched.iter().map(|(key, _)| key.to_owned()).collect();
+        for command in self.commands.iter() {
+            if let Route::Keys(x) = Self::route(command) {
+                keys.extend(x);
+            }
+        }
+        shards.merged(&keys, |shards| {
+            let data = shards.first_mut();
+            let dirty = data.dirty();
+            let reply = match self.check(data) {
+                Some(reply) => reply,
+                None => Value::Array(
+                    shards.exec_multi(
+                        self.commands
+                            .iter()
+                            .map(|x| (x.data.as_ref(), x.args.as_slice(), Self::route(x))),
+                    ),
+                ),
+            };
+            shards.first_mut().propagate(args, dirty);
+            reply
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::{Exec, QueuedCommand};
+    use crate::data_watcher::shard::{shard_index, LentShards};
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use crate::redis_protocol::{cmd_dbsize::DbSize, cmd_get::Get, cmd_incrby::IncrBy};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn queued(input: &[&str]) -> QueuedCommand {
+        let args: Vec<Vec<u8>> = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        let cmd: VecDeque<Vec<u8>> = args[1..].iter().cloned().collect();
+        let data: Box<dyn Execution + Send> = match input[0] {
+            "incr" => IncrBy::parse(cmd, false, false).unwrap(),
+            "dbsize" => DbSize::parse(cmd).unwrap(),
+            _ => Get::parse(cmd).unwrap(),
+        };
+        QueuedCommand { data, args }
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        let version = data.watch(b"k");
+        let exec = Exec::new(
+            vec![queued(&["incr", "k"]), queued(&["get", "k"])],
+            vec![(b"k".to_vec(), version)],
+        );
+        // act
+        let result = exec.exec(&mut data);
+        // assert
+        assert_eq!(
+            Value::Array(vec![Value::Integer(1), Value::BufBulk(b"1".to_vec())]),
+            result
+        );
+    }
+
+    #[test]
+    fn test_exec_watched_key_changed() {
+        // arrange
+        let mut data = DataStorage::new();
+        let version = data.watch(b"k");
+        data.insert(b"k".to_vec(), DataTTL::new(b"5".to_vec()));
+        let exec = Exec::new(vec![queued(&["incr", "k"])], vec![(b"k".to_vec(), version)]);
+        // act
+        let result = exec.exec(&mut data);
+        // assert
+        assert_eq!(Value::NullArray, result);
+        assert_eq!(
+            Some(b"5".to_vec()),
+            data.get(b"k".as_slice()).unwrap().get()
+        );
+    }
+
+    #[test]
+    fn test_exec_shards_whole_keyspace() {
+        // arrange
+        let (k0, k1) = (b"{a}".to_vec(), b"{b}".to_vec());
+        assert_eq!((0, 1), (shard_index(&k0, 2), shard_index(&k1, 2)));
+        let mut storages = vec![DataStorage::new(), DataStorage::new()];
+        storages[1].insert(k1.clone(), DataTTL::new(b"5".to_vec()));
+        let mut shards = LentShards::new(storages);
+        let exec = Exec::new(
+            vec![
+                queued(&["incr", "{a}"]),
+                queued(&["dbsize"]),
+                queued(&["incr", "{b}"]),
+            ],
+            Vec::new(),
+        );
+        // act
+        let result = exec.exec_shards(&mut shards, &[b"EXEC".to_vec()]);
+        // assert
+        assert_eq!(
+            Value::Array(vec![
+                Value::Integer(1),
+                Value::Integer(2),
+                Value::Integer(6)
+            ]),
+            result
+        );
+        let storages = shards.storages();
+        assert!(storages[0].contains_key(&k0) && !storages[0].contains_key(&k1));
+        assert!(storages[1].contains_key(&k1) && !storages[1].contains_key(&k0));
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/exists/
+#[derive(Default, PartialEq, Debug)]
+pub struct Exists {
+    keys: Vec<Vec<u8>>,
+}
+
+impl Exists {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(!input.is_empty(), "wrong number of arguments for exists");
+        Ok(Box::new(Exists { keys: input.into() }))
+    }
+}
+
+impl Execution for Exists {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        // the same key mentioned multiple times is counted multiple times
+        let count = self.keys.iter().filter(|x| data.exists(x)).count();
+        Value::Integer(count as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Exists;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"a".to_vec(), DataTTL::new(b"v".to_vec()));
+        let exists = Exists {
+            keys: vec![b"a".to_vec(), b"b".to_vec(), b"a".to_vec()],
+        };
+        // act
+        let result = exists.exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(2), result);
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::VecDeque;
+use std::time;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::{epoch_now, DataStorage};
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/expire/
+// https://redis.io/commands/pexpire/
+// https://redis.io/commands/expireat/
+// https://redis.io/commands/pexpireat/
+#[derive(Default, PartialEq, Debug)]
+pub struct Expire {
+    key: Vec<u8>,
+    // the ttl or the unix time in milliseconds
+    time_ms: i64,
+    absolute: bool,
+    // NX -- Set expiry only when the key has no expiry
+    nx: bool,
+    // XX -- Set expiry only when the key has an existing expiry
+    xx: bool,
+    // GT -- Set expiry only when the new expiry is greater than current one, no expiry is an infinite ttl
+    gt: bool,
+    // LT -- Set expiry only when the new expiry is less than current one
+    lt: bool,
+}
+
+impl Expire {
+    pub fn parse(input: VecDeque<Vec<u8>>, millis: bool, absolute: bool) -> Result<Box<Self>> {
+        let command = match (millis, absolute) {
+            (false, false) => "expire",
+            (true, false) => "pexpire",
+            (false, true) => "expireat",
+            (true, true) => "pexpireat",
+        };
+        anyhow::ensure!(input.len() >= 2, "wrong number of arguments for {command}");
+        let time = parse_integer::<i64>(&input[1])?;
+        let time_ms = if millis {
+            Some(time)
+        } else {
+            time.checked_mul(1000)
+        };
+        let Some(time_ms) = time_ms else {
+            anyhow::bail!("invalid expire time in '{command}' command");
+        };
+        let mut expire_obj = Expire {
+            key: input[0].to_owned(),
+            time_ms,
+            absolute,
+            ..Default::default()
+        };
+        for token in input.iter().skip(2) {
+            match token.to_ascii_lowercase().as_slice() {
+                b"nx" => expire_obj.nx = true,
+                b"xx" => expire_obj.xx = true,
+                b"gt" => expire_obj.gt = true,
+                b"lt" => expire_obj.lt = true,
+                _ => anyhow::bail!("Unsupported option {}", String::from_utf8_lossy(token)),
+            }
+        }
+        anyhow::ensure!(
+            !(expire_obj.nx && (expire_obj.xx || expire_obj.gt || expire_obj.lt)),
+            "NX and XX, GT or LT options at the same time are not compatible"
+        );
+        anyhow::ensure!(
+            !(expire_obj.gt && expire_obj.lt),
+            "GT and LT options at the same time are not compatible"
+        );
+        Ok(Box::new(expire_obj))
+    }
+
+    // the unix time in milliseconds, None when it overflows
+    fn expired_ms(&self, now_ms: i64) -> Option<i64> {
+        if self.absolute {
+            Some(self.time_ms)
+        } else {
+            self.time_ms.checked_add(now_ms)
+        }
+    }
+
+    fn is_allowed(&self, current: Option<i64>, expired: i64) -> bool {
+        match current {
+            Some(_) if self.nx => false,
+            Some(current) if self.gt => expired > current,
+            Some(current) if self.lt => expired < current,
+            Some(_) => true,
+            None => !self.xx && !self.gt,
+        }
+    }
+}
+
+impl Execution for Expire {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let Some(data_ttl) = data.get_live(&self.key) else {
+            return Value::Integer(0);
+        };
+        let current = data_ttl.expired_epoch().map(|x| x.as_millis() as i64);
+        let now_ms = epoch_now().as_millis() as i64;
+        let Some(expired) = self.expired_ms(now_ms) else {
+            return Value::Error("ERR invalid expire time in 'expire' command".to_string());
+        };
+        if !self.is_allowed(current, expired) {
+            return Value::Integer(0);
+        }
+        // the expired time in the past deletes the key
+        // the relative time is logged as an absolute time
+        if expired <= now_ms {
+            data.remove(&self.key);
+            data.rewrite_command(vec![b"DEL".to_vec(), self.key.to_owned()]);
+        } else {
+            data.set_expired_epoch(&self.key, Some(time::Duration::from_millis(expired as u64)));
+            data.rewrite_command(vec![
+                b"PEXPIREAT".to_vec(),
+                self.key.to_owned(),
+                expired.to_string().into_bytes(),
+            ]);
+        }
+        Value::Integer(1)
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::Expire;
+    use std::collections::VecDeque;
+
+    fn input(args: &[&str]) -> VecDeque<Vec<u8>> {
+        args.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_success() {
+        // arrange
+        let expected = Expire {
+            key: b"k".to_vec(),
+            time_ms: 10000,
+            absolute: false,
+            xx: true,
+            gt: true,
+            ..Default::default()
+        };
+        // act
+        let result = Expire::parse(input(&["k", "10", "XX", "gt"]), false, false);
+        // assert
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert!(Expire::parse(input(&["k", "a"]), false, false).is_err());
+        assert!(Expire::parse(input(&["k", "9223372036854775807"]), false, false).is_err());
+        assert!(Expire::parse(input(&["k", "10", "nx", "xx"]), false, false).is_err());
+        assert!(Expire::parse(input(&["k", "10", "gt", "lt"]), true, false).is_err());
+        assert!(Expire::parse(input(&["k", "10", "ab"]), true, true).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Expire;
+    use crate::data_watcher::{epoch_now, execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::collections::VecDeque;
+    use std::time;
+
+    fn expire(args: &[&str], millis: bool, absolute: bool) -> Box<Expire> {
+        let input: VecDeque<Vec<u8>> = args.iter().map(|x| x.as_bytes().to_vec()).collect();
+        Expire::parse(input, millis, absolute).unwrap()
+    }
+
+    fn expired_epoch(data: &mut DataStorage) -> Option<time::Duration> {
+        data.get_live(b"k").and_then(|x| x.expired_epoch())
+    }
+
+    #[test]
+    fn test_exec_conditions() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act & assert
+        assert_eq!(
+            Value::Integer(0),
+            expire(&["k", "100", "xx"], false, false).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(0),
+            expire(&["k", "100", "gt"], false, false).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(1),
+            expire(&["k", "100", "lt"], false, false).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(0),
+            expire(&["k", "100", "nx"], false, false).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(0),
+            expire(&["k", "50", "gt"], false, false).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(1),
+            expire(&["k", "200000", "gt", "xx"], true, false).exec(&mut data)
+        );
+        let remaining = expired_epoch(&mut data).unwrap() - epoch_now();
+        assert!(remaining > time::Duration::from_secs(190));
+        assert_eq!(
+            Value::Integer(0),
+            expire(&["other", "100"], false, false).exec(&mut data)
+        );
+    }
+
+    #[test]
+    fn test_exec_expire_at() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        let at = (epoch_now().as_secs() + 100).to_string();
+        // act
+        let result = expire(&["k", &at], false, true).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(1), result);
+        assert_eq!(
+            Some(time::Duration::from_secs(epoch_now().as_secs() + 100)),
+            expired_epoch(&mut data)
+        );
+    }
+
+    #[test]
+    fn test_exec_past_time_delete_key() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let result = expire(&["k", "-1"], false, false).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(1), result);
+        assert!(!data.exists(b"k"));
+    }
+}
@@ -8,11 +8,11 @@ use resp::Value;
 
 #[derive(Default, PartialEq, Debug)]
 pub struct Get {
-    key: String,
+    key: Vec<u8>,
 }
 
 impl Get {
-    pub fn parse(input: VecDeque<String>) -> Result<Box<Self>> {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
         anyhow::ensure!(input.len() == 1, "too many argument for get");
         Ok(Box::new(Get {
             key: input[0].to_owned(),
@@ -22,17 +22,10 @@ impl Get {
 
 impl Execution for Get {
     fn exec(&self, data: &mut DataStorage) -> Value {
-        match data.get(&self.key) {
-            Some(data_ttl) => {
-                if let Some(v) = data_ttl.get() {
-                    Value::Bulk(v)
-                } else {
-                    // data expired
-                    data.remove(&self.key);
-                    Value::Null
-                }
-            }
-            None => Value::Null,
+        match data.get_typed::<Vec<u8>>(&self.key) {
+            Ok(Some(v)) => Value::BufBulk(v.to_owned()),
+            Ok(None) => Value::Null,
+            Err(e) => e.into(),
         }
     }
 }
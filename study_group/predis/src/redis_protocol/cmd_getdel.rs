@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/getdel/
+#[derive(Default, PartialEq, Debug)]
+pub struct GetDel {
+    key: Vec<u8>,
+}
+
+impl GetDel {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for getdel");
+        Ok(Box::new(GetDel {
+            key: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for GetDel {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let value = match data.get_typed::<Vec<u8>>(&self.key) {
+            Ok(Some(v)) => Value::BufBulk(v.to_owned()),
+            Ok(None) => return Value::Null,
+            Err(e) => return e.into(),
+        };
+        data.remove(&self.key);
+        value
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::GetDel;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        let getdel = GetDel { key: b"k".to_vec() };
+        // act
+        let result = getdel.exec(&mut data);
+        let result2 = getdel.exec(&mut data);
+        // assert
+        assert_eq!(Value::BufBulk(b"v".to_vec()), result);
+        assert_eq!(Value::Null, result2);
+        assert!(!data.exists(b"k"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::ttl_helper::TTLState;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/getex/
+#[derive(Default, PartialEq, Debug)]
+pub struct GetEx {
+    key: Vec<u8>,
+    ttl_state: Option<TTLState>,
+}
+
+impl GetEx {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(!input.is_empty(), "wrong number of arguments for getex");
+        let mut getex_obj = GetEx {
+            key: input.pop_front().unwrap(),
+            ..Default::default()
+        };
+        // EX, PX, EXAT, PXAT and PERSIST
+        while let Some(token) = input.pop_front() {
+            match TTLState::parse(&token, &mut input, "getex")? {
+                Some(TTLState::KeepTTL) | None => {
+                    anyhow::bail!("{} unknown option", String::from_utf8_lossy(&token))
+                }
+                Some(ttl_state) => {
+                    anyhow::ensure!(getex_obj.ttl_state.is_none(), "ttl already be set");
+                    getex_obj.ttl_state = Some(ttl_state);
+                }
+            }
+        }
+        Ok(Box::new(getex_obj))
+    }
+}
+
+impl Execution for GetEx {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let value = match data.get_typed::<Vec<u8>>(&self.key) {
+            Ok(Some(v)) => Value::BufBulk(v.to_owned()),
+            Ok(None) => return Value::Null,
+            Err(e) => return e.into(),
+        };
+        if let Some(ttl_state) = &self.ttl_state {
+            let expired = ttl_state.expired_epoch(None);
+            data.set_expired_epoch(&self.key, expired);
+            data.rewrite_command(match expired {
+                Some(expired) => vec![
+                    b"PEXPIREAT".to_vec(),
+                    self.key.to_owned(),
+                    expired.as_millis().to_string().into_bytes(),
+                ],
+                None => vec![b"PERSIST".to_vec(), self.key.to_owned()],
+            });
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::GetEx;
+    use crate::redis_protocol::ttl_helper::TTLState;
+    use std::collections::VecDeque;
+    use std::time;
+
+    fn input(args: &[&str]) -> VecDeque<Vec<u8>> {
+        args.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_success() {
+        // arrange
+        let expected = GetEx {
+            key: b"k".to_vec(),
+            ttl_state: Some(TTLState::Ttl(time::Duration::from_millis(5))),
+        };
+        // act
+        let result = GetEx::parse(input(&["k", "px", "5"]));
+        // assert
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert!(GetEx::parse(input(&["k", "keepttl"])).is_err());
+        assert!(GetEx::parse(input(&["k", "ex", "1", "persist"])).is_err());
+        assert!(GetEx::parse(input(&["k", "ex"])).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::GetEx;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use crate::redis_protocol::ttl_helper::TTLState;
+    use resp::Value;
+    use std::time;
+
+    #[test]
+    fn test_exec_set_and_persist() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        let getex = |ttl_state| GetEx {
+            key: b"k".to_vec(),
+            ttl_state: Some(ttl_state),
+        };
+        // act
+        let result = getex(TTLState::ExpiredTimestamp(time::Duration::from_secs(
+            4102444800,
+        )))
+        .exec(&mut data);
+        // assert
+        assert_eq!(Value::BufBulk(b"v".to_vec()), result);
+        assert_eq!(
+            Some(time::Duration::from_secs(4102444800)),
+            data.get_live(b"k").unwrap().expired_epoch()
+        );
+        getex(TTLState::Persist).exec(&mut data);
+        assert_eq!(None, data.get_live(b"k").unwrap().expired_epoch());
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/getrange/
+#[derive(Default, PartialEq, Debug)]
+pub struct GetRange {
+    key: Vec<u8>,
+    start: i64,
+    end: i64,
+}
+
+impl GetRange {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for getrange");
+        Ok(Box::new(GetRange {
+            key: input[0].to_owned(),
+            start: parse_integer(&input[1])?,
+            end: parse_integer(&input[2])?,
+        }))
+    }
+
+    // the byte range of getrange, negative offsets count from the end and are clamped to the start
+    fn range(&self, len: usize) -> Option<std::ops::RangeInclusive<usize>> {
+        if (self.start < 0 && self.end < 0 && self.start > self.end) || len == 0 {
+            return None;
+        }
+        let len = len as i64;
+        let clamp = |x: i64| if x < 0 { (len + x).max(0) } else { x };
+        let start = clamp(self.start);
+        let end = clamp(self.end).min(len - 1);
+        if start > end {
+            return None;
+        }
+        Some(start as usize..=end as usize)
+    }
+}
+
+impl Execution for GetRange {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let value = match data.get_typed::<Vec<u8>>(&self.key) {
+            Ok(Some(value)) => value,
+            Ok(None) => return Value::BufBulk(Vec::new()),
+            Err(e) => return e.into(),
+        };
+        match self.range(value.len()) {
+            Some(range) => Value::BufBulk(value[range].to_vec()),
+            None => Value::BufBulk(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::GetRange;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"This is a string".to_vec()));
+        let mut getrange = |start: i64, end: i64| {
+            GetRange {
+                key: b"k".to_vec(),
+                start,
+                end,
+            }
+            .exec(&mut data)
+        };
+        // act & assert
+        assert_eq!(Value::BufBulk(b"This".to_vec()), getrange(0, 3));
+        assert_eq!(Value::BufBulk(b"ing".to_vec()), getrange(-3, -1));
+        assert_eq!(
+            Value::BufBulk(b"This is a string".to_vec()),
+            getrange(0, -1)
+        );
+        assert_eq!(Value::BufBulk(b"string".to_vec()), getrange(10, 100));
+        assert_eq!(Value::BufBulk(b"T".to_vec()), getrange(-100, -16));
+        assert_eq!(Value::BufBulk(Vec::new()), getrange(-1, -5));
+        assert_eq!(Value::BufBulk(Vec::new()), getrange(5, 3));
+    }
+}
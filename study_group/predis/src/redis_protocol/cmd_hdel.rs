@@ -0,0 +1,67 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/hdel/
+#[derive(Default, PartialEq, Debug)]
+pub struct HDel {
+    key: Vec<u8>,
+    fields: Vec<Vec<u8>>,
+}
+
+impl HDel {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 2, "wrong number of arguments for hdel");
+        Ok(Box::new(HDel {
+            key: input.pop_front().unwrap(),
+            fields: input.into(),
+        }))
+    }
+}
+
+impl Execution for HDel {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let hash = match data.get_typed_mut::<HashMap<Vec<u8>, Vec<u8>>>(&self.key) {
+            Ok(Some(hash)) => hash,
+            Ok(None) => return Value::Integer(0),
+            Err(e) => return e.into(),
+        };
+        let removed = self
+            .fields
+            .iter()
+            .filter(|field| hash.remove(*field).is_some())
+            .count();
+        data.remove_if_empty(&self.key);
+        Value::Integer(removed as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::HDel;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_exec_remove_empty_hash() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<HashMap<Vec<u8>, Vec<u8>>>(b"k")
+            .unwrap()
+            .insert(b"f1".to_vec(), b"v1".to_vec());
+        let hdel = HDel {
+            key: b"k".to_vec(),
+            fields: vec![b"f1".to_vec(), b"f2".to_vec()],
+        };
+        // act
+        let result = hdel.exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(1), result);
+        assert!(!data.exists(b"k"));
+    }
+}
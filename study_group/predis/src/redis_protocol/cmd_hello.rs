@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+
+use crate::pubsub::ClientId;
+use crate::redis_protocol::parse_integer;
+use crate::redis_protocol::resp3::{Protocol, Reply};
+
+use anyhow::Result;
+
+// https://redis.io/commands/hello/
+// HELLO [protover [AUTH username password] [SETNAME clientname]], handled by the connection
+#[derive(Default, PartialEq, Debug)]
+pub struct Hello {
+    // None keeps the protocol of the connection
+    pub protocol: Option<Protocol>,
+    pub auth: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Hello {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Self> {
+        let mut hello = Hello::default();
+        let Some(version) = input.pop_front() else {
+            return Ok(hello);
+        };
+        hello.protocol = match parse_integer::<i64>(&version) {
+            Ok(2) => Some(Protocol::Resp2),
+            Ok(3) => Some(Protocol::Resp3),
+            Ok(_) => anyhow::bail!("NOPROTO unsupported protocol version"),
+            Err(_) => anyhow::bail!("ERR Protocol version is not an integer or out of range"),
+        };
+        while let Some(option) = input.pop_front() {
+            match option.to_ascii_lowercase().as_slice() {
+                b"auth" if input.len() >= 2 => {
+                    let username = input.pop_front().unwrap();
+                    hello.auth = Some((username, input.pop_front().unwrap()));
+                }
+                // predis has no CLIENT command reading the name, it is accepted for the clients sending it
+                b"setname" if !input.is_empty() => {
+                    input.pop_front();
+                }
+                _ => anyhow::bail!(
+                    "ERR Syntax error in HELLO option '{}'",
+                    String::from_utf8_lossy(&option)
+                ),
+            }
+        }
+        Ok(hello)
+    }
+
+    // the server properties, the map is a flat array in RESP2
+    pub fn reply(protocol: Protocol, client_id: ClientId, replica: bool) -> Reply {
+        let bulk = |x: &str| Reply::Bulk(x.as_bytes().to_vec());
+        let proto = match protocol {
+            Protocol::Resp2 => 2,
+            Protocol::Resp3 => 3,
+        };
+        Reply::Map(vec![
+            (bulk("server"), bulk("predis")),
+            (bulk("version"), bulk(env!("CARGO_PKG_VERSION"))),
+            (bulk("proto"), Reply::Integer(proto)),
+            (bulk("id"), Reply::Integer(client_id as i64)),
+            (bulk("mode"), bulk("standalone")),
+            (
+                bulk("role"),
+                bulk(if replica { "replica" } else { "master" }),
+            ),
+            (bulk("modules"), Reply::Array(Vec::new())),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::Hello;
+    use crate::redis_protocol::resp3::Protocol;
+    use std::collections::VecDeque;
+
+    fn input(input: &[&str]) -> VecDeque<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_success() {
+        assert_eq!(Hello::default(), Hello::parse(input(&[])).unwrap());
+        assert_eq!(
+            Hello {
+                protocol: Some(Protocol::Resp3),
+                auth: Some((b"default".to_vec(), b"secret".to_vec())),
+            },
+            Hello::parse(input(&["3", "auth", "default", "secret", "setname", "c"])).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert_eq!(
+            "NOPROTO unsupported protocol version",
+            Hello::parse(input(&["4"])).unwrap_err().to_string()
+        );
+        assert!(Hello::parse(input(&["x"])).is_err());
+        assert!(Hello::parse(input(&["3", "auth", "default"])).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_reply {
+    use super::Hello;
+    use crate::redis_protocol::resp3::{Protocol, Reply};
+
+    fn role(reply: Reply) -> Option<Reply> {
+        let Reply::Map(properties) = reply else {
+            return None;
+        };
+        properties
+            .into_iter()
+            .find(|(key, _)| *key == Reply::Bulk(b"role".to_vec()))
+            .map(|(_, value)| value)
+    }
+
+    #[test]
+    fn test_reply_role() {
+        assert_eq!(
+            Some(Reply::Bulk(b"master".to_vec())),
+            role(Hello::reply(Protocol::Resp3, 1, false))
+        );
+        assert_eq!(
+            Some(Reply::Bulk(b"replica".to_vec())),
+            role(Hello::reply(Protocol::Resp3, 1, true))
+        );
+    }
+}
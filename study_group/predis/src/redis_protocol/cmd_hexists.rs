@@ -0,0 +1,33 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/hexists/
+#[derive(Default, PartialEq, Debug)]
+pub struct HExists {
+    key: Vec<u8>,
+    field: Vec<u8>,
+}
+
+impl HExists {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 2, "wrong number of arguments for hexists");
+        Ok(Box::new(HExists {
+            key: input[0].to_owned(),
+            field: input[1].to_owned(),
+        }))
+    }
+}
+
+impl Execution for HExists {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<HashMap<Vec<u8>, Vec<u8>>>(&self.key) {
+            Ok(hash) => Value::Integer(hash.is_some_and(|x| x.contains_key(&self.field)) as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/hget/
+#[derive(Default, PartialEq, Debug)]
+pub struct HGet {
+    key: Vec<u8>,
+    field: Vec<u8>,
+}
+
+impl HGet {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 2, "wrong number of arguments for hget");
+        Ok(Box::new(HGet {
+            key: input[0].to_owned(),
+            field: input[1].to_owned(),
+        }))
+    }
+}
+
+impl Execution for HGet {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<HashMap<Vec<u8>, Vec<u8>>>(&self.key) {
+            Ok(hash) => match hash.and_then(|x| x.get(&self.field)) {
+                Some(v) => Value::BufBulk(v.to_owned()),
+                None => Value::Null,
+            },
+            Err(e) => e.into(),
+        }
+    }
+}
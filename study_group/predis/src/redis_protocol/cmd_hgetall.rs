@@ -0,0 +1,86 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+#[derive(PartialEq, Debug)]
+pub enum HashPart {
+    FieldValues,
+    Fields,
+    Values,
+}
+
+// https://redis.io/commands/hgetall/
+// https://redis.io/commands/hkeys/
+// https://redis.io/commands/hvals/
+#[derive(PartialEq, Debug)]
+pub struct HGetAll {
+    key: Vec<u8>,
+    part: HashPart,
+}
+
+impl HGetAll {
+    pub fn parse(input: VecDeque<Vec<u8>>, part: HashPart) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for hgetall");
+        Ok(Box::new(HGetAll {
+            key: input[0].to_owned(),
+            part,
+        }))
+    }
+}
+
+impl Execution for HGetAll {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let hash = match data.get_typed::<HashMap<Vec<u8>, Vec<u8>>>(&self.key) {
+            Ok(Some(hash)) => hash,
+            Ok(None) => return Value::Array(Vec::new()),
+            Err(e) => return e.into(),
+        };
+        let output = hash.iter().flat_map(|(field, value)| match self.part {
+            HashPart::FieldValues => vec![field, value],
+            HashPart::Fields => vec![field],
+            HashPart::Values => vec![value],
+        });
+        Value::Array(output.map(|x| Value::BufBulk(x.to_owned())).collect())
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::{HGetAll, HashPart};
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_exec() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<HashMap<Vec<u8>, Vec<u8>>>(b"k")
+            .unwrap()
+            .insert(b"f1".to_vec(), b"v1".to_vec());
+        let hgetall = |part| HGetAll {
+            key: b"k".to_vec(),
+            part,
+        };
+        // act & assert
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(b"f1".to_vec()),
+                Value::BufBulk(b"v1".to_vec())
+            ]),
+            hgetall(HashPart::FieldValues).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Array(vec![Value::BufBulk(b"f1".to_vec())]),
+            hgetall(HashPart::Fields).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Array(vec![Value::BufBulk(b"v1".to_vec())]),
+            hgetall(HashPart::Values).exec(&mut data)
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/hincrby/
+#[derive(Default, PartialEq, Debug)]
+pub struct HIncrBy {
+    key: Vec<u8>,
+    field: Vec<u8>,
+    increment: i64,
+}
+
+impl HIncrBy {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for hincrby");
+        Ok(Box::new(HIncrBy {
+            key: input[0].to_owned(),
+            field: input[1].to_owned(),
+            increment: parse_integer(&input[2])?,
+        }))
+    }
+}
+
+impl Execution for HIncrBy {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let hash = match data.get_typed_or_default::<HashMap<Vec<u8>, Vec<u8>>>(&self.key) {
+            Ok(hash) => hash,
+            Err(e) => return e.into(),
+        };
+        let current = match hash.get(&self.field) {
+            Some(v) => match parse_integer::<i64>(v) {
+                Ok(v) => v,
+                Err(_) => return Value::Error("ERR hash value is not an integer".to_string()),
+            },
+            None => 0,
+        };
+        let Some(result) = current.checked_add(self.increment) else {
+            return Value::Error("ERR increment or decrement would overflow".to_string());
+        };
+        hash.insert(self.field.to_owned(), result.to_string().into_bytes());
+        Value::Integer(result)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::HIncrBy;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::HashMap;
+
+    fn hincrby(field: &str, increment: i64) -> HIncrBy {
+        HIncrBy {
+            key: b"k".to_vec(),
+            field: field.as_bytes().to_vec(),
+            increment,
+        }
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = hincrby("f", 5).exec(&mut data);
+        let result2 = hincrby("f", -7).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(5), result);
+        assert_eq!(Value::Integer(-2), result2);
+    }
+
+    #[test]
+    fn test_exec_not_integer() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<HashMap<Vec<u8>, Vec<u8>>>(b"k")
+            .unwrap()
+            .insert(b"f".to_vec(), b"v".to_vec());
+        // act
+        let result = hincrby("f", 1).exec(&mut data);
+        // assert
+        assert!(result.is_error());
+    }
+
+    #[test]
+    fn test_exec_overflow() {
+        // arrange
+        let mut data = DataStorage::new();
+        hincrby("f", i64::MAX).exec(&mut data);
+        // act
+        let result = hincrby("f", 1).exec(&mut data);
+        // assert
+        assert!(result.is_error());
+    }
+}
@@ -0,0 +1,31 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/hlen/
+#[derive(Default, PartialEq, Debug)]
+pub struct HLen {
+    key: Vec<u8>,
+}
+
+impl HLen {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for hlen");
+        Ok(Box::new(HLen {
+            key: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for HLen {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<HashMap<Vec<u8>, Vec<u8>>>(&self.key) {
+            Ok(hash) => Value::Integer(hash.map_or(0, |x| x.len()) as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
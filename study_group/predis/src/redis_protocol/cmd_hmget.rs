@@ -0,0 +1,70 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/hmget/
+#[derive(Default, PartialEq, Debug)]
+pub struct HMGet {
+    key: Vec<u8>,
+    fields: Vec<Vec<u8>>,
+}
+
+impl HMGet {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 2, "wrong number of arguments for hmget");
+        Ok(Box::new(HMGet {
+            key: input.pop_front().unwrap(),
+            fields: input.into(),
+        }))
+    }
+}
+
+impl Execution for HMGet {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let hash = match data.get_typed::<HashMap<Vec<u8>, Vec<u8>>>(&self.key) {
+            Ok(hash) => hash,
+            Err(e) => return e.into(),
+        };
+        Value::Array(
+            self.fields
+                .iter()
+                .map(|field| match hash.and_then(|x| x.get(field)) {
+                    Some(v) => Value::BufBulk(v.to_owned()),
+                    None => Value::Null,
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::HMGet;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_exec() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<HashMap<Vec<u8>, Vec<u8>>>(b"k")
+            .unwrap()
+            .insert(b"f1".to_vec(), b"v1".to_vec());
+        let hmget = HMGet {
+            key: b"k".to_vec(),
+            fields: vec![b"f1".to_vec(), b"f2".to_vec()],
+        };
+        // act
+        let result = hmget.exec(&mut data);
+        // assert
+        assert_eq!(
+            Value::Array(vec![Value::BufBulk(b"v1".to_vec()), Value::Null]),
+            result
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::scan_helper::{self, ScanOption};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/hscan/
+#[derive(PartialEq, Debug)]
+pub struct HScan {
+    key: Vec<u8>,
+    option: ScanOption,
+}
+
+impl HScan {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 2, "wrong number of arguments for hscan");
+        let key = input.pop_front().unwrap();
+        let option = ScanOption::parse(input)?;
+        anyhow::ensure!(option.type_name.is_none(), "syntax error");
+        Ok(Box::new(HScan { key, option }))
+    }
+}
+
+impl Execution for HScan {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let hash = match data.get_typed::<HashMap<Vec<u8>, Vec<u8>>>(&self.key) {
+            Ok(Some(hash)) => hash,
+            Ok(None) => {
+                return Value::Array(vec![
+                    Value::BufBulk(b"0".to_vec()),
+                    Value::Array(Vec::new()),
+                ])
+            }
+            Err(e) => return e.into(),
+        };
+        let (cursor, fields) =
+            scan_helper::scan(hash.keys(), self.option.cursor, self.option.count);
+        let mut output = Vec::new();
+        for field in fields.into_iter().filter(|x| self.option.is_match(x)) {
+            output.push(Value::BufBulk(field.to_owned()));
+            if !self.option.no_values {
+                output.push(Value::BufBulk(hash[field].to_owned()));
+            }
+        }
+        Value::Array(vec![
+            Value::BufBulk(cursor.to_string().into_bytes()),
+            Value::Array(output),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::HScan;
+    use crate::{
+        data_watcher::{execution::Execution, DataStorage},
+        redis_protocol::scan_helper::ScanOption,
+    };
+    use resp::Value;
+    use std::collections::{HashMap, VecDeque};
+
+    #[test]
+    fn test_exec_until_cursor_zero() {
+        // arrange
+        let mut data = DataStorage::new();
+        let hash = data
+            .get_typed_or_default::<HashMap<Vec<u8>, Vec<u8>>>(b"k")
+            .unwrap();
+        for i in 0..20 {
+            hash.insert(format!("f{i}").into_bytes(), i.to_string().into_bytes());
+        }
+        hash.insert(b"other".to_vec(), b"v".to_vec());
+        let mut fields = Vec::new();
+        let mut cursor = b"0".to_vec();
+        // act
+        loop {
+            let input = VecDeque::from(vec![cursor, b"match".to_vec(), b"f*".to_vec()]);
+            let hscan = HScan {
+                key: b"k".to_vec(),
+                option: ScanOption::parse(input).unwrap(),
+            };
+            let Value::Array(mut result) = hscan.exec(&mut data) else {
+                unreachable!();
+            };
+            if let Value::Array(v) = result.pop().unwrap() {
+                fields.extend(v.chunks(2).map(|x| x[0].to_owned()));
+            }
+            let Value::BufBulk(next) = result.pop().unwrap() else {
+                unreachable!();
+            };
+            cursor = next;
+            if cursor == b"0" {
+                break;
+            }
+        }
+        // assert
+        assert_eq!(20, fields.len());
+    }
+}
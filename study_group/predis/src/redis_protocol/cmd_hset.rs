@@ -0,0 +1,160 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/hset/
+// https://redis.io/commands/hmset/
+#[derive(Default, PartialEq, Debug)]
+pub struct HSet {
+    key: Vec<u8>,
+    field_values: Vec<(Vec<u8>, Vec<u8>)>,
+    // HMSET replies OK instead of the number of new fields
+    reply_ok: bool,
+}
+
+impl HSet {
+    pub fn parse(mut input: VecDeque<Vec<u8>>, reply_ok: bool) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            input.len() >= 3 && input.len() % 2 == 1,
+            "wrong number of arguments for hset"
+        );
+        let key = input.pop_front().unwrap();
+        let mut field_values = Vec::new();
+        while let (Some(field), Some(value)) = (input.pop_front(), input.pop_front()) {
+            field_values.push((field, value));
+        }
+        Ok(Box::new(HSet {
+            key,
+            field_values,
+            reply_ok,
+        }))
+    }
+}
+
+impl Execution for HSet {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let hash = match data.get_typed_or_default::<HashMap<Vec<u8>, Vec<u8>>>(&self.key) {
+            Ok(hash) => hash,
+            Err(e) => return e.into(),
+        };
+        let mut created = 0;
+        for (field, value) in &self.field_values {
+            if hash.insert(field.to_owned(), value.to_owned()).is_none() {
+                created += 1;
+            }
+        }
+        if self.reply_ok {
+            Value::String("OK".to_string())
+        } else {
+            Value::Integer(created)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::HSet;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_parse_success() {
+        // arrange
+        let input = VecDeque::from(vec!["k", "f1", "v1", "f2", "v2"]);
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        let expected = HSet {
+            key: b"k".to_vec(),
+            field_values: vec![
+                (b"f1".to_vec(), b"v1".to_vec()),
+                (b"f2".to_vec(), b"v2".to_vec()),
+            ],
+            reply_ok: false,
+        };
+        // act
+        let result = HSet::parse(input, false);
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_field_without_value() {
+        // arrange
+        let input = VecDeque::from(vec!["k", "f1", "v1", "f2"]);
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        // act
+        let result = HSet::parse(input, false);
+        // assert
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::HSet;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::collections::HashMap;
+    use std::time::{self, UNIX_EPOCH};
+
+    fn hset(field_values: &[(&str, &str)]) -> HSet {
+        HSet {
+            key: b"k".to_vec(),
+            field_values: field_values
+                .iter()
+                .map(|(f, v)| (f.as_bytes().to_vec(), v.as_bytes().to_vec()))
+                .collect(),
+            reply_ok: false,
+        }
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = hset(&[("f1", "v1"), ("f2", "v2")]).exec(&mut data);
+        let result2 = hset(&[("f1", "v3"), ("f3", "v3")]).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(2), result);
+        assert_eq!(Value::Integer(1), result2);
+        let hash = data
+            .get_typed::<HashMap<Vec<u8>, Vec<u8>>>(b"k")
+            .unwrap()
+            .unwrap();
+        assert_eq!(3, hash.len());
+        assert_eq!(b"v3", hash[b"f1".as_slice()].as_slice());
+    }
+
+    #[test]
+    fn test_exec_keep_ttl() {
+        // arrange
+        let expired = time::SystemTime::now().duration_since(UNIX_EPOCH).unwrap()
+            + time::Duration::from_secs(10);
+        let mut data = DataStorage::new();
+        hset(&[("f1", "v1")]).exec(&mut data);
+        let data_ttl = data.get(b"k".as_slice()).unwrap().to_owned();
+        data.insert(b"k".to_vec(), data_ttl.expired_timestamp(&expired));
+        // act
+        hset(&[("f2", "v2")]).exec(&mut data);
+        // assert
+        assert_eq!(
+            Some(expired),
+            data.get(b"k".as_slice()).unwrap().expired_epoch()
+        );
+    }
+
+    #[test]
+    fn test_exec_wrong_type() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let result = hset(&[("f1", "v1")]).exec(&mut data);
+        // assert
+        assert!(result.is_error());
+    }
+}
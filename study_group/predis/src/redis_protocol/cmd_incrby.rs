@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/incr/
+// https://redis.io/commands/incrby/
+// https://redis.io/commands/decr/
+// https://redis.io/commands/decrby/
+#[derive(Default, PartialEq, Debug)]
+pub struct IncrBy {
+    key: Vec<u8>,
+    increment: i64,
+}
+
+impl IncrBy {
+    // INCR and DECR don't have the increment argument
+    pub fn parse(input: VecDeque<Vec<u8>>, by: bool, decrement: bool) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            input.len() == if by { 2 } else { 1 },
+            "wrong number of arguments for incr"
+        );
+        let increment = if by {
+            parse_integer::<i64>(&input[1])?
+        } else {
+            1
+        };
+        let increment = if decrement {
+            increment
+                .checked_neg()
+                .ok_or_else(|| anyhow::anyhow!("decrement would overflow"))?
+        } else {
+            increment
+        };
+        Ok(Box::new(IncrBy {
+            key: input[0].to_owned(),
+            increment,
+        }))
+    }
+}
+
+impl Execution for IncrBy {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let current = match data.get_typed::<Vec<u8>>(&self.key) {
+            Ok(Some(value)) => match parse_integer::<i64>(value) {
+                Ok(v) => v,
+                Err(e) => return Value::Error(e.to_string()),
+            },
+            Ok(None) => 0,
+            Err(e) => return e.into(),
+        };
+        let Some(result) = current.checked_add(self.increment) else {
+            return Value::Error("ERR increment or decrement would overflow".to_string());
+        };
+        // update the value in place, so the ttl of the key is kept
+        if let Ok(value) = data.get_typed_or_default::<Vec<u8>>(&self.key) {
+            *value = result.to_string().into_bytes();
+        }
+        Value::Integer(result)
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::IncrBy;
+    use std::collections::VecDeque;
+
+    fn input(args: &[&str]) -> VecDeque<Vec<u8>> {
+        args.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_success() {
+        // arrange
+        let expected = IncrBy {
+            key: b"k".to_vec(),
+            increment: -5,
+        };
+        // act
+        let result = IncrBy::parse(input(&["k", "5"]), true, true);
+        // assert
+        assert_eq!(Box::new(expected), result.unwrap());
+        assert_eq!(
+            1,
+            IncrBy::parse(input(&["k"]), false, false)
+                .unwrap()
+                .increment
+        );
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert!(IncrBy::parse(input(&["k", "1"]), false, false).is_err());
+        assert!(IncrBy::parse(input(&["k", "a"]), true, false).is_err());
+        assert!(IncrBy::parse(input(&["k", "-9223372036854775808"]), true, true).is_err());
+        assert_eq!(
+            "ERR value is not an integer or out of range",
+            IncrBy::parse(input(&["k", "+5"]), true, false)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::IncrBy;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::time;
+
+    fn incrby(increment: i64) -> IncrBy {
+        IncrBy {
+            key: b"k".to_vec(),
+            increment,
+        }
+    }
+
+    #[test]
+    fn test_exec_keep_ttl() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(
+            b"k".to_vec(),
+            DataTTL::new(b"10".to_vec()).ttl(&time::Duration::from_secs(100)),
+        );
+        // act
+        let result = incrby(5).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(15), result);
+        assert_eq!(Ok(Some(&b"15".to_vec())), data.get_typed::<Vec<u8>>(b"k"));
+        assert!(data.get_live(b"k").unwrap().expired_epoch().is_some());
+    }
+
+    #[test]
+    fn test_exec_new_key() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = incrby(-1).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(-1), result);
+    }
+
+    #[test]
+    fn test_exec_failed() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"a".to_vec()));
+        data.insert(b"plus".to_vec(), DataTTL::new(b"+1".to_vec()));
+        data.insert(
+            b"max".to_vec(),
+            DataTTL::new(i64::MAX.to_string().into_bytes()),
+        );
+        let overflow = IncrBy {
+            key: b"max".to_vec(),
+            increment: 1,
+        };
+        // act & assert
+        assert_eq!(
+            Value::Error("ERR value is not an integer or out of range".to_string()),
+            incrby(1).exec(&mut data)
+        );
+        let plus = IncrBy {
+            key: b"plus".to_vec(),
+            increment: 1,
+        };
+        assert_eq!(
+            Value::Error("ERR value is not an integer or out of range".to_string()),
+            plus.exec(&mut data)
+        );
+        assert_eq!(
+            Value::Error("ERR increment or decrement would overflow".to_string()),
+            overflow.exec(&mut data)
+        );
+    }
+}
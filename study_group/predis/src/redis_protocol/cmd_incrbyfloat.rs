@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/incrbyfloat/
+#[derive(Default, PartialEq, Debug)]
+pub struct IncrByFloat {
+    key: Vec<u8>,
+    increment: f64,
+}
+
+impl IncrByFloat {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            input.len() == 2,
+            "wrong number of arguments for incrbyfloat"
+        );
+        Ok(Box::new(IncrByFloat {
+            key: input[0].to_owned(),
+            increment: Self::parse_float(&input[1])
+                .ok_or_else(|| anyhow::anyhow!("value is not a valid float"))?,
+        }))
+    }
+
+    // NaN and infinity are not valid values
+    fn parse_float(input: &[u8]) -> Option<f64> {
+        std::str::from_utf8(input)
+            .ok()?
+            .parse::<f64>()
+            .ok()
+            .filter(|x| x.is_finite())
+    }
+}
+
+impl Execution for IncrByFloat {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let current = match data.get_typed::<Vec<u8>>(&self.key) {
+            Ok(Some(value)) => Self::parse_float(value),
+            Ok(None) => Some(0.0),
+            Err(e) => return e.into(),
+        };
+        let Some(current) = current else {
+            return Value::Error("ERR value is not a valid float".to_string());
+        };
+        let result = current + self.increment;
+        if !result.is_finite() {
+            return Value::Error("ERR increment would produce NaN or Infinity".to_string());
+        }
+        // update the value in place, so the ttl of the key is kept
+        if let Ok(value) = data.get_typed_or_default::<Vec<u8>>(&self.key) {
+            *value = result.to_string().into_bytes();
+        }
+        Value::BufBulk(result.to_string().into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::IncrByFloat;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+
+    fn incrbyfloat(increment: f64) -> IncrByFloat {
+        IncrByFloat {
+            key: b"k".to_vec(),
+            increment,
+        }
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"10.50".to_vec()));
+        // act
+        let result = incrbyfloat(0.1).exec(&mut data);
+        let result2 = incrbyfloat(-5.6).exec(&mut data);
+        // assert
+        assert_eq!(Value::BufBulk(b"10.6".to_vec()), result);
+        assert_eq!(Value::BufBulk(b"5".to_vec()), result2);
+    }
+
+    #[test]
+    fn test_exec_failed() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(
+            b"k".to_vec(),
+            DataTTL::new(f64::MAX.to_string().into_bytes()),
+        );
+        // act
+        let result = incrbyfloat(f64::MAX).exec(&mut data);
+        // assert
+        assert_eq!(
+            Value::Error("ERR increment would produce NaN or Infinity".to_string()),
+            result
+        );
+    }
+}
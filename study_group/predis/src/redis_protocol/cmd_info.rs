@@ -0,0 +1,326 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::configuration::live::LiveConfig;
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::shard::LentShards;
+use crate::data_watcher::DataStorage;
+use crate::replication::Replication;
+use crate::tcp_server::server_stats::ServerStats;
+
+use resp::Value;
+
+// the sections of INFO without argument, the other sections are in all and everything
+const DEFAULT_SECTIONS: [&str; 7] = [
+    "server",
+    "clients",
+    "memory",
+    "persistence",
+    "stats",
+    "replication",
+    "keyspace",
+];
+const SECTIONS: [&str; 8] = [
+    "server",
+    "clients",
+    "memory",
+    "persistence",
+    "stats",
+    "replication",
+    "commandstats",
+    "keyspace",
+];
+
+// https://redis.io/commands/info/
+// INFO [section [section ...]], the keyspace of all the shards with the stats of the server
+pub struct Info {
+    sections: Vec<String>,
+    stats: Arc<ServerStats>,
+    config: Arc<LiveConfig>,
+    replication: Arc<Replication>,
+}
+
+impl Info {
+    pub fn new(
+        input: VecDeque<Vec<u8>>,
+        stats: Arc<ServerStats>,
+        config: Arc<LiveConfig>,
+        replication: Arc<Replication>,
+    ) -> Box<Self> {
+        Box::new(Info {
+            sections: input
+                .iter()
+                .map(|x| String::from_utf8_lossy(x).to_lowercase())
+                .collect(),
+            stats,
+            config,
+            replication,
+        })
+    }
+
+    // in the order of the table like redis, the unknown sections are ignored
+    fn selected(&self) -> Vec<&'static str> {
+        if self.sections.is_empty() {
+            return DEFAULT_SECTIONS.to_vec();
+        }
+        SECTIONS
+            .into_iter()
+            .filter(|section| {
+                self.sections.iter().any(|x| {
+                    x == section
+                        || x == "all"
+                        || x == "everything"
+                        || (x == "default" && DEFAULT_SECTIONS.contains(section))
+                })
+            })
+            .collect()
+    }
+
+    // the lines field:value of the sections, a blank line between the sections
+    fn render(&self, storages: &[&DataStorage]) -> String {
+        let mut text = String::new();
+        for section in self.selected() {
+            if !text.is_empty() {
+                text.push_str("\r\n");
+            }
+            let mut title = section.to_string();
+            title[..1].make_ascii_uppercase();
+            text.push_str(&format!("# {title}\r\n"));
+            for (name, value) in self.fields(section, storages) {
+                text.push_str(&format!("{name}:{value}\r\n"));
+            }
+        }
+        text
+    }
+
+    fn fields(&self, section: &str, storages: &[&DataStorage]) -> Vec<(String, String)> {
+        let config = self.config.current();
+        let memory = storages[0].memory();
+        let persistence = storages[0].persistence();
+        let fields: Vec<(&str, String)> = match section {
+            "server" => {
+                let uptime = self.stats.uptime().as_secs();
+                vec![
+                    ("predis_version", env!("CARGO_PKG_VERSION").to_string()),
+                    ("process_id", std::process::id().to_string()),
+                    ("tcp_port", config.port.to_string()),
+                    ("uptime_in_seconds", uptime.to_string()),
+                    ("uptime_in_days", (uptime / 86400).to_string()),
+                    ("shards", config.shards.to_string()),
+                    (
+                        "config_file",
+                        config
+                            .config_file
+                            .map(|x| x.display().to_string())
+                            .unwrap_or_default(),
+                    ),
+                ]
+            }
+            // the connections over the workers are rejected
+            "clients" => {
+                let blocked: usize = storages.iter().map(|x| x.blocked_clients()).sum();
+                vec![
+                    (
+                        "connected_clients",
+                        self.stats.connected_clients().to_string(),
+                    ),
+                    ("maxclients", config.workers.to_string()),
+                    ("blocked_clients", blocked.to_string()),
+                ]
+            }
+            "memory" => {
+                let maxmemory = memory.config();
+                vec![
+                    ("used_memory", memory.used().to_string()),
+                    ("used_memory_human", human(memory.used())),
+                    ("maxmemory", maxmemory.maxmemory.to_string()),
+                    ("maxmemory_human", human(maxmemory.maxmemory)),
+                    ("maxmemory_policy", maxmemory.policy.name().to_string()),
+                ]
+            }
+            "persistence" => [("loading", "0".to_string())]
+                .into_iter()
+                .chain(persistence.snapshot_info())
+                .chain(persistence.aof_info())
+                .collect(),
+            "stats" => {
+                let expired: u64 = storages
+                    .iter()
+                    .map(|x| x.expire_stats().expired_keys())
+                    .sum();
+                let hits: u64 = storages.iter().map(|x| x.keyspace_stats().hits).sum();
+                let misses: u64 = storages.iter().map(|x| x.keyspace_stats().misses).sum();
+                vec![
+                    (
+                        "total_connections_received",
+                        self.stats.total_connections_received().to_string(),
+                    ),
+                    (
+                        "total_commands_processed",
+                        self.stats.total_commands_processed().to_string(),
+                    ),
+                    (
+                        "rejected_connections",
+                        self.stats.rejected_connections().to_string(),
+                    ),
+                    ("expired_keys", expired.to_string()),
+                    ("evicted_keys", memory.evicted_keys().to_string()),
+                    ("keyspace_hits", hits.to_string()),
+                    ("keyspace_misses", misses.to_string()),
+                ]
+            }
+            // the link to the primary of the replica, then the stream of this server
+            "replication" => {
+                let (replid, offset, replicas) = persistence.replication_info();
+                self.replication
+                    .info()
+                    .into_iter()
+                    .chain([
+                        ("connected_slaves", replicas.to_string()),
+                        ("master_replid", replid),
+                        ("master_repl_offset", offset.to_string()),
+                    ])
+                    .collect()
+            }
+            "commandstats" => {
+                return self
+                    .stats
+                    .commands()
+                    .into_iter()
+                    .map(|(name, x)| {
+                        (
+                            format!("cmdstat_{name}"),
+                            format!(
+                                "calls={},usec={},usec_per_call={:.2},rejected_calls={},failed_calls={}",
+                                x.calls,
+                                x.usec,
+                                x.usec as f64 / x.calls.max(1) as f64,
+                                x.rejected_calls,
+                                x.failed_calls
+                            ),
+                        )
+                    })
+                    .collect();
+            }
+            // one database, it is omitted when it is empty like redis
+            "keyspace" => {
+                let keys: usize = storages.iter().map(|x| x.len()).sum();
+                let expires: usize = storages.iter().map(|x| x.volatile_len()).sum();
+                if keys == 0 {
+                    return Vec::new();
+                }
+                vec![("db0", format!("keys={keys},expires={expires},avg_ttl=0"))]
+            }
+            _ => Vec::new(),
+        };
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    }
+}
+
+impl Execution for Info {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        Value::BufBulk(self.render(&[&*data]).into_bytes())
+    }
+
+    fn exec_shards(&self, shards: &mut LentShards, _args: &[Vec<u8>]) -> Value {
+        Value::BufBulk(self.render(&shards.storages()).into_bytes())
+    }
+}
+
+// the bytes with a unit like redis, 1.50K
+fn human(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.2}{}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::{human, Info};
+    use crate::acl::{Acl, AclConfig};
+    use crate::configuration::{live::LiveConfig, Configuration};
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use crate::replication::Replication;
+    use crate::tcp_server::server_stats::ServerStats;
+    use resp::Value;
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::time;
+
+    fn info(data: &mut DataStorage, input: &[&str]) -> String {
+        let config = Arc::new(LiveConfig::new(
+            Configuration::default(),
+            Arc::new(Acl::new(AclConfig::default())),
+            data.persistence().clone(),
+            data.memory().clone(),
+            Arc::new(Replication::default()),
+        ));
+        let input: VecDeque<Vec<u8>> = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        let replication = Arc::new(Replication::default());
+        match Info::new(input, Arc::new(ServerStats::new()), config, replication).exec(data) {
+            Value::BufBulk(text) => String::from_utf8(text).unwrap(),
+            value => panic!("{value:?}"),
+        }
+    }
+
+    #[test]
+    fn test_exec_sections() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let default = info(&mut data, &[]);
+        let all = info(&mut data, &["ALL"]);
+        let selected = info(&mut data, &["stats", "server", "unknown"]);
+        // assert
+        assert!(default.starts_with("# Server\r\npredis_version:"));
+        assert!(default.contains("\r\n\r\n# Clients\r\nconnected_clients:0\r\nmaxclients:1\r\n"));
+        assert!(default.contains("\r\n\r\n# Replication\r\nrole:master\r\nconnected_slaves:0\r\n"));
+        assert!(!default.contains("# Commandstats"));
+        assert!(all.contains("# Commandstats"));
+        assert!(selected.starts_with("# Server\r\n"));
+        assert!(selected.contains("# Stats\r\n"));
+        assert!(!selected.contains("# Memory"));
+        assert_eq!("", info(&mut data, &["unknown"]));
+    }
+
+    #[test]
+    fn test_exec_keyspace() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"a".to_vec(), DataTTL::new(b"1".to_vec()));
+        data.insert(
+            b"b".to_vec(),
+            DataTTL::new(b"2".to_vec()).ttl(&time::Duration::from_secs(100)),
+        );
+        data.get_value(b"a");
+        data.get_value(b"c");
+        // act
+        let keyspace = info(&mut data, &["keyspace"]);
+        let stats = info(&mut data, &["stats"]);
+        // assert
+        assert_eq!("# Keyspace\r\ndb0:keys=2,expires=1,avg_ttl=0\r\n", keyspace);
+        assert!(stats.contains("keyspace_hits:1\r\nkeyspace_misses:1\r\n"));
+        assert_eq!(
+            "# Keyspace\r\n",
+            info(&mut DataStorage::new(), &["keyspace"])
+        );
+    }
+
+    #[test]
+    fn test_human() {
+        assert_eq!("1000B", human(1000));
+        assert_eq!("1.50K", human(1536));
+        assert_eq!("2.00M", human(2 * 1024 * 1024));
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::shard::LentShards;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::string_match::string_match;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/keys/
+#[derive(Default, PartialEq, Debug)]
+pub struct Keys {
+    pattern: Vec<u8>,
+}
+
+impl Keys {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for keys");
+        Ok(Box::new(Keys {
+            pattern: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for Keys {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let all = self.pattern == b"*";
+        Value::Array(
+            data.iter()
+                .filter(|(_, v)| !v.is_expired())
+                .filter(|(k, _)| all || string_match(&self.pattern, k, false))
+                .map(|(k, _)| Value::BufBulk(k.to_owned()))
+                .collect(),
+        )
+    }
+
+    fn exec_shards(&self, shards: &mut LentShards, _args: &[Vec<u8>]) -> Value {
+        let mut keys = Vec::new();
+        for data in shards.storages_mut() {
+            if let Value::Array(x) = self.exec(data) {
+                keys.extend(x);
+            }
+        }
+        Value::Array(keys)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Keys;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::time;
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        for key in ["hello", "hallo", "hxllo", "world"] {
+            data.insert(key.as_bytes().to_vec(), DataTTL::new(b"v".to_vec()));
+        }
+        data.insert(
+            b"hillo".to_vec(),
+            DataTTL::new(b"v".to_vec()).expired_timestamp(&time::Duration::from_secs(1)),
+        );
+        let keys = Keys {
+            pattern: b"h[ae]llo".to_vec(),
+        };
+        // act
+        let Value::Array(mut result) = keys.exec(&mut data) else {
+            unreachable!();
+        };
+        // assert
+        result.sort_by_key(|x| x.encode());
+        assert_eq!(
+            vec![
+                Value::BufBulk(b"hallo".to_vec()),
+                Value::BufBulk(b"hello".to_vec())
+            ],
+            result
+        );
+    }
+}
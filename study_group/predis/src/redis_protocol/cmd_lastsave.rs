@@ -0,0 +1,24 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/lastsave/
+#[derive(Default, PartialEq, Debug)]
+pub struct LastSave;
+
+impl LastSave {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.is_empty(), "wrong number of arguments for lastsave");
+        Ok(Box::new(LastSave))
+    }
+}
+
+impl Execution for LastSave {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        Value::Integer(data.last_save().as_secs() as i64)
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::{list_helper, parse_integer};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/lindex/
+#[derive(Default, PartialEq, Debug)]
+pub struct LIndex {
+    key: Vec<u8>,
+    index: i64,
+}
+
+impl LIndex {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 2, "wrong number of arguments for lindex");
+        Ok(Box::new(LIndex {
+            key: input[0].to_owned(),
+            index: parse_integer(&input[1])?,
+        }))
+    }
+}
+
+impl Execution for LIndex {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let list = match data.get_typed::<VecDeque<Vec<u8>>>(&self.key) {
+            Ok(Some(list)) => list,
+            Ok(None) => return Value::Null,
+            Err(e) => return e.into(),
+        };
+        match list_helper::index(self.index, list.len()) {
+            Some(i) => Value::BufBulk(list[i].to_owned()),
+            None => Value::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::LIndex;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_exec() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(["a", "b", "c"].iter().map(|x| x.as_bytes().to_vec()));
+        let lindex = |index| LIndex {
+            key: b"k".to_vec(),
+            index,
+        };
+        // act & assert
+        assert_eq!(Value::BufBulk(b"a".to_vec()), lindex(0).exec(&mut data));
+        assert_eq!(Value::BufBulk(b"c".to_vec()), lindex(-1).exec(&mut data));
+        assert_eq!(Value::Null, lindex(3).exec(&mut data));
+    }
+}
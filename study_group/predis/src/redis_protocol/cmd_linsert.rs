@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/linsert/
+#[derive(Default, PartialEq, Debug)]
+pub struct LInsert {
+    key: Vec<u8>,
+    before: bool,
+    pivot: Vec<u8>,
+    element: Vec<u8>,
+}
+
+impl LInsert {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 4, "wrong number of arguments for linsert");
+        let key = input.pop_front().unwrap();
+        let before = match input.pop_front().unwrap().to_ascii_lowercase().as_slice() {
+            b"before" => true,
+            b"after" => false,
+            _ => anyhow::bail!("syntax error"),
+        };
+        Ok(Box::new(LInsert {
+            key,
+            before,
+            pivot: input.pop_front().unwrap(),
+            element: input.pop_front().unwrap(),
+        }))
+    }
+}
+
+impl Execution for LInsert {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let list = match data.get_typed_mut::<VecDeque<Vec<u8>>>(&self.key) {
+            Ok(Some(list)) => list,
+            Ok(None) => return Value::Integer(0),
+            Err(e) => return e.into(),
+        };
+        match list.iter().position(|x| *x == self.pivot) {
+            Some(i) => {
+                let i = if self.before { i } else { i + 1 };
+                list.insert(i, self.element.to_owned());
+                Value::Integer(list.len() as i64)
+            }
+            None => Value::Integer(-1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::LInsert;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_parse_success() {
+        // arrange
+        let input = VecDeque::from(vec!["k", "AFTER", "p", "v"]);
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        let expected = LInsert {
+            key: b"k".to_vec(),
+            before: false,
+            pivot: b"p".to_vec(),
+            element: b"v".to_vec(),
+        };
+        // act
+        let result = LInsert::parse(input);
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_syntax_error() {
+        // arrange
+        let input = VecDeque::from(vec!["k", "middle", "p", "v"]);
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        // act
+        let result = LInsert::parse(input);
+        // assert
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::LInsert;
+    use crate::data_watcher::{data_value::DataValue, execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_exec() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(["a", "c"].iter().map(|x| x.as_bytes().to_vec()));
+        let linsert = |before, pivot: &str| LInsert {
+            key: b"k".to_vec(),
+            before,
+            pivot: pivot.as_bytes().to_vec(),
+            element: b"b".to_vec(),
+        };
+        // act & assert
+        assert_eq!(Value::Integer(3), linsert(true, "c").exec(&mut data));
+        assert_eq!(Value::Integer(-1), linsert(false, "x").exec(&mut data));
+        assert_eq!(Value::Integer(4), linsert(false, "c").exec(&mut data));
+        assert_eq!(
+            Some(&mut DataValue::List(
+                ["a", "b", "c", "b"]
+                    .iter()
+                    .map(|x| x.as_bytes().to_vec())
+                    .collect()
+            )),
+            data.get_value(b"k")
+        );
+    }
+}
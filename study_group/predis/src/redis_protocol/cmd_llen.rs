@@ -0,0 +1,31 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/llen/
+#[derive(Default, PartialEq, Debug)]
+pub struct LLen {
+    key: Vec<u8>,
+}
+
+impl LLen {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for llen");
+        Ok(Box::new(LLen {
+            key: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for LLen {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<VecDeque<Vec<u8>>>(&self.key) {
+            Ok(list) => Value::Integer(list.map_or(0, |x| x.len()) as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
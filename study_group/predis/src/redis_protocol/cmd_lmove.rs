@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::list_helper::Side;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/lmove/
+#[derive(PartialEq, Debug)]
+pub struct LMove {
+    source: Vec<u8>,
+    destination: Vec<u8>,
+    from: Side,
+    to: Side,
+}
+
+impl LMove {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 4, "wrong number of arguments for lmove");
+        Ok(Box::new(LMove {
+            source: input[0].to_owned(),
+            destination: input[1].to_owned(),
+            from: Side::parse(&input[2])?,
+            to: Side::parse(&input[3])?,
+        }))
+    }
+}
+
+impl Execution for LMove {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        // check the destination type before pop anything from the source
+        if let Err(e) = data.get_typed::<VecDeque<Vec<u8>>>(&self.destination) {
+            return e.into();
+        }
+        let source = match data.get_typed_mut::<VecDeque<Vec<u8>>>(&self.source) {
+            Ok(Some(list)) => list,
+            Ok(None) => return Value::Null,
+            Err(e) => return e.into(),
+        };
+        let element = match self.from {
+            Side::Left => source.pop_front(),
+            Side::Right => source.pop_back(),
+        };
+        let Some(element) = element else {
+            return Value::Null;
+        };
+        data.remove_if_empty(&self.source);
+        // the type is checked, source and destination may be the same list
+        let destination = data
+            .get_typed_or_default::<VecDeque<Vec<u8>>>(&self.destination)
+            .unwrap();
+        match self.to {
+            Side::Left => destination.push_front(element.to_owned()),
+            Side::Right => destination.push_back(element.to_owned()),
+        }
+        Value::BufBulk(element)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::LMove;
+    use crate::{
+        data_watcher::{data_value::DataValue, execution::Execution, DataStorage, DataTTL},
+        redis_protocol::list_helper::Side,
+    };
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn list(elements: &[&str]) -> DataValue {
+        DataValue::List(elements.iter().map(|x| x.as_bytes().to_vec()).collect())
+    }
+
+    #[test]
+    fn test_exec_move_to_other_list() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"src")
+            .unwrap()
+            .push_back(b"a".to_vec());
+        let lmove = LMove {
+            source: b"src".to_vec(),
+            destination: b"dst".to_vec(),
+            from: Side::Right,
+            to: Side::Left,
+        };
+        // act
+        let result = lmove.exec(&mut data);
+        // assert
+        assert_eq!(Value::BufBulk(b"a".to_vec()), result);
+        assert!(!data.exists(b"src"));
+        assert_eq!(Some(&mut list(&["a"])), data.get_value(b"dst"));
+    }
+
+    #[test]
+    fn test_exec_rotate_same_list() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(["a", "b", "c"].iter().map(|x| x.as_bytes().to_vec()));
+        let lmove = LMove {
+            source: b"k".to_vec(),
+            destination: b"k".to_vec(),
+            from: Side::Left,
+            to: Side::Right,
+        };
+        // act
+        let result = lmove.exec(&mut data);
+        // assert
+        assert_eq!(Value::BufBulk(b"a".to_vec()), result);
+        assert_eq!(Some(&mut list(&["b", "c", "a"])), data.get_value(b"k"));
+    }
+
+    #[test]
+    fn test_exec_destination_wrong_type() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"src")
+            .unwrap()
+            .push_back(b"a".to_vec());
+        data.insert(b"dst".to_vec(), DataTTL::new(b"v".to_vec()));
+        let lmove = LMove {
+            source: b"src".to_vec(),
+            destination: b"dst".to_vec(),
+            from: Side::Left,
+            to: Side::Left,
+        };
+        // act
+        let result = lmove.exec(&mut data);
+        // assert
+        assert!(result.is_error());
+        assert_eq!(Some(&mut list(&["a"])), data.get_value(b"src"));
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::{list_helper, parse_integer};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/lrange/
+#[derive(Default, PartialEq, Debug)]
+pub struct LRange {
+    key: Vec<u8>,
+    start: i64,
+    stop: i64,
+}
+
+impl LRange {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for lrange");
+        Ok(Box::new(LRange {
+            key: input[0].to_owned(),
+            start: parse_integer(&input[1])?,
+            stop: parse_integer(&input[2])?,
+        }))
+    }
+}
+
+impl Execution for LRange {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let list = match data.get_typed::<VecDeque<Vec<u8>>>(&self.key) {
+            Ok(Some(list)) => list,
+            Ok(None) => return Value::Array(Vec::new()),
+            Err(e) => return e.into(),
+        };
+        match list_helper::range(self.start, self.stop, list.len()) {
+            Some(range) => Value::Array(
+                list.range(range)
+                    .map(|x| Value::BufBulk(x.to_owned()))
+                    .collect(),
+            ),
+            None => Value::Array(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::LRange;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_exec_negative_index() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(["a", "b", "c"].iter().map(|x| x.as_bytes().to_vec()));
+        let lrange = LRange {
+            key: b"k".to_vec(),
+            start: -2,
+            stop: -1,
+        };
+        // act
+        let result = lrange.exec(&mut data);
+        // assert
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(b"b".to_vec()),
+                Value::BufBulk(b"c".to_vec())
+            ]),
+            result
+        );
+    }
+
+    #[test]
+    fn test_exec_key_not_exist() {
+        // arrange
+        let mut data = DataStorage::new();
+        let lrange = LRange {
+            key: b"k".to_vec(),
+            start: 0,
+            stop: -1,
+        };
+        // act
+        let result = lrange.exec(&mut data);
+        // assert
+        assert_eq!(Value::Array(Vec::new()), result);
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/lrem/
+#[derive(Default, PartialEq, Debug)]
+pub struct LRem {
+    key: Vec<u8>,
+    // count > 0: from head to tail, count < 0: from tail to head, count = 0: remove all
+    count: i64,
+    element: Vec<u8>,
+}
+
+impl LRem {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for lrem");
+        Ok(Box::new(LRem {
+            key: input[0].to_owned(),
+            count: parse_integer(&input[1])?,
+            element: input[2].to_owned(),
+        }))
+    }
+}
+
+impl Execution for LRem {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let list = match data.get_typed_mut::<VecDeque<Vec<u8>>>(&self.key) {
+            Ok(Some(list)) => list,
+            Ok(None) => return Value::Integer(0),
+            Err(e) => return e.into(),
+        };
+        let limit = if self.count == 0 {
+            usize::MAX
+        } else {
+            self.count.unsigned_abs() as usize
+        };
+        let mut removed = 0;
+        if self.count >= 0 {
+            let mut i = 0;
+            while i < list.len() && removed < limit {
+                if list[i] == self.element {
+                    list.remove(i);
+                    removed += 1;
+                } else {
+                    i += 1;
+                }
+            }
+        } else {
+            let mut i = list.len();
+            while i > 0 && removed < limit {
+                i -= 1;
+                if list[i] == self.element {
+                    list.remove(i);
+                    removed += 1;
+                }
+            }
+        }
+        data.remove_if_empty(&self.key);
+        Value::Integer(removed as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::LRem;
+    use crate::data_watcher::{data_value::DataValue, execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn storage() -> DataStorage {
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(
+                ["a", "b", "a", "c", "a"]
+                    .iter()
+                    .map(|x| x.as_bytes().to_vec()),
+            );
+        data
+    }
+
+    fn list(elements: &[&str]) -> DataValue {
+        DataValue::List(elements.iter().map(|x| x.as_bytes().to_vec()).collect())
+    }
+
+    #[test]
+    fn test_exec_from_tail() {
+        // arrange
+        let mut data = storage();
+        let lrem = LRem {
+            key: b"k".to_vec(),
+            count: -2,
+            element: b"a".to_vec(),
+        };
+        // act
+        let result = lrem.exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(2), result);
+        assert_eq!(Some(&mut list(&["a", "b", "c"])), data.get_value(b"k"));
+    }
+
+    #[test]
+    fn test_exec_from_head() {
+        // arrange
+        let mut data = storage();
+        let lrem = LRem {
+            key: b"k".to_vec(),
+            count: 1,
+            element: b"a".to_vec(),
+        };
+        // act
+        let result = lrem.exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(1), result);
+        assert_eq!(Some(&mut list(&["b", "a", "c", "a"])), data.get_value(b"k"));
+    }
+
+    #[test]
+    fn test_exec_remove_all() {
+        // arrange
+        let mut data = storage();
+        let lrem = LRem {
+            key: b"k".to_vec(),
+            count: 0,
+            element: b"a".to_vec(),
+        };
+        // act
+        let result = lrem.exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(3), result);
+        assert_eq!(Some(&mut list(&["b", "c"])), data.get_value(b"k"));
+    }
+}
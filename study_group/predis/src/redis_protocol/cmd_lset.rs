@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::{list_helper, parse_integer};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/lset/
+#[derive(Default, PartialEq, Debug)]
+pub struct LSet {
+    key: Vec<u8>,
+    index: i64,
+    element: Vec<u8>,
+}
+
+impl LSet {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for lset");
+        Ok(Box::new(LSet {
+            key: input[0].to_owned(),
+            index: parse_integer(&input[1])?,
+            element: input[2].to_owned(),
+        }))
+    }
+}
+
+impl Execution for LSet {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let list = match data.get_typed_mut::<VecDeque<Vec<u8>>>(&self.key) {
+            Ok(Some(list)) => list,
+            Ok(None) => return Value::Error("ERR no such key".to_string()),
+            Err(e) => return e.into(),
+        };
+        match list_helper::index(self.index, list.len()) {
+            Some(i) => {
+                list[i] = self.element.to_owned();
+                Value::String("OK".to_string())
+            }
+            None => Value::Error("ERR index out of range".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::LSet;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_exec() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(["a", "b"].iter().map(|x| x.as_bytes().to_vec()));
+        let lset = |index| LSet {
+            key: b"k".to_vec(),
+            index,
+            element: b"x".to_vec(),
+        };
+        // act & assert
+        assert_eq!(Value::String("OK".to_string()), lset(-1).exec(&mut data));
+        assert!(lset(2).exec(&mut data).is_error());
+        assert_eq!(
+            Some(&b"x".to_vec()),
+            data.get_typed::<VecDeque<Vec<u8>>>(b"k")
+                .unwrap()
+                .unwrap()
+                .get(1)
+        );
+    }
+}
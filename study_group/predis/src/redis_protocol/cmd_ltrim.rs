@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::{list_helper, parse_integer};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/ltrim/
+#[derive(Default, PartialEq, Debug)]
+pub struct LTrim {
+    key: Vec<u8>,
+    start: i64,
+    stop: i64,
+}
+
+impl LTrim {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for ltrim");
+        Ok(Box::new(LTrim {
+            key: input[0].to_owned(),
+            start: parse_integer(&input[1])?,
+            stop: parse_integer(&input[2])?,
+        }))
+    }
+}
+
+impl Execution for LTrim {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let list = match data.get_typed_mut::<VecDeque<Vec<u8>>>(&self.key) {
+            Ok(Some(list)) => list,
+            Ok(None) => return Value::String("OK".to_string()),
+            Err(e) => return e.into(),
+        };
+        match list_helper::range(self.start, self.stop, list.len()) {
+            Some(range) => {
+                list.truncate(range.end() + 1);
+                list.drain(..range.start());
+            }
+            None => list.clear(),
+        }
+        data.remove_if_empty(&self.key);
+        Value::String("OK".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::LTrim;
+    use crate::data_watcher::{data_value::DataValue, execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn storage() -> DataStorage {
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(["a", "b", "c", "d"].iter().map(|x| x.as_bytes().to_vec()));
+        data
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = storage();
+        let ltrim = LTrim {
+            key: b"k".to_vec(),
+            start: 1,
+            stop: -2,
+        };
+        // act
+        let result = ltrim.exec(&mut data);
+        // assert
+        assert_eq!(Value::String("OK".to_string()), result);
+        assert_eq!(
+            Some(&mut DataValue::List(VecDeque::from([
+                b"b".to_vec(),
+                b"c".to_vec()
+            ]))),
+            data.get_value(b"k")
+        );
+    }
+
+    #[test]
+    fn test_exec_empty_range_remove_key() {
+        // arrange
+        let mut data = storage();
+        let ltrim = LTrim {
+            key: b"k".to_vec(),
+            start: 5,
+            stop: 10,
+        };
+        // act
+        let result = ltrim.exec(&mut data);
+        // assert
+        assert_eq!(Value::String("OK".to_string()), result);
+        assert!(!data.exists(b"k"));
+    }
+}
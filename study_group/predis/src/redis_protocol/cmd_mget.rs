@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/mget/
+#[derive(Default, PartialEq, Debug)]
+pub struct MGet {
+    keys: Vec<Vec<u8>>,
+}
+
+impl MGet {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(!input.is_empty(), "wrong number of arguments for mget");
+        Ok(Box::new(MGet { keys: input.into() }))
+    }
+}
+
+impl Execution for MGet {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        // the key holding another type is returned as nil
+        Value::Array(
+            self.keys
+                .iter()
+                .map(|key| match data.get_typed::<Vec<u8>>(key) {
+                    Ok(Some(v)) => Value::BufBulk(v.to_owned()),
+                    _ => Value::Null,
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::MGet;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"a".to_vec(), DataTTL::new(b"1".to_vec()));
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"list")
+            .unwrap()
+            .push_back(b"v".to_vec());
+        let mget = MGet {
+            keys: vec![b"a".to_vec(), b"b".to_vec(), b"list".to_vec()],
+        };
+        // act
+        let result = mget.exec(&mut data);
+        // assert
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(b"1".to_vec()),
+                Value::Null,
+                Value::Null
+            ]),
+            result
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::{DataStorage, DataTTL};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/mset/
+// https://redis.io/commands/msetnx/
+// https://redis.io/commands/setnx/
+#[derive(Default, PartialEq, Debug)]
+pub struct MSet {
+    key_values: Vec<(Vec<u8>, Vec<u8>)>,
+    // MSETNX and SETNX set nothing when any key exists and reply 1 or 0
+    nx: bool,
+}
+
+impl MSet {
+    pub fn parse(mut input: VecDeque<Vec<u8>>, nx: bool) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            !input.is_empty() && input.len().is_multiple_of(2),
+            "wrong number of arguments for mset"
+        );
+        let mut key_values = Vec::new();
+        while let (Some(key), Some(value)) = (input.pop_front(), input.pop_front()) {
+            key_values.push((key, value));
+        }
+        Ok(Box::new(MSet { key_values, nx }))
+    }
+
+    pub fn parse_setnx(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 2, "wrong number of arguments for setnx");
+        Self::parse(input, true)
+    }
+}
+
+impl Execution for MSet {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        if self.nx && self.key_values.iter().any(|(key, _)| data.exists(key)) {
+            return Value::Integer(0);
+        }
+        // like SET, the ttl of the existing key is discarded
+        for (key, value) in self.key_values.iter() {
+            data.insert(key.to_owned(), DataTTL::new(value.to_owned()));
+        }
+        if self.nx {
+            Value::Integer(1)
+        } else {
+            Value::String("OK".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::MSet;
+    use std::collections::VecDeque;
+
+    fn input(args: &[&str]) -> VecDeque<Vec<u8>> {
+        args.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert!(MSet::parse(input(&["a", "1", "b"]), false).is_err());
+        assert!(MSet::parse_setnx(input(&["a", "1", "b", "2"])).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::MSet;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::time;
+
+    fn mset(key_values: &[(&str, &str)], nx: bool) -> MSet {
+        MSet {
+            key_values: key_values
+                .iter()
+                .map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec()))
+                .collect(),
+            nx,
+        }
+    }
+
+    #[test]
+    fn test_exec_mset() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(
+            b"a".to_vec(),
+            DataTTL::new(b"old".to_vec()).ttl(&time::Duration::from_secs(100)),
+        );
+        // act
+        let result = mset(&[("a", "1"), ("b", "2")], false).exec(&mut data);
+        // assert
+        assert_eq!(Value::String("OK".to_string()), result);
+        assert_eq!(Ok(Some(&b"1".to_vec())), data.get_typed::<Vec<u8>>(b"a"));
+        assert_eq!(None, data.get_live(b"a").unwrap().expired_epoch());
+        assert_eq!(Ok(Some(&b"2".to_vec())), data.get_typed::<Vec<u8>>(b"b"));
+    }
+
+    #[test]
+    fn test_exec_msetnx() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = mset(&[("a", "1"), ("b", "2")], true).exec(&mut data);
+        let result2 = mset(&[("b", "3"), ("c", "4")], true).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(1), result);
+        assert_eq!(Value::Integer(0), result2);
+        assert_eq!(Ok(Some(&b"2".to_vec())), data.get_typed::<Vec<u8>>(b"b"));
+        assert!(!data.exists(b"c"));
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/persist/
+#[derive(Default, PartialEq, Debug)]
+pub struct Persist {
+    key: Vec<u8>,
+}
+
+impl Persist {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for persist");
+        Ok(Box::new(Persist {
+            key: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for Persist {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let has_ttl = data
+            .get_live(&self.key)
+            .is_some_and(|x| x.expired_epoch().is_some());
+        if has_ttl {
+            data.set_expired_epoch(&self.key, None);
+        }
+        Value::Integer(has_ttl as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Persist;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::time;
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(
+            b"k".to_vec(),
+            DataTTL::new(b"v".to_vec()).ttl(&time::Duration::from_secs(100)),
+        );
+        let persist = Persist { key: b"k".to_vec() };
+        // act
+        let result = persist.exec(&mut data);
+        let result2 = persist.exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(1), result);
+        assert_eq!(Value::Integer(0), result2);
+        assert_eq!(None, data.get_live(b"k").unwrap().expired_epoch());
+    }
+}
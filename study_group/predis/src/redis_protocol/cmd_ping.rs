@@ -0,0 +1,52 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/ping/
+// the subscribed connection replies it in pubsub_client
+#[derive(Default, PartialEq, Debug)]
+pub struct Ping {
+    message: Option<Vec<u8>>,
+}
+
+impl Ping {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() <= 1, "wrong number of arguments for ping");
+        Ok(Box::new(Ping {
+            message: input.pop_front(),
+        }))
+    }
+}
+
+impl Execution for Ping {
+    fn exec(&self, _data: &mut DataStorage) -> Value {
+        match &self.message {
+            Some(message) => Value::BufBulk(message.to_owned()),
+            None => Value::String("PONG".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Ping;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_exec() {
+        // arrange
+        let mut data = DataStorage::new();
+        let ping = Ping::parse(VecDeque::new()).unwrap();
+        let echo = Ping::parse(VecDeque::from([b"hi".to_vec()])).unwrap();
+        // act & assert
+        assert_eq!(Value::String("PONG".to_string()), ping.exec(&mut data));
+        assert_eq!(Value::BufBulk(b"hi".to_vec()), echo.exec(&mut data));
+        assert!(Ping::parse(VecDeque::from([b"a".to_vec(), b"b".to_vec()])).is_err());
+    }
+}
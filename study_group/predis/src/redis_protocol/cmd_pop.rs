@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::list_helper::Side;
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/lpop/
+// https://redis.io/commands/rpop/
+#[derive(PartialEq, Debug)]
+pub struct Pop {
+    key: Vec<u8>,
+    count: Option<usize>,
+    side: Side,
+}
+
+impl Pop {
+    pub fn parse(mut input: VecDeque<Vec<u8>>, side: Side) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            input.len() == 1 || input.len() == 2,
+            "wrong number of arguments for pop"
+        );
+        let key = input.pop_front().unwrap();
+        let count = match input.pop_front() {
+            Some(count) => Some(
+                parse_integer::<usize>(&count)
+                    .map_err(|_| anyhow::anyhow!("value is out of range, must be positive"))?,
+            ),
+            None => None,
+        };
+        Ok(Box::new(Pop { key, count, side }))
+    }
+}
+
+impl Execution for Pop {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let list = match data.get_typed_mut::<VecDeque<Vec<u8>>>(&self.key) {
+            Ok(Some(list)) => list,
+            Ok(None) if self.count.is_some() => return Value::NullArray,
+            Ok(None) => return Value::Null,
+            Err(e) => return e.into(),
+        };
+        let count = self.count.unwrap_or(1).min(list.len());
+        let popped: Vec<Value> = (0..count)
+            .filter_map(|_| match self.side {
+                Side::Left => list.pop_front(),
+                Side::Right => list.pop_back(),
+            })
+            .map(Value::BufBulk)
+            .collect();
+        data.remove_if_empty(&self.key);
+        if self.count.is_some() {
+            Value::Array(popped)
+        } else {
+            popped.into_iter().next().unwrap_or(Value::Null)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::Pop;
+    use crate::redis_protocol::list_helper::Side;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_parse_count_success() {
+        // arrange
+        let input = VecDeque::from(vec!["k", "2"]);
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        let expected = Pop {
+            key: b"k".to_vec(),
+            count: Some(2),
+            side: Side::Right,
+        };
+        // act
+        let result = Pop::parse(input, Side::Right);
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_negative_count() {
+        // arrange
+        let input = VecDeque::from(vec!["k", "-1"]);
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        // act
+        let result = Pop::parse(input, Side::Left);
+        // assert
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Pop;
+    use crate::{
+        data_watcher::{execution::Execution, DataStorage},
+        redis_protocol::list_helper::Side,
+    };
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn storage() -> DataStorage {
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<VecDeque<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(["a", "b", "c"].iter().map(|x| x.as_bytes().to_vec()));
+        data
+    }
+
+    #[test]
+    fn test_exec_lpop_success() {
+        // arrange
+        let mut data = storage();
+        let pop = Pop {
+            key: b"k".to_vec(),
+            count: None,
+            side: Side::Left,
+        };
+        // act
+        let result = pop.exec(&mut data);
+        // assert
+        assert_eq!(Value::BufBulk(b"a".to_vec()), result);
+    }
+
+    #[test]
+    fn test_exec_rpop_count_remove_empty_list() {
+        // arrange
+        let mut data = storage();
+        let pop = Pop {
+            key: b"k".to_vec(),
+            count: Some(5),
+            side: Side::Right,
+        };
+        // act
+        let result = pop.exec(&mut data);
+        // assert
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(b"c".to_vec()),
+                Value::BufBulk(b"b".to_vec()),
+                Value::BufBulk(b"a".to_vec()),
+            ]),
+            result
+        );
+        assert!(!data.exists(b"k"));
+        assert_eq!(Value::NullArray, pop.exec(&mut data));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::list_helper::Side;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/lpush/
+// https://redis.io/commands/rpush/
+// https://redis.io/commands/lpushx/
+// https://redis.io/commands/rpushx/
+#[derive(PartialEq, Debug)]
+pub struct Push {
+    key: Vec<u8>,
+    elements: Vec<Vec<u8>>,
+    side: Side,
+    only_exist: bool,
+}
+
+impl Push {
+    pub fn parse(mut input: VecDeque<Vec<u8>>, side: Side, only_exist: bool) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 2, "wrong number of arguments for push");
+        Ok(Box::new(Push {
+            key: input.pop_front().unwrap(),
+            elements: input.into(),
+            side,
+            only_exist,
+        }))
+    }
+}
+
+impl Execution for Push {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        if self.only_exist && !data.exists(&self.key) {
+            return Value::Integer(0);
+        }
+        let list = match data.get_typed_or_default::<VecDeque<Vec<u8>>>(&self.key) {
+            Ok(list) => list,
+            Err(e) => return e.into(),
+        };
+        for element in &self.elements {
+            match self.side {
+                Side::Left => list.push_front(element.to_owned()),
+                Side::Right => list.push_back(element.to_owned()),
+            }
+        }
+        Value::Integer(list.len() as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::Push;
+    use crate::redis_protocol::list_helper::Side;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_parse_success() {
+        // arrange
+        let input = VecDeque::from(vec!["k", "a", "b"]);
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        let expected = Push {
+            key: b"k".to_vec(),
+            elements: vec![b"a".to_vec(), b"b".to_vec()],
+            side: Side::Left,
+            only_exist: false,
+        };
+        // act
+        let result = Push::parse(input, Side::Left, false);
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_without_element() {
+        // arrange
+        let input = VecDeque::from(vec![b"k".to_vec()]);
+        // act
+        let result = Push::parse(input, Side::Left, false);
+        // assert
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Push;
+    use crate::{
+        data_watcher::{data_value::DataValue, execution::Execution, DataStorage, DataTTL},
+        redis_protocol::list_helper::Side,
+    };
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn push(side: Side, only_exist: bool) -> Push {
+        Push {
+            key: b"k".to_vec(),
+            elements: vec![b"a".to_vec(), b"b".to_vec()],
+            side,
+            only_exist,
+        }
+    }
+
+    #[test]
+    fn test_exec_lpush_rpush_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = push(Side::Left, false).exec(&mut data);
+        let result2 = push(Side::Right, false).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(2), result);
+        assert_eq!(Value::Integer(4), result2);
+        let expected: VecDeque<Vec<u8>> = ["b", "a", "a", "b"]
+            .iter()
+            .map(|x| x.as_bytes().to_vec())
+            .collect();
+        assert_eq!(Some(&mut DataValue::List(expected)), data.get_value(b"k"));
+    }
+
+    #[test]
+    fn test_exec_pushx_key_not_exist() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = push(Side::Left, true).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(0), result);
+        assert!(!data.exists(b"k"));
+    }
+
+    #[test]
+    fn test_exec_wrong_type() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let result = push(Side::Left, false).exec(&mut data);
+        // assert
+        assert!(result.is_error());
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::shard::LentShards;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use rand::Rng;
+use resp::Value;
+
+// https://redis.io/commands/randomkey/
+#[derive(Default, PartialEq, Debug)]
+pub struct RandomKey;
+
+impl RandomKey {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.is_empty(), "wrong number of arguments for randomkey");
+        Ok(Box::new(RandomKey))
+    }
+}
+
+impl Execution for RandomKey {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.random_key() {
+            Some(key) => Value::BufBulk(key),
+            None => Value::Null,
+        }
+    }
+
+    // the shard is picked by its number of keys so every key has the same chance
+    // the next shards are tried when it only has expired keys
+    fn exec_shards(&self, shards: &mut LentShards, _args: &[Vec<u8>]) -> Value {
+        let mut storages: Vec<&mut DataStorage> = shards.storages_mut().collect();
+        let total: usize = storages.iter().map(|x| x.len()).sum();
+        if total == 0 {
+            return Value::Null;
+        }
+        let mut position = rand::thread_rng().gen_range(0..total);
+        let start = storages
+            .iter()
+            .position(|x| {
+                if position < x.len() {
+                    return true;
+                }
+                position -= x.len();
+                false
+            })
+            .unwrap_or(0);
+        let count = storages.len();
+        (0..count)
+            .map(|i| self.exec(storages[(start + i) % count]))
+            .find(|x| *x != Value::Null)
+            .unwrap_or(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::RandomKey;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act & assert
+        assert_eq!(Value::Null, RandomKey.exec(&mut data));
+        data.insert(b"a".to_vec(), DataTTL::new(b"v".to_vec()));
+        assert_eq!(Value::BufBulk(b"a".to_vec()), RandomKey.exec(&mut data));
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/sadd/
+#[derive(Default, PartialEq, Debug)]
+pub struct SAdd {
+    key: Vec<u8>,
+    members: Vec<Vec<u8>>,
+}
+
+impl SAdd {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 2, "wrong number of arguments for sadd");
+        Ok(Box::new(SAdd {
+            key: input.pop_front().unwrap(),
+            members: input.into(),
+        }))
+    }
+}
+
+impl Execution for SAdd {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let set = match data.get_typed_or_default::<HashSet<Vec<u8>>>(&self.key) {
+            Ok(set) => set,
+            Err(e) => return e.into(),
+        };
+        let added = self
+            .members
+            .iter()
+            .filter(|x| set.insert(x.to_vec()))
+            .count();
+        Value::Integer(added as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::SAdd;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+
+    fn sadd(members: &[&str]) -> SAdd {
+        SAdd {
+            key: b"k".to_vec(),
+            members: members.iter().map(|x| x.as_bytes().to_vec()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = sadd(&["a", "b", "a"]).exec(&mut data);
+        let result2 = sadd(&["b", "c"]).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(2), result);
+        assert_eq!(Value::Integer(1), result2);
+    }
+
+    #[test]
+    fn test_exec_wrong_type() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let result = sadd(&["a"]).exec(&mut data);
+        // assert
+        assert!(result.is_error());
+    }
+}
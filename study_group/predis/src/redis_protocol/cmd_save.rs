@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::shard::{LentShards, Shards};
+use crate::data_watcher::snapshot;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/save/
+// https://redis.io/commands/bgsave/
+#[derive(Default, PartialEq, Debug)]
+pub struct Save {
+    background: bool,
+}
+
+impl Save {
+    pub fn parse(input: VecDeque<Vec<u8>>, background: bool) -> Result<Box<Self>> {
+        let command = if background { "bgsave" } else { "save" };
+        anyhow::ensure!(input.is_empty(), "wrong number of arguments for {command}");
+        Ok(Box::new(Save { background }))
+    }
+}
+
+impl Save {
+    // BGSAVE out of a transaction, the shards are copied one at a time
+    pub async fn bgsave_shards(&self, shards: &Shards) -> Value {
+        Self::bgsave_reply(snapshot::bgsave_shards(shards).await)
+    }
+
+    fn bgsave_reply(result: Result<()>) -> Value {
+        match result {
+            Ok(()) => Value::String("Background saving started".to_string()),
+            Err(e) => Value::Error(format!("ERR {e:#}")),
+        }
+    }
+
+    fn save(&self, shards: &[&DataStorage]) -> Value {
+        if self.background {
+            Self::bgsave_reply(snapshot::bgsave(shards))
+        } else {
+            match snapshot::save(shards) {
+                Ok(()) => Value::String("OK".to_string()),
+                Err(e) => Value::Error(format!("ERR {e:#}")),
+            }
+        }
+    }
+}
+
+impl Execution for Save {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        self.save(&[&*data])
+    }
+
+    // the snapshot has the keys of all the shards
+    fn exec_shards(&self, shards: &mut LentShards, _args: &[Vec<u8>]) -> Value {
+        self.save(&shards.storages())
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Save;
+    use crate::data_watcher::snapshot::SnapshotConfig;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+
+    #[tokio::test]
+    async fn test_exec_save_and_bgsave() {
+        // arrange
+        let path = std::env::temp_dir().join(format!("predis-{}-cmd.pdb", std::process::id()));
+        let config = SnapshotConfig {
+            path: path.clone(),
+            save_rules: Vec::new(),
+        };
+        let mut data = DataStorage::load(config.clone()).unwrap();
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let save = Save { background: false }.exec(&mut data);
+        let bgsave = Save { background: true }.exec(&mut data);
+        let save_in_progress = Save { background: false }.exec(&mut data);
+        while data.persistence().bgsave_in_progress() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        // assert
+        assert_eq!(Value::String("OK".to_string()), save);
+        assert_eq!(
+            Value::String("Background saving started".to_string()),
+            bgsave
+        );
+        assert!(save_in_progress.is_error());
+        assert_eq!(1, DataStorage::load(config).unwrap().len());
+        std::fs::remove_file(path).unwrap();
+    }
+}
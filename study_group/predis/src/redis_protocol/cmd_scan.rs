@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::scan_index::scan_hash;
+use crate::data_watcher::shard::LentShards;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::scan_helper::ScanOption;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/scan/
+#[derive(PartialEq, Debug)]
+pub struct Scan {
+    option: ScanOption,
+}
+
+impl Scan {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        let option = ScanOption::parse(input)?;
+        anyhow::ensure!(!option.no_values, "syntax error");
+        Ok(Box::new(Scan { option }))
+    }
+
+    // like redis the filters are applied after the batch is selected, the expired keys are removed
+    fn filter(&self, data: &mut DataStorage, key: Vec<u8>) -> Option<Value> {
+        if !self.option.is_match(&key) {
+            return None;
+        }
+        let value = data.get_value(&key)?;
+        if self
+            .option
+            .type_name
+            .as_ref()
+            .is_some_and(|x| x != value.type_name())
+        {
+            return None;
+        }
+        Some(Value::BufBulk(key))
+    }
+}
+
+impl Execution for Scan {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let (cursor, keys) = data.scan(self.option.cursor, self.option.count);
+        let keys: Vec<Vec<u8>> = keys.into_iter().cloned().collect();
+        let output = keys
+            .into_iter()
+            .filter_map(|key| self.filter(data, key))
+            .collect();
+        Value::Array(vec![
+            Value::BufBulk(cursor.to_string().into_bytes()),
+            Value::Array(output),
+        ])
+    }
+
+    // the shards are scanned in the same hash order, the batch stops before the smallest next cursor
+    // of the shards so no key below the returned cursor is skipped
+    fn exec_shards(&self, shards: &mut LentShards, _args: &[Vec<u8>]) -> Value {
+        let mut storages: Vec<&mut DataStorage> = shards.storages_mut().collect();
+        let mut bound: Option<u64> = None;
+        let mut keys: Vec<(u64, Vec<u8>, usize)> = Vec::new();
+        for (position, data) in storages.iter().enumerate() {
+            let (next, batch) = data.scan(self.option.cursor, self.option.count);
+            if next != 0 {
+                bound = Some(bound.map_or(next, |x| x.min(next)));
+            }
+            keys.extend(
+                batch
+                    .into_iter()
+                    .map(|x| (scan_hash(x), x.to_owned(), position)),
+            );
+        }
+        keys.retain(|(hash, ..)| bound.is_none_or(|x| *hash < x));
+        keys.sort_unstable();
+        // keys with the same hash are returned in the same batch
+        let mut cursor = bound.unwrap_or(0);
+        if let Some(end) = (self.option.count..keys.len()).find(|x| keys[*x].0 != keys[*x - 1].0) {
+            cursor = keys[end].0;
+            keys.truncate(end);
+        }
+        let output = keys
+            .into_iter()
+            .filter_map(|(_, key, position)| self.filter(storages[position], key))
+            .collect();
+        Value::Array(vec![
+            Value::BufBulk(cursor.to_string().into_bytes()),
+            Value::Array(output),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Scan;
+    use crate::data_watcher::{execution::Execution, shard::LentShards, DataStorage, DataTTL};
+    use resp::Value;
+    use std::collections::{HashSet, VecDeque};
+
+    #[test]
+    fn test_exec_until_cursor_zero() {
+        // arrange
+        let mut data = DataStorage::new();
+        for i in 0..50 {
+            data.insert(format!("k{i}").into_bytes(), DataTTL::new(b"v".to_vec()));
+        }
+        data.get_typed_or_default::<HashSet<Vec<u8>>>(b"k_set")
+            .unwrap()
+            .insert(b"a".to_vec());
+        let mut keys = Vec::new();
+        let mut cursor = b"0".to_vec();
+        // act
+        loop {
+            let mut input: VecDeque<Vec<u8>> = ["match", "k*", "count", "5", "type", "string"]
+                .iter()
+                .map(|x| x.as_bytes().to_vec())
+                .collect();
+            input.push_front(cursor.clone());
+            let scan = Scan::parse(input);
+            let Value::Array(mut result) = scan.unwrap().exec(&mut data) else {
+                unreachable!();
+            };
+            let Value::Array(batch) = result.pop().unwrap() else {
+                unreachable!();
+            };
+            keys.extend(batch);
+            let Value::BufBulk(next) = result.pop().unwrap() else {
+                unreachable!();
+            };
+            // delete keys during the iteration
+            data.remove(format!("k{}", keys.len() + 10).as_bytes());
+            cursor = next;
+            if cursor == b"0" {
+                break;
+            }
+        }
+        // assert
+        let keys: HashSet<Vec<u8>> = keys
+            .into_iter()
+            .map(|x| match x {
+                Value::BufBulk(x) => x,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert!(!keys.contains(b"k_set".as_slice()));
+        for i in 0..10 {
+            assert!(keys.contains(format!("k{i}").as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_exec_shards_until_cursor_zero() {
+        // arrange
+        let mut data = DataStorage::new();
+        for i in 0..100 {
+            data.insert(format!("k{i}").into_bytes(), DataTTL::new(b"v".to_vec()));
+        }
+        let mut shards = LentShards::new(data.split(4));
+        let mut keys = Vec::new();
+        let mut cursor = b"0".to_vec();
+        // act
+        loop {
+            let input = VecDeque::from([cursor.clone(), b"count".to_vec(), b"7".to_vec()]);
+            let Value::Array(mut result) =
+                Scan::parse(input).unwrap().exec_shards(&mut shards, &[])
+            else {
+                unreachable!();
+            };
+            let Value::Array(batch) = result.pop().unwrap() else {
+                unreachable!();
+            };
+            assert!(batch.len() >= 7 || result[0] == Value::BufBulk(b"0".to_vec()));
+            keys.extend(batch);
+            let Value::BufBulk(next) = result.pop().unwrap() else {
+                unreachable!();
+            };
+            cursor = next;
+            if cursor == b"0" {
+                break;
+            }
+        }
+        // assert
+        assert_eq!(100, keys.len());
+        let keys: HashSet<Vec<u8>> = keys
+            .into_iter()
+            .map(|x| match x {
+                Value::BufBulk(x) => x,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(100, keys.len());
+    }
+}
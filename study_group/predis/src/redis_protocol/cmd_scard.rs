@@ -0,0 +1,31 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/scard/
+#[derive(Default, PartialEq, Debug)]
+pub struct SCard {
+    key: Vec<u8>,
+}
+
+impl SCard {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for scard");
+        Ok(Box::new(SCard {
+            key: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for SCard {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<HashSet<Vec<u8>>>(&self.key) {
+            Ok(set) => Value::Integer(set.map_or(0, |x| x.len()) as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
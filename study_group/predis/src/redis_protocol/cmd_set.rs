@@ -1,8 +1,8 @@
 use std::collections::VecDeque;
-use std::time;
 
 use crate::data_watcher::execution::Execution;
 use crate::data_watcher::{DataStorage, DataTTL};
+use crate::redis_protocol::ttl_helper::TTLState;
 
 use anyhow::Result;
 use resp::Value;
@@ -10,22 +10,15 @@ use resp::Value;
 // https://redis.io/commands/set/
 #[derive(Default, PartialEq, Debug)]
 pub struct Set {
-    key: String,
-    value: String,
+    key: Vec<u8>,
+    value: Vec<u8>,
     get: Option<()>,
     ttl_state: Option<TTLState>,
     key_exist_then_insert: Option<bool>,
 }
 
-#[derive(PartialEq, Debug)]
-enum TTLState {
-    Ttl(time::Duration),
-    ExpiredTimestamp(time::Duration),
-    KeepTTL,
-}
-
 impl Set {
-    pub fn parse(mut input: VecDeque<String>) -> Result<Box<Self>> {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
         anyhow::ensure!(input.len() >= 2, "at least two argument for set");
         let mut set_obj = Set {
             key: input.pop_front().unwrap(),
@@ -33,72 +26,9 @@ impl Set {
             ..Default::default()
         };
         while let Some(token) = input.pop_front() {
-            match token.to_lowercase().as_str() {
-                "get" => set_obj.get = Some(()),
-                "ex" => {
-                    // EX seconds -- Set the specified expire time, in seconds (a positive integer).
-                    if let Some(ttl) = input.pop_front() {
-                        anyhow::ensure!(set_obj.ttl_state.is_none(), "ttl already be set");
-                        if let Ok(ttl_u64) = ttl.parse::<u64>() {
-                            set_obj.ttl_state =
-                                Some(TTLState::Ttl(time::Duration::from_secs(ttl_u64)));
-                        } else {
-                            anyhow::bail!("{token} value is not integer");
-                        }
-                    } else {
-                        anyhow::bail!("{token} without value");
-                    }
-                }
-                "px" => {
-                    // PX milliseconds -- Set the specified expire time, in milliseconds (a positive integer).
-                    if let Some(ttl) = input.pop_front() {
-                        anyhow::ensure!(set_obj.ttl_state.is_none(), "ttl already be set");
-                        if let Ok(ttl_u64) = ttl.parse::<u64>() {
-                            set_obj.ttl_state =
-                                Some(TTLState::Ttl(time::Duration::from_millis(ttl_u64)));
-                        } else {
-                            anyhow::bail!("{token} value is not integer");
-                        }
-                    } else {
-                        anyhow::bail!("{token} without value");
-                    }
-                }
-                "exat" => {
-                    // EXAT timestamp-seconds -- Set the specified Unix time at which the key will expire, in seconds (a positive integer).
-                    if let Some(ttl) = input.pop_front() {
-                        anyhow::ensure!(set_obj.ttl_state.is_none(), "ttl already be set");
-                        if let Ok(ttl_u64) = ttl.parse::<u64>() {
-                            set_obj.ttl_state = Some(TTLState::ExpiredTimestamp(
-                                time::Duration::from_secs(ttl_u64),
-                            ));
-                        } else {
-                            anyhow::bail!("{token} value is not integer");
-                        }
-                    } else {
-                        anyhow::bail!("{token} without value");
-                    }
-                }
-                "pxat" => {
-                    // PXAT timestamp-milliseconds -- Set the specified Unix time at which the key will expire, in milliseconds (a positive integer).
-                    if let Some(ttl) = input.pop_front() {
-                        anyhow::ensure!(set_obj.ttl_state.is_none(), "ttl already be set");
-                        if let Ok(ttl_u64) = ttl.parse::<u64>() {
-                            set_obj.ttl_state = Some(TTLState::ExpiredTimestamp(
-                                time::Duration::from_millis(ttl_u64),
-                            ));
-                        } else {
-                            anyhow::bail!("{token} value is not integer");
-                        }
-                    } else {
-                        anyhow::bail!("{token} without value");
-                    }
-                }
-                "keepttl" => {
-                    // KEEPTTL -- Retain the time to live associated with the key.
-                    anyhow::ensure!(set_obj.ttl_state.is_none(), "ttl already be set");
-                    set_obj.ttl_state = Some(TTLState::KeepTTL)
-                }
-                "nx" => {
+            match token.to_ascii_lowercase().as_slice() {
+                b"get" => set_obj.get = Some(()),
+                b"nx" => {
                     set_obj.key_exist_then_insert = {
                         anyhow::ensure!(
                             set_obj.key_exist_then_insert.is_none(),
@@ -107,7 +37,7 @@ impl Set {
                         Some(false)
                     }
                 }
-                "xx" => {
+                b"xx" => {
                     set_obj.key_exist_then_insert = {
                         anyhow::ensure!(
                             set_obj.key_exist_then_insert.is_none(),
@@ -116,22 +46,58 @@ impl Set {
                         Some(true)
                     }
                 }
-                _ => {
-                    anyhow::bail!("{token} unknown option");
-                }
+                // EX, PX, EXAT, PXAT and KEEPTTL
+                _ => match TTLState::parse(&token, &mut input, "set")? {
+                    Some(TTLState::Persist) | None => {
+                        anyhow::bail!("{} unknown option", String::from_utf8_lossy(&token))
+                    }
+                    Some(ttl_state) => {
+                        anyhow::ensure!(set_obj.ttl_state.is_none(), "ttl already be set");
+                        set_obj.ttl_state = Some(ttl_state);
+                    }
+                },
             }
         }
         Ok(Box::new(set_obj))
     }
+
+    // SETEX key seconds value, PSETEX key milliseconds value
+    pub fn parse_setex(mut input: VecDeque<Vec<u8>>, millis: bool) -> Result<Box<Self>> {
+        let (command, token) = if millis {
+            ("psetex", "px")
+        } else {
+            ("setex", "ex")
+        };
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for {command}");
+        let key = input.pop_front().unwrap();
+        let value = input.pop_back().unwrap();
+        Ok(Box::new(Set {
+            key,
+            value,
+            ttl_state: TTLState::parse(token.as_bytes(), &mut input, command)?,
+            ..Default::default()
+        }))
+    }
+
+    // GETSET key value is SET key value GET
+    pub fn parse_getset(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 2, "wrong number of arguments for getset");
+        Ok(Box::new(Set {
+            key: input[0].to_owned(),
+            value: input[1].to_owned(),
+            get: Some(()),
+            ..Default::default()
+        }))
+    }
 }
 
 impl Execution for Set {
     fn exec(&self, data: &mut DataStorage) -> Value {
         let return_value = if self.get.is_some() {
-            if let Some(v) = data.get(&self.key) {
-                Value::String(v.get().unwrap())
-            } else {
-                Value::Null
+            match data.get_typed::<Vec<u8>>(&self.key) {
+                Ok(Some(v)) => Value::BufBulk(v.to_owned()),
+                Ok(None) => Value::Null,
+                Err(e) => return e.into(),
             }
         } else {
             Value::String("ok".to_string())
@@ -139,23 +105,23 @@ impl Execution for Set {
         let mut data_ttl = DataTTL::new(self.value.to_owned());
         // handle key exist
         if let Some(key_exist_then_insert) = &self.key_exist_then_insert {
-            if *key_exist_then_insert != data.get(&self.key).is_some() {
+            if *key_exist_then_insert != data.exists(&self.key) {
                 return Value::Null;
             }
         }
         // handle data ttl
         if let Some(ttl_state) = &self.ttl_state {
-            data_ttl = match ttl_state {
-                TTLState::Ttl(d) => data_ttl.ttl(d),
-                TTLState::ExpiredTimestamp(d) => data_ttl.expired_timestamp(d),
-                TTLState::KeepTTL => {
-                    if let Some(v) = data.get(&self.key) {
-                        v.to_owned().update(self.value.to_owned())
-                    } else {
-                        data_ttl
-                    }
-                }
+            let current = data.get_live(&self.key).and_then(|v| v.expired_epoch());
+            let mut command = vec![b"SET".to_vec(), self.key.to_owned(), self.value.to_owned()];
+            if let Some(expired) = ttl_state.expired_epoch(current) {
+                data_ttl = data_ttl.expired_timestamp(&expired);
+                command.extend([
+                    b"PXAT".to_vec(),
+                    expired.as_millis().to_string().into_bytes(),
+                ]);
             }
+            // the relative ttl is logged as an absolute time
+            data.rewrite_command(command);
         }
         data.insert(self.key.to_owned(), data_ttl);
         return_value
@@ -165,7 +131,7 @@ impl Execution for Set {
 #[cfg(test)]
 mod test_parse {
     use super::Set;
-    use crate::redis_protocol::cmd_set::TTLState;
+    use crate::redis_protocol::ttl_helper::TTLState;
     use core::time;
     use std::collections::VecDeque;
 
@@ -173,10 +139,10 @@ mod test_parse {
     fn test_parse_success() {
         // arrange
         let input = VecDeque::from(vec!["k", "v"]);
-        let input = input.iter().map(|x| x.to_string()).collect();
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
         let expected = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             ..Default::default()
         };
         // act
@@ -190,10 +156,10 @@ mod test_parse {
     fn test_parse_get_success() {
         // arrange
         let input = VecDeque::from(vec!["k", "v", "get"]);
-        let input = input.iter().map(|x| x.to_string()).collect();
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
         let expected = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             get: Some(()),
             ..Default::default()
         };
@@ -208,10 +174,10 @@ mod test_parse {
     fn test_parse_get_ex_success() {
         // arrange
         let input = VecDeque::from(vec!["k", "v", "ex", "5", "get"]);
-        let input = input.iter().map(|x| x.to_string()).collect();
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
         let expected = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             get: Some(()),
             ttl_state: Some(TTLState::Ttl(time::Duration::from_secs(5))),
             ..Default::default()
@@ -227,10 +193,10 @@ mod test_parse {
     fn test_parse_get_px_success() {
         // arrange
         let input = VecDeque::from(vec!["k", "v", "get", "px", "5"]);
-        let input = input.iter().map(|x| x.to_string()).collect();
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
         let expected = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             get: Some(()),
             ttl_state: Some(TTLState::Ttl(time::Duration::from_millis(5))),
             ..Default::default()
@@ -246,10 +212,10 @@ mod test_parse {
     fn test_parse_get_pxat_xx_success() {
         // arrange
         let input = VecDeque::from(vec!["k", "v", "get", "pxat", "5", "xx"]);
-        let input = input.iter().map(|x| x.to_string()).collect();
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
         let expected = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             get: Some(()),
             ttl_state: Some(TTLState::ExpiredTimestamp(time::Duration::from_millis(5))),
             key_exist_then_insert: Some(true),
@@ -265,10 +231,10 @@ mod test_parse {
     fn test_parse_get_exat_nx_success() {
         // arrange
         let input = VecDeque::from(vec!["k", "v", "get", "exat", "5", "nx"]);
-        let input = input.iter().map(|x| x.to_string()).collect();
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
         let expected = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             get: Some(()),
             ttl_state: Some(TTLState::ExpiredTimestamp(time::Duration::from_secs(5))),
             key_exist_then_insert: Some(false),
@@ -287,7 +253,7 @@ mod test_exec {
     use super::Set;
     use crate::{
         data_watcher::{execution::Execution, DataStorage, DataTTL},
-        redis_protocol::cmd_set::TTLState,
+        redis_protocol::ttl_helper::TTLState,
     };
     use resp::Value;
     use std::{
@@ -299,8 +265,8 @@ mod test_exec {
     fn test_exec_success() {
         // arrange
         let set_obj = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             ..Default::default()
         };
         let mut data = DataStorage::new();
@@ -318,14 +284,14 @@ mod test_exec {
     fn test_exec_get_success() {
         // arrange
         let set_obj = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             get: Some(()),
             ..Default::default()
         };
         let set_obj2 = Set {
-            key: "k".to_string(),
-            value: "v2".to_string(),
+            key: b"k".to_vec(),
+            value: b"v2".to_vec(),
             get: Some(()),
             ..Default::default()
         };
@@ -340,7 +306,7 @@ mod test_exec {
         );
         let result2 = set_obj2.exec(&mut data);
         // assert
-        assert_eq!(Value::String("v".to_string()), result2);
+        assert_eq!(Value::BufBulk(b"v".to_vec()), result2);
         assert_eq!(
             set_obj2.value,
             data.get(&set_obj2.key).unwrap().get().unwrap()
@@ -351,8 +317,8 @@ mod test_exec {
         // arrange
         let ttl = time::Duration::from_secs(1);
         let set_obj = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             ttl_state: Some(TTLState::Ttl(ttl.to_owned())),
             ..Default::default()
         };
@@ -372,16 +338,16 @@ mod test_exec {
         // arrange
         let ttl = time::Duration::from_secs(1);
         let set_obj = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             ttl_state: Some(TTLState::ExpiredTimestamp(
                 time::SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + ttl,
             )),
             ..Default::default()
         };
         let set_obj2 = Set {
-            key: "k".to_string(),
-            value: "v2".to_string(),
+            key: b"k".to_vec(),
+            value: b"v2".to_vec(),
             ttl_state: Some(TTLState::KeepTTL),
             ..Default::default()
         };
@@ -403,8 +369,8 @@ mod test_exec {
         // arrange
         let ttl = time::Duration::from_secs(1);
         let set_obj = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             ttl_state: Some(TTLState::ExpiredTimestamp(
                 time::SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + ttl,
             )),
@@ -425,13 +391,13 @@ mod test_exec {
     fn test_exec_xx_success() {
         // arrange
         let set_obj = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             key_exist_then_insert: Some(true),
             ..Default::default()
         };
         let mut data = DataStorage::new();
-        data.insert(set_obj.key.to_owned(), DataTTL::new("some v".to_string()));
+        data.insert(set_obj.key.to_owned(), DataTTL::new(b"some v".to_vec()));
 
         // act
         let result = set_obj.exec(&mut data);
@@ -444,8 +410,8 @@ mod test_exec {
     fn test_exec_xx_failed() {
         // arrange
         let set_obj = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             key_exist_then_insert: Some(true),
             ..Default::default()
         };
@@ -455,26 +421,26 @@ mod test_exec {
         let result = set_obj.exec(&mut data);
         // assert
         assert_eq!(Value::Null, result);
-        assert!(data.get(&set_obj.key).is_none());
+        assert!(!data.contains_key(&set_obj.key));
     }
     #[test]
     fn test_exec_nx_failed() {
         // arrange
         let set_obj = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             key_exist_then_insert: Some(false),
             ..Default::default()
         };
         let mut data = DataStorage::new();
-        data.insert(set_obj.key.to_owned(), DataTTL::new("some v".to_string()));
+        data.insert(set_obj.key.to_owned(), DataTTL::new(b"some v".to_vec()));
 
         // act
         let result = set_obj.exec(&mut data);
         // assert
         assert_eq!(Value::Null, result);
         assert_eq!(
-            "some v".to_string(),
+            b"some v".to_vec(),
             data.get(&set_obj.key).unwrap().get().unwrap()
         );
     }
@@ -482,8 +448,8 @@ mod test_exec {
     fn test_exec_nx_success() {
         // arrange
         let set_obj = Set {
-            key: "k".to_string(),
-            value: "v".to_string(),
+            key: b"k".to_vec(),
+            value: b"v".to_vec(),
             key_exist_then_insert: Some(false),
             ..Default::default()
         };
@@ -0,0 +1,224 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::data_watcher::data_value::DataValue;
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::{DataStorage, DataTTL};
+
+use anyhow::Result;
+use resp::Value;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SetOp {
+    Inter,
+    Union,
+    Diff,
+}
+
+// https://redis.io/commands/sinter/
+// https://redis.io/commands/sunion/
+// https://redis.io/commands/sdiff/
+// https://redis.io/commands/sinterstore/
+// https://redis.io/commands/sunionstore/
+// https://redis.io/commands/sdiffstore/
+#[derive(PartialEq, Debug)]
+pub struct SetOperation {
+    op: SetOp,
+    // the STORE variants write the result to the destination
+    destination: Option<Vec<u8>>,
+    keys: Vec<Vec<u8>>,
+}
+
+impl SetOperation {
+    pub fn parse(mut input: VecDeque<Vec<u8>>, op: SetOp, store: bool) -> Result<Box<Self>> {
+        let destination = if store { input.pop_front() } else { None };
+        anyhow::ensure!(
+            !input.is_empty(),
+            "wrong number of arguments for set operation"
+        );
+        Ok(Box::new(SetOperation {
+            op,
+            destination,
+            keys: input.into(),
+        }))
+    }
+
+    fn compute(&self, data: &mut DataStorage) -> Result<HashSet<Vec<u8>>, Value> {
+        // check all types first, a missing key is an empty set
+        let mut sets = Vec::new();
+        for key in &self.keys {
+            match data.get_typed::<HashSet<Vec<u8>>>(key) {
+                Ok(set) => sets.push(set.cloned().unwrap_or_default()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let mut sets = sets.into_iter();
+        let mut result = sets.next().unwrap_or_default();
+        for set in sets {
+            match self.op {
+                SetOp::Inter => result.retain(|x| set.contains(x)),
+                SetOp::Union => result.extend(set),
+                SetOp::Diff => result.retain(|x| !set.contains(x)),
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Execution for SetOperation {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let result = match self.compute(data) {
+            Ok(result) => result,
+            Err(e) => return e,
+        };
+        match &self.destination {
+            Some(destination) => {
+                let len = result.len();
+                // the destination is overwritten no matter the type, an empty result removes it
+                data.remove(destination);
+                if !result.is_empty() {
+                    data.insert(destination.to_owned(), DataTTL::new(DataValue::Set(result)));
+                }
+                Value::Integer(len as i64)
+            }
+            None => Value::Array(result.into_iter().map(Value::BufBulk).collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::{SetOp, SetOperation};
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_parse_store_success() {
+        // arrange
+        let input = VecDeque::from(vec!["dst", "k1", "k2"]);
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        let expected = SetOperation {
+            op: SetOp::Union,
+            destination: Some(b"dst".to_vec()),
+            keys: vec![b"k1".to_vec(), b"k2".to_vec()],
+        };
+        // act
+        let result = SetOperation::parse(input, SetOp::Union, true);
+        // assert
+        assert!(result.is_ok());
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_store_without_key() {
+        // arrange
+        let input = VecDeque::from(vec![b"dst".to_vec()]);
+        // act
+        let result = SetOperation::parse(input, SetOp::Inter, true);
+        // assert
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::{SetOp, SetOperation};
+    use crate::data_watcher::{data_value::DataValue, execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::collections::HashSet;
+
+    fn storage() -> DataStorage {
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<HashSet<Vec<u8>>>(b"k1")
+            .unwrap()
+            .extend(["a", "b", "c"].iter().map(|x| x.as_bytes().to_vec()));
+        data.get_typed_or_default::<HashSet<Vec<u8>>>(b"k2")
+            .unwrap()
+            .extend(["b", "c", "d"].iter().map(|x| x.as_bytes().to_vec()));
+        data
+    }
+
+    fn set(members: &[&str]) -> HashSet<Vec<u8>> {
+        members.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    fn result_set(result: Value) -> HashSet<Vec<u8>> {
+        let Value::Array(v) = result else {
+            unreachable!();
+        };
+        v.into_iter()
+            .map(|x| match x {
+                Value::BufBulk(x) => x,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    fn operation(op: SetOp, destination: Option<&str>) -> SetOperation {
+        SetOperation {
+            op,
+            destination: destination.map(|x| x.as_bytes().to_vec()),
+            keys: vec![b"k1".to_vec(), b"k2".to_vec()],
+        }
+    }
+
+    #[test]
+    fn test_exec_inter_union_diff() {
+        // arrange
+        let mut data = storage();
+        // act & assert
+        assert_eq!(
+            set(&["b", "c"]),
+            result_set(operation(SetOp::Inter, None).exec(&mut data))
+        );
+        assert_eq!(
+            set(&["a", "b", "c", "d"]),
+            result_set(operation(SetOp::Union, None).exec(&mut data))
+        );
+        assert_eq!(
+            set(&["a"]),
+            result_set(operation(SetOp::Diff, None).exec(&mut data))
+        );
+    }
+
+    #[test]
+    fn test_exec_store_overwrite_destination() {
+        // arrange
+        let mut data = storage();
+        data.insert(b"dst".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let result = operation(SetOp::Inter, Some("dst")).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(2), result);
+        assert_eq!(
+            Some(&mut DataValue::Set(set(&["b", "c"]))),
+            data.get_value(b"dst")
+        );
+    }
+
+    #[test]
+    fn test_exec_store_empty_result_remove_destination() {
+        // arrange
+        let mut data = storage();
+        let sinterstore = SetOperation {
+            op: SetOp::Inter,
+            destination: Some(b"k1".to_vec()),
+            keys: vec![b"k1".to_vec(), b"missing".to_vec()],
+        };
+        // act
+        let result = sinterstore.exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(0), result);
+        assert!(!data.exists(b"k1"));
+    }
+
+    #[test]
+    fn test_exec_wrong_type() {
+        // arrange
+        let mut data = storage();
+        data.insert(b"k2".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act
+        let result = operation(SetOp::Union, Some("dst")).exec(&mut data);
+        // assert
+        assert!(result.is_error());
+        assert!(!data.exists(b"dst"));
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+use resp::Value;
+
+// same as the max bulk length
+const MAX_STRING_LENGTH: usize = 512 * 1024 * 1024;
+
+// https://redis.io/commands/setrange/
+#[derive(Default, PartialEq, Debug)]
+pub struct SetRange {
+    key: Vec<u8>,
+    offset: usize,
+    value: Vec<u8>,
+}
+
+impl SetRange {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for setrange");
+        let offset = parse_integer::<usize>(&input[1])
+            .ok()
+            .filter(|x| *x <= MAX_STRING_LENGTH)
+            .ok_or_else(|| anyhow::anyhow!("offset is out of range"))?;
+        anyhow::ensure!(
+            offset
+                .checked_add(input[2].len())
+                .is_some_and(|x| x <= MAX_STRING_LENGTH),
+            "string exceeds maximum allowed size (proto-max-bulk-len)"
+        );
+        Ok(Box::new(SetRange {
+            key: input[0].to_owned(),
+            offset,
+            value: input[2].to_owned(),
+        }))
+    }
+}
+
+impl Execution for SetRange {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        // an empty value doesn't create the key
+        if self.value.is_empty() {
+            return match data.get_typed::<Vec<u8>>(&self.key) {
+                Ok(value) => Value::Integer(value.map_or(0, |x| x.len()) as i64),
+                Err(e) => e.into(),
+            };
+        }
+        let value = match data.get_typed_or_default::<Vec<u8>>(&self.key) {
+            Ok(value) => value,
+            Err(e) => return e.into(),
+        };
+        // pad with zero bytes when the offset is beyond the end
+        let end = self.offset + self.value.len();
+        if value.len() < end {
+            value.resize(end, 0);
+        }
+        value[self.offset..end].copy_from_slice(&self.value);
+        Value::Integer(value.len() as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::SetRange;
+    use std::collections::VecDeque;
+
+    fn input(input: &[&str]) -> VecDeque<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert_eq!(
+            "offset is out of range",
+            SetRange::parse(input(&["k", "18446744073709551615", "x"]))
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "offset is out of range",
+            SetRange::parse(input(&["k", "-1", "x"]))
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "string exceeds maximum allowed size (proto-max-bulk-len)",
+            SetRange::parse(input(&["k", "536870912", "x"]))
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(SetRange::parse(input(&["k", "536870912", ""])).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::SetRange;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+
+    fn setrange(key: &str, offset: usize, value: &str) -> SetRange {
+        SetRange {
+            key: key.as_bytes().to_vec(),
+            offset,
+            value: value.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"k".to_vec(), DataTTL::new(b"Hello World".to_vec()));
+        // act
+        let result = setrange("k", 6, "Redis").exec(&mut data);
+        let result2 = setrange("pad", 2, "ab").exec(&mut data);
+        let result3 = setrange("empty", 2, "").exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(11), result);
+        assert_eq!(
+            Ok(Some(&b"Hello Redis".to_vec())),
+            data.get_typed::<Vec<u8>>(b"k")
+        );
+        assert_eq!(Value::Integer(4), result2);
+        assert_eq!(
+            Ok(Some(&b"\0\0ab".to_vec())),
+            data.get_typed::<Vec<u8>>(b"pad")
+        );
+        assert_eq!(Value::Integer(0), result3);
+        assert!(!data.exists(b"empty"));
+    }
+}
@@ -0,0 +1,33 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/sismember/
+#[derive(Default, PartialEq, Debug)]
+pub struct SIsMember {
+    key: Vec<u8>,
+    member: Vec<u8>,
+}
+
+impl SIsMember {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 2, "wrong number of arguments for sismember");
+        Ok(Box::new(SIsMember {
+            key: input[0].to_owned(),
+            member: input[1].to_owned(),
+        }))
+    }
+}
+
+impl Execution for SIsMember {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<HashSet<Vec<u8>>>(&self.key) {
+            Ok(set) => Value::Integer(set.is_some_and(|x| x.contains(&self.member)) as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/smembers/
+#[derive(Default, PartialEq, Debug)]
+pub struct SMembers {
+    key: Vec<u8>,
+}
+
+impl SMembers {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for smembers");
+        Ok(Box::new(SMembers {
+            key: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for SMembers {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<HashSet<Vec<u8>>>(&self.key) {
+            Ok(set) => Value::Array(
+                set.into_iter()
+                    .flatten()
+                    .map(|x| Value::BufBulk(x.to_owned()))
+                    .collect(),
+            ),
+            Err(e) => e.into(),
+        }
+    }
+}
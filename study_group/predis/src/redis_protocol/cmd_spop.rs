@@ -0,0 +1,93 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+use rand::seq::IteratorRandom;
+use resp::Value;
+
+// https://redis.io/commands/spop/
+#[derive(Default, PartialEq, Debug)]
+pub struct SPop {
+    key: Vec<u8>,
+    count: Option<usize>,
+}
+
+impl SPop {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            input.len() == 1 || input.len() == 2,
+            "wrong number of arguments for spop"
+        );
+        let key = input.pop_front().unwrap();
+        let count = match input.pop_front() {
+            Some(count) => Some(
+                parse_integer::<usize>(&count)
+                    .map_err(|_| anyhow::anyhow!("value is out of range, must be positive"))?,
+            ),
+            None => None,
+        };
+        Ok(Box::new(SPop { key, count }))
+    }
+}
+
+impl Execution for SPop {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let set = match data.get_typed_mut::<HashSet<Vec<u8>>>(&self.key) {
+            Ok(Some(set)) => set,
+            Ok(None) if self.count.is_some() => return Value::Array(Vec::new()),
+            Ok(None) => return Value::Null,
+            Err(e) => return e.into(),
+        };
+        let members: Vec<Vec<u8>> = set
+            .iter()
+            .cloned()
+            .choose_multiple(&mut rand::thread_rng(), self.count.unwrap_or(1));
+        for member in &members {
+            set.remove(member);
+        }
+        data.remove_if_empty(&self.key);
+        // the random members are logged so the replay removes the same ones
+        if !members.is_empty() {
+            let mut command = vec![b"SREM".to_vec(), self.key.to_owned()];
+            command.extend(members.iter().cloned());
+            data.rewrite_command(command);
+        }
+        let mut members = members.into_iter().map(Value::BufBulk);
+        match self.count {
+            Some(_) => Value::Array(members.collect()),
+            None => members.next().unwrap_or(Value::Null),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::SPop;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_exec_pop_all() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<HashSet<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(["a", "b", "c"].iter().map(|x| x.as_bytes().to_vec()));
+        let spop = SPop {
+            key: b"k".to_vec(),
+            count: Some(2),
+        };
+        // act
+        let result = spop.exec(&mut data);
+        let result2 = spop.exec(&mut data);
+        // assert
+        assert!(matches!(result, Value::Array(v) if v.len() == 2));
+        assert!(matches!(result2, Value::Array(v) if v.len() == 1));
+        assert!(!data.exists(b"k"));
+        assert_eq!(Value::Array(Vec::new()), spop.exec(&mut data));
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+use rand::seq::{IteratorRandom, SliceRandom};
+use resp::Value;
+
+// the reply of a negative count is built in the shard, the repeated members are bounded so a
+// huge count doesn't run it out of memory
+const MAX_REPEATED_COUNT: i64 = 1 << 20;
+
+// https://redis.io/commands/srandmember/
+#[derive(Default, PartialEq, Debug)]
+pub struct SRandMember {
+    key: Vec<u8>,
+    // positive count: distinct members, negative count: the same member may be returned multiple times
+    count: Option<i64>,
+}
+
+impl SRandMember {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            input.len() == 1 || input.len() == 2,
+            "wrong number of arguments for srandmember"
+        );
+        let key = input.pop_front().unwrap();
+        let count = match input.pop_front() {
+            Some(count) => Some(parse_integer::<i64>(&count)?),
+            None => None,
+        };
+        anyhow::ensure!(
+            count.is_none_or(|x| x >= -MAX_REPEATED_COUNT),
+            "value is out of range"
+        );
+        Ok(Box::new(SRandMember { key, count }))
+    }
+}
+
+impl Execution for SRandMember {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let set = match data.get_typed::<HashSet<Vec<u8>>>(&self.key) {
+            Ok(Some(set)) => set,
+            Ok(None) if self.count.is_some() => return Value::Array(Vec::new()),
+            Ok(None) => return Value::Null,
+            Err(e) => return e.into(),
+        };
+        let mut rng = rand::thread_rng();
+        match self.count {
+            None => match set.iter().choose(&mut rng) {
+                Some(member) => Value::BufBulk(member.to_owned()),
+                None => Value::Null,
+            },
+            Some(count) if count >= 0 => Value::Array(
+                set.iter()
+                    .choose_multiple(&mut rng, set.len().min(count as usize))
+                    .into_iter()
+                    .map(|x| Value::BufBulk(x.to_owned()))
+                    .collect(),
+            ),
+            Some(count) => {
+                let members: Vec<&Vec<u8>> = set.iter().collect();
+                Value::Array(
+                    (0..count.unsigned_abs())
+                        .filter_map(|_| members.choose(&mut rng))
+                        .map(|x| Value::BufBulk(x.to_vec()))
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::SRandMember;
+    use std::collections::VecDeque;
+
+    fn input(input: &[&str]) -> VecDeque<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_count_out_of_range() {
+        assert_eq!(
+            "value is out of range",
+            SRandMember::parse(input(&["k", "-9223372036854775808"]))
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(SRandMember::parse(input(&["k", "-4611686018427387903"])).is_err());
+        assert!(SRandMember::parse(input(&["k", "-1048577"])).is_err());
+        assert!(SRandMember::parse(input(&["k", "-1048576"])).is_ok());
+        assert!(SRandMember::parse(input(&["k", "9223372036854775807"])).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::SRandMember;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::HashSet;
+
+    fn srandmember(count: Option<i64>) -> SRandMember {
+        SRandMember {
+            key: b"k".to_vec(),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_exec() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<HashSet<Vec<u8>>>(b"k")
+            .unwrap()
+            .extend(["a", "b"].iter().map(|x| x.as_bytes().to_vec()));
+        // act & assert
+        assert!(matches!(
+            srandmember(None).exec(&mut data),
+            Value::BufBulk(_)
+        ));
+        assert!(matches!(srandmember(Some(5)).exec(&mut data), Value::Array(v) if v.len() == 2));
+        assert!(matches!(srandmember(Some(-5)).exec(&mut data), Value::Array(v) if v.len() == 5));
+        assert!(
+            matches!(srandmember(Some(i64::MAX)).exec(&mut data), Value::Array(v) if v.len() == 2)
+        );
+        assert_eq!(
+            2,
+            data.get_typed::<HashSet<Vec<u8>>>(b"k")
+                .unwrap()
+                .unwrap()
+                .len()
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/srem/
+#[derive(Default, PartialEq, Debug)]
+pub struct SRem {
+    key: Vec<u8>,
+    members: Vec<Vec<u8>>,
+}
+
+impl SRem {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 2, "wrong number of arguments for srem");
+        Ok(Box::new(SRem {
+            key: input.pop_front().unwrap(),
+            members: input.into(),
+        }))
+    }
+}
+
+impl Execution for SRem {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let set = match data.get_typed_mut::<HashSet<Vec<u8>>>(&self.key) {
+            Ok(Some(set)) => set,
+            Ok(None) => return Value::Integer(0),
+            Err(e) => return e.into(),
+        };
+        let removed = self.members.iter().filter(|x| set.remove(*x)).count();
+        data.remove_if_empty(&self.key);
+        Value::Integer(removed as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::SRem;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_exec_remove_empty_set() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<HashSet<Vec<u8>>>(b"k")
+            .unwrap()
+            .insert(b"a".to_vec());
+        let srem = SRem {
+            key: b"k".to_vec(),
+            members: vec![b"a".to_vec(), b"b".to_vec()],
+        };
+        // act
+        let result = srem.exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(1), result);
+        assert!(!data.exists(b"k"));
+    }
+}
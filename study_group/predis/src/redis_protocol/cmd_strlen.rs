@@ -0,0 +1,31 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/strlen/
+#[derive(Default, PartialEq, Debug)]
+pub struct StrLen {
+    key: Vec<u8>,
+}
+
+impl StrLen {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for strlen");
+        Ok(Box::new(StrLen {
+            key: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for StrLen {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<Vec<u8>>(&self.key) {
+            Ok(value) => Value::Integer(value.map_or(0, |x| x.len()) as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
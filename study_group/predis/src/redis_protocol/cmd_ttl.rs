@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::{epoch_now, DataStorage};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/ttl/
+// https://redis.io/commands/pttl/
+// https://redis.io/commands/expiretime/
+// https://redis.io/commands/pexpiretime/
+#[derive(Default, PartialEq, Debug)]
+pub struct Ttl {
+    key: Vec<u8>,
+    millis: bool,
+    // return the unix time instead of the remaining time
+    absolute: bool,
+}
+
+impl Ttl {
+    pub fn parse(input: VecDeque<Vec<u8>>, millis: bool, absolute: bool) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for ttl");
+        Ok(Box::new(Ttl {
+            key: input[0].to_owned(),
+            millis,
+            absolute,
+        }))
+    }
+}
+
+impl Execution for Ttl {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        // -2 if the key does not exist, -1 if the key exists but has no associated expire
+        let Some(data_ttl) = data.get_live(&self.key) else {
+            return Value::Integer(-2);
+        };
+        let Some(expired) = data_ttl.expired_epoch() else {
+            return Value::Integer(-1);
+        };
+        let time_ms = if self.absolute {
+            expired.as_millis()
+        } else {
+            expired.saturating_sub(epoch_now()).as_millis()
+        };
+        let time = match (self.millis, self.absolute) {
+            (true, _) => time_ms,
+            (false, true) => time_ms / 1000,
+            // round the remaining time like redis
+            (false, false) => (time_ms + 500) / 1000,
+        };
+        Value::Integer(time as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::Ttl;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::time;
+
+    fn ttl(key: &str, millis: bool, absolute: bool) -> Ttl {
+        Ttl {
+            key: key.as_bytes().to_vec(),
+            millis,
+            absolute,
+        }
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(
+            b"k".to_vec(),
+            DataTTL::new(b"v".to_vec()).ttl(&time::Duration::from_secs(100)),
+        );
+        data.insert(
+            b"at".to_vec(),
+            DataTTL::new(b"v".to_vec()).expired_timestamp(&time::Duration::from_secs(4102444800)),
+        );
+        data.insert(b"persistent".to_vec(), DataTTL::new(b"v".to_vec()));
+        // act & assert
+        assert_eq!(Value::Integer(100), ttl("k", false, false).exec(&mut data));
+        assert!(
+            matches!(ttl("k", true, false).exec(&mut data), Value::Integer(v) if v > 99000 && v <= 100000)
+        );
+        assert_eq!(
+            Value::Integer(4102444800),
+            ttl("at", false, true).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(4102444800000),
+            ttl("at", true, true).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(-1),
+            ttl("persistent", false, false).exec(&mut data)
+        );
+        assert_eq!(Value::Integer(-2), ttl("none", true, true).exec(&mut data));
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/type/
+#[derive(Default, PartialEq, Debug)]
+pub struct Type {
+    key: Vec<u8>,
+}
+
+impl Type {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for type");
+        Ok(Box::new(Type {
+            key: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for Type {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_value(&self.key) {
+            Some(v) => Value::String(v.type_name().to_string()),
+            None => Value::String("none".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Type;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_exec() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"s".to_vec(), DataTTL::new(b"v".to_vec()));
+        data.get_typed_or_default::<HashSet<Vec<u8>>>(b"set")
+            .unwrap()
+            .insert(b"m".to_vec());
+        let cmd = |key: &str| Type {
+            key: key.as_bytes().to_vec(),
+        };
+        // act & assert
+        assert_eq!(
+            Value::String("string".to_string()),
+            cmd("s").exec(&mut data)
+        );
+        assert_eq!(Value::String("set".to_string()), cmd("set").exec(&mut data));
+        assert_eq!(Value::String("none".to_string()), cmd("k").exec(&mut data));
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/watch/
+// reply the versions of the keys, the connection keeps them for EXEC
+#[derive(Default, PartialEq, Debug)]
+pub struct Watch {
+    keys: Vec<Vec<u8>>,
+}
+
+impl Watch {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(!input.is_empty(), "wrong number of arguments for watch");
+        Ok(Box::new(Watch { keys: input.into() }))
+    }
+}
+
+impl Execution for Watch {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        Value::Array(
+            self.keys
+                .iter()
+                .map(|key| Value::Integer(data.watch(key) as i64))
+                .collect(),
+        )
+    }
+}
+
+// https://redis.io/commands/unwatch/
+// the keys are the ones watched by the connection
+#[derive(Default, PartialEq, Debug)]
+pub struct Unwatch {
+    keys: Vec<Vec<u8>>,
+}
+
+impl Unwatch {
+    pub fn new(keys: Vec<Vec<u8>>) -> Box<Self> {
+        Box::new(Unwatch { keys })
+    }
+}
+
+impl Execution for Unwatch {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        self.keys.iter().for_each(|key| data.unwatch(key));
+        Value::String("OK".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::{Unwatch, Watch};
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_exec_watch_and_unwatch() {
+        // arrange
+        let mut data = DataStorage::new();
+        let watch = Watch::parse(VecDeque::from([b"k".to_vec()])).unwrap();
+        // act
+        let Value::Array(versions) = watch.exec(&mut data) else {
+            panic!("array expected");
+        };
+        let Value::Integer(version) = versions[0] else {
+            panic!("integer expected");
+        };
+        data.insert(b"k".to_vec(), DataTTL::new(b"v".to_vec()));
+        // assert
+        assert!(data.is_watched_key_changed(b"k", version as u64));
+        Unwatch::new(vec![b"k".to_vec()]).exec(&mut data);
+        assert!(data.is_watched_key_changed(b"k", version as u64 + 1));
+    }
+}
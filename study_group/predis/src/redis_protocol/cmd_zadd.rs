@@ -0,0 +1,265 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::zset_helper;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/zadd/
+#[derive(Default, PartialEq, Debug)]
+pub struct ZAdd {
+    key: Vec<u8>,
+    elements: Vec<(f64, Vec<u8>)>,
+    key_exist_then_update: Option<bool>,
+    compare: Option<Compare>,
+    ch: bool,
+    incr: bool,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Compare {
+    // GT -- Only update existing elements if the new score is greater than the current score.
+    Greater,
+    // LT -- Only update existing elements if the new score is less than the current score.
+    Less,
+}
+
+impl ZAdd {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 3, "wrong number of arguments for zadd");
+        let mut zadd_obj = ZAdd {
+            key: input.pop_front().unwrap(),
+            ..Default::default()
+        };
+        // options are before the score member pairs
+        while let Some(token) = input.front().map(|x| x.to_ascii_lowercase()) {
+            match token.as_slice() {
+                b"nx" | b"xx" => {
+                    let exist = Some(token == b"xx");
+                    anyhow::ensure!(
+                        zadd_obj.key_exist_then_update.is_none()
+                            || zadd_obj.key_exist_then_update == exist,
+                        "XX and NX options at the same time are not compatible"
+                    );
+                    zadd_obj.key_exist_then_update = exist;
+                }
+                b"gt" | b"lt" => {
+                    let compare = if token == b"gt" {
+                        Compare::Greater
+                    } else {
+                        Compare::Less
+                    };
+                    anyhow::ensure!(
+                        zadd_obj.compare.is_none() || zadd_obj.compare == Some(compare),
+                        "GT, LT, and/or NX options at the same time are not compatible"
+                    );
+                    zadd_obj.compare = Some(compare);
+                }
+                b"ch" => zadd_obj.ch = true,
+                b"incr" => zadd_obj.incr = true,
+                _ => break,
+            }
+            input.pop_front();
+        }
+        anyhow::ensure!(
+            !input.is_empty() && input.len().is_multiple_of(2),
+            "syntax error"
+        );
+        let nx = zadd_obj.key_exist_then_update == Some(false);
+        anyhow::ensure!(
+            !(nx && zadd_obj.compare.is_some()),
+            "GT, LT, and/or NX options at the same time are not compatible"
+        );
+        anyhow::ensure!(
+            !zadd_obj.incr || input.len() == 2,
+            "INCR option supports a single increment-element pair"
+        );
+        while let (Some(score), Some(member)) = (input.pop_front(), input.pop_front()) {
+            zadd_obj
+                .elements
+                .push((zset_helper::parse_score(&score)?, member));
+        }
+        Ok(Box::new(zadd_obj))
+    }
+
+    // the new score of the member, None when the options skip the element
+    fn update_score(&self, current: Option<f64>, score: f64) -> Result<Option<f64>, Value> {
+        let Some(current) = current else {
+            return Ok((self.key_exist_then_update != Some(true)).then_some(score));
+        };
+        if self.key_exist_then_update == Some(false) {
+            return Ok(None);
+        }
+        let score = if self.incr { current + score } else { score };
+        if score.is_nan() {
+            return Err(Value::Error(
+                "ERR resulting score is not a number (NaN)".to_string(),
+            ));
+        }
+        Ok(match self.compare {
+            Some(Compare::Greater) if score <= current => None,
+            Some(Compare::Less) if score >= current => None,
+            _ => Some(score),
+        })
+    }
+}
+
+impl Execution for ZAdd {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let zset = match data.get_typed_or_default::<SortedSet>(&self.key) {
+            Ok(zset) => zset,
+            Err(e) => return e.into(),
+        };
+        let mut added = 0;
+        let mut changed = 0;
+        let mut incr_result = Value::Null;
+        for (score, member) in self.elements.iter() {
+            let current = zset.score(member);
+            let score = match self.update_score(current, *score) {
+                Ok(Some(score)) => score,
+                Ok(None) => continue,
+                Err(e) => {
+                    data.remove_if_empty(&self.key);
+                    return e;
+                }
+            };
+            if current.is_none() {
+                added += 1;
+            } else if current != Some(score) {
+                changed += 1;
+            }
+            zset.insert(member.to_owned(), score);
+            incr_result = Value::BufBulk(zset_helper::format_score(score).into_bytes());
+        }
+        // NX/XX may leave the new key empty
+        data.remove_if_empty(&self.key);
+        if self.incr {
+            incr_result
+        } else if self.ch {
+            Value::Integer(added + changed)
+        } else {
+            Value::Integer(added)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::{Compare, ZAdd};
+    use std::collections::VecDeque;
+
+    fn input(args: &[&str]) -> VecDeque<Vec<u8>> {
+        args.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        // arrange
+        let expected = ZAdd {
+            key: b"k".to_vec(),
+            elements: vec![(1.0, b"a".to_vec()), (2.5, b"b".to_vec())],
+            key_exist_then_update: Some(true),
+            compare: Some(Compare::Greater),
+            ch: true,
+            incr: false,
+        };
+        // act
+        let result = ZAdd::parse(input(&["k", "XX", "gt", "ch", "1", "a", "2.5", "b"]));
+        // assert
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert!(ZAdd::parse(input(&["k", "1", "a", "2"])).is_err());
+        assert!(ZAdd::parse(input(&["k", "nx", "gt", "1", "a"])).is_err());
+        assert!(ZAdd::parse(input(&["k", "incr", "1", "a", "2", "b"])).is_err());
+        assert!(ZAdd::parse(input(&["k", "x", "a"])).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::ZAdd;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn zadd(args: &[&str]) -> Box<ZAdd> {
+        ZAdd::parse(
+            args.iter()
+                .map(|x| x.as_bytes().to_vec())
+                .collect::<VecDeque<_>>(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_exec_add_and_change() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = zadd(&["k", "1", "a", "2", "b"]).exec(&mut data);
+        let result2 = zadd(&["k", "ch", "3", "a", "2", "b", "1", "c"]).exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(2), result);
+        assert_eq!(Value::Integer(2), result2);
+    }
+
+    #[test]
+    fn test_exec_gt_lt_nx_xx() {
+        // arrange
+        let mut data = DataStorage::new();
+        zadd(&["k", "5", "a"]).exec(&mut data);
+        // act & assert
+        assert_eq!(
+            Value::Integer(0),
+            zadd(&["k", "ch", "gt", "1", "a"]).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(1),
+            zadd(&["k", "ch", "lt", "1", "a"]).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(0),
+            zadd(&["k", "xx", "1", "b"]).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(0),
+            zadd(&["k", "nx", "9", "a"]).exec(&mut data)
+        );
+        assert_eq!(
+            Value::Integer(0),
+            zadd(&["other", "xx", "1", "b"]).exec(&mut data)
+        );
+        assert!(!data.exists(b"other"));
+    }
+
+    #[test]
+    fn test_exec_incr() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = zadd(&["k", "incr", "1.5", "a"]).exec(&mut data);
+        let result2 = zadd(&["k", "incr", "1.5", "a"]).exec(&mut data);
+        let result3 = zadd(&["k", "incr", "gt", "-1", "a"]).exec(&mut data);
+        // assert
+        assert_eq!(Value::BufBulk(b"1.5".to_vec()), result);
+        assert_eq!(Value::BufBulk(b"3".to_vec()), result2);
+        assert_eq!(Value::Null, result3);
+    }
+
+    #[test]
+    fn test_exec_incr_nan() {
+        // arrange
+        let mut data = DataStorage::new();
+        zadd(&["k", "+inf", "a"]).exec(&mut data);
+        // act
+        let result = zadd(&["k", "incr", "-inf", "a"]).exec(&mut data);
+        // assert
+        assert!(result.is_error());
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/zcard/
+#[derive(Default, PartialEq, Debug)]
+pub struct ZCard {
+    key: Vec<u8>,
+}
+
+impl ZCard {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 1, "wrong number of arguments for zcard");
+        Ok(Box::new(ZCard {
+            key: input[0].to_owned(),
+        }))
+    }
+}
+
+impl Execution for ZCard {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<SortedSet>(&self.key) {
+            Ok(zset) => Value::Integer(zset.map_or(0, |x| x.len()) as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
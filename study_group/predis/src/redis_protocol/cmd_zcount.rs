@@ -0,0 +1,66 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::skip_list::ScoreRange;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::zset_helper;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/zcount/
+#[derive(PartialEq, Debug)]
+pub struct ZCount {
+    key: Vec<u8>,
+    range: ScoreRange,
+}
+
+impl ZCount {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for zcount");
+        Ok(Box::new(ZCount {
+            key: input[0].to_owned(),
+            range: zset_helper::parse_score_range(&input[1], &input[2])?,
+        }))
+    }
+}
+
+impl Execution for ZCount {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<SortedSet>(&self.key) {
+            Ok(zset) => Value::Integer(zset.map_or(0, |x| x.count_by_score(&self.range)) as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::ZCount;
+    use crate::data_watcher::{execution::Execution, sorted_set::SortedSet, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        let zset = data.get_typed_or_default::<SortedSet>(b"k").unwrap();
+        for i in 0..100 {
+            zset.insert(format!("m{i}").into_bytes(), i as f64);
+        }
+        let zcount = |min: &str, max: &str| {
+            let input = VecDeque::from(vec![
+                b"k".to_vec(),
+                min.as_bytes().to_vec(),
+                max.as_bytes().to_vec(),
+            ]);
+            ZCount::parse(input).unwrap()
+        };
+        // act & assert
+        assert_eq!(Value::Integer(100), zcount("-inf", "+inf").exec(&mut data));
+        assert_eq!(Value::Integer(9), zcount("(10", "(20").exec(&mut data));
+        assert_eq!(Value::Integer(0), zcount("5", "1").exec(&mut data));
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::zset_helper;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/zincrby/
+#[derive(Default, PartialEq, Debug)]
+pub struct ZIncrBy {
+    key: Vec<u8>,
+    increment: f64,
+    member: Vec<u8>,
+}
+
+impl ZIncrBy {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 3, "wrong number of arguments for zincrby");
+        Ok(Box::new(ZIncrBy {
+            key: input[0].to_owned(),
+            increment: zset_helper::parse_score(&input[1])?,
+            member: input[2].to_owned(),
+        }))
+    }
+}
+
+impl Execution for ZIncrBy {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let zset = match data.get_typed_or_default::<SortedSet>(&self.key) {
+            Ok(zset) => zset,
+            Err(e) => return e.into(),
+        };
+        let score = zset.score(&self.member).unwrap_or_default() + self.increment;
+        if score.is_nan() {
+            data.remove_if_empty(&self.key);
+            return Value::Error("ERR resulting score is not a number (NaN)".to_string());
+        }
+        zset.insert(self.member.to_owned(), score);
+        Value::BufBulk(zset_helper::format_score(score).into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::ZIncrBy;
+    use crate::data_watcher::{execution::Execution, DataStorage};
+    use resp::Value;
+
+    fn zincrby(increment: f64) -> ZIncrBy {
+        ZIncrBy {
+            key: b"k".to_vec(),
+            increment,
+            member: b"a".to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        // act
+        let result = zincrby(2.5).exec(&mut data);
+        let result2 = zincrby(-1.0).exec(&mut data);
+        // assert
+        assert_eq!(Value::BufBulk(b"2.5".to_vec()), result);
+        assert_eq!(Value::BufBulk(b"1.5".to_vec()), result2);
+    }
+
+    #[test]
+    fn test_exec_nan() {
+        // arrange
+        let mut data = DataStorage::new();
+        zincrby(f64::INFINITY).exec(&mut data);
+        // act
+        let result = zincrby(f64::NEG_INFINITY).exec(&mut data);
+        // assert
+        assert!(result.is_error());
+    }
+}
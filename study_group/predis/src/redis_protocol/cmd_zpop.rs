@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::{parse_integer, zset_helper};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/zpopmin/
+// https://redis.io/commands/zpopmax/
+#[derive(Default, PartialEq, Debug)]
+pub struct ZPop {
+    key: Vec<u8>,
+    count: usize,
+    highest: bool,
+}
+
+impl ZPop {
+    pub fn parse(input: VecDeque<Vec<u8>>, highest: bool) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            input.len() == 1 || input.len() == 2,
+            "wrong number of arguments for zpopmin"
+        );
+        let count = match input.get(1) {
+            Some(count) => parse_integer::<usize>(count)
+                .map_err(|_| anyhow::anyhow!("value is out of range, must be positive"))?,
+            None => 1,
+        };
+        Ok(Box::new(ZPop {
+            key: input[0].to_owned(),
+            count,
+            highest,
+        }))
+    }
+}
+
+impl Execution for ZPop {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let zset = match data.get_typed_mut::<SortedSet>(&self.key) {
+            Ok(Some(zset)) => zset,
+            Ok(None) => return Value::Array(Vec::new()),
+            Err(e) => return e.into(),
+        };
+        let popped = zset.pop(self.count, self.highest);
+        data.remove_if_empty(&self.key);
+        zset_helper::members_reply(popped.iter().map(|(member, score)| (member, *score)), true)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::ZPop;
+    use crate::data_watcher::{execution::Execution, sorted_set::SortedSet, DataStorage};
+    use resp::Value;
+
+    #[test]
+    fn test_exec_pop_all() {
+        // arrange
+        let mut data = DataStorage::new();
+        let zset = data.get_typed_or_default::<SortedSet>(b"k").unwrap();
+        zset.insert(b"a".to_vec(), 1.0);
+        zset.insert(b"b".to_vec(), 2.0);
+        let zpop = ZPop {
+            key: b"k".to_vec(),
+            count: 5,
+            highest: false,
+        };
+        // act
+        let result = zpop.exec(&mut data);
+        // assert
+        assert_eq!(
+            Value::Array(vec![
+                Value::BufBulk(b"a".to_vec()),
+                Value::BufBulk(b"1".to_vec()),
+                Value::BufBulk(b"b".to_vec()),
+                Value::BufBulk(b"2".to_vec()),
+            ]),
+            result
+        );
+        assert!(!data.exists(b"k"));
+        assert_eq!(Value::Array(Vec::new()), zpop.exec(&mut data));
+    }
+
+    #[test]
+    fn test_exec_pop_zero() {
+        // arrange
+        let mut data = DataStorage::new();
+        let zset = data.get_typed_or_default::<SortedSet>(b"k").unwrap();
+        zset.insert(b"a".to_vec(), 1.0);
+        let zpop = ZPop {
+            key: b"k".to_vec(),
+            count: 0,
+            highest: true,
+        };
+        // act
+        let result = zpop.exec(&mut data);
+        // assert
+        assert_eq!(Value::Array(Vec::new()), result);
+        assert_eq!(1, data.get_typed::<SortedSet>(b"k").unwrap().unwrap().len());
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::skip_list::{LexRange, ScoreRange};
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::{list_helper, parse_integer, zset_helper};
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/zrange/
+// ZRANGEBYSCORE, ZREVRANGEBYSCORE, ZREVRANGE and ZRANGEBYLEX are the fixed forms of ZRANGE
+#[derive(PartialEq, Debug)]
+pub struct ZRange {
+    key: Vec<u8>,
+    range: RangeBy,
+    reverse: bool,
+    // LIMIT offset count, negative count returns all elements from the offset
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum RangeKind {
+    Rank,
+    Score,
+    Lex,
+}
+
+#[derive(PartialEq, Debug)]
+enum RangeBy {
+    Rank(i64, i64),
+    Score(ScoreRange),
+    Lex(LexRange),
+}
+
+impl ZRange {
+    // kind is None for ZRANGE which selects the kind with BYSCORE/BYLEX and the order with REV
+    pub fn parse(
+        mut input: VecDeque<Vec<u8>>,
+        kind: Option<RangeKind>,
+        reverse: bool,
+    ) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 3, "wrong number of arguments for zrange");
+        let key = input.pop_front().unwrap();
+        let start = input.pop_front().unwrap();
+        let stop = input.pop_front().unwrap();
+        let mut range_kind = kind.unwrap_or(RangeKind::Rank);
+        let mut reverse = reverse;
+        let mut limit = None;
+        let mut with_scores = false;
+        while let Some(token) = input.pop_front() {
+            match token.to_ascii_lowercase().as_slice() {
+                b"withscores" => with_scores = true,
+                b"limit" => {
+                    let (Some(offset), Some(count)) = (input.pop_front(), input.pop_front()) else {
+                        anyhow::bail!("syntax error");
+                    };
+                    limit = Some((parse_integer(&offset)?, parse_integer(&count)?));
+                }
+                b"byscore" if kind.is_none() => range_kind = RangeKind::Score,
+                b"bylex" if kind.is_none() => range_kind = RangeKind::Lex,
+                b"rev" if kind.is_none() => reverse = true,
+                _ => anyhow::bail!("syntax error"),
+            }
+        }
+        anyhow::ensure!(
+            limit.is_none() || range_kind != RangeKind::Rank,
+            "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+        );
+        anyhow::ensure!(
+            !with_scores || range_kind != RangeKind::Lex,
+            "syntax error, WITHSCORES not supported in combination with BYLEX"
+        );
+        // the reverse score and lex range is given as max then min
+        let (min, max) = if reverse && range_kind != RangeKind::Rank {
+            (stop, start)
+        } else {
+            (start, stop)
+        };
+        let range = match range_kind {
+            RangeKind::Rank => RangeBy::Rank(parse_integer(&min)?, parse_integer(&max)?),
+            RangeKind::Score => RangeBy::Score(zset_helper::parse_score_range(&min, &max)?),
+            RangeKind::Lex => RangeBy::Lex(zset_helper::parse_lex_range(&min, &max)?),
+        };
+        Ok(Box::new(ZRange {
+            key,
+            range,
+            reverse,
+            limit,
+            with_scores,
+        }))
+    }
+
+    fn range<'a>(&self, zset: &'a SortedSet) -> Vec<(&'a Vec<u8>, f64)> {
+        let (offset, count) = match self.limit {
+            Some((offset, _)) if offset < 0 => return Vec::new(),
+            Some((offset, count)) => (offset as usize, usize::try_from(count).ok()),
+            None => (0, None),
+        };
+        match &self.range {
+            RangeBy::Rank(start, stop) => match list_helper::range(*start, *stop, zset.len()) {
+                Some(range) => zset.range_by_rank(*range.start(), *range.end(), self.reverse),
+                None => Vec::new(),
+            },
+            RangeBy::Score(range) => zset.range_by_score(range, self.reverse, offset, count),
+            RangeBy::Lex(range) => zset.range_by_lex(range, self.reverse, offset, count),
+        }
+    }
+}
+
+impl Execution for ZRange {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<SortedSet>(&self.key) {
+            Ok(Some(zset)) => zset_helper::members_reply(self.range(zset), self.with_scores),
+            Ok(None) => Value::Array(Vec::new()),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::{RangeBy, RangeKind, ZRange};
+    use crate::data_watcher::skip_list::ScoreRange;
+    use std::collections::VecDeque;
+
+    fn input(args: &[&str]) -> VecDeque<Vec<u8>> {
+        args.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_parse_rev_byscore() {
+        // arrange
+        let expected = ZRange {
+            key: b"k".to_vec(),
+            range: RangeBy::Score(ScoreRange {
+                min: 1.0,
+                max: 5.0,
+                min_exclusive: false,
+                max_exclusive: true,
+            }),
+            reverse: true,
+            limit: Some((0, 2)),
+            with_scores: true,
+        };
+        // act
+        let result = ZRange::parse(
+            input(&[
+                "k",
+                "(5",
+                "1",
+                "byscore",
+                "rev",
+                "limit",
+                "0",
+                "2",
+                "withscores",
+            ]),
+            None,
+            false,
+        );
+        // assert
+        assert_eq!(Box::new(expected), result.unwrap());
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert!(ZRange::parse(input(&["k", "0", "1", "limit", "0", "1"]), None, false).is_err());
+        assert!(
+            ZRange::parse(input(&["k", "-", "+", "bylex", "withscores"]), None, false).is_err()
+        );
+        assert!(ZRange::parse(
+            input(&["k", "0", "1", "rev"]),
+            Some(RangeKind::Score),
+            false
+        )
+        .is_err());
+        assert!(ZRange::parse(input(&["k", "a", "1"]), Some(RangeKind::Score), false).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::{RangeKind, ZRange};
+    use crate::data_watcher::{execution::Execution, sorted_set::SortedSet, DataStorage};
+    use resp::Value;
+    use std::collections::VecDeque;
+
+    fn storage() -> DataStorage {
+        let mut data = DataStorage::new();
+        let zset = data.get_typed_or_default::<SortedSet>(b"k").unwrap();
+        for (member, score) in [("a", 1.0), ("b", 2.0), ("c", 3.0), ("d", 3.0)] {
+            zset.insert(member.as_bytes().to_vec(), score);
+        }
+        data
+    }
+
+    fn zrange(args: &[&str], kind: Option<RangeKind>, reverse: bool) -> Value {
+        let input: VecDeque<Vec<u8>> = args.iter().map(|x| x.as_bytes().to_vec()).collect();
+        ZRange::parse(input, kind, reverse)
+            .unwrap()
+            .exec(&mut storage())
+    }
+
+    fn bulks(values: &[&str]) -> Value {
+        Value::Array(
+            values
+                .iter()
+                .map(|x| Value::BufBulk(x.as_bytes().to_vec()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_exec_by_rank() {
+        assert_eq!(bulks(&["b", "c"]), zrange(&["k", "1", "2"], None, false));
+        assert_eq!(
+            bulks(&["d", "3", "c", "3"]),
+            zrange(&["k", "0", "1", "rev", "withscores"], None, false)
+        );
+        assert_eq!(bulks(&["d"]), zrange(&["k", "-1", "-1"], None, false));
+        assert_eq!(bulks(&[]), zrange(&["k", "5", "10"], None, false));
+    }
+
+    #[test]
+    fn test_exec_by_score() {
+        assert_eq!(
+            bulks(&["b", "c", "d"]),
+            zrange(&["k", "(1", "+inf"], Some(RangeKind::Score), false)
+        );
+        assert_eq!(
+            bulks(&["c", "b"]),
+            zrange(
+                &["k", "+inf", "-inf", "limit", "1", "2"],
+                Some(RangeKind::Score),
+                true
+            )
+        );
+        assert_eq!(
+            bulks(&["c", "d"]),
+            zrange(&["k", "3", "3", "byscore", "limit", "0", "-1"], None, false)
+        );
+    }
+
+    #[test]
+    fn test_exec_by_lex() {
+        // arrange
+        let mut data = DataStorage::new();
+        let zset = data.get_typed_or_default::<SortedSet>(b"k").unwrap();
+        for member in ["a", "b", "c", "d"] {
+            zset.insert(member.as_bytes().to_vec(), 0.0);
+        }
+        let input = ["k", "[c", "(a", "bylex", "rev"]
+            .iter()
+            .map(|x| x.as_bytes().to_vec())
+            .collect();
+        // act
+        let result = ZRange::parse(input, None, false).unwrap().exec(&mut data);
+        // assert
+        assert_eq!(bulks(&["c", "b"]), result);
+        assert_eq!(
+            bulks(&["d", "c"]),
+            ZRange::parse(
+                ["k", "+", "[c"]
+                    .iter()
+                    .map(|x| x.as_bytes().to_vec())
+                    .collect(),
+                Some(RangeKind::Lex),
+                true
+            )
+            .unwrap()
+            .exec(&mut data)
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::zset_helper;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/zrank/
+// https://redis.io/commands/zrevrank/
+#[derive(Default, PartialEq, Debug)]
+pub struct ZRank {
+    key: Vec<u8>,
+    member: Vec<u8>,
+    reverse: bool,
+    with_score: bool,
+}
+
+impl ZRank {
+    pub fn parse(input: VecDeque<Vec<u8>>, reverse: bool) -> Result<Box<Self>> {
+        anyhow::ensure!(
+            input.len() == 2 || input.len() == 3,
+            "wrong number of arguments for zrank"
+        );
+        let with_score = match input.get(2) {
+            Some(token) if token.eq_ignore_ascii_case(b"withscore") => true,
+            Some(_) => anyhow::bail!("syntax error"),
+            None => false,
+        };
+        Ok(Box::new(ZRank {
+            key: input[0].to_owned(),
+            member: input[1].to_owned(),
+            reverse,
+            with_score,
+        }))
+    }
+}
+
+impl Execution for ZRank {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let zset = match data.get_typed::<SortedSet>(&self.key) {
+            Ok(Some(zset)) => zset,
+            Ok(None) => return Value::Null,
+            Err(e) => return e.into(),
+        };
+        let (Some(rank), Some(score)) = (
+            zset.rank(&self.member, self.reverse),
+            zset.score(&self.member),
+        ) else {
+            return Value::Null;
+        };
+        if self.with_score {
+            Value::Array(vec![
+                Value::Integer(rank as i64),
+                Value::BufBulk(zset_helper::format_score(score).into_bytes()),
+            ])
+        } else {
+            Value::Integer(rank as i64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::ZRank;
+    use crate::data_watcher::{execution::Execution, sorted_set::SortedSet, DataStorage};
+    use resp::Value;
+
+    fn zrank(member: &str, reverse: bool, with_score: bool) -> ZRank {
+        ZRank {
+            key: b"k".to_vec(),
+            member: member.as_bytes().to_vec(),
+            reverse,
+            with_score,
+        }
+    }
+
+    #[test]
+    fn test_exec_success() {
+        // arrange
+        let mut data = DataStorage::new();
+        let zset = data.get_typed_or_default::<SortedSet>(b"k").unwrap();
+        for (member, score) in [("a", 1.0), ("b", 2.0), ("c", 3.0)] {
+            zset.insert(member.as_bytes().to_vec(), score);
+        }
+        // act & assert
+        assert_eq!(Value::Integer(1), zrank("b", false, false).exec(&mut data));
+        assert_eq!(Value::Integer(0), zrank("c", true, false).exec(&mut data));
+        assert_eq!(
+            Value::Array(vec![Value::Integer(0), Value::BufBulk(b"1".to_vec())]),
+            zrank("a", false, true).exec(&mut data)
+        );
+        assert_eq!(Value::Null, zrank("d", false, false).exec(&mut data));
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::DataStorage;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/zrem/
+#[derive(Default, PartialEq, Debug)]
+pub struct ZRem {
+    key: Vec<u8>,
+    members: Vec<Vec<u8>>,
+}
+
+impl ZRem {
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() >= 2, "wrong number of arguments for zrem");
+        Ok(Box::new(ZRem {
+            key: input.pop_front().unwrap(),
+            members: input.into(),
+        }))
+    }
+}
+
+impl Execution for ZRem {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let zset = match data.get_typed_mut::<SortedSet>(&self.key) {
+            Ok(Some(zset)) => zset,
+            Ok(None) => return Value::Integer(0),
+            Err(e) => return e.into(),
+        };
+        let removed = self
+            .members
+            .iter()
+            .filter(|x| zset.remove(x).is_some())
+            .count();
+        data.remove_if_empty(&self.key);
+        Value::Integer(removed as i64)
+    }
+}
+
+#[cfg(test)]
+mod test_exec {
+    use super::ZRem;
+    use crate::data_watcher::{execution::Execution, sorted_set::SortedSet, DataStorage};
+    use resp::Value;
+
+    #[test]
+    fn test_exec_remove_empty_zset() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.get_typed_or_default::<SortedSet>(b"k")
+            .unwrap()
+            .insert(b"a".to_vec(), 1.0);
+        let zrem = ZRem {
+            key: b"k".to_vec(),
+            members: vec![b"a".to_vec(), b"b".to_vec()],
+        };
+        // act
+        let result = zrem.exec(&mut data);
+        // assert
+        assert_eq!(Value::Integer(1), result);
+        assert!(!data.exists(b"k"));
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::sorted_set::SortedSet;
+use crate::data_watcher::DataStorage;
+use crate::redis_protocol::zset_helper;
+
+use anyhow::Result;
+use resp::Value;
+
+// https://redis.io/commands/zscore/
+#[derive(Default, PartialEq, Debug)]
+pub struct ZScore {
+    key: Vec<u8>,
+    member: Vec<u8>,
+}
+
+impl ZScore {
+    pub fn parse(input: VecDeque<Vec<u8>>) -> Result<Box<Self>> {
+        anyhow::ensure!(input.len() == 2, "wrong number of arguments for zscore");
+        Ok(Box::new(ZScore {
+            key: input[0].to_owned(),
+            member: input[1].to_owned(),
+        }))
+    }
+}
+
+impl Execution for ZScore {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        match data.get_typed::<SortedSet>(&self.key) {
+            Ok(zset) => match zset.and_then(|x| x.score(&self.member)) {
+                Some(score) => Value::BufBulk(zset_helper::format_score(score).into_bytes()),
+                None => Value::Null,
+            },
+            Err(e) => e.into(),
+        }
+    }
+}
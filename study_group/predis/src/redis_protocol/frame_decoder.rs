@@ -0,0 +1,265 @@
+use std::io::BufReader;
+
+use anyhow::Result;
+use resp::Value;
+
+// same limits as redis: a bulk string up to 512 MB and an array up to 1M elements per inline/multibulk request
+const MAX_BULK_LENGTH: i64 = 512 * 1024 * 1024;
+const MAX_ARRAY_LENGTH: i64 = 1024 * 1024;
+const MAX_INLINE_LENGTH: usize = 64 * 1024;
+
+// streaming decoder keeps the bytes of one connection
+// the tcp stream may deliver several commands in one read (pipeline) or one command across several reads
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        FrameDecoder { buffer: Vec::new() }
+    }
+
+    pub fn extend(&mut self, input: &[u8]) {
+        self.buffer.extend_from_slice(input);
+    }
+
+    // the bytes not decoded yet
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    // return the first complete frame and remove it from the buffer
+    // Ok(None) means the frame is not complete, wait for more bytes
+    pub fn next_frame(&mut self) -> Result<Option<Value>> {
+        loop {
+            if self.buffer.is_empty() {
+                return Ok(None);
+            }
+            if matches!(self.buffer[0], b'*' | b'$' | b'+' | b'-' | b':') {
+                break;
+            }
+            match self.next_inline_frame()? {
+                // like redis, the empty inline line is skipped
+                Some(Value::Array(args)) if args.is_empty() => continue,
+                frame => return Ok(frame),
+            }
+        }
+        let Some(frame_len) = Self::frame_len(&self.buffer)? else {
+            return Ok(None);
+        };
+        let frame: Vec<u8> = self.buffer.drain(..frame_len).collect();
+        // the bulk strings are kept as bytes, the keys and values may be any binary data
+        let value = resp::Decoder::with_buf_bulk(BufReader::new(frame.as_slice())).decode()?;
+        Ok(Some(value))
+    }
+
+    // inline command is used by telnet, e.g. "PING\r\n" or "SET k v\n"
+    fn next_inline_frame(&mut self) -> Result<Option<Value>> {
+        let Some(end) = self.buffer.iter().position(|x| *x == b'\n') else {
+            anyhow::ensure!(
+                self.buffer.len() <= MAX_INLINE_LENGTH,
+                "too big inline request"
+            );
+            return Ok(None);
+        };
+        let line: Vec<u8> = self.buffer.drain(..=end).collect();
+        let args = line
+            .split(|x| x.is_ascii_whitespace())
+            .filter(|x| !x.is_empty())
+            .map(|x| Value::BufBulk(x.to_vec()))
+            .collect();
+        Ok(Some(Value::Array(args)))
+    }
+
+    // scan the buffer to get the length of the first frame without decoding it
+    fn frame_len(buf: &[u8]) -> Result<Option<usize>> {
+        let mut pos = 0;
+        // number of values still needed to finish the frame, array adds its elements
+        let mut pending: i64 = 1;
+        while pending > 0 {
+            let Some(line_end) = Self::find_crlf(buf, pos) else {
+                // like the inline request, the header line without crlf is bounded
+                anyhow::ensure!(buf.len() - pos <= MAX_INLINE_LENGTH, "too big count string");
+                return Ok(None);
+            };
+            let prefix = buf[pos];
+            let line = &buf[pos + 1..line_end];
+            pos = line_end + 2;
+            pending -= 1;
+            match prefix {
+                b'+' | b'-' => {}
+                b':' => {
+                    Self::parse_integer(line)?;
+                }
+                b'$' => {
+                    let len = Self::parse_integer(line)?;
+                    anyhow::ensure!((-1..MAX_BULK_LENGTH).contains(&len), "invalid bulk length");
+                    if len >= 0 {
+                        // bulk body and the trailing crlf
+                        let end = pos + len as usize + 2;
+                        if buf.len() < end {
+                            return Ok(None);
+                        }
+                        anyhow::ensure!(&buf[end - 2..end] == b"\r\n", "expected crlf");
+                        pos = end;
+                    }
+                }
+                b'*' => {
+                    let len = Self::parse_integer(line)?;
+                    anyhow::ensure!(
+                        (-1..MAX_ARRAY_LENGTH).contains(&len),
+                        "invalid multibulk length"
+                    );
+                    if len > 0 {
+                        pending += len;
+                    }
+                }
+                _ => anyhow::bail!("expected '$', got '{}'", prefix as char),
+            }
+        }
+        Ok(Some(pos))
+    }
+
+    fn find_crlf(buf: &[u8], from: usize) -> Option<usize> {
+        buf.get(from..)?
+            .windows(2)
+            .position(|x| x == b"\r\n")
+            .map(|x| x + from)
+    }
+
+    fn parse_integer(line: &[u8]) -> Result<i64> {
+        Ok(std::str::from_utf8(line)?.parse::<i64>()?)
+    }
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameDecoder;
+    use resp::Value;
+
+    fn command(args: &[&str]) -> Value {
+        Value::Array(
+            args.iter()
+                .map(|x| Value::BufBulk(x.as_bytes().to_vec()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_pipelined_frames() {
+        // arrange
+        let mut decoder = FrameDecoder::new();
+        let mut input = command(&["set", "k", "v"]).encode();
+        input.extend(command(&["get", "k"]).encode());
+        // act
+        decoder.extend(&input);
+        // assert
+        assert_eq!(
+            command(&["set", "k", "v"]),
+            decoder.next_frame().unwrap().unwrap()
+        );
+        assert_eq!(
+            command(&["get", "k"]),
+            decoder.next_frame().unwrap().unwrap()
+        );
+        assert!(decoder.next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_partial_frame() {
+        // arrange
+        let mut decoder = FrameDecoder::new();
+        let input = command(&["set", "key", "value"]).encode();
+        // act & assert
+        for b in &input[..input.len() - 1] {
+            decoder.extend(&[*b]);
+            assert!(decoder.next_frame().unwrap().is_none());
+        }
+        decoder.extend(&input[input.len() - 1..]);
+        assert_eq!(
+            command(&["set", "key", "value"]),
+            decoder.next_frame().unwrap().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_binary_bulk() {
+        // arrange
+        let mut decoder = FrameDecoder::new();
+        let value = b"\r\n\x00\xff*1\r\n$1\r\n".to_vec();
+        let frame = Value::Array(vec![
+            Value::BufBulk(b"set".to_vec()),
+            Value::BufBulk(b"\xc3\x28".to_vec()),
+            Value::BufBulk(value),
+        ]);
+        let input = frame.encode();
+        // act
+        decoder.extend(&input[..input.len() - 3]);
+        let partial = decoder.next_frame().unwrap();
+        decoder.extend(&input[input.len() - 3..]);
+        // assert
+        assert!(partial.is_none());
+        assert_eq!(frame, decoder.next_frame().unwrap().unwrap());
+        assert_eq!(0, decoder.buffered());
+    }
+
+    #[test]
+    fn test_inline_frame() {
+        // arrange
+        let mut decoder = FrameDecoder::new();
+        // act
+        decoder.extend(b"set k  v\r\nget k\n");
+        // assert
+        assert_eq!(
+            command(&["set", "k", "v"]),
+            decoder.next_frame().unwrap().unwrap()
+        );
+        assert_eq!(
+            command(&["get", "k"]),
+            decoder.next_frame().unwrap().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_frame() {
+        // arrange
+        let mut decoder = FrameDecoder::new();
+        // act
+        decoder.extend(b"*1\r\n$x\r\n");
+        // assert
+        assert!(decoder.next_frame().is_err());
+    }
+
+    #[test]
+    fn test_empty_inline_line() {
+        // arrange
+        let mut decoder = FrameDecoder::new();
+        // act
+        decoder.extend(b"\r\n  \n*1\r\n$4\r\nping\r\n\r\n");
+        // assert
+        assert_eq!(command(&["ping"]), decoder.next_frame().unwrap().unwrap());
+        assert_eq!(None, decoder.next_frame().unwrap());
+        assert_eq!(0, decoder.buffered());
+    }
+
+    #[test]
+    fn test_too_big_header_line() {
+        // arrange
+        let mut multibulk = FrameDecoder::new();
+        let mut bulk = FrameDecoder::new();
+        // act
+        multibulk.extend(b"*1");
+        multibulk.extend(&vec![b'1'; 64 * 1024]);
+        bulk.extend(b"*1\r\n$1");
+        bulk.extend(&vec![b'1'; 64 * 1024]);
+        // assert
+        assert!(multibulk.next_frame().is_err());
+        assert!(bulk.next_frame().is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::data_watcher::shard::Route;
+
+// the keys of the command, like the key specs of redis COMMAND INFO
+// args[0] is the command name in lowercase
+pub fn route(command: &str, args: &[Vec<u8>]) -> Route {
+    let keys = |step: usize| args.iter().skip(1).step_by(step).cloned().collect();
+    match command {
+        "keys" | "scan" | "randomkey" | "dbsize" | "save" | "bgsave" | "bgrewriteaof" | "info" => {
+            Route::All
+        }
+        // the commands without key, the argument of pub/sub is a channel
+        "command" | "lastsave" | "acl" | "config" | "auth" | "hello" | "ping" | "subscribe"
+        | "unsubscribe" | "psubscribe" | "punsubscribe" | "publish" | "pubsub" | "replicaof"
+        | "slaveof" | "psync" | "sync" | "replconf" => Route::Keys(Vec::new()),
+        "del" | "exists" | "mget" | "watch" | "sinter" | "sunion" | "sdiff" | "sinterstore"
+        | "sunionstore" | "sdiffstore" => Route::Keys(keys(1)),
+        "mset" | "msetnx" => Route::Keys(keys(2)),
+        "lmove" | "blmove" => Route::Keys(args.iter().skip(1).take(2).cloned().collect()),
+        // the last argument is the timeout
+        "blpop" | "brpop" => Route::Keys(
+            args.iter()
+                .skip(1)
+                .take(args.len().saturating_sub(2))
+                .cloned()
+                .collect(),
+        ),
+        _ => Route::Keys(args.iter().skip(1).take(1).cloned().collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::route;
+    use crate::data_watcher::shard::Route;
+
+    fn args(input: &[&str]) -> Vec<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_route() {
+        assert_eq!(
+            Route::Keys(args(&["k"])),
+            route("set", &args(&["SET", "k", "v"]))
+        );
+        assert_eq!(
+            Route::Keys(args(&["a", "b"])),
+            route("mset", &args(&["MSET", "a", "1", "b", "2"]))
+        );
+        assert_eq!(
+            Route::Keys(args(&["a", "b"])),
+            route("lmove", &args(&["LMOVE", "a", "b", "LEFT", "RIGHT"]))
+        );
+        assert_eq!(
+            Route::Keys(args(&["a", "b"])),
+            route("blpop", &args(&["BLPOP", "a", "b", "0"]))
+        );
+        assert_eq!(Route::All, route("scan", &args(&["SCAN", "0"])));
+        assert_eq!(
+            Route::Keys(Vec::new()),
+            route("lastsave", &args(&["LASTSAVE"]))
+        );
+        assert_eq!(
+            Route::Keys(Vec::new()),
+            route("publish", &args(&["PUBLISH", "channel", "message"]))
+        );
+    }
+}
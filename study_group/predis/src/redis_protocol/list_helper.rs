@@ -0,0 +1,107 @@
+use std::time;
+
+use anyhow::Result;
+
+// the end of a list, LEFT is the head
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn parse(token: &[u8]) -> Result<Self> {
+        match token.to_ascii_lowercase().as_slice() {
+            b"left" => Ok(Side::Left),
+            b"right" => Ok(Side::Right),
+            _ => anyhow::bail!("syntax error"),
+        }
+    }
+}
+
+// convert redis index (negative index counts from the tail) to the vector index
+pub fn index(index: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let index = if index < 0 { len + index } else { index };
+    if (0..len).contains(&index) {
+        Some(index as usize)
+    } else {
+        None
+    }
+}
+
+// convert redis inclusive range [start, stop] to the vector range, None means empty range
+pub fn range(start: i64, stop: i64, len: usize) -> Option<std::ops::RangeInclusive<usize>> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+    if start > stop || start >= len {
+        return None;
+    }
+    Some(start as usize..=stop as usize)
+}
+
+// the timeout of the blocking commands in seconds, 0 waits forever
+pub fn parse_timeout(token: &[u8]) -> Result<Option<time::Duration>> {
+    let timeout = std::str::from_utf8(token)
+        .ok()
+        .and_then(|x| x.parse::<f64>().ok())
+        .filter(|x| x.is_finite())
+        .ok_or_else(|| anyhow::anyhow!("timeout is not a float or out of range"))?;
+    anyhow::ensure!(timeout >= 0.0, "timeout is negative");
+    if timeout == 0.0 {
+        return Ok(None);
+    }
+    time::Duration::try_from_secs_f64(timeout)
+        .map(Some)
+        .map_err(|_| anyhow::anyhow!("timeout is out of range"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{index, parse_timeout, range};
+    use std::time;
+
+    #[test]
+    fn test_index() {
+        assert_eq!(Some(0), index(0, 3));
+        assert_eq!(Some(2), index(-1, 3));
+        assert_eq!(None, index(3, 3));
+        assert_eq!(None, index(-4, 3));
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(Some(0..=2), range(0, -1, 3));
+        assert_eq!(Some(1..=2), range(-2, 100, 3));
+        assert_eq!(Some(0..=0), range(-100, 0, 3));
+        assert_eq!(None, range(2, 1, 3));
+        assert_eq!(None, range(5, 10, 3));
+        assert_eq!(None, range(0, -1, 0));
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(None, parse_timeout(b"0").unwrap());
+        assert_eq!(
+            Some(time::Duration::from_millis(1500)),
+            parse_timeout(b"1.5").unwrap()
+        );
+        assert_eq!(
+            "timeout is negative",
+            parse_timeout(b"-1").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "timeout is not a float or out of range",
+            parse_timeout(b"abc").unwrap_err().to_string()
+        );
+    }
+}
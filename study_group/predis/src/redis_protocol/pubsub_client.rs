@@ -0,0 +1,420 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::pubsub::message::PubSubMessage;
+use crate::pubsub::{ClientId, SUBSCRIBER_QUEUE_CAPACITY};
+
+use anyhow::Result;
+use resp::Value;
+use tokio::sync::{mpsc, oneshot};
+
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+// the commands allowed when the connection is subscribed to a channel or a pattern
+// QUIT and RESET of redis are not supported
+const SUBSCRIBED_COMMANDS: [&str; 5] = [
+    "subscribe",
+    "unsubscribe",
+    "psubscribe",
+    "punsubscribe",
+    "ping",
+];
+
+// the pub/sub state of one connection
+// https://redis.io/docs/interact/pubsub/
+pub struct PubSubClient {
+    id: ClientId,
+    registry: mpsc::Sender<PubSubMessage>,
+    channels: HashSet<Vec<u8>>,
+    patterns: HashSet<Vec<u8>>,
+    // the registry holds the senders, the queue is closed when the registry drops the client
+    push_tx: Option<mpsc::WeakSender<Value>>,
+    push_rx: Option<mpsc::Receiver<Value>>,
+    // the registry dropped the client for the full queue
+    overflowed: bool,
+}
+
+impl PubSubClient {
+    pub fn new(registry: mpsc::Sender<PubSubMessage>) -> Self {
+        PubSubClient {
+            id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
+            registry,
+            channels: HashSet::new(),
+            patterns: HashSet::new(),
+            push_tx: None,
+            push_rx: None,
+            overflowed: false,
+        }
+    }
+
+    pub fn id(&self) -> ClientId {
+        self.id
+    }
+
+    pub fn is_subscribed(&self) -> bool {
+        !self.channels.is_empty() || !self.patterns.is_empty()
+    }
+
+    // the commands handled here instead of the data watcher
+    pub fn handles(&self, command: &str) -> bool {
+        matches!(
+            command,
+            "subscribe" | "unsubscribe" | "psubscribe" | "punsubscribe" | "publish" | "pubsub"
+        ) || (command == "ping" && self.is_subscribed())
+    }
+
+    // the error of the command not allowed in the subscribed mode
+    pub fn check_allowed(&self, command: &str) -> Result<()> {
+        anyhow::ensure!(
+            !self.is_subscribed() || SUBSCRIBED_COMMANDS.contains(&command),
+            "ERR Can't execute '{command}': only (P)SUBSCRIBE / (P)UNSUBSCRIBE / PING are allowed in this context"
+        );
+        Ok(())
+    }
+
+    // the replies of the command, SUBSCRIBE and UNSUBSCRIBE reply once per channel
+    pub async fn apply(
+        &mut self,
+        command: &str,
+        mut input: VecDeque<Vec<u8>>,
+    ) -> Result<Vec<Value>> {
+        match command {
+            "subscribe" | "psubscribe" => {
+                anyhow::ensure!(!input.is_empty(), "wrong number of arguments for {command}");
+                let names: Vec<Vec<u8>> = input.into();
+                let sender = self.sender();
+                let msg = if command == "subscribe" {
+                    PubSubMessage::Subscribe {
+                        client: self.id,
+                        channels: names.clone(),
+                        sender,
+                    }
+                } else {
+                    PubSubMessage::PSubscribe {
+                        client: self.id,
+                        patterns: names.clone(),
+                        sender,
+                    }
+                };
+                self.send(msg).await?;
+                // the count is the number of subscriptions after each channel like redis
+                let mut count = self.channels.len() + self.patterns.len();
+                let subscribed = if command == "subscribe" {
+                    &mut self.channels
+                } else {
+                    &mut self.patterns
+                };
+                Ok(names
+                    .into_iter()
+                    .map(|name| {
+                        if subscribed.insert(name.clone()) {
+                            count += 1;
+                        }
+                        reply(command, Value::BufBulk(name), count)
+                    })
+                    .collect())
+            }
+            "unsubscribe" | "punsubscribe" => {
+                let mut count = self.channels.len() + self.patterns.len();
+                let subscribed = if command == "unsubscribe" {
+                    &mut self.channels
+                } else {
+                    &mut self.patterns
+                };
+                // without argument unsubscribe all
+                let names: Vec<Vec<u8>> = if input.is_empty() {
+                    subscribed.iter().cloned().collect()
+                } else {
+                    input.into()
+                };
+                if names.is_empty() {
+                    return Ok(vec![reply(command, Value::Null, count)]);
+                }
+                let replies = names
+                    .iter()
+                    .map(|name| {
+                        if subscribed.remove(name) {
+                            count -= 1;
+                        }
+                        reply(command, Value::BufBulk(name.to_owned()), count)
+                    })
+                    .collect();
+                let msg = if command == "unsubscribe" {
+                    PubSubMessage::Unsubscribe {
+                        client: self.id,
+                        channels: names,
+                    }
+                } else {
+                    PubSubMessage::PUnsubscribe {
+                        client: self.id,
+                        patterns: names,
+                    }
+                };
+                self.send(msg).await?;
+                Ok(replies)
+            }
+            "publish" => {
+                anyhow::ensure!(input.len() == 2, "wrong number of arguments for publish");
+                let (callback, callback_rx) = oneshot::channel();
+                self.send(PubSubMessage::Publish {
+                    channel: input.pop_front().unwrap(),
+                    message: input.pop_front().unwrap(),
+                    callback,
+                })
+                .await?;
+                Ok(vec![Value::Integer(callback_rx.await? as i64)])
+            }
+            "pubsub" => self.pubsub(input).await.map(|x| vec![x]),
+            "ping" => {
+                anyhow::ensure!(input.len() <= 1, "wrong number of arguments for ping");
+                Ok(vec![Value::Array(vec![
+                    Value::BufBulk(b"pong".to_vec()),
+                    Value::BufBulk(input.pop_front().unwrap_or_default()),
+                ])])
+            }
+            _ => anyhow::bail!("command {command} not support"),
+        }
+    }
+
+    // PUBSUB CHANNELS [pattern] | NUMSUB [channel ...] | NUMPAT
+    async fn pubsub(&mut self, mut input: VecDeque<Vec<u8>>) -> Result<Value> {
+        let subcommand = input
+            .pop_front()
+            .ok_or_else(|| anyhow::anyhow!("wrong number of arguments for pubsub"))?;
+        let subcommand = String::from_utf8_lossy(&subcommand).to_lowercase();
+        match subcommand.as_str() {
+            "channels" => {
+                anyhow::ensure!(
+                    input.len() <= 1,
+                    "wrong number of arguments for pubsub channels"
+                );
+                let (callback, callback_rx) = oneshot::channel();
+                self.send(PubSubMessage::Channels {
+                    pattern: input.pop_front(),
+                    callback,
+                })
+                .await?;
+                let channels = callback_rx.await?;
+                Ok(Value::Array(
+                    channels.into_iter().map(Value::BufBulk).collect(),
+                ))
+            }
+            "numsub" => {
+                let channels: Vec<Vec<u8>> = input.into();
+                let (callback, callback_rx) = oneshot::channel();
+                self.send(PubSubMessage::NumSub {
+                    channels: channels.clone(),
+                    callback,
+                })
+                .await?;
+                let counts = callback_rx.await?;
+                Ok(Value::Array(
+                    channels
+                        .into_iter()
+                        .zip(counts)
+                        .flat_map(|(channel, count)| {
+                            [Value::BufBulk(channel), Value::Integer(count as i64)]
+                        })
+                        .collect(),
+                ))
+            }
+            "numpat" => {
+                anyhow::ensure!(
+                    input.is_empty(),
+                    "wrong number of arguments for pubsub numpat"
+                );
+                let (callback, callback_rx) = oneshot::channel();
+                self.send(PubSubMessage::NumPat { callback }).await?;
+                Ok(Value::Integer(callback_rx.await? as i64))
+            }
+            _ => anyhow::bail!("unknown subcommand '{subcommand}'"),
+        }
+    }
+
+    // the next message pushed by the registry, pending while the connection is not subscribed
+    // return None when the registry dropped the client for the full queue
+    pub async fn next_push(&mut self) -> Option<Value> {
+        loop {
+            if self.overflowed {
+                return None;
+            }
+            let Some(rx) = self.push_rx.as_mut() else {
+                return std::future::pending().await;
+            };
+            match rx.recv().await {
+                Some(value) => return Some(value),
+                None if self.is_subscribed() => return None,
+                // every subscription is removed
+                None => {
+                    self.push_tx = None;
+                    self.push_rx = None;
+                }
+            }
+        }
+    }
+
+    // the sender of the output queue, a new queue is created when the registry holds no sender
+    fn sender(&mut self) -> mpsc::Sender<Value> {
+        if let Some(tx) = self.push_tx.as_ref().and_then(|x| x.upgrade()) {
+            return tx;
+        }
+        if self.is_subscribed() && self.push_rx.is_some() {
+            self.overflowed = true;
+        }
+        let (tx, rx) = mpsc::channel(SUBSCRIBER_QUEUE_CAPACITY);
+        // keep the messages published before the last unsubscription
+        if let Some(mut old) = self.push_rx.replace(rx) {
+            while let Ok(value) = old.try_recv() {
+                let _ = tx.try_send(value);
+            }
+        }
+        self.push_tx = Some(tx.downgrade());
+        tx
+    }
+
+    async fn send(&self, msg: PubSubMessage) -> Result<()> {
+        self.registry
+            .send(msg)
+            .await
+            .map_err(|_| anyhow::anyhow!("pubsub registry is closed"))
+    }
+}
+
+impl Drop for PubSubClient {
+    // remove the subscriptions of the closed connection
+    fn drop(&mut self) {
+        if !self.is_subscribed() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let registry = self.registry.clone();
+        let client = self.id;
+        let channels: Vec<Vec<u8>> = self.channels.drain().collect();
+        let patterns: Vec<Vec<u8>> = self.patterns.drain().collect();
+        runtime.spawn(async move {
+            let _ = registry
+                .send(PubSubMessage::Unsubscribe { client, channels })
+                .await;
+            let _ = registry
+                .send(PubSubMessage::PUnsubscribe { client, patterns })
+                .await;
+        });
+    }
+}
+
+fn reply(kind: &str, name: Value, count: usize) -> Value {
+    Value::Array(vec![
+        Value::BufBulk(kind.as_bytes().to_vec()),
+        name,
+        Value::Integer(count as i64),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PubSubClient;
+    use resp::Value;
+    use std::collections::VecDeque;
+    use tokio::sync::mpsc;
+
+    fn args(input: &[&str]) -> VecDeque<Vec<u8>> {
+        input.iter().map(|x| x.as_bytes().to_vec()).collect()
+    }
+
+    fn reply(kind: &str, name: &str, count: i64) -> Value {
+        Value::Array(vec![
+            Value::BufBulk(kind.as_bytes().to_vec()),
+            Value::BufBulk(name.as_bytes().to_vec()),
+            Value::Integer(count),
+        ])
+    }
+
+    async fn clients() -> (PubSubClient, PubSubClient) {
+        let (tx, rx) = mpsc::channel(8);
+        crate::pubsub::new(rx).await;
+        (PubSubClient::new(tx.clone()), PubSubClient::new(tx))
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_publish() {
+        // arrange
+        let (mut subscriber, mut publisher) = clients().await;
+        // act
+        let subscribed = subscriber
+            .apply("subscribe", args(&["a", "b", "a"]))
+            .await
+            .unwrap();
+        let psubscribed = subscriber.apply("psubscribe", args(&["*"])).await.unwrap();
+        let receivers = publisher.apply("publish", args(&["a", "hi"])).await;
+        // assert
+        assert_eq!(
+            vec![
+                reply("subscribe", "a", 1),
+                reply("subscribe", "b", 2),
+                reply("subscribe", "a", 2)
+            ],
+            subscribed
+        );
+        assert_eq!(vec![reply("psubscribe", "*", 3)], psubscribed);
+        assert_eq!(vec![Value::Integer(2)], receivers.unwrap());
+        assert!(subscriber.check_allowed("get").is_err());
+        assert!(subscriber.check_allowed("quit").is_err());
+        assert!(subscriber.check_allowed("ping").is_ok());
+        assert!(subscriber.handles("ping"));
+        let Some(Value::Array(message)) = subscriber.next_push().await else {
+            panic!("message expected");
+        };
+        assert_eq!(Value::BufBulk(b"message".to_vec()), message[0]);
+        let Some(Value::Array(message)) = subscriber.next_push().await else {
+            panic!("message expected");
+        };
+        assert_eq!(Value::BufBulk(b"pmessage".to_vec()), message[0]);
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_all() {
+        // arrange
+        let (mut subscriber, mut publisher) = clients().await;
+        subscriber.apply("subscribe", args(&["a"])).await.unwrap();
+        // act
+        let unsubscribed = subscriber.apply("unsubscribe", args(&[])).await.unwrap();
+        let again = subscriber.apply("unsubscribe", args(&[])).await.unwrap();
+        let numsub = publisher.apply("pubsub", args(&["numsub", "a"])).await;
+        // assert
+        assert_eq!(vec![reply("unsubscribe", "a", 0)], unsubscribed);
+        assert_eq!(
+            vec![Value::Array(vec![
+                Value::BufBulk(b"unsubscribe".to_vec()),
+                Value::Null,
+                Value::Integer(0)
+            ])],
+            again
+        );
+        assert_eq!(
+            vec![Value::Array(vec![
+                Value::BufBulk(b"a".to_vec()),
+                Value::Integer(0)
+            ])],
+            numsub.unwrap()
+        );
+        assert!(subscriber.check_allowed("get").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_drop_removes_subscriptions() {
+        // arrange
+        let (mut subscriber, mut publisher) = clients().await;
+        subscriber
+            .apply("psubscribe", args(&["news.*"]))
+            .await
+            .unwrap();
+        // act
+        drop(subscriber);
+        tokio::task::yield_now().await;
+        let numpat = publisher.apply("pubsub", args(&["numpat"])).await;
+        // assert
+        assert_eq!(vec![Value::Integer(0)], numpat.unwrap());
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::acl::category;
+use crate::configuration::live::LiveConfig;
+use crate::data_watcher::backlog;
+use crate::data_watcher::shard::Shards;
+use crate::redis_protocol::parse_integer;
+use crate::replication::Replication;
+
+use anyhow::Result;
+use resp::Value;
+use tokio::sync::mpsc;
+
+// the replication commands of one connection
+// https://redis.io/docs/management/replication/
+// the connection of a replica gets the write commands of the server after PSYNC
+pub struct ReplicationClient {
+    replication: Arc<Replication>,
+    shards: Shards,
+    // the stream of the replica, None for the other connections
+    feed: Option<mpsc::Receiver<Vec<u8>>>,
+}
+
+impl ReplicationClient {
+    pub fn new(replication: Arc<Replication>, shards: Shards) -> Self {
+        ReplicationClient {
+            replication,
+            shards,
+            feed: None,
+        }
+    }
+
+    pub fn replication(&self) -> &Arc<Replication> {
+        &self.replication
+    }
+
+    // the commands handled here instead of being sent to the data watcher
+    pub fn handles(&self, command: &str) -> bool {
+        matches!(
+            command,
+            "replicaof" | "slaveof" | "psync" | "sync" | "replconf"
+        )
+    }
+
+    // the read only replica only changes its data with the stream of its primary
+    pub fn check(&self, command: &str) -> Result<()> {
+        anyhow::ensure!(
+            !category::in_category(command, "write") || !self.replication.is_read_only(),
+            "READONLY You can't write against a read only replica."
+        );
+        Ok(())
+    }
+
+    // the encoded reply, PSYNC replies the snapshot or the backlog as they are
+    pub async fn apply(
+        &mut self,
+        command: &str,
+        mut input: VecDeque<Vec<u8>>,
+        config: &LiveConfig,
+    ) -> Result<Vec<u8>> {
+        match command {
+            // REPLICAOF host port | NO ONE
+            "replicaof" | "slaveof" => {
+                anyhow::ensure!(input.len() == 2, "wrong number of arguments for {command}");
+                let host = String::from_utf8_lossy(&input[0]).to_string();
+                let primary =
+                    if host.eq_ignore_ascii_case("no") && input[1].eq_ignore_ascii_case(b"one") {
+                        None
+                    } else {
+                        let port = parse_integer::<u16>(&input[1])
+                            .map_err(|_| anyhow::anyhow!("ERR Invalid master port"))?;
+                        Some((host, port))
+                    };
+                config.set_replicaof(primary.clone());
+                let port = config.current().port;
+                let changed = self.replication.replicaof(primary, &self.shards, port);
+                let reply = match changed {
+                    true => "OK",
+                    false => "OK Already connected to specified master",
+                };
+                Ok(Value::String(reply.to_string()).encode())
+            }
+            // PSYNC replid offset, SYNC is PSYNC ? -1
+            "psync" | "sync" => {
+                let (replid, offset) = match (command, input.len()) {
+                    ("sync", 0) => (b"?".to_vec(), None),
+                    ("psync", 2) => {
+                        let replid = input.pop_front().unwrap();
+                        let offset = parse_integer::<i64>(&input[0])?;
+                        (replid, u64::try_from(offset).ok())
+                    }
+                    _ => anyhow::bail!("wrong number of arguments for {command}"),
+                };
+                let lent = self.shards.lend_all().await?;
+                let (reply, feed) = backlog::psync(&lent.storages(), &replid, offset);
+                drop(lent);
+                self.feed = Some(feed);
+                Ok(reply)
+            }
+            // REPLCONF option value ..., the acknowledgment of the replica gets no reply
+            "replconf" => {
+                anyhow::ensure!(
+                    !input.is_empty() && input.len().is_multiple_of(2),
+                    "wrong number of arguments for replconf"
+                );
+                if input[0].eq_ignore_ascii_case(b"ack") {
+                    return Ok(Vec::new());
+                }
+                Ok(Value::String("OK".to_string()).encode())
+            }
+            _ => anyhow::bail!("command {command} not support"),
+        }
+    }
+
+    // the write commands for the replica, pending for the other connections
+    // None when the stream is closed, the replica was too slow and reconnects with PSYNC
+    pub async fn next_feed(&mut self) -> Option<Vec<u8>> {
+        match self.feed.as_mut() {
+            Some(feed) => feed.recv().await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplicationClient;
+    use crate::data_watcher::shard::Shards;
+    use crate::replication::Replication;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_check() {
+        // arrange
+        let replication = Arc::new(Replication::default());
+        let shards = Shards::new(Vec::new());
+        let client = ReplicationClient::new(replication.clone(), shards.clone());
+        let primary = client.check("set");
+        // act
+        replication.replicaof(Some(("127.0.0.1".to_string(), 1)), &shards, 6379);
+        let write = client.check("set");
+        let read = client.check("get");
+        replication.replicaof(None, &shards, 6379);
+        // assert
+        assert!(primary.is_ok());
+        assert_eq!(
+            "READONLY You can't write against a read only replica.",
+            write.unwrap_err().to_string()
+        );
+        assert!(read.is_ok());
+        assert!(client.check("set").is_ok());
+    }
+}
@@ -0,0 +1,306 @@
+use crate::redis_protocol::zset_helper::format_score;
+
+use resp::Value;
+
+// the protocol of the connection, negotiated by HELLO
+// https://github.com/redis/redis-specifications/blob/master/protocol/RESP3.md
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub enum Protocol {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+// the reply with the RESP3 types, RESP2 gets the same data with the RESP2 types like redis
+#[derive(PartialEq, Clone, Debug)]
+pub enum Reply {
+    Null,
+    NullArray,
+    SimpleString(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Vec<u8>),
+    Array(Vec<Reply>),
+    Map(Vec<(Reply, Reply)>),
+    Set(Vec<Reply>),
+    Double(f64),
+    Boolean(bool),
+    BigNumber(String),
+    // the text with its format, "txt" or "mkd"
+    Verbatim(String, String),
+    // the out of band data like the pub/sub messages
+    Push(Vec<Reply>),
+}
+
+impl Reply {
+    pub fn encode(&self, protocol: Protocol) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write(&mut buf, protocol);
+        buf
+    }
+
+    fn write(&self, buf: &mut Vec<u8>, protocol: Protocol) {
+        let resp3 = protocol == Protocol::Resp3;
+        let mut line = |prefix: &str, content: &str| {
+            buf.extend(prefix.as_bytes());
+            buf.extend(content.as_bytes());
+            buf.extend(b"\r\n");
+        };
+        match self {
+            Reply::Null | Reply::NullArray if resp3 => line("_", ""),
+            Reply::Null => line("$", "-1"),
+            Reply::NullArray => line("*", "-1"),
+            Reply::SimpleString(s) => line("+", s),
+            Reply::Error(e) => line("-", e),
+            Reply::Integer(i) => line(":", &i.to_string()),
+            Reply::Bulk(bytes) => {
+                line("$", &bytes.len().to_string());
+                buf.extend(bytes);
+                buf.extend(b"\r\n");
+            }
+            Reply::Array(items) => {
+                line("*", &items.len().to_string());
+                items.iter().for_each(|x| x.write(buf, protocol));
+            }
+            Reply::Map(pairs) => {
+                if resp3 {
+                    line("%", &pairs.len().to_string());
+                } else {
+                    line("*", &(pairs.len() * 2).to_string());
+                }
+                for (key, value) in pairs {
+                    key.write(buf, protocol);
+                    value.write(buf, protocol);
+                }
+            }
+            Reply::Set(items) | Reply::Push(items) => {
+                let prefix = match self {
+                    _ if !resp3 => "*",
+                    Reply::Set(_) => "~",
+                    _ => ">",
+                };
+                line(prefix, &items.len().to_string());
+                items.iter().for_each(|x| x.write(buf, protocol));
+            }
+            Reply::Double(x) if resp3 => line(",", &format_double(*x)),
+            Reply::Double(x) => Reply::Bulk(format_score(*x).into_bytes()).write(buf, protocol),
+            Reply::Boolean(b) if resp3 => line("#", if *b { "t" } else { "f" }),
+            Reply::Boolean(b) => line(":", if *b { "1" } else { "0" }),
+            Reply::BigNumber(n) if resp3 => line("(", n),
+            Reply::BigNumber(n) => Reply::Bulk(n.as_bytes().to_vec()).write(buf, protocol),
+            Reply::Verbatim(format, text) if resp3 => {
+                line("=", &(format.len() + 1 + text.len()).to_string());
+                line("", &format!("{format}:{text}"));
+            }
+            Reply::Verbatim(_, text) => Reply::Bulk(text.as_bytes().to_vec()).write(buf, protocol),
+        }
+    }
+}
+
+impl From<Value> for Reply {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => Reply::Null,
+            Value::NullArray => Reply::NullArray,
+            Value::String(s) => Reply::SimpleString(s),
+            Value::Error(e) => Reply::Error(e),
+            Value::Integer(i) => Reply::Integer(i),
+            Value::Bulk(s) => Reply::Bulk(s.into_bytes()),
+            Value::BufBulk(bytes) => Reply::Bulk(bytes),
+            Value::Array(items) => Reply::Array(items.into_iter().map(Reply::from).collect()),
+        }
+    }
+}
+
+// RESP3 spells the special values in lowercase
+fn format_double(x: f64) -> String {
+    if x.is_nan() {
+        "nan".to_string()
+    } else {
+        format_score(x)
+    }
+}
+
+// the commands reply the RESP2 values, the RESP3 connection gets them with the types of redis
+// only the replies with another type in RESP3 are listed, args[0] is the command name
+pub fn upgrade(command: &str, args: &[Vec<u8>], value: Value) -> Reply {
+    let with_scores = || {
+        args.iter()
+            .skip(2)
+            .any(|x| x.eq_ignore_ascii_case(b"withscores"))
+    };
+    let subcommand = |name: &[u8]| args.get(1).is_some_and(|x| x.eq_ignore_ascii_case(name));
+    match (command, value) {
+        ("hgetall", Value::Array(items)) => map(items),
+        ("acl", Value::Array(items)) if subcommand(b"getuser") => map(items),
+        ("config", Value::Array(items)) if subcommand(b"get") => map(items),
+        ("info", Value::BufBulk(text)) => Reply::Verbatim(
+            "txt".to_string(),
+            String::from_utf8_lossy(&text).to_string(),
+        ),
+        ("smembers" | "sinter" | "sunion" | "sdiff" | "spop", Value::Array(items)) => {
+            Reply::Set(items.into_iter().map(Reply::from).collect())
+        }
+        ("zscore" | "zincrby", value) => double(value),
+        ("zrange" | "zrevrange" | "zrangebyscore" | "zrevrangebyscore", Value::Array(items))
+            if with_scores() =>
+        {
+            scored_pairs(items)
+        }
+        // ZPOPMIN key count replies the pairs, ZPOPMIN key replies one member with its score
+        ("zpopmin" | "zpopmax", Value::Array(items)) if args.len() > 2 => scored_pairs(items),
+        ("zpopmin" | "zpopmax", Value::Array(items)) => Reply::Array(
+            pairs(items)
+                .flat_map(|(member, score)| [Reply::from(member), double(score)])
+                .collect(),
+        ),
+        (_, value) => Reply::from(value),
+    }
+}
+
+// the pub/sub messages and the (un)subscribe replies are pushed in RESP3
+pub fn push(value: Value) -> Reply {
+    match value {
+        Value::Array(items) => Reply::Push(items.into_iter().map(Reply::from).collect()),
+        value => Reply::from(value),
+    }
+}
+
+fn pairs(items: Vec<Value>) -> impl Iterator<Item = (Value, Value)> {
+    let mut items = items.into_iter();
+    std::iter::from_fn(move || Some((items.next()?, items.next()?)))
+}
+
+// the flat array of keys and values
+fn map(items: Vec<Value>) -> Reply {
+    Reply::Map(
+        pairs(items)
+            .map(|(k, v)| (Reply::from(k), Reply::from(v)))
+            .collect(),
+    )
+}
+
+fn scored_pairs(items: Vec<Value>) -> Reply {
+    Reply::Array(
+        pairs(items)
+            .map(|(member, score)| Reply::Array(vec![Reply::from(member), double(score)]))
+            .collect(),
+    )
+}
+
+fn double(value: Value) -> Reply {
+    match value {
+        Value::BufBulk(bytes) => match std::str::from_utf8(&bytes).map(|x| x.parse::<f64>()) {
+            Ok(Ok(x)) => Reply::Double(x),
+            _ => Reply::Bulk(bytes),
+        },
+        value => Reply::from(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{upgrade, Protocol, Reply};
+    use resp::Value;
+
+    fn bulk(input: &[&str]) -> Vec<Value> {
+        input
+            .iter()
+            .map(|x| Value::BufBulk(x.as_bytes().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_resp3() {
+        // arrange
+        let reply = Reply::Array(vec![
+            Reply::Map(vec![(
+                Reply::SimpleString("k".to_string()),
+                Reply::Double(1.5),
+            )]),
+            Reply::Set(vec![Reply::Boolean(true)]),
+            Reply::Double(f64::NEG_INFINITY),
+            Reply::BigNumber("3492890328409238509324850943850943825024385".to_string()),
+            Reply::Verbatim("txt".to_string(), "Some string".to_string()),
+            Reply::Push(vec![Reply::Bulk(b"a\r\nb".to_vec())]),
+            Reply::Null,
+        ]);
+        // act
+        let resp3 = reply.encode(Protocol::Resp3);
+        let resp2 = reply.encode(Protocol::Resp2);
+        // assert
+        assert_eq!(
+            b"*7\r\n%1\r\n+k\r\n,1.5\r\n~1\r\n#t\r\n,-inf\r\n\
+            (3492890328409238509324850943850943825024385\r\n\
+            =15\r\ntxt:Some string\r\n>1\r\n$4\r\na\r\nb\r\n_\r\n"
+                .to_vec(),
+            resp3
+        );
+        assert_eq!(
+            b"*7\r\n*2\r\n+k\r\n$3\r\n1.5\r\n*1\r\n:1\r\n$4\r\n-inf\r\n\
+            $43\r\n3492890328409238509324850943850943825024385\r\n\
+            $11\r\nSome string\r\n*1\r\n$4\r\na\r\nb\r\n$-1\r\n"
+                .to_vec(),
+            resp2
+        );
+    }
+
+    #[test]
+    fn test_encode_resp2_same_as_value() {
+        // arrange
+        let value = Value::Array(vec![
+            Value::Null,
+            Value::NullArray,
+            Value::String("OK".to_string()),
+            Value::Error("ERR e".to_string()),
+            Value::Integer(-1),
+            Value::BufBulk(b"v".to_vec()),
+        ]);
+        // act & assert
+        assert_eq!(value.encode(), Reply::from(value).encode(Protocol::Resp2));
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let args = |input: &[&str]| {
+            input
+                .iter()
+                .map(|x| x.as_bytes().to_vec())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            Reply::Map(vec![(
+                Reply::Bulk(b"f".to_vec()),
+                Reply::Bulk(b"v".to_vec())
+            )]),
+            upgrade(
+                "hgetall",
+                &args(&["hgetall", "k"]),
+                Value::Array(bulk(&["f", "v"]))
+            )
+        );
+        assert_eq!(
+            Reply::Array(vec![Reply::Array(vec![
+                Reply::Bulk(b"a".to_vec()),
+                Reply::Double(1.0)
+            ])]),
+            upgrade(
+                "zrange",
+                &args(&["zrange", "k", "0", "-1", "WITHSCORES"]),
+                Value::Array(bulk(&["a", "1"]))
+            )
+        );
+        assert_eq!(
+            Reply::Array(vec![Reply::Bulk(b"a".to_vec()), Reply::Double(1.0)]),
+            upgrade(
+                "zpopmin",
+                &args(&["zpopmin", "k"]),
+                Value::Array(bulk(&["a", "1"]))
+            )
+        );
+        assert_eq!(
+            Reply::Null,
+            upgrade("zscore", &args(&["zscore", "k", "m"]), Value::Null)
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::scan_index::scan_hash;
+use crate::redis_protocol::parse_integer;
+use crate::redis_protocol::string_match::string_match;
+
+use anyhow::Result;
+
+// options shared by SCAN, HSCAN, SSCAN and ZSCAN
+#[derive(PartialEq, Debug)]
+pub struct ScanOption {
+    pub cursor: u64,
+    pub pattern: Option<Vec<u8>>,
+    pub count: usize,
+    // SCAN only
+    pub type_name: Option<String>,
+    // HSCAN only
+    pub no_values: bool,
+}
+
+impl ScanOption {
+    const DEFAULT_COUNT: usize = 10;
+
+    pub fn parse(mut input: VecDeque<Vec<u8>>) -> Result<Self> {
+        let cursor = input
+            .pop_front()
+            .ok_or(anyhow::anyhow!("wrong number of arguments for scan"))?;
+        let mut option = ScanOption {
+            cursor: parse_integer::<u64>(&cursor).map_err(|_| anyhow::anyhow!("invalid cursor"))?,
+            pattern: None,
+            count: Self::DEFAULT_COUNT,
+            type_name: None,
+            no_values: false,
+        };
+        while let Some(token) = input.pop_front() {
+            match token.to_ascii_lowercase().as_slice() {
+                b"match" => {
+                    let pattern = input.pop_front().ok_or(anyhow::anyhow!("syntax error"))?;
+                    // "*" matches everything, skip the matching
+                    option.pattern = if pattern == b"*" { None } else { Some(pattern) };
+                }
+                b"count" => {
+                    let count = input.pop_front().ok_or(anyhow::anyhow!("syntax error"))?;
+                    option.count = parse_integer(&count)?;
+                    anyhow::ensure!(option.count >= 1, "syntax error");
+                }
+                b"type" => {
+                    let type_name = input.pop_front().ok_or(anyhow::anyhow!("syntax error"))?;
+                    option.type_name = Some(String::from_utf8_lossy(&type_name).to_lowercase());
+                }
+                b"novalues" => option.no_values = true,
+                _ => anyhow::bail!("syntax error"),
+            }
+        }
+        Ok(option)
+    }
+
+    pub fn is_match(&self, input: &[u8]) -> bool {
+        match &self.pattern {
+            Some(pattern) => string_match(pattern, input, false),
+            None => true,
+        }
+    }
+}
+
+// return at least count elements in scan order from the cursor and the next cursor, 0 means finished
+pub fn scan<'a>(
+    elements: impl Iterator<Item = &'a Vec<u8>>,
+    cursor: u64,
+    count: usize,
+) -> (u64, Vec<&'a Vec<u8>>) {
+    let mut candidates: Vec<(u64, &Vec<u8>)> = elements
+        .map(|x| (scan_hash(x), x))
+        .filter(|(hash, _)| *hash >= cursor)
+        .collect();
+    candidates.sort_unstable();
+    if candidates.len() <= count {
+        return (0, candidates.into_iter().map(|(_, x)| x).collect());
+    }
+    // elements with the same hash are returned in the same batch
+    let last_hash = candidates[count - 1].0;
+    let end = candidates
+        .iter()
+        .position(|(hash, _)| *hash > last_hash)
+        .unwrap_or(candidates.len());
+    let next_cursor = if end == candidates.len() {
+        0
+    } else {
+        last_hash + 1
+    };
+    (
+        next_cursor,
+        candidates[..end].iter().map(|(_, x)| *x).collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scan, ScanOption};
+    use std::collections::{HashSet, VecDeque};
+
+    #[test]
+    fn test_parse() {
+        // arrange
+        let input = VecDeque::from(vec!["5", "MATCH", "a*", "COUNT", "100"]);
+        let input = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        // act
+        let result = ScanOption::parse(input);
+        // assert
+        assert_eq!(
+            ScanOption {
+                cursor: 5,
+                pattern: Some(b"a*".to_vec()),
+                count: 100,
+                type_name: None,
+                no_values: false,
+            },
+            result.unwrap()
+        );
+    }
+
+    #[test]
+    fn test_scan_with_mutation() {
+        // arrange
+        let mut elements: HashSet<Vec<u8>> =
+            (0..100).map(|x: i32| x.to_string().into_bytes()).collect();
+        let mut returned = HashSet::new();
+        let mut cursor = 0;
+        // act
+        loop {
+            let (next, batch) = scan(elements.iter(), cursor, 7);
+            returned.extend(batch.into_iter().cloned());
+            // mutate the collection between the calls
+            elements.insert(format!("new{next}").into_bytes());
+            elements.remove(format!("new{cursor}").as_bytes());
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+        // assert
+        for i in 0..100 {
+            assert!(returned.contains(i.to_string().as_bytes()));
+        }
+    }
+}
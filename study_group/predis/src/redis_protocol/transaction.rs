@@ -0,0 +1,288 @@
+use std::collections::VecDeque;
+
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::shard::{Route, Shards};
+use crate::redis_protocol::cmd_exec::{Exec, QueuedCommand};
+use crate::redis_protocol::cmd_watch::{Unwatch, Watch};
+use crate::redis_protocol::{key_spec, RedisProtocolAnalyzer};
+
+use anyhow::Result;
+use resp::Value;
+
+// the MULTI/EXEC state of one connection
+// https://redis.io/docs/interact/transactions/
+pub struct Transaction {
+    shards: Shards,
+    // the commands queued after MULTI, None when the connection is not in a transaction
+    queue: Option<Vec<QueuedCommand>>,
+    // a command failed to be queued, EXEC discards the transaction
+    aborted: bool,
+    // the keys with the versions returned by WATCH
+    watched: Vec<(Vec<u8>, u64)>,
+}
+
+impl Transaction {
+    pub fn new(shards: Shards) -> Self {
+        Transaction {
+            shards,
+            queue: None,
+            aborted: false,
+            watched: Vec::new(),
+        }
+    }
+
+    // the commands handled here instead of being sent to the data watcher, all of them after MULTI
+    pub fn handles(&self, command: &str) -> bool {
+        self.queue.is_some()
+            || matches!(command, "multi" | "exec" | "discard" | "watch" | "unwatch")
+    }
+
+    // the command rejected before it is queued, EXEC discards the transaction like redis
+    pub fn abort(&mut self) {
+        if self.queue.is_some() {
+            self.aborted = true;
+        }
+    }
+
+    // the commands replied by EXEC, empty when the connection is not in a transaction
+    pub fn queued_args(&self) -> Vec<Vec<Vec<u8>>> {
+        self.queue
+            .iter()
+            .flatten()
+            .map(|x| x.args.clone())
+            .collect()
+    }
+
+    pub async fn apply(&mut self, command: &str, cmd: Vec<Vec<u8>>) -> Value {
+        let result = match command {
+            "multi" => self.multi(&cmd),
+            "exec" => self.exec(&cmd).await,
+            "discard" => self.discard(&cmd).await,
+            "watch" => self.watch(&cmd).await,
+            "unwatch" if self.queue.is_none() => self.unwatch().await,
+            _ => return self.enqueue(command, cmd),
+        };
+        result.unwrap_or_else(|e| Value::Error(e.to_string()))
+    }
+
+    fn multi(&mut self, cmd: &[Vec<u8>]) -> Result<Value> {
+        anyhow::ensure!(cmd.len() == 1, "wrong number of arguments for multi");
+        anyhow::ensure!(self.queue.is_none(), "ERR MULTI calls can not be nested");
+        self.queue = Some(Vec::new());
+        self.aborted = false;
+        Ok(Value::String("OK".to_string()))
+    }
+
+    async fn exec(&mut self, cmd: &[Vec<u8>]) -> Result<Value> {
+        anyhow::ensure!(cmd.len() == 1, "wrong number of arguments for exec");
+        let Some(commands) = self.queue.take() else {
+            anyhow::bail!("ERR EXEC without MULTI");
+        };
+        if self.aborted {
+            self.unwatch().await?;
+            anyhow::bail!("EXECABORT Transaction discarded because of previous errors.");
+        }
+        let watched = std::mem::take(&mut self.watched);
+        let route = exec_route(&commands, &watched);
+        self.shards
+            .exec(route, Exec::new(commands, watched), vec![b"EXEC".to_vec()])
+            .await
+    }
+
+    async fn discard(&mut self, cmd: &[Vec<u8>]) -> Result<Value> {
+        anyhow::ensure!(cmd.len() == 1, "wrong number of arguments for discard");
+        anyhow::ensure!(self.queue.take().is_some(), "ERR DISCARD without MULTI");
+        self.unwatch().await
+    }
+
+    async fn watch(&mut self, cmd: &[Vec<u8>]) -> Result<Value> {
+        anyhow::ensure!(
+            self.queue.is_none(),
+            "ERR WATCH inside MULTI is not allowed"
+        );
+        let args = cmd.to_vec();
+        let keys: VecDeque<Vec<u8>> = args[1..].iter().cloned().collect();
+        let route = Route::Keys(keys.iter().cloned().collect());
+        let Value::Array(versions) = self
+            .shards
+            .exec(route, Watch::parse(keys.clone())?, args)
+            .await?
+        else {
+            anyhow::bail!("ERR watch failed");
+        };
+        for (key, version) in keys.into_iter().zip(versions) {
+            if let Value::Integer(version) = version {
+                self.watched.push((key, version as u64));
+            }
+        }
+        Ok(Value::String("OK".to_string()))
+    }
+
+    async fn unwatch(&mut self) -> Result<Value> {
+        if !self.watched.is_empty() {
+            let keys: Vec<Vec<u8>> = self.watched.drain(..).map(|(key, _)| key).collect();
+            self.shards
+                .exec(
+                    Route::Keys(keys.clone()),
+                    Unwatch::new(keys),
+                    vec![b"UNWATCH".to_vec()],
+                )
+                .await?;
+        }
+        Ok(Value::String("OK".to_string()))
+    }
+
+    // the command is checked when it is queued, the error aborts the transaction like redis
+    fn enqueue(&mut self, command: &str, cmd: Vec<Vec<u8>>) -> Value {
+        let args = cmd.clone();
+        let queue = self.queue.as_mut().unwrap();
+        // UNWATCH does nothing after MULTI, EXEC unwatches all keys
+        let data: Result<Box<dyn Execution + Send>> = if command == "unwatch" {
+            Ok(Unwatch::new(Vec::new()))
+        } else {
+            RedisProtocolAnalyzer::parse(cmd)
+        };
+        match data {
+            Ok(data) => {
+                queue.push(QueuedCommand { data, args });
+                Value::String("QUEUED".to_string())
+            }
+            Err(e) => {
+                self.aborted = true;
+                Value::Error(e.to_string())
+            }
+        }
+    }
+}
+
+impl Drop for Transaction {
+    // release the watched keys of the closed connection
+    fn drop(&mut self) {
+        if self.watched.is_empty() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let shards = self.shards.clone();
+        let keys: Vec<Vec<u8>> = self.watched.drain(..).map(|(key, _)| key).collect();
+        runtime.spawn(async move {
+            let route = Route::Keys(keys.clone());
+            let _ = shards
+                .exec(route, Unwatch::new(keys), vec![b"UNWATCH".to_vec()])
+                .await;
+        });
+    }
+}
+
+// the keys of the queued commands and the watched keys are moved to one shard for EXEC
+// all the shards are lent when a queued command is on the whole keyspace, see Exec::exec_shards
+pub(crate) fn exec_route(commands: &[QueuedCommand], watched: &[(Vec<u8>, u64)]) -> Route {
+    let mut keys: Vec<Vec<u8>> = watched.iter().map(|(key, _)| key.to_owned()).collect();
+    for command in commands {
+        let name = String::from_utf8_lossy(&command.args[0]).to_lowercase();
+        match key_spec::route(&name, &command.args) {
+            Route::Keys(x) => keys.extend(x),
+            Route::All => return Route::All,
+        }
+    }
+    Route::Keys(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transaction;
+    use crate::data_watcher::{shard::Shards, DataStorage};
+    use resp::Value;
+
+    async fn transactions() -> (Transaction, Transaction) {
+        let shards = Shards::start(DataStorage::new(), 4, 8).await;
+        (Transaction::new(shards.clone()), Transaction::new(shards))
+    }
+
+    async fn apply(transaction: &mut Transaction, input: &[&str]) -> Value {
+        let cmd = input.iter().map(|x| x.as_bytes().to_vec()).collect();
+        transaction.apply(input[0], cmd).await
+    }
+
+    #[tokio::test]
+    async fn test_multi_exec() {
+        // arrange
+        let (mut client, _) = transactions().await;
+        // act
+        let multi = apply(&mut client, &["multi"]).await;
+        let queued = apply(&mut client, &["incr", "k"]).await;
+        apply(&mut client, &["incr", "k"]).await;
+        let exec = apply(&mut client, &["exec"]).await;
+        // assert
+        assert_eq!(Value::String("OK".to_string()), multi);
+        assert_eq!(Value::String("QUEUED".to_string()), queued);
+        assert_eq!(
+            Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+            exec
+        );
+        assert!(!client.handles("get"));
+    }
+
+    #[tokio::test]
+    async fn test_exec_aborted() {
+        // arrange
+        let (mut client, _) = transactions().await;
+        apply(&mut client, &["multi"]).await;
+        apply(&mut client, &["set", "k", "v"]).await;
+        // act
+        let error = apply(&mut client, &["unknown"]).await;
+        let exec = apply(&mut client, &["exec"]).await;
+        let without_multi = apply(&mut client, &["exec"]).await;
+        // assert
+        assert!(error.is_error());
+        assert_eq!(
+            Value::Error("EXECABORT Transaction discarded because of previous errors.".to_string()),
+            exec
+        );
+        assert_eq!(
+            Value::Error("ERR EXEC without MULTI".to_string()),
+            without_multi
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_conflict() {
+        // arrange
+        let (mut client, mut other) = transactions().await;
+        apply(&mut client, &["watch", "k"]).await;
+        apply(&mut client, &["multi"]).await;
+        apply(&mut client, &["set", "k", "a"]).await;
+        // act
+        apply(&mut other, &["multi"]).await;
+        apply(&mut other, &["set", "k", "b"]).await;
+        apply(&mut other, &["exec"]).await;
+        let exec = apply(&mut client, &["exec"]).await;
+        // assert
+        assert_eq!(Value::NullArray, exec);
+    }
+
+    #[tokio::test]
+    async fn test_exec_keyspace_command() {
+        // arrange
+        let (mut client, mut other) = transactions().await;
+        apply(&mut other, &["multi"]).await;
+        apply(&mut other, &["mset", "a", "1", "b", "2", "c", "3"]).await;
+        apply(&mut other, &["exec"]).await;
+        apply(&mut client, &["multi"]).await;
+        apply(&mut client, &["set", "d", "4"]).await;
+        apply(&mut client, &["dbsize"]).await;
+        // act
+        let exec = apply(&mut client, &["exec"]).await;
+        apply(&mut other, &["multi"]).await;
+        apply(&mut other, &["mget", "a", "b", "c", "d"]).await;
+        let mget = apply(&mut other, &["exec"]).await;
+        // assert
+        assert_eq!(
+            Value::Array(vec![Value::String("ok".to_string()), Value::Integer(4)]),
+            exec
+        );
+        let values = ["1", "2", "3", "4"].map(|x| Value::BufBulk(x.as_bytes().to_vec()));
+        assert_eq!(Value::Array(vec![Value::Array(values.to_vec())]), mget);
+    }
+}
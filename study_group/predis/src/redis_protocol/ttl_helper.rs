@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+use std::time;
+
+use crate::data_watcher::epoch_now;
+use crate::redis_protocol::parse_integer;
+
+use anyhow::Result;
+
+// the largest ttl in milliseconds, same as redis it must fit in a signed 64 bits integer
+const MAX_TTL_MILLIS: u64 = i64::MAX as u64;
+
+// the expiration options shared by SET, GETEX, SETEX and PSETEX
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TTLState {
+    // EX seconds or PX milliseconds
+    Ttl(time::Duration),
+    // EXAT timestamp-seconds or PXAT timestamp-milliseconds
+    ExpiredTimestamp(time::Duration),
+    // KEEPTTL -- Retain the time to live associated with the key.
+    KeepTTL,
+    // PERSIST -- Remove the time to live associated with the key.
+    Persist,
+}
+
+impl TTLState {
+    // parse the option and take its value from the input, None when the token is not a ttl option
+    pub fn parse(
+        token: &[u8],
+        input: &mut VecDeque<Vec<u8>>,
+        command: &str,
+    ) -> Result<Option<Self>> {
+        let token = String::from_utf8_lossy(token).to_lowercase();
+        let millis = match token.as_str() {
+            "keepttl" => return Ok(Some(TTLState::KeepTTL)),
+            "persist" => return Ok(Some(TTLState::Persist)),
+            "ex" | "exat" => false,
+            "px" | "pxat" => true,
+            _ => return Ok(None),
+        };
+        let Some(value) = input.pop_front() else {
+            anyhow::bail!("{token} without value");
+        };
+        let Ok(value) = parse_integer::<u64>(&value) else {
+            anyhow::bail!("{token} value is not integer");
+        };
+        let max = if millis {
+            MAX_TTL_MILLIS
+        } else {
+            MAX_TTL_MILLIS / 1000
+        };
+        anyhow::ensure!(
+            value > 0 && value <= max,
+            "invalid expire time in '{command}' command"
+        );
+        let duration = if millis {
+            time::Duration::from_millis(value)
+        } else {
+            time::Duration::from_secs(value)
+        };
+        Ok(Some(if token.ends_with("at") {
+            TTLState::ExpiredTimestamp(duration)
+        } else {
+            TTLState::Ttl(duration)
+        }))
+    }
+
+    // the expired time after applying the option, current is the expired time of the existing key
+    pub fn expired_epoch(&self, current: Option<time::Duration>) -> Option<time::Duration> {
+        match self {
+            TTLState::Ttl(d) => Some(epoch_now() + *d),
+            TTLState::ExpiredTimestamp(d) => Some(*d),
+            TTLState::KeepTTL => current,
+            TTLState::Persist => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TTLState;
+    use std::collections::VecDeque;
+    use std::time;
+
+    fn parse(token: &str, value: &[&str]) -> anyhow::Result<Option<TTLState>> {
+        let mut input: VecDeque<Vec<u8>> = value.iter().map(|x| x.as_bytes().to_vec()).collect();
+        TTLState::parse(token.as_bytes(), &mut input, "set")
+    }
+
+    #[test]
+    fn test_parse_success() {
+        assert_eq!(
+            Some(TTLState::Ttl(time::Duration::from_secs(5))),
+            parse("EX", &["5"]).unwrap()
+        );
+        assert_eq!(
+            Some(TTLState::ExpiredTimestamp(time::Duration::from_millis(5))),
+            parse("pxat", &["5"]).unwrap()
+        );
+        assert_eq!(Some(TTLState::Persist), parse("persist", &[]).unwrap());
+        assert_eq!(None, parse("nx", &[]).unwrap());
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        assert!(parse("ex", &[]).is_err());
+        assert!(parse("ex", &["a"]).is_err());
+        assert!(parse("ex", &["0"]).is_err());
+        assert!(parse("ex", &["9223372036854775807"]).is_err());
+    }
+}
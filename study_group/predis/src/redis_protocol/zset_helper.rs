@@ -0,0 +1,105 @@
+use crate::data_watcher::skip_list::{LexBound, LexRange, ScoreRange};
+
+use anyhow::Result;
+use resp::Value;
+
+// score accepts "inf", "+inf" and "-inf" but NaN is not a valid score
+pub fn parse_score(input: &[u8]) -> Result<f64> {
+    match std::str::from_utf8(input).map(|x| x.parse::<f64>()) {
+        Ok(Ok(score)) if !score.is_nan() => Ok(score),
+        _ => anyhow::bail!("value is not a valid float"),
+    }
+}
+
+// redis formats the score in the shortest way, e.g. "1", "1.5", "inf"
+pub fn format_score(score: f64) -> String {
+    score.to_string()
+}
+
+// the score interval, "(" prefix means exclusive, e.g. "(1 5" is 1 < score <= 5
+pub fn parse_score_range(min: &[u8], max: &[u8]) -> Result<ScoreRange> {
+    let parse_bound = |input: &[u8]| match input.strip_prefix(b"(") {
+        Some(v) => parse_score(v).map(|x| (x, true)),
+        None => parse_score(input).map(|x| (x, false)),
+    };
+    let err = |_| anyhow::anyhow!("min or max is not a float");
+    let (min, min_exclusive) = parse_bound(min).map_err(err)?;
+    let (max, max_exclusive) = parse_bound(max).map_err(err)?;
+    Ok(ScoreRange {
+        min,
+        max,
+        min_exclusive,
+        max_exclusive,
+    })
+}
+
+// the member interval, "[" inclusive, "(" exclusive, "-" and "+" are the infinities
+pub fn parse_lex_range(min: &[u8], max: &[u8]) -> Result<LexRange> {
+    let parse_bound = |input: &[u8]| {
+        if input == b"-" {
+            Some(LexBound::Min)
+        } else if input == b"+" {
+            Some(LexBound::Max)
+        } else if let Some(v) = input.strip_prefix(b"[") {
+            Some(LexBound::Inclusive(v.to_vec()))
+        } else {
+            input
+                .strip_prefix(b"(")
+                .map(|v| LexBound::Exclusive(v.to_vec()))
+        }
+    };
+    match (parse_bound(min), parse_bound(max)) {
+        (Some(min), Some(max)) => Ok(LexRange { min, max }),
+        _ => anyhow::bail!("min or max not valid string range item"),
+    }
+}
+
+// member list, WITHSCORES puts the score after each member
+pub fn members_reply<'a>(
+    members: impl IntoIterator<Item = (&'a Vec<u8>, f64)>,
+    with_scores: bool,
+) -> Value {
+    let mut output = Vec::new();
+    for (member, score) in members {
+        output.push(Value::BufBulk(member.to_owned()));
+        if with_scores {
+            output.push(Value::BufBulk(format_score(score).into_bytes()));
+        }
+    }
+    Value::Array(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_score, parse_lex_range, parse_score, parse_score_range};
+    use crate::data_watcher::skip_list::{LexBound, ScoreRange};
+
+    #[test]
+    fn test_parse_score() {
+        assert_eq!(1.5, parse_score(b"1.5").unwrap());
+        assert_eq!(f64::INFINITY, parse_score(b"+inf").unwrap());
+        assert_eq!(f64::NEG_INFINITY, parse_score(b"-inf").unwrap());
+        assert!(parse_score(b"nan").is_err());
+        assert!(parse_score(b"a").is_err());
+        assert_eq!("1", format_score(1.0));
+        assert_eq!("-inf", format_score(f64::NEG_INFINITY));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(
+            ScoreRange {
+                min: 1.0,
+                max: f64::INFINITY,
+                min_exclusive: true,
+                max_exclusive: false
+            },
+            parse_score_range(b"(1", b"+inf").unwrap()
+        );
+        assert!(parse_score_range(b"(", b"1").is_err());
+        let lex = parse_lex_range(b"-", b"(b").unwrap();
+        assert_eq!(LexBound::Min, lex.min);
+        assert_eq!(LexBound::Exclusive(b"b".to_vec()), lex.max);
+        assert!(parse_lex_range(b"a", b"+").is_err());
+    }
+}
@@ -0,0 +1,134 @@
+pub mod replica_link;
+
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::data_watcher::shard::Shards;
+use crate::replication::replica_link::LinkStatus;
+
+use log::info;
+use tokio::task::JoinHandle;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReplicationConfig {
+    // the primary of the server at startup, see REPLICAOF
+    pub replicaof: Option<(String, u16)>,
+    // the replica rejects the write commands of its clients
+    pub read_only: bool,
+    // the password of the default user of the primary
+    pub masterauth: Option<String>,
+    pub backlog_size: usize,
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        ReplicationConfig {
+            replicaof: None,
+            read_only: true,
+            masterauth: None,
+            backlog_size: 1024 * 1024,
+        }
+    }
+}
+
+// the role of the server, shared by the connections
+// the server is a primary until REPLICAOF host port starts the link to its primary
+#[derive(Default, Debug)]
+pub struct Replication {
+    config: RwLock<ReplicationConfig>,
+    link: Mutex<Option<ReplicaLink>>,
+}
+
+#[derive(Debug)]
+struct ReplicaLink {
+    primary: (String, u16),
+    status: Arc<LinkStatus>,
+    task: JoinHandle<()>,
+}
+
+impl Replication {
+    pub fn new(config: ReplicationConfig) -> Self {
+        Replication {
+            config: RwLock::new(config),
+            link: Mutex::new(None),
+        }
+    }
+
+    // CONFIG SET replica-read-only and masterauth, the next write and the next connection use them
+    pub fn set_config(&self, config: ReplicationConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    pub fn masterauth(&self) -> Option<String> {
+        self.config.read().unwrap().masterauth.clone()
+    }
+
+    pub fn is_replica(&self) -> bool {
+        self.link.lock().unwrap().is_some()
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.is_replica() && self.config.read().unwrap().read_only
+    }
+
+    // REPLICAOF host port starts the link to the primary, REPLICAOF NO ONE stops it and keeps the
+    // data, return false when the server is already the replica of the primary
+    pub fn replicaof(
+        self: &Arc<Self>,
+        primary: Option<(String, u16)>,
+        shards: &Shards,
+        port: u16,
+    ) -> bool {
+        let mut link = self.link.lock().unwrap();
+        if link.as_ref().map(|x| &x.primary) == primary.as_ref() {
+            return false;
+        }
+        if let Some(link) = link.take() {
+            info!("stop replication with primary {:?}", link.primary);
+            link.task.abort();
+        }
+        *link = primary.map(|primary| {
+            info!("start replication with primary {primary:?}");
+            let status = Arc::new(LinkStatus::default());
+            let task = tokio::spawn(replica_link::run(
+                primary.clone(),
+                shards.clone(),
+                self.clone(),
+                status.clone(),
+                port,
+            ));
+            ReplicaLink {
+                primary,
+                status,
+                task,
+            }
+        });
+        true
+    }
+
+    // INFO replication, the role with the link of the replica
+    pub fn info(&self) -> Vec<(&'static str, String)> {
+        let link = self.link.lock().unwrap();
+        let Some(link) = link.as_ref() else {
+            return vec![("role", "master".to_string())];
+        };
+        let up = link.status.up.load(Ordering::Relaxed);
+        vec![
+            ("role", "slave".to_string()),
+            ("master_host", link.primary.0.clone()),
+            ("master_port", link.primary.1.to_string()),
+            (
+                "master_link_status",
+                if up { "up" } else { "down" }.to_string(),
+            ),
+            (
+                "slave_repl_offset",
+                link.status.offset.load(Ordering::Relaxed).to_string(),
+            ),
+            (
+                "slave_read_only",
+                (self.config.read().unwrap().read_only as u8).to_string(),
+            ),
+        ]
+    }
+}
@@ -0,0 +1,293 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time;
+
+use crate::data_watcher::aof::{encode_command, rewrite_commands};
+use crate::data_watcher::execution::Execution;
+use crate::data_watcher::shard::{LentShards, Route, Shards};
+use crate::data_watcher::{snapshot, DataStorage, DataTTL};
+use crate::redis_protocol::cmd_exec::{Exec, QueuedCommand};
+use crate::redis_protocol::frame_decoder::FrameDecoder;
+use crate::redis_protocol::transaction::exec_route;
+use crate::redis_protocol::{key_spec, RedisProtocolAnalyzer, RespValueExt};
+use crate::replication::Replication;
+
+use anyhow::{Context, Result};
+use log::{error, info};
+use resp::Value;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+const RECONNECT_DELAY: time::Duration = time::Duration::from_secs(1);
+
+// the keys of the snapshot of the primary
+type Entries = Vec<(Vec<u8>, DataTTL)>;
+
+// the state of the link shown by INFO replication
+#[derive(Default, Debug)]
+pub struct LinkStatus {
+    pub up: AtomicBool,
+    // the offset of the stream of the primary applied by this replica
+    pub offset: AtomicU64,
+    // the replication id of the primary, empty before the first full synchronization
+    replid: Mutex<String>,
+}
+
+// the replica side of the replication like redis replicationCron, connect to the primary, load its
+// snapshot then apply its write commands, a broken link reconnects and continues from its offset
+pub async fn run(
+    primary: (String, u16),
+    shards: Shards,
+    replication: Arc<Replication>,
+    status: Arc<LinkStatus>,
+    port: u16,
+) {
+    loop {
+        if let Err(e) = sync(&primary, &shards, &replication, &status, port).await {
+            error!(
+                "replication with primary {}:{} error={e:#}",
+                primary.0, primary.1
+            );
+        }
+        status.up.store(false, Ordering::Relaxed);
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn sync(
+    primary: &(String, u16),
+    shards: &Shards,
+    replication: &Replication,
+    status: &LinkStatus,
+    port: u16,
+) -> Result<()> {
+    let stream = TcpStream::connect((primary.0.as_str(), primary.1))
+        .await
+        .context("connect")?;
+    let mut reader = BufReader::new(stream);
+    if let Some(password) = replication.masterauth() {
+        request(&mut reader, &[b"AUTH", password.as_bytes()])
+            .await
+            .context("AUTH")?;
+    }
+    let port = port.to_string();
+    request(
+        &mut reader,
+        &[b"REPLCONF", b"listening-port", port.as_bytes()],
+    )
+    .await
+    .context("REPLCONF")?;
+    // the first synchronization is always a full one
+    let replid = status.replid.lock().unwrap().clone();
+    let offset = match replid.is_empty() {
+        true => "-1".to_string(),
+        false => status.offset.load(Ordering::Relaxed).to_string(),
+    };
+    let replid = if replid.is_empty() { "?" } else { &replid };
+    let reply = request(
+        &mut reader,
+        &[b"PSYNC", replid.as_bytes(), offset.as_bytes()],
+    )
+    .await
+    .context("PSYNC")?;
+    match reply.split(' ').collect::<Vec<_>>().as_slice() {
+        ["+FULLRESYNC", replid, offset] => {
+            let offset: u64 = offset.parse().context("PSYNC offset")?;
+            let entries = read_snapshot(&mut reader).await?;
+            info!(
+                "full resynchronization with {} keys at offset {offset}",
+                entries.len()
+            );
+            shards
+                .exec(Route::All, FullSync::new(entries), vec![b"SYNC".to_vec()])
+                .await?;
+            *status.replid.lock().unwrap() = replid.to_string();
+            status.offset.store(offset, Ordering::Relaxed);
+        }
+        ["+CONTINUE", replid] => {
+            info!(
+                "partial resynchronization at offset {}",
+                status.offset.load(Ordering::Relaxed)
+            );
+            *status.replid.lock().unwrap() = replid.to_string();
+        }
+        _ => anyhow::bail!("unexpected reply to PSYNC {reply}"),
+    }
+    status.up.store(true, Ordering::Relaxed);
+    apply_stream(&mut reader, shards, status).await
+}
+
+// send the command and read the one line reply of the primary
+async fn request(reader: &mut BufReader<TcpStream>, args: &[&[u8]]) -> Result<String> {
+    let args: Vec<Vec<u8>> = args.iter().map(|x| x.to_vec()).collect();
+    reader.get_mut().write_all(&encode_command(&args)).await?;
+    let reply = read_line(reader).await?;
+    if let Some(e) = reply.strip_prefix('-') {
+        anyhow::bail!("{e}");
+    }
+    Ok(reply)
+}
+
+async fn read_line(reader: &mut BufReader<TcpStream>) -> Result<String> {
+    let mut line = Vec::new();
+    reader.read_until(b'\n', &mut line).await?;
+    anyhow::ensure!(line.ends_with(b"\r\n"), "connection closed by the primary");
+    line.truncate(line.len() - 2);
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+// $len then the snapshot without trailing \r\n like the rdb transfer of redis
+async fn read_snapshot(reader: &mut BufReader<TcpStream>) -> Result<Entries> {
+    let line = read_line(reader).await?;
+    let len: usize = line
+        .strip_prefix('$')
+        .and_then(|x| x.parse().ok())
+        .with_context(|| format!("bad snapshot length {line}"))?;
+    let mut bytes = vec![0; len];
+    reader
+        .read_exact(&mut bytes)
+        .await
+        .context("read snapshot")?;
+    snapshot::decode(&bytes).context("bad snapshot of the primary")
+}
+
+// apply the write commands of the primary, the offset moves after each applied command
+// the commands of a transaction are applied together on EXEC like aof_loader
+async fn apply_stream(
+    reader: &mut BufReader<TcpStream>,
+    shards: &Shards,
+    status: &LinkStatus,
+) -> Result<()> {
+    const READ_SIZE: usize = 16 * 1024;
+    let mut buf = [0_u8; READ_SIZE];
+    let mut decoder = FrameDecoder::new();
+    let mut multi: Option<Vec<QueuedCommand>> = None;
+    let mut received = status.offset.load(Ordering::Relaxed);
+    loop {
+        let n = reader.read(&mut buf).await.context("read")?;
+        anyhow::ensure!(n > 0, "connection closed by the primary");
+        decoder.extend(&buf[..n]);
+        received += n as u64;
+        while let Some(frame) = decoder.next_frame().context("bad replication stream")? {
+            apply(shards, &mut multi, frame).await?;
+            // a broken transaction is sent again by the partial resynchronization
+            if multi.is_none() {
+                status
+                    .offset
+                    .store(received - decoder.buffered() as u64, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+async fn apply(
+    shards: &Shards,
+    multi: &mut Option<Vec<QueuedCommand>>,
+    frame: Value,
+) -> Result<()> {
+    let Value::Array(cmd) = frame else {
+        anyhow::bail!("bad replication stream");
+    };
+    let args = cmd
+        .iter()
+        .map(|x| x.to_bytes())
+        .collect::<Result<Vec<_>>>()?;
+    let command = args
+        .first()
+        .map(|x| String::from_utf8_lossy(x).to_lowercase())
+        .unwrap_or_default();
+    match (command.as_str(), multi.as_mut()) {
+        ("multi", None) => {
+            *multi = Some(Vec::new());
+            return Ok(());
+        }
+        ("exec", Some(_)) => {
+            let queued = multi.take().unwrap();
+            let route = exec_route(&queued, &[]);
+            shards
+                .exec(route, Exec::new(queued, Vec::new()), args)
+                .await?;
+            return Ok(());
+        }
+        _ => {}
+    }
+    // the primary only sends the commands it ran, an unknown one is skipped
+    let execution = match RedisProtocolAnalyzer::parse(args.clone()) {
+        Ok(execution) => execution,
+        Err(e) => {
+            error!("skip command {command} of the primary error={e}");
+            return Ok(());
+        }
+    };
+    match multi.as_mut() {
+        Some(queued) => queued.push(QueuedCommand {
+            data: execution,
+            args,
+        }),
+        None => {
+            let route = key_spec::route(&command, &args);
+            shards.exec(route, execution, args).await?;
+        }
+    }
+    Ok(())
+}
+
+// the snapshot of the primary replaces all the keys, the replacement is logged as commands so the
+// append only file and the replicas of this replica get the same data
+struct FullSync {
+    entries: Mutex<Option<Entries>>,
+}
+
+impl FullSync {
+    fn new(entries: Entries) -> Box<Self> {
+        Box::new(FullSync {
+            entries: Mutex::new(Some(entries)),
+        })
+    }
+}
+
+impl Execution for FullSync {
+    fn exec(&self, data: &mut DataStorage) -> Value {
+        let keys: Vec<Vec<u8>> = data.keys().cloned().collect();
+        for key in keys {
+            data.remove(&key);
+            data.rewrite_command(vec![b"DEL".to_vec(), key]);
+        }
+        let entries = self.entries.lock().unwrap().take().unwrap_or_default();
+        for (key, value) in entries.into_iter().filter(|(_, x)| !x.is_expired()) {
+            rewrite_commands(&key, &value)
+                .into_iter()
+                .for_each(|x| data.rewrite_command(x));
+            data.insert(key, value);
+        }
+        Value::String("OK".to_string())
+    }
+
+    fn exec_shards(&self, shards: &mut LentShards, args: &[Vec<u8>]) -> Value {
+        shards.exec_merged_all(self, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FullSync;
+    use crate::data_watcher::{execution::Execution, DataStorage, DataTTL};
+
+    #[test]
+    fn test_full_sync() {
+        // arrange
+        let mut data = DataStorage::new();
+        data.insert(b"old".to_vec(), DataTTL::new(b"1".to_vec()));
+        data.insert(b"k".to_vec(), DataTTL::new(b"1".to_vec()));
+        let sync = FullSync::new(vec![(b"k".to_vec(), DataTTL::new(b"2".to_vec()))]);
+        // act
+        sync.exec(&mut data);
+        // assert
+        assert_eq!(None, data.get(b"old".as_slice()));
+        assert_eq!(
+            Some(b"2".to_vec()),
+            data.get(b"k".as_slice()).unwrap().get()
+        );
+        assert_eq!(1, data.len());
+    }
+}
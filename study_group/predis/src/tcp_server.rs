@@ -1,7 +1,12 @@
 pub mod graceful_shutdown;
+pub mod server_stats;
 pub mod tcp_stream_handler;
 
-use crate::{data_watcher::message::DataWatcherMessage, tcp_server};
+use crate::{
+    acl::Acl, configuration::live::LiveConfig, data_watcher::shard::Shards,
+    pubsub::message::PubSubMessage, replication::Replication, tcp_server,
+    tcp_server::server_stats::ServerStats,
+};
 
 use std::sync::Arc;
 
@@ -9,24 +14,32 @@ use log::{debug, error, info};
 
 use tokio::{io::AsyncWriteExt, net::TcpListener, sync::mpsc, sync::Semaphore};
 
+// the parts of the server shared by the connections
+#[derive(Clone)]
+pub struct ServerContext {
+    pub shards: Shards,
+    pub pubsub_sender: mpsc::Sender<PubSubMessage>,
+    pub acl: Arc<Acl>,
+    pub config: Arc<LiveConfig>,
+    pub stats: Arc<ServerStats>,
+    pub replication: Arc<Replication>,
+}
+
 pub async fn tcp_listener_handle(
     shutdown_channel: tokio::sync::broadcast::Sender<()>,
     listener: &TcpListener,
     concurrent_connection: usize,
-    data_watcher_sender: mpsc::Sender<DataWatcherMessage>,
+    context: ServerContext,
 ) {
     let semaphore = Arc::new(Semaphore::new(concurrent_connection));
     let mut shutdown_channel_main = shutdown_channel.subscribe();
     loop {
-        let tx = data_watcher_sender.clone();
         tokio::select! {
             connection = listener.accept() => {
-                let r = if connection.is_ok() {
-                    connection.unwrap()
-                }else{
+                let Ok(r) = connection else {
                     continue
                 };
-                handle_connection(shutdown_channel.clone(), r, semaphore.clone(), tx).await;
+                handle_connection(shutdown_channel.clone(), r, semaphore.clone(), context.clone()).await;
             }
             _ = shutdown_channel_main.recv() => {
                 info!("close listener!");
@@ -40,13 +53,14 @@ async fn handle_connection(
     shutdown_channel: tokio::sync::broadcast::Sender<()>,
     connection: (tokio::net::TcpStream, std::net::SocketAddr),
     semaphore: Arc<Semaphore>,
-    data_watcher_sender: mpsc::Sender<DataWatcherMessage>,
+    context: ServerContext,
 ) {
     let (mut tcp_stream, addr) = connection;
     debug!("client connected={}", addr);
     let permit = semaphore.try_acquire_owned();
     if permit.is_err() {
         info!("too many connection drop this one={}", addr);
+        context.stats.connection_rejected();
         // drop connection
         tcp_stream
             .write_all(&resp::Value::Error("ERR 429 too many connection".to_string()).encode())
@@ -61,14 +75,17 @@ async fn handle_connection(
     }
     let shutdown_channel = shutdown_channel.subscribe();
     let permit = permit.unwrap();
+    let stats = context.stats.clone();
+    stats.client_connected();
     tokio::spawn(async move {
         tcp_server::tcp_stream_handler::TcpStreamHandler::new(
             shutdown_channel,
             tcp_stream,
-            data_watcher_sender,
+            context,
         )
         .run()
         .await;
+        stats.client_disconnected();
         drop(permit);
     });
 }
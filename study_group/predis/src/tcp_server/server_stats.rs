@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time;
+
+use crate::acl::category;
+
+// the counters of the connections and the commands for INFO, shared by the connections
+#[derive(Debug)]
+pub struct ServerStats {
+    started: time::Instant,
+    connected_clients: AtomicUsize,
+    total_connections_received: AtomicU64,
+    // the connections dropped over the limit of concurrent connections
+    rejected_connections: AtomicU64,
+    total_commands_processed: AtomicU64,
+    commands: Mutex<BTreeMap<&'static str, CommandStats>>,
+}
+
+// INFO commandstats of one command
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct CommandStats {
+    pub calls: u64,
+    pub usec: u64,
+    // denied before running, like the ACL errors
+    pub rejected_calls: u64,
+    // replied an error
+    pub failed_calls: u64,
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        ServerStats {
+            started: time::Instant::now(),
+            connected_clients: AtomicUsize::new(0),
+            total_connections_received: AtomicU64::new(0),
+            rejected_connections: AtomicU64::new(0),
+            total_commands_processed: AtomicU64::new(0),
+            commands: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl ServerStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn uptime(&self) -> time::Duration {
+        self.started.elapsed()
+    }
+
+    pub fn client_connected(&self) {
+        self.total_connections_received
+            .fetch_add(1, Ordering::Relaxed);
+        self.connected_clients.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn client_disconnected(&self) {
+        self.connected_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn connection_rejected(&self) {
+        self.rejected_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn connected_clients(&self) -> usize {
+        self.connected_clients.load(Ordering::Relaxed)
+    }
+
+    pub fn total_connections_received(&self) -> u64 {
+        self.total_connections_received.load(Ordering::Relaxed)
+    }
+
+    pub fn rejected_connections(&self) -> u64 {
+        self.rejected_connections.load(Ordering::Relaxed)
+    }
+
+    pub fn total_commands_processed(&self) -> u64 {
+        self.total_commands_processed.load(Ordering::Relaxed)
+    }
+
+    // the command replied, the unknown commands are only counted in the total like redis
+    pub fn record(&self, command: &str, elapsed: time::Duration, failed: bool) {
+        self.total_commands_processed
+            .fetch_add(1, Ordering::Relaxed);
+        let Some(command) = category::command(command) else {
+            return;
+        };
+        let mut commands = self.commands.lock().unwrap();
+        let stats = commands.entry(command).or_default();
+        stats.calls += 1;
+        stats.usec += elapsed.as_micros() as u64;
+        stats.failed_calls += failed as u64;
+    }
+
+    pub fn reject(&self, command: &str) {
+        let Some(command) = category::command(command) else {
+            return;
+        };
+        self.commands
+            .lock()
+            .unwrap()
+            .entry(command)
+            .or_default()
+            .rejected_calls += 1;
+    }
+
+    // the commands called at least once, in alphabetical order
+    pub fn commands(&self) -> Vec<(&'static str, CommandStats)> {
+        self.commands
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, stats)| (*name, *stats))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandStats, ServerStats};
+    use std::time;
+
+    #[test]
+    fn test_record() {
+        // arrange
+        let stats = ServerStats::new();
+        // act
+        stats.record("get", time::Duration::from_micros(3), false);
+        stats.record("get", time::Duration::from_micros(5), true);
+        stats.record("unknown", time::Duration::from_micros(1), true);
+        stats.reject("set");
+        // assert
+        assert_eq!(3, stats.total_commands_processed());
+        assert_eq!(
+            vec![
+                (
+                    "get",
+                    CommandStats {
+                        calls: 2,
+                        usec: 8,
+                        rejected_calls: 0,
+                        failed_calls: 1,
+                    }
+                ),
+                (
+                    "set",
+                    CommandStats {
+                        rejected_calls: 1,
+                        ..Default::default()
+                    }
+                ),
+            ],
+            stats.commands()
+        );
+    }
+}
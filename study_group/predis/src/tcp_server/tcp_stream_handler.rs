@@ -1,30 +1,41 @@
-use crate::data_watcher::message::DataWatcherMessage;
+use crate::redis_protocol::frame_decoder::FrameDecoder;
 use crate::redis_protocol::RedisProtocolAnalyzer;
+use crate::tcp_server::ServerContext;
 
 use log::{debug, error, info};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::sync::mpsc;
 
 pub struct TcpStreamHandler {
     shutdown_channel: tokio::sync::broadcast::Receiver<()>,
     tcp_stream: tokio::net::TcpStream,
     rpa: RedisProtocolAnalyzer,
+    frame_decoder: FrameDecoder,
 }
 
 impl TcpStreamHandler {
     pub fn new(
         shutdown_channel: tokio::sync::broadcast::Receiver<()>,
         tcp_stream: tokio::net::TcpStream,
-        tx: mpsc::Sender<DataWatcherMessage>,
+        context: ServerContext,
     ) -> Self {
         TcpStreamHandler {
             shutdown_channel,
             tcp_stream,
-            rpa: RedisProtocolAnalyzer::new(tx),
+            rpa: RedisProtocolAnalyzer::new(
+                context.shards,
+                context.pubsub_sender,
+                context.acl,
+                context.config,
+                context.stats,
+                context.replication,
+            ),
+            frame_decoder: FrameDecoder::new(),
         }
     }
 
     pub async fn run(&mut self) {
+        const READ_SIZE: usize = 16 * 1024;
+        let mut buf = [0_u8; READ_SIZE];
         loop {
             tokio::select! {
                 _ = self.shutdown_channel.recv() => {
@@ -34,31 +45,198 @@ impl TcpStreamHandler {
                     info!("close tcp stream!");
                     break;
                 },
-                buf = Self::read_one_command(&mut self.tcp_stream) => {
-                    if buf.is_empty() {
+                n = self.tcp_stream.read(&mut buf) => {
+                    let n = n.unwrap_or_else(|x| {
+                        error!("read tcp stream error={}", x);
+                        0
+                    });
+                    if n == 0 {
                         info!("client close={:?}", self.tcp_stream.peer_addr());
                         break;
                     }
-                    debug!("input={}", std::str::from_utf8(&buf).unwrap());
-                    let response = self.rpa.apply(&buf).await;
-                    self.tcp_stream.write_all(&response).await.unwrap();
+                    self.frame_decoder.extend(&buf[0..n]);
+                    if !self.handle_frames().await {
+                        break;
+                    }
+                }
+                // the server pushes the published messages to the subscribed connection and the
+                // write commands to the replica
+                push = self.rpa.next_push() => {
+                    let Some(push) = push else {
+                        info!("push queue overflow close client={:?}", self.tcp_stream.peer_addr());
+                        break;
+                    };
+                    if let Err(e) = self.tcp_stream.write_all(&push).await {
+                        error!("write tcp stream error={}", e);
+                        break;
+                    }
                 }
             }
         }
     }
 
-    // this function is protocol dependence, expected the client send the message then wait the server response
-    // if the client continually sending the message, the server will be stock at this function and get out of memory
-    async fn read_one_command(tcp_stream: &mut tokio::net::TcpStream) -> Vec<u8> {
-        const READ_SIZE: usize = 1024;
-        let mut read_buf = Vec::new();
-        let mut buf = [0_u8; READ_SIZE];
-        while let Ok(n) = tcp_stream.read(&mut buf).await {
-            read_buf.extend_from_slice(&buf[0..n]);
-            if n != READ_SIZE {
-                break;
+    // apply every complete frame in the buffer and write the responses in the same order
+    // return false when the connection should be closed
+    async fn handle_frames(&mut self) -> bool {
+        let mut response = Vec::new();
+        let mut keep_alive = true;
+        loop {
+            match self.frame_decoder.next_frame() {
+                Ok(Some(frame)) => {
+                    debug!("input={:?}", frame);
+                    // the other commands reply before the closed connection is noticed
+                    if !self.rpa.blocks(&frame) {
+                        response.extend(self.rpa.apply(frame).await);
+                        continue;
+                    }
+                    // the replies of the commands before it are not held while it waits
+                    if let Err(e) = self.tcp_stream.write_all(&response).await {
+                        error!("write tcp stream error={}", e);
+                        return false;
+                    }
+                    response.clear();
+                    // a blocked command waits for a push, it is dropped with the connection
+                    tokio::select! {
+                        biased;
+                        reply = self.rpa.apply(frame) => response.extend(reply),
+                        _ = Self::wait_closed(
+                            &mut self.tcp_stream,
+                            &mut self.frame_decoder,
+                            &mut self.shutdown_channel,
+                        ) => return false,
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    // the stream can not be synchronized anymore, reply the error then close it like redis
+                    response
+                        .extend(resp::Value::Error(format!("ERR Protocol error: {e}")).encode());
+                    keep_alive = false;
+                    break;
+                }
+            }
+        }
+        if let Err(e) = self.tcp_stream.write_all(&response).await {
+            error!("write tcp stream error={}", e);
+            return false;
+        }
+        keep_alive
+    }
+
+    // wait until the client closes the connection or the server shuts down
+    // the pipelined bytes read meanwhile are decoded after the running command
+    async fn wait_closed(
+        tcp_stream: &mut tokio::net::TcpStream,
+        frame_decoder: &mut FrameDecoder,
+        shutdown_channel: &mut tokio::sync::broadcast::Receiver<()>,
+    ) {
+        let mut buf = [0_u8; 1024];
+        loop {
+            tokio::select! {
+                _ = shutdown_channel.recv() => {
+                    info!("close tcp stream!");
+                    return;
+                }
+                n = tcp_stream.read(&mut buf) => match n {
+                    Ok(0) | Err(_) => {
+                        info!("client close={:?}", tcp_stream.peer_addr());
+                        return;
+                    }
+                    Ok(n) => frame_decoder.extend(&buf[0..n]),
+                },
             }
         }
-        read_buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TcpStreamHandler;
+    use crate::acl::{Acl, AclConfig};
+    use crate::data_watcher::{shard::Shards, DataStorage};
+    use crate::redis_protocol::test_config;
+    use crate::replication::Replication;
+    use crate::tcp_server::{server_stats::ServerStats, ServerContext};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::{broadcast, mpsc};
+
+    // the replies of the commands sent by a new connection which closes its write side
+    async fn send(listener: &TcpListener, context: &ServerContext, input: &[u8]) -> Vec<u8> {
+        let (shutdown, _) = broadcast::channel(1);
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut handler = TcpStreamHandler::new(shutdown.subscribe(), stream, context.clone());
+        let handler = tokio::spawn(async move { handler.run().await });
+        client.write_all(input).await.unwrap();
+        client.shutdown().await.unwrap();
+        let mut output = Vec::new();
+        client.read_to_end(&mut output).await.unwrap();
+        handler.await.unwrap();
+        output
+    }
+
+    async fn context() -> ServerContext {
+        let acl = Arc::new(Acl::new(AclConfig::default()));
+        let (pubsub_sender, _pubsub) = mpsc::channel(1);
+        ServerContext {
+            shards: Shards::start(DataStorage::new(), 1, 16).await,
+            pubsub_sender,
+            acl: acl.clone(),
+            config: test_config(&acl),
+            stats: Arc::new(ServerStats::new()),
+            replication: Arc::new(Replication::default()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_half_closed_pipeline() {
+        // arrange
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let context = context().await;
+        // act
+        let pipelined = send(
+            &listener,
+            &context,
+            b"*3\r\n$3\r\nSET\r\n$1\r\na\r\n$1\r\n1\r\n*2\r\n$3\r\nGET\r\n$1\r\na\r\n",
+        )
+        .await;
+        let next = send(&listener, &context, b"*2\r\n$3\r\nGET\r\n$1\r\na\r\n").await;
+        // assert
+        assert_eq!(b"+ok\r\n$1\r\n1\r\n".to_vec(), pipelined);
+        assert_eq!(b"$1\r\n1\r\n".to_vec(), next);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_before_blocking() {
+        // arrange
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let context = context().await;
+        let (shutdown, _) = broadcast::channel(1);
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut handler = TcpStreamHandler::new(shutdown.subscribe(), stream, context);
+        let handler = tokio::spawn(async move { handler.run().await });
+        // act
+        client
+            .write_all(b"*1\r\n$4\r\nPING\r\n*3\r\n$5\r\nBLPOP\r\n$1\r\nk\r\n$1\r\n0\r\n")
+            .await
+            .unwrap();
+        let mut output = [0_u8; 7];
+        let read = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            client.read_exact(&mut output),
+        )
+        .await;
+        client.shutdown().await.unwrap();
+        handler.await.unwrap();
+        // assert
+        assert!(read.is_ok());
+        assert_eq!(b"+PONG\r\n", &output);
     }
 }